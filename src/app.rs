@@ -1,23 +1,107 @@
-use actix_web::{web, App, HttpServer, middleware};
+use actix_web::{web, App, HttpMessage, HttpServer, middleware};
 use actix_cors::Cors;
 use mongodb::{Client, Database};
 use crate::config::environment::Environment;
+use crate::config::features::FeatureFlags;
 use crate::modules::user::user_router::user_routes;
 use crate::modules::calendar::calendar_router::calendar_routes;
+use crate::modules::booking::booking_router::{action_routes, booking_routes, public_booking_routes};
+use crate::modules::admin::admin_crud::AdminAuditRepository;
+use crate::modules::admin::admin_router::admin_routes;
+use crate::modules::system::system_router::system_routes;
+use crate::modules::bootstrap::bootstrap_router::bootstrap_routes;
+use crate::modules::notification::notification_crud::{MessageLogRepository, NotificationRepository};
+use crate::modules::notification::notification_router::notification_routes;
+use crate::modules::analytics::analytics_crud::AnalyticsRepository;
+use crate::modules::calendar::calendar_crud::{AvailabilityRepository, AvailabilitySnapshotRepository, EventTypeRepository, HostInviteRepository};
+use crate::modules::calendar::calendar_engine::BookingHorizon;
+use crate::modules::booking::booking_crud::{BookingRepository, ConsumedActionRepository, IdempotencyRepository, RetentionRunRepository, SlotHoldRepository};
+use crate::modules::booking::booking_jobs;
+use crate::modules::analytics::analytics_router::{analytics_routes, public_analytics_routes};
+use crate::modules::conferencing::conferencing_crud::ConferencingConnectionRepository;
+use crate::modules::conferencing::conferencing_router::conferencing_routes;
+use crate::services::booking_hooks::{HookRegistry, SlackHook};
+use crate::services::conferencing::ZoomConfig;
+use crate::services::email::EmailService;
+use crate::services::email_queue::EmailQueue;
+use crate::services::notification_channel::{ChannelMetrics, TwilioConfig};
 use crate::errors::error::AppError;
-use std::sync::OnceLock;
+use crate::errors::error_handler::json_error_handler;
+use crate::middleware::client_ip::{ClientInfo, ClientIpMiddleware};
+use std::sync::{Arc, OnceLock};
 
 static APP_STATE: OnceLock<AppState> = OnceLock::new();
 
 #[derive(Clone, Debug)]
 pub struct AppState {
     pub db: Database,
+    pub email_queue: EmailQueue,
+    pub features: FeatureFlags,
+    pub pending_booking_ttl_hours: u64,
+    pub action_signing_secret: String,  // Signs the host action links in booking emails
+    pub booking_horizon: BookingHorizon,
+    pub zoom: Option<ZoomConfig>,
+    pub twilio: Option<TwilioConfig>,
+    pub channel_metrics: Arc<ChannelMetrics>,  // Reminders sent and failed per channel
+    pub booking_hooks: HookRegistry,
+    pub retention_months: Option<u32>,  // The retention job's default age, also used by admin runs
 }
 
 impl AppState {
     pub fn get() -> &'static AppState {
         APP_STATE.get().expect("AppState not initialized")
     }
+
+    pub fn init(state: AppState) {
+        APP_STATE.set(state).expect("Failed to set AppState");
+    }
+}
+
+/// Creates the indexes every repository relies on. A failure is logged and
+/// startup goes on, as the indexes usually exist from an earlier start.
+pub async fn ensure_indexes(db: &Database) {
+    if let Err(e) = AdminAuditRepository::new(db.clone()).ensure_indexes().await {
+        println!("Failed to create admin audit log indexes: {}", e);
+    }
+    if let Err(e) = AnalyticsRepository::new(db.clone()).ensure_indexes().await {
+        println!("Failed to create analytics indexes: {}", e);
+    }
+    if let Err(e) = AvailabilityRepository::new(db.clone()).ensure_indexes().await {
+        println!("Failed to create availability indexes: {}", e);
+    }
+    if let Err(e) = AvailabilitySnapshotRepository::new(db.clone()).ensure_indexes().await {
+        println!("Failed to create availability snapshot indexes: {}", e);
+    }
+    if let Err(e) = BookingRepository::new(db.clone()).ensure_indexes().await {
+        println!("Failed to create booking indexes: {}", e);
+    }
+    if let Err(e) = ConsumedActionRepository::new(db.clone()).ensure_indexes().await {
+        println!("Failed to create consumed action indexes: {}", e);
+    }
+    if let Err(e) = IdempotencyRepository::new(db.clone()).ensure_indexes().await {
+        println!("Failed to create idempotency key indexes: {}", e);
+    }
+    if let Err(e) = EventTypeRepository::new(db.clone()).ensure_indexes().await {
+        println!("Failed to create event type indexes: {}", e);
+    }
+    if let Err(e) = HostInviteRepository::new(db.clone()).ensure_indexes().await {
+        println!("Failed to create host invite indexes: {}", e);
+    }
+    if let Err(e) = RetentionRunRepository::new(db.clone()).ensure_indexes().await {
+        println!("Failed to create retention run indexes: {}", e);
+    }
+    if let Err(e) = SlotHoldRepository::new(db.clone()).ensure_indexes().await {
+        println!("Failed to create slot hold indexes: {}", e);
+    }
+    if let Err(e) = NotificationRepository::new(db.clone()).ensure_indexes().await {
+        println!("Failed to create notification indexes: {}", e);
+    }
+    if let Err(e) = MessageLogRepository::new(db.clone()).ensure_indexes().await {
+        println!("Failed to create message log indexes: {}", e);
+    }
+    if let Err(e) = ConferencingConnectionRepository::new(db.clone()).ensure_indexes().await {
+        println!("Failed to create conferencing connection indexes: {}", e);
+    }
 }
 
 pub async fn create_app() -> Result<(), AppError> {
@@ -41,13 +125,66 @@ pub async fn create_app() -> Result<(), AppError> {
         .map_err(|e| AppError::InternalServerError(format!("Failed to ping database: {}", e)))?;
     
     println!("Database connection successful");
+
+    ensure_indexes(&db).await;
     
+    // Start the outgoing email worker
+    let email_queue = EmailQueue::new(EmailService::new(&env)?, env.email_queue_capacity);
+    actix_web::rt::spawn(email_queue.clone().run());
+
+    // Start the job that marks past bookings completed
+    actix_web::rt::spawn(booking_jobs::run_completion(db.clone()));
+
+    // Start the job that declines booking requests the host never answered
+    actix_web::rt::spawn(booking_jobs::run_pending_expiry(
+        db.clone(),
+        email_queue.clone(),
+        env.pending_booking_ttl_hours,
+    ));
+
+    // Start the job that removes invitee details from old bookings, if a retention period is set
+    if let Some(months) = env.retention_months {
+        actix_web::rt::spawn(booking_jobs::run_retention(db.clone(), months));
+    }
+
+    let features = FeatureFlags::from_env();
+    println!("Enabled features: {:?}", features.enabled_names());
+
+    // Deployment-specific booking behaviour is registered here
+    let mut booking_hooks = HookRegistry::default();
+    if let Some(webhook_url) = env.slack_webhook_url.clone() {
+        booking_hooks.register(SlackHook::new(webhook_url));
+    }
+    println!("Booking hooks: {:?}", booking_hooks.names());
+
     // Initialize global AppState
-    APP_STATE.set(AppState { db: db.clone() }).expect("Failed to set AppState");
+    let app_state = AppState {
+        db,
+        email_queue,
+        features,
+        pending_booking_ttl_hours: env.pending_booking_ttl_hours,
+        action_signing_secret: env.action_signing_secret.clone(),
+        booking_horizon: BookingHorizon {
+            default_days: env.default_max_booking_horizon_days,
+            hard_max_days: env.hard_max_booking_horizon_days,
+        },
+        zoom: env.zoom.clone(),
+        twilio: env.twilio.clone(),
+        channel_metrics: Arc::new(ChannelMetrics::default()),
+        booking_hooks,
+        retention_months: env.retention_months,
+    };
+    AppState::init(app_state.clone());
+
+    // Start the job that reminds attendees of upcoming bookings; its channels read the AppState
+    actix_web::rt::spawn(booking_jobs::run_reminders(app_state.db.clone(), app_state.email_queue.clone()));
     
-    let app_state = web::Data::new(AppState { db });
+    let app_state = web::Data::new(app_state);
+
+    let api_path = env.path("/api");
+    let trusted_proxies = env.trusted_proxies.clone();
 
-    println!("Starting HTTP server on port {}", env.port);
+    println!("Starting HTTP server on port {} under {}", env.port, api_path);
 
     // Create HTTP server
     HttpServer::new(move || {
@@ -59,10 +196,27 @@ pub async fn create_app() -> Result<(), AppError> {
 
         App::new()
             .app_data(app_state.clone())
+            .app_data(web::JsonConfig::default().error_handler(json_error_handler))
             .wrap(cors)
-            .wrap(middleware::Logger::default())
+            .wrap(
+                middleware::Logger::new(r#"%{client_ip}xi %{client_scheme}xi "%r" %s %b "%{Referer}i" "%{User-Agent}i" %T"#)
+                    .custom_request_replace("client_ip", |req| {
+                        req.extensions()
+                            .get::<ClientInfo>()
+                            .and_then(|info| info.ip)
+                            .map(|ip| ip.to_string())
+                            .unwrap_or_else(|| "-".to_string())
+                    })
+                    .custom_request_replace("client_scheme", |req| {
+                        req.extensions()
+                            .get::<ClientInfo>()
+                            .map(|info| info.scheme.clone())
+                            .unwrap_or_else(|| "-".to_string())
+                    })
+            )
+            .wrap(ClientIpMiddleware::new(trusted_proxies.clone()))
             .service(
-                web::scope("/api")
+                web::scope(&api_path)
                     .configure(|cfg| {
                         if let Ok(routes) = user_routes() {
                             println!("User routes configured successfully");
@@ -71,12 +225,82 @@ pub async fn create_app() -> Result<(), AppError> {
                             println!("Failed to configure user routes");
                         }
                         
+                        if let Ok(routes) = booking_routes() {
+                            println!("Booking routes configured successfully");
+                            cfg.service(routes);
+                        } else {
+                            println!("Failed to configure booking routes");
+                        }
+
                         if let Ok(routes) = calendar_routes() {
                             println!("Calendar routes configured successfully");
                             cfg.service(routes);
                         } else {
                             println!("Failed to configure calendar routes");
                         }
+
+                        if let Ok(routes) = admin_routes() {
+                            println!("Admin routes configured successfully");
+                            cfg.service(routes);
+                        } else {
+                            println!("Failed to configure admin routes");
+                        }
+
+                        if let Ok(routes) = system_routes() {
+                            println!("System routes configured successfully");
+                            cfg.service(routes);
+                        } else {
+                            println!("Failed to configure system routes");
+                        }
+
+                        if let Ok(routes) = bootstrap_routes() {
+                            println!("Bootstrap routes configured successfully");
+                            cfg.service(routes);
+                        } else {
+                            println!("Failed to configure bootstrap routes");
+                        }
+
+                        if let Ok(routes) = notification_routes() {
+                            println!("Notification routes configured successfully");
+                            cfg.service(routes);
+                        } else {
+                            println!("Failed to configure notification routes");
+                        }
+
+                        if let Ok(routes) = analytics_routes() {
+                            println!("Analytics routes configured successfully");
+                            cfg.service(routes);
+                        } else {
+                            println!("Failed to configure analytics routes");
+                        }
+
+                        if let Ok(routes) = public_analytics_routes() {
+                            println!("Public analytics routes configured successfully");
+                            cfg.service(routes);
+                        } else {
+                            println!("Failed to configure public analytics routes");
+                        }
+
+                        if let Ok(routes) = public_booking_routes() {
+                            println!("Public booking routes configured successfully");
+                            cfg.service(routes);
+                        } else {
+                            println!("Failed to configure public booking routes");
+                        }
+
+                        if let Ok(routes) = action_routes() {
+                            println!("Action routes configured successfully");
+                            cfg.service(routes);
+                        } else {
+                            println!("Failed to configure action routes");
+                        }
+
+                        if let Ok(routes) = conferencing_routes() {
+                            println!("Conferencing routes configured successfully");
+                            cfg.service(routes);
+                        } else {
+                            println!("Failed to configure conferencing routes");
+                        }
                     })
             )
     })
@@ -1,8 +1,10 @@
 use mongodb::{Client, Database};
 use std::sync::OnceLock;
 
+#[allow(dead_code)]
 static DB: OnceLock<Database> = OnceLock::new();
 
+#[allow(dead_code)]
 pub async fn init_database(mongodb_uri: &str, database_name: &str) -> Result<(), mongodb::error::Error> {
     let client = Client::with_uri_str(mongodb_uri).await?;
     let database = client.database(database_name);
@@ -13,6 +15,7 @@ pub async fn init_database(mongodb_uri: &str, database_name: &str) -> Result<(),
     Ok(())
 }
 
+#[allow(dead_code)]
 pub fn get_database() -> &'static Database {
     DB.get().expect("Database not initialized")
 }
@@ -1,5 +1,8 @@
 use std::env;
+use std::net::IpAddr;
 use dotenv::dotenv;
+use crate::services::conferencing::ZoomConfig;
+use crate::services::notification_channel::TwilioConfig;
 
 #[derive(Clone)]
 pub struct Environment {
@@ -9,6 +12,19 @@ pub struct Environment {
     pub jwt_secret: String,
     pub email_user: String,
     pub email_password: String,
+    pub base_path: String,
+    pub trusted_proxies: Vec<IpAddr>,
+    pub email_queue_capacity: usize,
+    pub email_dedup_window_seconds: u64,
+    pub pending_booking_ttl_hours: u64,
+    pub action_signing_secret: String,
+    pub default_max_booking_horizon_days: i64,
+    pub hard_max_booking_horizon_days: i64,
+    pub app_url: String,
+    pub zoom: Option<ZoomConfig>,  // None unless all ZOOM_* variables are set
+    pub twilio: Option<TwilioConfig>,  // None unless all TWILIO_* variables are set; SMS then only goes to the log
+    pub slack_webhook_url: Option<String>,  // Incoming webhook the Slack booking hook posts to
+    pub retention_months: Option<u32>,  // Invitee details are removed from bookings this much older; None keeps them
 }
 
 impl Environment {
@@ -61,6 +77,84 @@ impl Environment {
         let email_password = env::var("EMAIL_PASSWORD").expect("EMAIL_PASSWORD must be set");
         println!("✓ EMAIL_PASSWORD loaded");
 
+        let base_path = Self::normalize_base_path(&env::var("BASE_PATH").unwrap_or_default());
+        println!("✓ BASE_PATH loaded");
+
+        let trusted_proxies = env::var("TRUSTED_PROXIES")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(|value| value.parse().expect("TRUSTED_PROXIES must be a comma-separated list of IP addresses"))
+            .collect();
+        println!("✓ TRUSTED_PROXIES loaded");
+
+        let email_queue_capacity = env::var("EMAIL_QUEUE_CAPACITY")
+            .unwrap_or_else(|_| "1000".to_string())
+            .parse()
+            .expect("EMAIL_QUEUE_CAPACITY must be a number");
+        println!("✓ EMAIL_QUEUE_CAPACITY loaded");
+
+        let email_dedup_window_seconds = env::var("EMAIL_DEDUP_WINDOW_SECONDS")
+            .unwrap_or_else(|_| "60".to_string())
+            .parse()
+            .expect("EMAIL_DEDUP_WINDOW_SECONDS must be a number");
+        println!("✓ EMAIL_DEDUP_WINDOW_SECONDS loaded");
+
+        let pending_booking_ttl_hours = env::var("PENDING_BOOKING_TTL_HOURS")
+            .unwrap_or_else(|_| "24".to_string())
+            .parse()
+            .expect("PENDING_BOOKING_TTL_HOURS must be a number");
+        println!("✓ PENDING_BOOKING_TTL_HOURS loaded");
+
+        let app_url = env::var("APP_URL")
+            .unwrap_or_else(|_| "http://localhost:3000".to_string())
+            .trim_end_matches('/')
+            .to_string();
+        println!("✓ APP_URL loaded");
+
+        let action_signing_secret = env::var("ACTION_SIGNING_SECRET").unwrap_or_else(|_| jwt_secret.clone());
+        println!("✓ ACTION_SIGNING_SECRET loaded");
+
+        let hard_max_booking_horizon_days: i64 = env::var("HARD_MAX_BOOKING_HORIZON_DAYS")
+            .unwrap_or_else(|_| "365".to_string())
+            .parse()
+            .expect("HARD_MAX_BOOKING_HORIZON_DAYS must be a number");
+        println!("✓ HARD_MAX_BOOKING_HORIZON_DAYS loaded");
+
+        let default_max_booking_horizon_days: i64 = env::var("DEFAULT_MAX_BOOKING_HORIZON_DAYS")
+            .unwrap_or_else(|_| "60".to_string())
+            .parse()
+            .expect("DEFAULT_MAX_BOOKING_HORIZON_DAYS must be a number");
+        if !(0..=hard_max_booking_horizon_days).contains(&default_max_booking_horizon_days) {
+            panic!("DEFAULT_MAX_BOOKING_HORIZON_DAYS must be between 0 and HARD_MAX_BOOKING_HORIZON_DAYS");
+        }
+        println!("✓ DEFAULT_MAX_BOOKING_HORIZON_DAYS loaded");
+
+        let zoom = match (env::var("ZOOM_CLIENT_ID"), env::var("ZOOM_CLIENT_SECRET"), env::var("ZOOM_REDIRECT_URI")) {
+            (Ok(client_id), Ok(client_secret), Ok(redirect_uri)) => Some(ZoomConfig { client_id, client_secret, redirect_uri }),
+            _ => None,
+        };
+        println!("✓ ZOOM_* loaded (Zoom {})", if zoom.is_some() { "enabled" } else { "disabled" });
+
+        let twilio = match (env::var("TWILIO_ACCOUNT_SID"), env::var("TWILIO_AUTH_TOKEN"), env::var("TWILIO_FROM_NUMBER")) {
+            (Ok(account_sid), Ok(auth_token), Ok(from_number)) => Some(TwilioConfig { account_sid, auth_token, from_number }),
+            _ => None,
+        };
+        println!("✓ TWILIO_* loaded (SMS {})", if twilio.is_some() { "via Twilio" } else { "to the log only" });
+
+        let slack_webhook_url = env::var("SLACK_WEBHOOK_URL").ok().filter(|url| !url.trim().is_empty());
+        println!("✓ SLACK_WEBHOOK_URL loaded (Slack hook {})", if slack_webhook_url.is_some() { "enabled" } else { "disabled" });
+
+        let retention_months = env::var("RETENTION_MONTHS")
+            .ok()
+            .filter(|value| !value.trim().is_empty())
+            .map(|value| value.trim().parse().expect("RETENTION_MONTHS must be a number"));
+        if retention_months == Some(0) {
+            panic!("RETENTION_MONTHS must be at least 1");
+        }
+        println!("✓ RETENTION_MONTHS loaded (retention job {})", if retention_months.is_some() { "enabled" } else { "disabled" });
+
         Self {
             mongodb_uri,
             database_name,
@@ -68,9 +162,42 @@ impl Environment {
             jwt_secret,
             email_user,
             email_password,
+            base_path,
+            trusted_proxies,
+            email_queue_capacity,
+            email_dedup_window_seconds,
+            pending_booking_ttl_hours,
+            app_url,
+            action_signing_secret,
+            default_max_booking_horizon_days,
+            hard_max_booking_horizon_days,
+            zoom,
+            twilio,
+            slack_webhook_url,
+            retention_months,
         }
     }
 
+    /// Turns "scheduling/", "/scheduling" or "" into "/scheduling" or "".
+    fn normalize_base_path(value: &str) -> String {
+        let trimmed = value.trim().trim_matches('/');
+        if trimmed.is_empty() {
+            String::new()
+        } else {
+            format!("/{}", trimmed)
+        }
+    }
+
+    /// Prefixes an absolute path with the configured base path for generated links.
+    pub fn path(&self, path: &str) -> String {
+        format!("{}{}", self.base_path, path)
+    }
+
+    /// Where links in emails start: APP_URL followed by the base path.
+    pub fn app_base_url(&self) -> String {
+        format!("{}{}", self.app_url, self.base_path)
+    }
+
     pub fn get_jwt_secret(&self) -> &str {
         &self.jwt_secret
     }
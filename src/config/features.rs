@@ -0,0 +1,80 @@
+use std::env;
+
+use crate::app::AppState;
+use crate::errors::error::AppError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Feature {
+    Payments,
+    Teams,
+    Webhooks,
+}
+
+impl Feature {
+    pub const ALL: [Feature; 3] = [Feature::Payments, Feature::Teams, Feature::Webhooks];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Feature::Payments => "payments",
+            Feature::Teams => "teams",
+            Feature::Webhooks => "webhooks",
+        }
+    }
+
+    fn env_key(&self) -> String {
+        format!("FEATURE_{}", self.as_str().to_uppercase())
+    }
+}
+
+/// Features that ship dark until switched on through the environment.
+#[derive(Debug, Clone, Default)]
+pub struct FeatureFlags {
+    enabled: Vec<Feature>,
+}
+
+impl FeatureFlags {
+    /// Reads the comma-separated `FEATURES` list, then lets individual
+    /// `FEATURE_<NAME>=true|false` variables switch single flags on or off.
+    pub fn from_env() -> Self {
+        let listed = env::var("FEATURES").unwrap_or_default();
+        let mut enabled = Vec::new();
+
+        for name in listed.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+            match Feature::ALL.iter().find(|feature| feature.as_str().eq_ignore_ascii_case(name)) {
+                Some(feature) => enabled.push(*feature),
+                None => println!("Ignoring unknown feature flag: {}", name),
+            }
+        }
+
+        for feature in Feature::ALL {
+            match env::var(feature.env_key()).map(|value| value.trim().to_lowercase()).as_deref() {
+                Ok("true") | Ok("1") if !enabled.contains(&feature) => enabled.push(feature),
+                Ok("false") | Ok("0") => enabled.retain(|f| *f != feature),
+                _ => {}
+            }
+        }
+
+        Self { enabled }
+    }
+
+    pub fn is_enabled(&self, feature: Feature) -> bool {
+        self.enabled.contains(&feature)
+    }
+
+    pub fn enabled_names(&self) -> Vec<&'static str> {
+        Feature::ALL
+            .iter()
+            .filter(|feature| self.is_enabled(**feature))
+            .map(Feature::as_str)
+            .collect()
+    }
+}
+
+/// Guard for endpoints of dark-launched features; disabled features look like missing routes.
+pub fn require_feature(feature: Feature) -> Result<(), AppError> {
+    if AppState::get().features.is_enabled(feature) {
+        Ok(())
+    } else {
+        Err(AppError::NotFound("Resource not found".to_string()))
+    }
+}
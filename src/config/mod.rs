@@ -1,5 +1,6 @@
 pub mod database;
 pub mod environment;
+pub mod features;
  
  
  
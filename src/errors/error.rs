@@ -1,4 +1,4 @@
-use actix_web::{HttpResponse, ResponseError};
+use actix_web::{http::StatusCode, HttpResponse, ResponseError};
 use derive_more::Display;
 use mongodb::error::Error as MongoError;
 use serde::Serialize;
@@ -29,6 +29,22 @@ pub enum AppError {
 
     #[display(fmt = "Forbidden: {}", _0)]
     Forbidden(String),
+
+    #[display(fmt = "Conflict: {}", _0)]
+    Conflict(String),
+
+    #[display(fmt = "Method Not Allowed: {}", _0)]
+    MethodNotAllowed(String),  // Comma-separated allowed methods
+
+    #[display(fmt = "{}: {}", _1, _2)]
+    Coded(u16, String, String),  // HTTP status, machine-readable code, message
+}
+
+impl AppError {
+    /// An error whose body carries a stable `code` clients can branch on.
+    pub fn coded(status: StatusCode, code: &str, message: &str) -> Self {
+        AppError::Coded(status.as_u16(), code.to_string(), message.to_string())
+    }
 }
 
 impl ResponseError for AppError {
@@ -62,6 +78,24 @@ impl ResponseError for AppError {
                 "error": "Forbidden",
                 "message": msg
             })),
+            AppError::Conflict(msg) => HttpResponse::Conflict().json(json!({
+                "error": "Conflict",
+                "message": msg
+            })),
+            AppError::MethodNotAllowed(allowed) => HttpResponse::MethodNotAllowed()
+                .insert_header(("Allow", allowed.as_str()))
+                .json(json!({
+                    "error": "Method Not Allowed",
+                    "message": format!("Allowed methods: {}", allowed)
+                })),
+            AppError::Coded(status, code, msg) => {
+                let status = StatusCode::from_u16(*status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+                HttpResponse::build(status).json(json!({
+                    "error": status.canonical_reason().unwrap_or("Error"),
+                    "code": code,
+                    "message": msg
+                }))
+            }
         }
     }
 }
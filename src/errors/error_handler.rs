@@ -0,0 +1,107 @@
+use actix_web::{error::JsonPayloadError, web, HttpRequest, HttpResponse, Route};
+
+use crate::errors::error::AppError;
+
+/// Turns JSON extractor failures into the regular AppError body instead of
+/// actix's plain-text default, keeping serde's message since it names the
+/// offending field for missing, unknown and mistyped fields.
+pub fn json_error_handler(err: JsonPayloadError, _req: &HttpRequest) -> actix_web::Error {
+    let error = match &err {
+        JsonPayloadError::ContentType => {
+            AppError::BadRequest("Content-Type must be application/json".to_string())
+        }
+        JsonPayloadError::Deserialize(e) if e.is_data() => {
+            AppError::ValidationError(e.to_string())
+        }
+        JsonPayloadError::Deserialize(e) => {
+            AppError::BadRequest(format!("Malformed JSON: {}", e))
+        }
+        JsonPayloadError::Overflow { limit } | JsonPayloadError::OverflowKnownLength { limit, .. } => {
+            AppError::BadRequest(format!("Request body exceeds {} bytes", limit))
+        }
+        _ => AppError::BadRequest(err.to_string()),
+    };
+
+    error.into()
+}
+
+/// Fallback for a resource's unsupported methods: 405 with an Allow header.
+pub fn method_not_allowed(allowed: &'static str) -> Route {
+    web::route().to(move || async move {
+        Err::<HttpResponse, AppError>(AppError::MethodNotAllowed(allowed.to_string()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{dev::ServiceResponse, http::StatusCode, test as actix_test, App};
+    use serde::Deserialize;
+    use serde_json::{json, Value};
+
+    #[derive(Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Payload {
+        name: String,
+    }
+
+    async fn call(req: actix_test::TestRequest) -> ServiceResponse {
+        let app = actix_test::init_service(
+            App::new()
+                .app_data(web::JsonConfig::default().limit(64).error_handler(json_error_handler))
+                .service(
+                    web::resource("/items")
+                        .default_service(method_not_allowed("POST"))
+                        .route(web::post().to(|payload: web::Json<Payload>| async move { HttpResponse::Ok().body(payload.into_inner().name) })),
+                ),
+        ).await;
+        actix_test::call_service(&app, req.uri("/items").to_request()).await
+    }
+
+    async fn error_body(response: ServiceResponse) -> (StatusCode, Value) {
+        let status = response.status();
+        (status, actix_test::read_body_json(response).await)
+    }
+
+    #[actix_web::test]
+    async fn unsupported_methods_get_405_with_allow_header() {
+        let response = call(actix_test::TestRequest::delete()).await;
+        assert_eq!(response.headers().get("Allow").unwrap(), "POST");
+        let (status, body) = error_body(response).await;
+        assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(body, json!({ "error": "Method Not Allowed", "message": "Allowed methods: POST" }));
+    }
+
+    #[actix_web::test]
+    async fn json_failures_use_the_error_body() {
+        let wrong_type = actix_test::TestRequest::post()
+            .insert_header(("Content-Type", "text/plain"))
+            .set_payload(r#"{"name":"a"}"#);
+        let (status, body) = error_body(call(wrong_type).await).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["message"], "Content-Type must be application/json");
+
+        let malformed = actix_test::TestRequest::post()
+            .insert_header(("Content-Type", "application/json"))
+            .set_payload("{");
+        let (status, body) = error_body(call(malformed).await).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["message"].as_str().unwrap().starts_with("Malformed JSON"), "{}", body);
+
+        let too_large = actix_test::TestRequest::post().set_json(json!({ "name": "n".repeat(100) }));
+        let (status, body) = error_body(call(too_large).await).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["message"], "Request body exceeds 64 bytes");
+    }
+
+    #[actix_web::test]
+    async fn unknown_and_missing_fields_are_validation_errors() {
+        for payload in [json!({ "name": "a", "extra": 1 }), json!({})] {
+            let (status, body) = error_body(call(actix_test::TestRequest::post().set_json(&payload)).await).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+            assert_eq!(body["error"], "Validation Error", "{}", payload);
+        }
+        let (_, body) = error_body(call(actix_test::TestRequest::post().set_json(json!({ "name": 1 }))).await).await;
+        assert!(body["message"].as_str().unwrap().contains("invalid type"), "{}", body);
+    }
+}
@@ -6,6 +6,9 @@ mod modules;
 mod services;
 mod utils;
 
+#[cfg(test)]
+mod test_support;
+
 use env_logger::Env;
 
 #[actix_web::main]
@@ -16,6 +19,6 @@ async fn main() -> std::io::Result<()> {
     // Start the application
     app::create_app().await.map_err(|e| {
         eprintln!("Application error: {}", e);
-        std::io::Error::new(std::io::ErrorKind::Other, e.to_string())
+        std::io::Error::other(e.to_string())
     })
 }
@@ -8,8 +8,13 @@ use crate::modules::user::user_schema::Claims;
 use crate::errors::error::AppError;
 use crate::config::environment::Environment;
 
+/// Rejects requests without a valid bearer token and exposes its `Claims`.
 pub struct AuthMiddleware;
 
+/// Like `AuthMiddleware`, but lets requests without a token through so the
+/// handler can accept another credential. An invalid token is still rejected.
+pub struct OptionalAuthMiddleware;
+
 impl<S, B> Transform<S, ServiceRequest> for AuthMiddleware
 where
     S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
@@ -23,12 +28,30 @@ where
     type Future = Ready<Result<Self::Transform, Self::InitError>>;
 
     fn new_transform(&self, service: S) -> Self::Future {
-        ready(Ok(AuthMiddlewareService { service }))
+        ready(Ok(AuthMiddlewareService { service, required: true }))
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for OptionalAuthMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = AuthMiddlewareService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(AuthMiddlewareService { service, required: false }))
     }
 }
 
 pub struct AuthMiddlewareService<S> {
     service: S,
+    required: bool,
 }
 
 impl<S, B> Service<ServiceRequest> for AuthMiddlewareService<S>
@@ -58,6 +81,9 @@ where
 
         let token = match token {
             Some(token) => token,
+            None if !self.required => {
+                return Box::pin(self.service.call(req));
+            }
             None => {
                 return Box::pin(async move {
                     Err(AppError::Unauthorized("No token provided".to_string()).into())
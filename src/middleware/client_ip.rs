@@ -0,0 +1,188 @@
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    Error, HttpMessage,
+};
+use futures::future::{ready, LocalBoxFuture, Ready};
+use std::net::IpAddr;
+use std::rc::Rc;
+
+/// The real client address and scheme of a request, as seen past any trusted proxies.
+#[derive(Debug, Clone)]
+pub struct ClientInfo {
+    pub ip: Option<IpAddr>,
+    pub scheme: String,
+}
+
+/// Resolves `ClientInfo` for every request. X-Forwarded-For and X-Forwarded-Proto
+/// are only honored when the immediate peer is one of the trusted proxies, so a
+/// client connecting directly cannot spoof its address.
+pub struct ClientIpMiddleware {
+    trusted_proxies: Rc<Vec<IpAddr>>,
+}
+
+impl ClientIpMiddleware {
+    pub fn new(trusted_proxies: Vec<IpAddr>) -> Self {
+        Self { trusted_proxies: Rc::new(trusted_proxies) }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for ClientIpMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = ClientIpMiddlewareService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ClientIpMiddlewareService {
+            service,
+            trusted_proxies: self.trusted_proxies.clone(),
+        }))
+    }
+}
+
+pub struct ClientIpMiddlewareService<S> {
+    service: S,
+    trusted_proxies: Rc<Vec<IpAddr>>,
+}
+
+impl<S, B> Service<ServiceRequest> for ClientIpMiddlewareService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let peer = req.peer_addr().map(|addr| addr.ip());
+        let forwarded_for = req
+            .headers()
+            .get("X-Forwarded-For")
+            .and_then(|value| value.to_str().ok());
+        let forwarded_proto = req
+            .headers()
+            .get("X-Forwarded-Proto")
+            .and_then(|value| value.to_str().ok());
+
+        // connection_info() already trusts X-Forwarded-Proto from anyone, so the
+        // peer's scheme comes from how this server was reached
+        let peer_scheme = if req.app_config().secure() { "https" } else { "http" };
+        let client_info = resolve_client_info(
+            peer,
+            peer_scheme,
+            forwarded_for,
+            forwarded_proto,
+            &self.trusted_proxies,
+        );
+        req.extensions_mut().insert(client_info);
+
+        let fut = self.service.call(req);
+        Box::pin(async move {
+            let res = fut.await?;
+            Ok(res)
+        })
+    }
+}
+
+pub fn resolve_client_info(
+    peer: Option<IpAddr>,
+    peer_scheme: &str,
+    forwarded_for: Option<&str>,
+    forwarded_proto: Option<&str>,
+    trusted_proxies: &[IpAddr],
+) -> ClientInfo {
+    let peer_is_trusted = peer.is_some_and(|ip| trusted_proxies.contains(&ip));
+    if !peer_is_trusted {
+        return ClientInfo { ip: peer, scheme: peer_scheme.to_string() };
+    }
+
+    // Walk the chain from the nearest hop outwards, skipping our own proxies.
+    // The first untrusted address is the client; everything before it is
+    // client-controlled and cannot be relied on.
+    let mut ip = peer;
+    if let Some(chain) = forwarded_for {
+        for hop in chain.rsplit(',') {
+            match hop.trim().parse::<IpAddr>() {
+                Ok(hop_ip) => {
+                    ip = Some(hop_ip);
+                    if !trusted_proxies.contains(&hop_ip) {
+                        break;
+                    }
+                }
+                Err(_) => break,
+            }
+        }
+    }
+
+    let scheme = forwarded_proto
+        .and_then(|proto| proto.split(',').next())
+        .map(|proto| proto.trim().to_lowercase())
+        .filter(|proto| proto == "http" || proto == "https")
+        .unwrap_or_else(|| peer_scheme.to_string());
+
+    ClientInfo { ip, scheme }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{test as actix_test, web, App, HttpRequest, HttpResponse};
+
+    const PROXY: &str = "10.0.0.1";
+
+    async fn client_info(req: HttpRequest) -> HttpResponse {
+        let info = req.extensions().get::<ClientInfo>().cloned().unwrap();
+        HttpResponse::Ok().body(format!("{} {}", info.ip.map(|ip| ip.to_string()).unwrap_or_default(), info.scheme))
+    }
+
+    /// What the middleware resolved for a request from `peer` with forwarded headers.
+    async fn resolve(peer: &str, forwarded_for: &str, forwarded_proto: &str) -> String {
+        let app = actix_test::init_service(
+            App::new()
+                .wrap(ClientIpMiddleware::new(vec![PROXY.parse().unwrap()]))
+                .route("/", web::get().to(client_info)),
+        ).await;
+        let req = actix_test::TestRequest::get()
+            .uri("/")
+            .peer_addr(format!("{}:4000", peer).parse().unwrap())
+            .insert_header(("X-Forwarded-For", forwarded_for))
+            .insert_header(("X-Forwarded-Proto", forwarded_proto))
+            .to_request();
+        String::from_utf8(actix_test::call_and_read_body(&app, req).await.to_vec()).unwrap()
+    }
+
+    #[actix_web::test]
+    async fn forwarded_headers_from_an_untrusted_peer_are_ignored() {
+        assert_eq!(resolve("203.0.113.9", "198.51.100.7", "https").await, "203.0.113.9 http");
+    }
+
+    #[actix_web::test]
+    async fn forwarded_headers_from_a_trusted_proxy_are_honored() {
+        assert_eq!(resolve(PROXY, "198.51.100.7", "https").await, "198.51.100.7 https");
+    }
+
+    #[actix_web::test]
+    async fn spoofed_hops_before_the_proxy_are_not_the_client() {
+        // The client sent "1.2.3.4" itself; the proxy appended the address it saw
+        assert_eq!(resolve(PROXY, "1.2.3.4, 198.51.100.7", "https").await, "198.51.100.7 https");
+    }
+
+    #[test]
+    fn trusted_proxy_chains_are_walked_to_the_first_untrusted_hop() {
+        let proxies: Vec<IpAddr> = vec![PROXY.parse().unwrap(), "10.0.0.2".parse().unwrap()];
+        let info = resolve_client_info(Some(proxies[0]), "http", Some("198.51.100.7, 10.0.0.2"), Some("ftp"), &proxies);
+
+        assert_eq!(info.ip, Some("198.51.100.7".parse().unwrap()));
+        assert_eq!(info.scheme, "http");
+    }
+}
@@ -1,5 +1,8 @@
 pub mod auth;
+pub mod client_ip;
 pub mod error;
+pub mod permission;
+pub mod rate_limit;
  
  
  
@@ -0,0 +1,130 @@
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::StatusCode,
+    Error, HttpMessage,
+};
+use futures::future::{ready, LocalBoxFuture, Ready};
+use crate::errors::error::AppError;
+use crate::modules::user::user_permission::{role_has_permission, Permission};
+use crate::modules::user::user_schema::Claims;
+
+/// Rejects requests whose token role lacks the permission with a 403 naming it.
+/// Must run inside `AuthMiddleware`, i.e. be wrapped before it.
+pub struct RequirePermission(pub Permission);
+
+impl<S, B> Transform<S, ServiceRequest> for RequirePermission
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RequirePermissionService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequirePermissionService { service, permission: self.0 }))
+    }
+}
+
+pub struct RequirePermissionService<S> {
+    service: S,
+    permission: Permission,
+}
+
+impl<S, B> Service<ServiceRequest> for RequirePermissionService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let allowed = req
+            .extensions()
+            .get::<Claims>()
+            .is_some_and(|claims| role_has_permission(&claims.role, self.permission));
+
+        if !allowed {
+            let permission = self.permission;
+            return Box::pin(async move {
+                Err(AppError::coded(
+                    StatusCode::FORBIDDEN,
+                    "missing_permission",
+                    &format!("Missing permission: {}", permission),
+                )
+                .into())
+            });
+        }
+
+        let fut = self.service.call(req);
+        Box::pin(async move {
+            let res = fut.await?;
+            Ok(res)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{test as actix_test, web, App, HttpResponse};
+    use serde_json::Value;
+
+    /// Status and body of a request through `RequirePermission(ManageSystem)`,
+    /// signed in with `role` or not at all.
+    async fn call(role: Option<&'static str>) -> (StatusCode, Value) {
+        let app = actix_test::init_service(
+            App::new().service(
+                web::resource("/")
+                    .wrap(RequirePermission(Permission::ManageSystem))
+                    // Stands in for AuthMiddleware
+                    .wrap_fn(move |req, srv| {
+                        if let Some(role) = role {
+                            req.extensions_mut().insert(Claims {
+                                sub: "user".to_string(),
+                                exp: 0,
+                                iat: 0,
+                                email: "user@example.com".to_string(),
+                                role: role.to_string(),
+                            });
+                        }
+                        srv.call(req)
+                    })
+                    .route(web::get().to(|| async { HttpResponse::Ok().json(serde_json::json!({ "ok": true })) })),
+            ),
+        ).await;
+        let req = actix_test::TestRequest::get().uri("/").to_request();
+        match actix_test::try_call_service(&app, req).await {
+            Ok(response) => (response.status(), actix_test::read_body_json(response).await),
+            Err(e) => {
+                let response = e.error_response();
+                let status = response.status();
+                let body = actix_web::body::to_bytes(response.into_body()).await.unwrap();
+                (status, serde_json::from_slice(&body).unwrap())
+            }
+        }
+    }
+
+    #[actix_web::test]
+    async fn role_with_the_permission_passes() {
+        assert_eq!(call(Some("admin")).await, (StatusCode::OK, serde_json::json!({ "ok": true })));
+    }
+
+    #[actix_web::test]
+    async fn missing_permission_is_named_in_a_403() {
+        for role in [Some("member"), Some("org_admin"), None] {
+            let (status, body) = call(role).await;
+            assert_eq!(status, StatusCode::FORBIDDEN, "{:?}", role);
+            assert_eq!(body["code"], "missing_permission");
+            assert_eq!(body["message"], "Missing permission: manage_system");
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::StatusCode,
+    Error, HttpMessage,
+};
+use futures::future::{ready, LocalBoxFuture, Ready};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use crate::errors::error::AppError;
+use crate::middleware::client_ip::ClientInfo;
+
+/// Entries are pruned once the table grows past this many keys.
+const PRUNE_THRESHOLD: usize = 10_000;
+
+struct Window {
+    started: Instant,
+    count: u32,
+}
+
+type Buckets = Mutex<HashMap<(&'static str, IpAddr), Window>>;
+
+// Shared by every worker so the limit is per process, not per worker thread
+static BUCKETS: OnceLock<Buckets> = OnceLock::new();
+
+/// Fixed-window request limit per client IP, for unauthenticated routes.
+/// Must be wrapped inside `ClientIpMiddleware` so the real client address is known.
+/// Limits with the same `name` share a budget.
+#[derive(Clone, Copy)]
+pub struct RateLimit {
+    name: &'static str,
+    max_requests: u32,
+    window: Duration,
+}
+
+impl RateLimit {
+    pub fn new(name: &'static str, max_requests: u32, window: Duration) -> Self {
+        Self { name, max_requests, window }
+    }
+
+    fn check(&self, ip: IpAddr) -> bool {
+        let now = Instant::now();
+        let mut buckets = BUCKETS.get_or_init(Default::default).lock().unwrap();
+
+        if buckets.len() > PRUNE_THRESHOLD {
+            let window = self.window;
+            buckets.retain(|_, entry| now.duration_since(entry.started) < window);
+        }
+
+        let entry = buckets
+            .entry((self.name, ip))
+            .or_insert(Window { started: now, count: 0 });
+        if now.duration_since(entry.started) >= self.window {
+            entry.started = now;
+            entry.count = 0;
+        }
+
+        entry.count += 1;
+        entry.count <= self.max_requests
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RateLimit
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RateLimitService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RateLimitService { service, limit: *self }))
+    }
+}
+
+pub struct RateLimitService<S> {
+    service: S,
+    limit: RateLimit,
+}
+
+impl<S, B> Service<ServiceRequest> for RateLimitService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let ip = req.extensions().get::<ClientInfo>().and_then(|info| info.ip);
+
+        // Requests without a known address (e.g. unix sockets) are not limited
+        if let Some(ip) = ip
+            && !self.limit.check(ip) {
+            return Box::pin(async move {
+                Err(AppError::coded(
+                    StatusCode::TOO_MANY_REQUESTS,
+                    "rate_limited",
+                    "Too many requests, try again later",
+                )
+                .into())
+            });
+        }
+
+        let fut = self.service.call(req);
+        Box::pin(async move {
+            let res = fut.await?;
+            Ok(res)
+        })
+    }
+}
@@ -0,0 +1,486 @@
+use std::future::Future;
+use std::io::Write;
+use std::sync::Mutex;
+use std::time::{Duration as StdDuration, Instant};
+
+use actix_web::{http::header, web, HttpResponse};
+use chrono::{Duration, NaiveDate, Utc};
+use flate2::{write::GzEncoder, Compression};
+use hmac::{Hmac, Mac};
+use mongodb::bson::{oid::ObjectId, DateTime};
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use validator::Validate;
+
+use crate::app::AppState;
+use crate::errors::error::AppError;
+use crate::modules::admin::admin_crud::AdminAuditRepository;
+use crate::modules::admin::admin_model::{AdminAction, AdminAuditEntry};
+use crate::modules::admin::admin_schema::{
+    AdminOverviewResponse, AvailabilitySnapshotComparison, AvailabilitySnapshotQuery, AvailabilitySnapshotResponse,
+    DailyBookings, EmailOverview, OverviewSectionError, RetentionRunResponse, RunRetentionRequest, SetDiagnosticsRequest,
+    UserOverview,
+};
+use crate::modules::booking::booking_crud::{BookingRepository, RetentionRunRepository};
+use crate::modules::booking::booking_jobs;
+use crate::modules::booking::booking_schema::BookingResponse;
+use crate::modules::calendar::calendar_crud::{
+    AvailabilityRepository, AvailabilitySnapshotRepository, CalendarSettingsRepository, EventTypeRepository,
+};
+use crate::modules::calendar::calendar_model::DIAGNOSTICS_DAYS;
+use crate::modules::calendar::calendar_schema::{AvailabilityResponse, CalendarSettingsResponse, EventTypeResponse};
+use crate::modules::user::user_crud::UserRepository;
+use crate::modules::user::user_schema::Claims;
+use crate::services::notification_channel;
+
+/// How long a computed overview is served before the aggregates run again.
+const OVERVIEW_CACHE_TTL: StdDuration = StdDuration::from_secs(60);
+/// Longest a single overview aggregate may run before its section is reported as failed.
+const OVERVIEW_SECTION_TIMEOUT: StdDuration = StdDuration::from_secs(5);
+/// Days of booking volume on the overview, including today.
+const OVERVIEW_BOOKING_DAYS: i64 = 30;
+/// Keeps export signatures from being valid for anything else signed with the same secret.
+const EXPORT_SIGNATURE_DOMAIN: &[u8] = b"user-export:v1:";
+
+/// Shared by every worker; only complete overviews are cached.
+static OVERVIEW_CACHE: Mutex<Option<(Instant, AdminOverviewResponse)>> = Mutex::new(None);
+
+/// Admin routes are guarded by `RequirePermission` in the router, so handlers
+/// here can assume the caller is allowed.
+pub struct AdminController {
+    user_repository: UserRepository,
+    settings_repository: CalendarSettingsRepository,
+    availability_repository: AvailabilityRepository,
+    event_type_repository: EventTypeRepository,
+    snapshot_repository: AvailabilitySnapshotRepository,
+    booking_repository: BookingRepository,
+    audit_repository: AdminAuditRepository,
+    retention_run_repository: RetentionRunRepository,
+}
+
+impl AdminController {
+    pub fn new() -> Self {
+        let db = AppState::get().db.clone();
+        Self {
+            user_repository: UserRepository::new(),
+            settings_repository: CalendarSettingsRepository::new(db.clone()),
+            availability_repository: AvailabilityRepository::new(db.clone()),
+            event_type_repository: EventTypeRepository::new(db.clone()),
+            snapshot_repository: AvailabilitySnapshotRepository::new(db.clone()),
+            booking_repository: BookingRepository::new(db.clone()),
+            audit_repository: AdminAuditRepository::new(db.clone()),
+            retention_run_repository: RetentionRunRepository::new(db),
+        }
+    }
+
+    /// Dashboard aggregates, computed concurrently and cached for a minute.
+    pub async fn get_overview(&self) -> Result<HttpResponse, AppError> {
+        if let Some((computed_at, overview)) = OVERVIEW_CACHE.lock().unwrap().as_ref()
+            && computed_at.elapsed() < OVERVIEW_CACHE_TTL {
+            return Ok(HttpResponse::Ok().json(overview));
+        }
+
+        let today = Utc::now().date_naive();
+        let first_day = today - Duration::days(OVERVIEW_BOOKING_DAYS - 1);
+        let since = DateTime::from_millis(first_day.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp_millis());
+
+        let (users, users_with_settings, event_types, bookings) = futures::join!(
+            within_timeout(async {
+                self.user_repository.count_by_verification().await
+                    .map_err(|e| AppError::DatabaseError(e.to_string()))
+            }),
+            within_timeout(self.settings_repository.count_users()),
+            within_timeout(self.event_type_repository.count_all()),
+            within_timeout(self.booking_repository.count_created_per_day(since)),
+        );
+
+        let mut errors = Vec::new();
+        let users = settle("users", users, &mut errors).map(|(total, verified)| UserOverview {
+            total,
+            verified,
+            verified_ratio: ratio(verified, total),
+        });
+        let users_with_settings = settle("users_with_settings", users_with_settings, &mut errors);
+        let event_types = settle("event_types", event_types, &mut errors);
+        let bookings_per_day = settle("bookings_per_day", bookings, &mut errors).map(|counts| {
+            first_day
+                .iter_days()
+                .take_while(|day| *day <= today)
+                .map(|day| {
+                    let date = day.format("%Y-%m-%d").to_string();
+                    let bookings = counts.get(&date).copied().unwrap_or(0);
+                    DailyBookings { date, bookings }
+                })
+                .collect()
+        });
+
+        let queue = AppState::get().email_queue.stats();
+        let overview = AdminOverviewResponse {
+            users,
+            users_with_settings,
+            event_types,
+            bookings_per_day,
+            email: EmailOverview {
+                sent: queue.sent + queue.sent_inline,
+                failed: queue.failed,
+                failure_rate: ratio(queue.failed, queue.sent + queue.sent_inline + queue.failed),
+            },
+            errors,
+            generated_at: DateTime::now().to_string(),
+        };
+
+        // A partial overview is not cached, so the next request retries the failed sections
+        if overview.errors.is_empty() {
+            *OVERVIEW_CACHE.lock().unwrap() = Some((Instant::now(), overview.clone()));
+        }
+
+        Ok(HttpResponse::Ok().json(overview))
+    }
+
+    pub async fn get_email_queue(&self) -> Result<HttpResponse, AppError> {
+        Ok(HttpResponse::Ok().json(AppState::get().email_queue.stats()))
+    }
+
+    pub async fn flush_email_queue(&self) -> Result<HttpResponse, AppError> {
+        let email_queue = &AppState::get().email_queue;
+        email_queue.flush();
+
+        Ok(HttpResponse::Accepted().json(json!({
+            "message": "Email queue flush started",
+            "queue": email_queue.stats()
+        })))
+    }
+
+    /// Reminders sent and failed per channel since the server started, and
+    /// which provider carries SMS.
+    pub async fn get_message_channels(&self) -> Result<HttpResponse, AppError> {
+        Ok(HttpResponse::Ok().json(json!({
+            "channels": AppState::get().channel_metrics.stats(),
+            "sms_provider": notification_channel::sms_provider().name(),
+        })))
+    }
+
+    /// Everything stored about a user as a gzipped JSON attachment, for
+    /// support and data access requests. Credentials and tokens are left out.
+    /// The manifest holds a SHA-256 digest of each section and is signed with
+    /// the action signing secret, so a handed-out archive can be checked for
+    /// changes. Each export is recorded in the admin audit log.
+    pub async fn export_user(
+        &self,
+        claims: web::ReqData<Claims>,
+        user_id: ObjectId,
+    ) -> Result<HttpResponse, AppError> {
+        let claims = claims.into_inner();
+
+        let user = self.user_repository
+            .find_by_id(&user_id.to_hex())
+            .await?
+            .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+        let settings: Vec<CalendarSettingsResponse> = self.settings_repository
+            .find_by_user_id(&user_id)
+            .await?
+            .into_iter()
+            .map(CalendarSettingsResponse::from)
+            .collect();
+        let availability: Vec<AvailabilityResponse> = self.availability_repository
+            .find_all_by_user_id(&user_id, true)
+            .await?
+            .into_iter()
+            .map(AvailabilityResponse::from)
+            .collect();
+        let event_types: Vec<EventTypeResponse> = self.event_type_repository
+            .find_by_user_id(&user_id, true)
+            .await?
+            .into_iter()
+            .map(EventTypeResponse::from)
+            .collect();
+        let bookings: Vec<BookingResponse> = self.booking_repository
+            .find_by_user(&user_id, &user.email)
+            .await?
+            .into_iter()
+            .map(BookingResponse::from)
+            .collect();
+
+        let manifest = json!({
+            "user_id": user_id.to_hex(),
+            "exported_at": DateTime::now().to_string(),
+            "exported_by": claims.sub,
+            "counts": {
+                "profile": 1,
+                "calendar_settings": settings.len(),
+                "availability": availability.len(),
+                "event_types": event_types.len(),
+                "bookings": bookings.len(),
+            },
+        });
+        let sections = json!({
+            "profile": {
+                "id": user_id.to_hex(),
+                "email": user.email,
+                "name": user.name,
+                "role": user.role,
+                "is_verified": user.is_verified,
+                "created_at": user.created_at.to_string(),
+                "updated_at": user.updated_at.to_string(),
+            },
+            "calendar_settings": settings,
+            "availability": availability,
+            "event_types": event_types,
+            "bookings": bookings,
+        });
+        let secret = AppState::get().action_signing_secret.as_bytes();
+        let (document, signature) = signed_export(secret, manifest, sections);
+
+        // Recorded before the archive goes out, so no export is left unaudited
+        self.audit_repository
+            .record(&AdminAuditEntry {
+                id: None,
+                admin_user_id: claims.sub.clone(),
+                action: AdminAction::UserExport,
+                target_user_id: user_id,
+                signature: Some(signature),
+                created_at: DateTime::now(),
+            })
+            .await?;
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        serde_json::to_writer(&mut encoder, &document)
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        encoder.flush()
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        let archive = encoder.finish()
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+
+        Ok(HttpResponse::Ok()
+            .content_type("application/gzip")
+            .insert_header((
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"user-{}-export.json.gz\"", user_id.to_hex()),
+            ))
+            .body(archive))
+    }
+
+    /// Turns availability snapshots on for a user for the next few days, or off.
+    pub async fn set_diagnostics(
+        &self,
+        claims: web::ReqData<Claims>,
+        user_id: ObjectId,
+        data: web::Json<SetDiagnosticsRequest>,
+    ) -> Result<HttpResponse, AppError> {
+        let until = data.enabled.then(|| {
+            DateTime::from_millis((Utc::now() + Duration::days(DIAGNOSTICS_DAYS)).timestamp_millis())
+        });
+
+        let settings = self.settings_repository
+            .set_diagnostics_until(&user_id, until)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Calendar settings not found".to_string()))?;
+
+        self.audit_repository
+            .record(&AdminAuditEntry {
+                id: None,
+                admin_user_id: claims.sub.clone(),
+                action: if data.enabled { AdminAction::DiagnosticsEnabled } else { AdminAction::DiagnosticsDisabled },
+                target_user_id: user_id,
+                signature: None,
+                created_at: DateTime::now(),
+            })
+            .await?;
+
+        Ok(HttpResponse::Ok().json(json!({
+            "user_id": user_id.to_hex(),
+            "diagnostics_until": settings.diagnostics_until.map(|until| until.to_string()),
+        })))
+    }
+
+    /// Anonymizes old bookings now, or with `dry_run` only counts them. The
+    /// run is recorded either way.
+    pub async fn run_retention(
+        &self,
+        claims: web::ReqData<Claims>,
+        data: web::Json<RunRetentionRequest>,
+    ) -> Result<HttpResponse, AppError> {
+        data.validate()
+            .map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+        let months = data.months
+            .or(AppState::get().retention_months)
+            .ok_or_else(|| AppError::BadRequest("Set months, as RETENTION_MONTHS is not configured".to_string()))?;
+
+        let run = booking_jobs::apply_retention(
+            &self.booking_repository,
+            &self.retention_run_repository,
+            months,
+            data.dry_run,
+            &format!("admin {}", claims.sub),
+        ).await?;
+
+        Ok(HttpResponse::Ok().json(RetentionRunResponse::from(run)))
+    }
+
+    pub async fn get_availability_snapshots(
+        &self,
+        user_id: ObjectId,
+        query: web::Query<AvailabilitySnapshotQuery>,
+    ) -> Result<HttpResponse, AppError> {
+        let date = match &query.date {
+            Some(date) => NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .map_err(|_| AppError::BadRequest("Invalid date format. Use YYYY-MM-DD".to_string()))?,
+            None => Utc::now().date_naive(),
+        };
+        let previous = date - Duration::days(1);
+        let date = date.format("%Y-%m-%d").to_string();
+        let previous_date = previous.format("%Y-%m-%d").to_string();
+
+        let snapshots = self.snapshot_repository
+            .find_by_user_id_and_dates(&user_id, &[date.clone(), previous_date.clone()])
+            .await?;
+        let (snapshots, previous_snapshots): (Vec<_>, Vec<_>) = snapshots
+            .into_iter()
+            .partition(|snapshot| snapshot.date == date);
+
+        Ok(HttpResponse::Ok().json(AvailabilitySnapshotComparison {
+            date,
+            snapshots: snapshots.into_iter().map(AvailabilitySnapshotResponse::from).collect(),
+            previous_date,
+            previous_snapshots: previous_snapshots.into_iter().map(AvailabilitySnapshotResponse::from).collect(),
+        }))
+    }
+}
+
+/// The export document: each section of `sections` at the top level, the
+/// manifest with a `sections` map of their hex SHA-256 digests, and a
+/// `signature` over the manifest. Serialized JSON has sorted keys, so the
+/// digests and signature can be recomputed from the archive. Returns the
+/// document and the signature.
+fn signed_export(secret: &[u8], mut manifest: Value, sections: Value) -> (Value, String) {
+    let Value::Object(sections) = sections else {
+        unreachable!("Export sections are built as a JSON object");
+    };
+    let digests: Map<String, Value> = sections
+        .iter()
+        .map(|(name, section)| (name.clone(), Value::String(hex(&Sha256::digest(section.to_string().as_bytes())))))
+        .collect();
+    manifest["sections"] = Value::Object(digests);
+    let signature = sign_manifest(secret, &manifest);
+
+    let mut document = sections;
+    document.insert("manifest".to_string(), manifest);
+    document.insert("signature".to_string(), json!({ "algorithm": "HMAC-SHA256", "value": signature }));
+    (Value::Object(document), signature)
+}
+
+fn sign_manifest(secret: &[u8], manifest: &Value) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(EXPORT_SIGNATURE_DOMAIN);
+    mac.update(manifest.to_string().as_bytes());
+    hex(&mac.finalize().into_bytes())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+async fn within_timeout<T>(future: impl Future<Output = Result<T, AppError>>) -> Result<T, String> {
+    match tokio::time::timeout(OVERVIEW_SECTION_TIMEOUT, future).await {
+        Ok(result) => result.map_err(|e| e.to_string()),
+        Err(_) => Err(format!("Timed out after {} seconds", OVERVIEW_SECTION_TIMEOUT.as_secs())),
+    }
+}
+
+/// Keeps a section's value, or records why it is missing.
+fn settle<T>(section: &'static str, result: Result<T, String>, errors: &mut Vec<OverviewSectionError>) -> Option<T> {
+    match result {
+        Ok(value) => Some(value),
+        Err(message) => {
+            println!("Admin overview section {} failed: {}", section, message);
+            errors.push(OverviewSectionError { section, message });
+            None
+        }
+    }
+}
+
+fn ratio(part: u64, whole: u64) -> Option<f64> {
+    if whole == 0 {
+        return None;
+    }
+    Some((part as f64 / whole as f64 * 1000.0).round() / 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &[u8] = b"test-signing-secret";
+
+    fn export() -> Value {
+        let manifest = json!({ "user_id": "u1", "counts": { "bookings": 1 } });
+        let sections = json!({
+            "profile": { "email": "host@example.com" },
+            "bookings": [{ "id": "b1", "invitee_name": "Ivy" }],
+        });
+        signed_export(SECRET, manifest, sections).0
+    }
+
+    /// Whether the digests in the manifest match the sections and the
+    /// signature matches the manifest, as someone checking an archive would.
+    fn is_intact(document: &Value, secret: &[u8]) -> bool {
+        let manifest = &document["manifest"];
+        let digests_match = manifest["sections"].as_object().unwrap().iter().all(|(name, digest)| {
+            digest.as_str() == Some(hex(&Sha256::digest(document[name].to_string().as_bytes())).as_str())
+        });
+        digests_match && document["signature"]["value"].as_str() == Some(sign_manifest(secret, manifest).as_str())
+    }
+
+    #[test]
+    fn an_export_verifies_with_the_signing_secret() {
+        let document = export();
+
+        assert_eq!(document["signature"]["algorithm"], "HMAC-SHA256");
+        assert_eq!(document["bookings"][0]["id"], "b1");
+        assert!(is_intact(&document, SECRET));
+        assert!(!is_intact(&document, b"another-secret"));
+    }
+
+    #[test]
+    fn a_changed_section_or_manifest_fails_verification() {
+        let mut document = export();
+        document["bookings"][0]["invitee_name"] = json!("Mallory");
+        assert!(!is_intact(&document, SECRET));
+
+        let mut document = export();
+        document["manifest"]["counts"]["bookings"] = json!(0);
+        assert!(!is_intact(&document, SECRET));
+    }
+
+    #[test]
+    fn a_manifest_serializes_the_same_after_a_round_trip() {
+        let document = export();
+        let reread: Value = serde_json::from_str(&document.to_string()).unwrap();
+
+        assert!(is_intact(&reread, SECRET));
+    }
+
+    #[test]
+    fn ratios_round_to_three_places_and_need_a_whole() {
+        assert_eq!(ratio(1, 3), Some(0.333));
+        assert_eq!(ratio(2, 3), Some(0.667));
+        assert_eq!(ratio(0, 5), Some(0.0));
+        assert_eq!(ratio(0, 0), None);
+    }
+
+    #[actix_web::test]
+    async fn a_failed_section_is_reported_instead_of_failing_the_overview() {
+        let mut errors = Vec::new();
+
+        let users = within_timeout(async { Ok::<_, AppError>(3u64) }).await;
+        let event_types = within_timeout(async { Err::<u64, _>(AppError::DatabaseError("connection reset".to_string())) }).await;
+
+        assert_eq!(settle("users", users, &mut errors), Some(3));
+        assert_eq!(settle("event_types", event_types, &mut errors), None);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].section, "event_types");
+        assert!(errors[0].message.contains("connection reset"), "{}", errors[0].message);
+    }
+}
@@ -0,0 +1,75 @@
+use mongodb::{bson::doc, Collection, Database, IndexModel};
+use crate::errors::error::AppError;
+use crate::modules::admin::admin_model::AdminAuditEntry;
+
+pub struct AdminAuditRepository {
+    collection: Collection<AdminAuditEntry>,
+}
+
+impl AdminAuditRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection("admin_audit_log");
+        Self { collection }
+    }
+
+    /// Creates the per-user index. Entries are never expired. Safe to call on every start.
+    pub async fn ensure_indexes(&self) -> Result<(), AppError> {
+        let index = IndexModel::builder()
+            .keys(doc! { "target_user_id": 1, "created_at": -1 })
+            .build();
+
+        self.collection
+            .create_index(index, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Unlike the message log this is not best effort: callers fail the
+    /// action when it cannot be recorded.
+    pub async fn record(&self, entry: &AdminAuditEntry) -> Result<(), AppError> {
+        self.collection
+            .insert_one(entry, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mongodb::bson::{oid::ObjectId, DateTime};
+
+    use super::*;
+    use crate::modules::admin::admin_model::AdminAction;
+    use crate::test_support::with_database;
+
+    #[test]
+    fn recorded_entries_are_stored() {
+        with_database(|db| async move {
+            let repository = AdminAuditRepository::new(db.clone());
+            let target_user_id = ObjectId::new();
+            let entry = AdminAuditEntry {
+                id: None,
+                admin_user_id: ObjectId::new().to_hex(),
+                action: AdminAction::UserExport,
+                target_user_id,
+                signature: Some("abc".to_string()),
+                created_at: DateTime::now(),
+            };
+
+            repository.record(&entry).await.unwrap();
+
+            let stored = repository.collection
+                .find_one(doc! { "target_user_id": target_user_id }, None)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(stored.action, AdminAction::UserExport);
+            assert_eq!(stored.admin_user_id, entry.admin_user_id);
+            assert_eq!(stored.signature.as_deref(), Some("abc"));
+        });
+    }
+}
@@ -0,0 +1,22 @@
+use mongodb::bson::{DateTime, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AdminAction {
+    UserExport,
+    DiagnosticsEnabled,
+    DiagnosticsDisabled,
+}
+
+/// Something an admin did to another user's data, kept for audits.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AdminAuditEntry {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub admin_user_id: String,  // The admin's user id, from their token
+    pub action: AdminAction,
+    pub target_user_id: ObjectId,
+    pub signature: Option<String>,  // The export's manifest signature, to match a handed-out archive
+    pub created_at: DateTime,
+}
@@ -0,0 +1,89 @@
+use actix_web::{web, Scope};
+use crate::modules::admin::admin_controller::AdminController;
+use crate::modules::admin::admin_schema::{AvailabilitySnapshotQuery, RunRetentionRequest, SetDiagnosticsRequest};
+use crate::modules::user::user_schema::Claims;
+use crate::errors::error::AppError;
+use crate::errors::error_handler::method_not_allowed;
+use crate::middleware::auth::AuthMiddleware;
+use crate::middleware::permission::RequirePermission;
+use crate::modules::user::user_permission::Permission;
+use crate::utils::object_id::PathObjectId;
+
+pub fn admin_routes() -> Result<Scope, AppError> {
+    let controller = web::Data::new(AdminController::new());
+
+    Ok(web::scope("/admin")
+        .app_data(controller.clone())
+        .service(
+            web::resource("/overview")
+                .default_service(method_not_allowed("GET"))
+                .wrap(RequirePermission(Permission::AdminUsers))
+                .wrap(AuthMiddleware)
+                .route(web::get().to(|controller: web::Data<AdminController>| {
+                    async move { controller.get_overview().await }
+                }))
+        )
+        .service(
+            web::resource("/email-queue")
+                .default_service(method_not_allowed("GET"))
+                .wrap(RequirePermission(Permission::ManageSystem))
+                .wrap(AuthMiddleware)
+                .route(web::get().to(|controller: web::Data<AdminController>| {
+                    async move { controller.get_email_queue().await }
+                }))
+        )
+        .service(
+            web::resource("/email-queue/flush")
+                .default_service(method_not_allowed("POST"))
+                .wrap(RequirePermission(Permission::ManageSystem))
+                .wrap(AuthMiddleware)
+                .route(web::post().to(|controller: web::Data<AdminController>| {
+                    async move { controller.flush_email_queue().await }
+                }))
+        )
+        .service(
+            web::resource("/message-channels")
+                .default_service(method_not_allowed("GET"))
+                .wrap(RequirePermission(Permission::ManageSystem))
+                .wrap(AuthMiddleware)
+                .route(web::get().to(|controller: web::Data<AdminController>| {
+                    async move { controller.get_message_channels().await }
+                }))
+        )
+        .service(
+            web::resource("/retention/run")
+                .default_service(method_not_allowed("POST"))
+                .wrap(RequirePermission(Permission::ManageSystem))
+                .wrap(AuthMiddleware)
+                .route(web::post().to(|claims: web::ReqData<Claims>, data: web::Json<RunRetentionRequest>, controller: web::Data<AdminController>| {
+                    async move { controller.run_retention(claims, data).await }
+                }))
+        )
+        .service(
+            web::resource("/users/{id}/export")
+                .default_service(method_not_allowed("POST"))
+                .wrap(RequirePermission(Permission::AdminUsers))
+                .wrap(AuthMiddleware)
+                .route(web::post().to(|claims: web::ReqData<Claims>, PathObjectId(user_id): PathObjectId, controller: web::Data<AdminController>| {
+                    async move { controller.export_user(claims, user_id).await }
+                }))
+        )
+        .service(
+            web::resource("/users/{id}/diagnostics")
+                .default_service(method_not_allowed("POST"))
+                .wrap(RequirePermission(Permission::AdminUsers))
+                .wrap(AuthMiddleware)
+                .route(web::post().to(|claims: web::ReqData<Claims>, PathObjectId(user_id): PathObjectId, data: web::Json<SetDiagnosticsRequest>, controller: web::Data<AdminController>| {
+                    async move { controller.set_diagnostics(claims, user_id, data).await }
+                }))
+        )
+        .service(
+            web::resource("/users/{id}/availability-snapshots")
+                .default_service(method_not_allowed("GET"))
+                .wrap(RequirePermission(Permission::AdminUsers))
+                .wrap(AuthMiddleware)
+                .route(web::get().to(|PathObjectId(user_id): PathObjectId, query: web::Query<AvailabilitySnapshotQuery>, controller: web::Data<AdminController>| {
+                    async move { controller.get_availability_snapshots(user_id, query).await }
+                }))
+        ))
+}
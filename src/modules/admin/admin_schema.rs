@@ -0,0 +1,131 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+use crate::modules::booking::booking_model::RetentionRun;
+use crate::modules::calendar::calendar_model::AvailabilitySnapshot;
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct SetDiagnosticsRequest {
+    pub enabled: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct AvailabilitySnapshotQuery {
+    pub date: Option<String>,  // YYYY-MM-DD format, defaults to today (UTC)
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct RunRetentionRequest {
+    #[serde(default)]
+    pub dry_run: bool,  // Only count the bookings a run would anonymize
+    #[validate(range(min = 1, max = 1200, message = "Months must be between 1 and 1200"))]
+    pub months: Option<u32>,  // Defaults to RETENTION_MONTHS
+}
+
+#[derive(Debug, Serialize)]
+pub struct RetentionRunResponse {
+    pub id: String,
+    pub cutoff_date: String,
+    pub months: u32,
+    pub dry_run: bool,
+    pub matched: u64,
+    pub anonymized: u64,
+    pub triggered_by: String,
+    pub started_at: String,
+    pub finished_at: String,
+}
+
+impl From<RetentionRun> for RetentionRunResponse {
+    fn from(run: RetentionRun) -> Self {
+        Self {
+            id: run.id.map(|id| id.to_hex()).unwrap_or_default(),
+            cutoff_date: run.cutoff_date,
+            months: run.months,
+            dry_run: run.dry_run,
+            matched: run.matched,
+            anonymized: run.anonymized,
+            triggered_by: run.triggered_by,
+            started_at: run.started_at.to_string(),
+            finished_at: run.finished_at.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct AvailabilitySnapshotResponse {
+    pub event_type_id: String,
+    pub inputs_hash: String,
+    pub window_start: String,
+    pub window_end: String,
+    pub slots_per_day: BTreeMap<String, u32>,
+    pub engine_version: String,
+    pub computed_at: String,
+}
+
+impl From<AvailabilitySnapshot> for AvailabilitySnapshotResponse {
+    fn from(snapshot: AvailabilitySnapshot) -> Self {
+        Self {
+            event_type_id: snapshot.event_type_id.to_hex(),
+            inputs_hash: snapshot.inputs_hash,
+            window_start: snapshot.window_start,
+            window_end: snapshot.window_end,
+            slots_per_day: snapshot.slots_per_day,
+            engine_version: snapshot.engine_version,
+            computed_at: snapshot.computed_at.to_string(),
+        }
+    }
+}
+
+/// Snapshots of the requested day next to the day before, so drops in slot counts stand out.
+#[derive(Debug, Serialize)]
+pub struct AvailabilitySnapshotComparison {
+    pub date: String,
+    pub snapshots: Vec<AvailabilitySnapshotResponse>,
+    pub previous_date: String,
+    pub previous_snapshots: Vec<AvailabilitySnapshotResponse>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UserOverview {
+    pub total: u64,
+    pub verified: u64,
+    pub verified_ratio: Option<f64>,  // None when there are no users
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DailyBookings {
+    pub date: String,  // YYYY-MM-DD (UTC)
+    pub bookings: u64,
+}
+
+/// Outgoing email since the server started, from the in-process queue.
+#[derive(Debug, Clone, Serialize)]
+pub struct EmailOverview {
+    pub sent: u64,
+    pub failed: u64,
+    pub failure_rate: Option<f64>,  // None before any email was attempted
+}
+
+/// A section of the overview that could not be computed; the section itself is null.
+#[derive(Debug, Clone, Serialize)]
+pub struct OverviewSectionError {
+    pub section: &'static str,
+    pub message: String,
+}
+
+/// System-wide numbers for the operator dashboard. Sections degrade
+/// independently, so a failed aggregate leaves the rest intact.
+#[derive(Debug, Clone, Serialize)]
+pub struct AdminOverviewResponse {
+    pub users: Option<UserOverview>,
+    pub users_with_settings: Option<u64>,
+    pub event_types: Option<u64>,
+    pub bookings_per_day: Option<Vec<DailyBookings>>,  // Oldest first, days without bookings included
+    pub email: EmailOverview,
+    pub errors: Vec<OverviewSectionError>,
+    pub generated_at: String,
+}
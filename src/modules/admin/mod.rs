@@ -0,0 +1,5 @@
+pub mod admin_model;
+pub mod admin_crud;
+pub mod admin_schema;
+pub mod admin_controller;
+pub mod admin_router;
@@ -0,0 +1,168 @@
+use std::collections::HashSet;
+
+use actix_web::{web, HttpResponse};
+use chrono::{Duration, NaiveDate, Utc};
+use mongodb::bson::{oid::ObjectId, DateTime};
+use mongodb::Database;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::errors::error::AppError;
+use crate::modules::analytics::analytics_crud::AnalyticsRepository;
+use crate::modules::analytics::analytics_model::{AnalyticsEvent, FunnelStep};
+use crate::modules::analytics::analytics_schema::{
+    EventTypeFunnel, FunnelQuery, FunnelResponse, FunnelStepStats,
+    IngestAnalyticsRequest, IngestAnalyticsResponse,
+};
+use crate::modules::calendar::calendar_crud::EventTypeRepository;
+use crate::modules::calendar::calendar_engine;
+use crate::modules::user::user_schema::Claims;
+
+const DEFAULT_RANGE_DAYS: i64 = 30;
+
+pub struct AnalyticsController {
+    analytics_repository: AnalyticsRepository,
+    event_type_repository: EventTypeRepository,
+}
+
+impl AnalyticsController {
+    pub fn new(db: Database) -> Self {
+        Self {
+            analytics_repository: AnalyticsRepository::new(db.clone()),
+            event_type_repository: EventTypeRepository::new(db),
+        }
+    }
+
+    pub async fn ingest(
+        &self,
+        data: web::Json<IngestAnalyticsRequest>,
+    ) -> Result<HttpResponse, AppError> {
+        // Validate request data
+        data.validate()
+            .map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+        let data = data.into_inner();
+        let received_at = DateTime::now();
+
+        let mut events = Vec::with_capacity(data.events.len());
+        for event in data.events {
+            let event_type_id = ObjectId::parse_str(&event.event_type_id)
+                .map_err(|_| AppError::BadRequest("Invalid event type ID".to_string()))?;
+            let session_id = Uuid::parse_str(&event.session_id)
+                .map_err(|_| AppError::BadRequest("Session ID must be a uuid".to_string()))?;
+            let occurred_at = DateTime::parse_rfc3339_str(&event.timestamp)
+                .map_err(|_| AppError::BadRequest("Invalid timestamp format".to_string()))?;
+
+            events.push(AnalyticsEvent {
+                id: None,
+                name: event.name,
+                event_type_id,
+                session_id: session_id.to_string(),
+                occurred_at,
+                date: calendar_engine::to_naive_date(&occurred_at).format("%Y-%m-%d").to_string(),
+                received_at,
+            });
+        }
+
+        // Only keep events for event types that exist and are bookable
+        let requested_ids: Vec<ObjectId> = events
+            .iter()
+            .map(|event| event.event_type_id)
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        let known_ids: HashSet<ObjectId> = self.event_type_repository
+            .find_active_by_ids(&requested_ids)
+            .await?
+            .into_iter()
+            .filter_map(|event_type| event_type.id)
+            .collect();
+
+        let total = events.len();
+        events.retain(|event| known_ids.contains(&event.event_type_id));
+        let accepted = events.len();
+
+        self.analytics_repository.insert_many(events).await?;
+
+        Ok(HttpResponse::Accepted().json(IngestAnalyticsResponse {
+            accepted,
+            dropped: total - accepted,
+        }))
+    }
+
+    pub async fn funnel(
+        &self,
+        claims: web::ReqData<Claims>,
+        query: web::Query<FunnelQuery>,
+    ) -> Result<HttpResponse, AppError> {
+        let claims = claims.into_inner();
+        let user_id = ObjectId::parse_str(&claims.sub)
+            .map_err(|_| AppError::BadRequest("Invalid user ID".to_string()))?;
+
+        let parse_date = |value: &str| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .map_err(|_| AppError::BadRequest("Invalid date format. Use YYYY-MM-DD".to_string()))
+        };
+        let end_date = match &query.end_date {
+            Some(value) => parse_date(value)?,
+            None => Utc::now().date_naive(),
+        };
+        let start_date = match &query.start_date {
+            Some(value) => parse_date(value)?,
+            None => end_date - Duration::days(DEFAULT_RANGE_DAYS - 1),
+        };
+        if start_date > end_date {
+            return Err(AppError::BadRequest("start_date must not be after end_date".to_string()));
+        }
+
+        let start_date = start_date.format("%Y-%m-%d").to_string();
+        let end_date = end_date.format("%Y-%m-%d").to_string();
+
+        let event_types = self.event_type_repository.find_by_user_id(&user_id, true).await?;
+        let event_type_ids: Vec<ObjectId> = event_types.iter().filter_map(|et| et.id).collect();
+        let counts = self.analytics_repository
+            .count_sessions(&event_type_ids, &start_date, &end_date)
+            .await?;
+
+        let funnels = event_types
+            .into_iter()
+            .filter_map(|event_type| {
+                let id = event_type.id?;
+                let mut steps = Vec::with_capacity(FunnelStep::ALL.len());
+                let mut previous: Option<u64> = None;
+                for step in FunnelStep::ALL {
+                    let sessions = counts.get(&(id, step)).copied().unwrap_or(0);
+                    steps.push(FunnelStepStats {
+                        step,
+                        sessions,
+                        conversion_percent: previous.and_then(|prev| percent(sessions, prev)),
+                    });
+                    previous = Some(sessions);
+                }
+
+                let viewed = steps.first().map(|s| s.sessions).unwrap_or(0);
+                let booked = steps.last().map(|s| s.sessions).unwrap_or(0);
+
+                Some(EventTypeFunnel {
+                    event_type_id: id.to_hex(),
+                    name: event_type.name,
+                    steps,
+                    overall_conversion_percent: percent(booked, viewed),
+                })
+            })
+            .collect();
+
+        Ok(HttpResponse::Ok().json(FunnelResponse {
+            start_date,
+            end_date,
+            event_types: funnels,
+        }))
+    }
+}
+
+fn percent(part: u64, whole: u64) -> Option<f64> {
+    if whole == 0 {
+        return None;
+    }
+    Some((part as f64 / whole as f64 * 1000.0).round() / 10.0)
+}
@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use mongodb::{
+    bson::{self, doc, oid::ObjectId, Bson},
+    options::IndexOptions,
+    Collection, Database, IndexModel,
+};
+use futures::TryStreamExt;
+use crate::errors::error::AppError;
+use crate::modules::analytics::analytics_model::{AnalyticsEvent, FunnelStep};
+
+const RETENTION: Duration = Duration::from_secs(90 * 24 * 60 * 60);
+
+pub struct AnalyticsRepository {
+    collection: Collection<AnalyticsEvent>,
+}
+
+impl AnalyticsRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection("analytics_events");
+        Self { collection }
+    }
+
+    /// Creates the funnel query index and the 90-day TTL index. Safe to call on every start.
+    pub async fn ensure_indexes(&self) -> Result<(), AppError> {
+        let indexes = vec![
+            IndexModel::builder()
+                .keys(doc! { "event_type_id": 1, "name": 1, "date": 1 })
+                .build(),
+            IndexModel::builder()
+                .keys(doc! { "received_at": 1 })
+                .options(IndexOptions::builder().expire_after(RETENTION).build())
+                .build(),
+        ];
+
+        self.collection
+            .create_indexes(indexes, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    pub async fn insert_many(&self, events: Vec<AnalyticsEvent>) -> Result<(), AppError> {
+        if events.is_empty() {
+            return Ok(());
+        }
+
+        self.collection
+            .insert_many(events, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Distinct sessions per event type and funnel step between two YYYY-MM-DD dates, inclusive.
+    pub async fn count_sessions(
+        &self,
+        event_type_ids: &[ObjectId],
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<HashMap<(ObjectId, FunnelStep), u64>, AppError> {
+        let pipeline = vec![
+            doc! { "$match": {
+                "event_type_id": { "$in": event_type_ids },
+                "date": { "$gte": start_date, "$lte": end_date },
+            } },
+            doc! { "$group": {
+                "_id": { "event_type_id": "$event_type_id", "name": "$name" },
+                "sessions": { "$addToSet": "$session_id" },
+            } },
+            doc! { "$project": { "sessions": { "$size": "$sessions" } } },
+        ];
+
+        let mut cursor = self.collection
+            .aggregate(pipeline, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let mut counts = HashMap::new();
+        while let Some(row) = cursor.try_next().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))? {
+            let Ok(key) = row.get_document("_id") else { continue };
+            let Ok(event_type_id) = key.get_object_id("event_type_id") else { continue };
+            let Some(Ok(step)) = key.get("name").cloned().map(bson::from_bson::<FunnelStep>) else { continue };
+            let sessions = match row.get("sessions") {
+                Some(Bson::Int32(n)) => *n as u64,
+                Some(Bson::Int64(n)) => *n as u64,
+                _ => 0,
+            };
+            counts.insert((event_type_id, step), sessions);
+        }
+
+        Ok(counts)
+    }
+}
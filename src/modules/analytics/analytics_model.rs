@@ -0,0 +1,34 @@
+use mongodb::bson::{DateTime, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+
+/// Steps of the public booking funnel, in order.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum FunnelStep {
+    PageViewed,
+    SlotsViewed,
+    BookingStarted,
+    Booked,
+}
+
+impl FunnelStep {
+    pub const ALL: [FunnelStep; 4] = [
+        FunnelStep::PageViewed,
+        FunnelStep::SlotsViewed,
+        FunnelStep::BookingStarted,
+        FunnelStep::Booked,
+    ];
+}
+
+/// An anonymous event reported by the public booking page.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AnalyticsEvent {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub name: FunnelStep,
+    pub event_type_id: ObjectId,
+    pub session_id: String,    // Client-generated uuid
+    pub occurred_at: DateTime, // Client-reported time
+    pub date: String,          // YYYY-MM-DD of occurred_at, for range queries
+    pub received_at: DateTime, // Server time; drives the TTL
+}
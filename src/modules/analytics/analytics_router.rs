@@ -0,0 +1,44 @@
+use std::time::Duration;
+
+use actix_web::{web, Scope};
+use crate::modules::analytics::analytics_controller::AnalyticsController;
+use crate::modules::analytics::analytics_schema::{FunnelQuery, IngestAnalyticsRequest};
+use crate::modules::user::user_schema::Claims;
+use crate::errors::error::AppError;
+use crate::errors::error_handler::method_not_allowed;
+use crate::middleware::auth::AuthMiddleware;
+use crate::middleware::rate_limit::RateLimit;
+use crate::app::AppState;
+
+/// Batches allowed per client IP per minute on the public ingestion endpoint.
+const INGEST_REQUESTS_PER_MINUTE: u32 = 30;
+
+pub fn analytics_routes() -> Result<Scope, AppError> {
+    let controller = web::Data::new(AnalyticsController::new(AppState::get().db.clone()));
+
+    Ok(web::scope("/analytics")
+        .app_data(controller.clone())
+        .service(
+            web::resource("/funnel")
+                .default_service(method_not_allowed("GET"))
+                .wrap(AuthMiddleware)
+                .route(web::get().to(|claims: web::ReqData<Claims>, query: web::Query<FunnelQuery>, controller: web::Data<AnalyticsController>| {
+                    async move { controller.funnel(claims, query).await }
+                }))
+        ))
+}
+
+pub fn public_analytics_routes() -> Result<Scope, AppError> {
+    let controller = web::Data::new(AnalyticsController::new(AppState::get().db.clone()));
+
+    Ok(web::scope("/public/analytics")
+        .app_data(controller.clone())
+        .service(
+            web::resource("")
+                .default_service(method_not_allowed("POST"))
+                .wrap(RateLimit::new("analytics_ingest", INGEST_REQUESTS_PER_MINUTE, Duration::from_secs(60)))
+                .route(web::post().to(|data: web::Json<IngestAnalyticsRequest>, controller: web::Data<AnalyticsController>| {
+                    async move { controller.ingest(data).await }
+                }))
+        ))
+}
@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+use crate::modules::analytics::analytics_model::FunnelStep;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AnalyticsEventRequest {
+    pub name: FunnelStep,
+    pub event_type_id: String,
+    pub session_id: String,  // uuid
+    pub timestamp: String,   // ISO 8601 format
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct IngestAnalyticsRequest {
+    #[validate(length(min = 1, max = 50, message = "A batch must contain between 1 and 50 events"))]
+    pub events: Vec<AnalyticsEventRequest>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IngestAnalyticsResponse {
+    pub accepted: usize,
+    pub dropped: usize,  // Events for unknown or inactive event types
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FunnelQuery {
+    pub start_date: Option<String>,  // YYYY-MM-DD, defaults to 29 days before end_date
+    pub end_date: Option<String>,    // YYYY-MM-DD, defaults to today
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FunnelStepStats {
+    pub step: FunnelStep,
+    pub sessions: u64,
+    pub conversion_percent: Option<f64>,  // Relative to the previous step
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EventTypeFunnel {
+    pub event_type_id: String,
+    pub name: String,
+    pub steps: Vec<FunnelStepStats>,
+    pub overall_conversion_percent: Option<f64>,  // Booked relative to page views
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FunnelResponse {
+    pub start_date: String,
+    pub end_date: String,
+    pub event_types: Vec<EventTypeFunnel>,
+}
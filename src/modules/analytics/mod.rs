@@ -0,0 +1,5 @@
+pub mod analytics_model;
+pub mod analytics_schema;
+pub mod analytics_crud;
+pub mod analytics_controller;
+pub mod analytics_router;
@@ -0,0 +1,2875 @@
+use std::collections::{BTreeMap, HashMap};
+
+use actix_web::{http::{header, StatusCode}, web, HttpRequest, HttpResponse};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use chrono_tz::Tz;
+use futures::{stream, StreamExt};
+use mongodb::bson::{oid::ObjectId, DateTime};
+use mongodb::Database;
+use rand::{thread_rng, Rng};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use validator::{Validate, ValidateEmail};
+
+use uuid::Uuid;
+
+use crate::app::AppState;
+use crate::errors::error::AppError;
+use crate::modules::booking::booking_crud::{BookingRepository, ConsumedActionRepository, IdempotencyRepository, SlotHoldRepository};
+use crate::modules::booking::booking_model::{
+    AnswerValue, Booking, BookingAnswer, BookingAttendee, BookingStatus, BookingTracking, PreviousSlot, SlotHold,
+    MAX_ATTENDEES,
+};
+use crate::modules::booking::booking_schema::{
+    AddAttendeeRequest, AttendeePath, BookingDetailResponse, BookingEventTypeSummary, BookingHistoryEntry, BookingResponse, BookingStatsResponse,
+    CancelBookingRequest, CreateBookingRequest, CreateSlotHoldRequest, EventTypeBookingStats, ExistingBookingQuery,
+    ExistingBookingResponse, ExportBookingsQuery,
+    LabeledAnswer, MarkNoShowRequest, Prefill, PublicAvailabilityQuery, PublicBookingRequest, PublicCancelBookingRequest,
+    PublicEmbedConfigResponse, PublicEventTypeResponse, PublicRescheduleBookingRequest, RescheduleBookingRequest, SlotHoldResponse, UpdateBookingStatusRequest,
+};
+use crate::modules::booking::state;
+use crate::modules::calendar::calendar_crud::{
+    AvailabilityRepository, AvailabilitySnapshotRepository, CalendarSettingsRepository, EventTypeRepository,
+};
+use crate::modules::calendar::calendar_engine;
+use crate::modules::calendar::calendar_model::{AvailabilitySnapshot, CalendarSettings, ConfirmationSettings, EventType, Location, NoticePolicy, Question, QuestionKind, RoundRobinStrategy, WhoCalls};
+use crate::modules::calendar::calendar_schema::{CheckAvailabilityResponse, SlotConflict};
+use crate::modules::calendar::calendar_slots::{BusyTime, HostSchedule, SlotSources};
+use crate::modules::notification::notification_crud::NotificationRepository;
+use crate::modules::notification::notification_model::NotificationKind;
+use crate::modules::user::user_crud::UserRepository;
+use crate::modules::user::user_schema::Claims;
+use crate::services::conferencing;
+use crate::services::email::CustomCopy;
+use crate::services::email_queue::{EmailJob, EmailQueue};
+use crate::utils::csv;
+use crate::utils::date_format;
+use crate::utils::ics::{self, IcsAttendee, IcsEvent, IcsMethod};
+use crate::utils::object_id::PathObjectId;
+use crate::utils::signed_actions::{self, ActionClaims, SignedAction, SignedActionError};
+use crate::utils::template::{self, TemplateContext};
+use crate::utils::text;
+use crate::utils::time_of_day;
+use crate::utils::timezone::{self, TimezoneResolution};
+use crate::utils::validation;
+
+/// Longest date range a public availability request may cover.
+const PUBLIC_AVAILABILITY_MAX_DAYS: i64 = 62;
+/// How long the approve and decline links in a booking request email work.
+const ACTION_LINK_HOURS: i64 = 24;
+/// Recorded as the actor of changes made through signed links.
+const SIGNED_ACTION_ACTOR: &str = "signed-action";
+/// Header clients send to make retried booking requests safe.
+const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+const IDEMPOTENCY_KEY_MAX_CHARS: usize = 255;
+/// Limits on booking tracking data, which anyone booking can send.
+const TRACKING_MAX_METADATA_KEYS: usize = 10;
+const TRACKING_MAX_KEY_CHARS: usize = 64;
+const TRACKING_MAX_VALUE_CHARS: usize = 256;
+/// Query parameters of a public booking link that prefill an answer start
+/// with this, followed by the question key.
+const PREFILL_ANSWER_PREFIX: &str = "a_";
+/// How long a slot stays reserved while the invitee fills in the booking form.
+const SLOT_HOLD_MINUTES: i64 = 5;
+/// Minimum gap between two availability snapshots of the same host.
+const SNAPSHOT_INTERVAL_MINUTES: i64 = 5;
+/// How long browsers and CDNs may cache an event type's embed configuration.
+const EMBED_CONFIG_MAX_AGE_SECONDS: u32 = 24 * 60 * 60;
+/// Error code of slot and booking requests to a host whose public page is off.
+const PUBLIC_PAGE_DISABLED: &str = "public_page_disabled";
+/// Longest invitee-supplied values written to a CSV export; bookings stored
+/// before length limits existed are cut to these.
+const EXPORT_NAME_CHARS: usize = 100;
+const EXPORT_ANSWER_CHARS: usize = 2000;
+const EXPORT_METADATA_CHARS: usize = 4000;
+
+/// An invitee change that the event type's policies can restrict.
+#[derive(Debug, Clone, Copy)]
+enum PolicyChange {
+    Cancel,
+    Reschedule,
+}
+
+impl PolicyChange {
+    /// Prefix of the error codes for this change.
+    fn noun(self) -> &'static str {
+        match self {
+            PolicyChange::Cancel => "cancellation",
+            PolicyChange::Reschedule => "reschedule",
+        }
+    }
+
+    fn past_tense(self) -> &'static str {
+        match self {
+            PolicyChange::Cancel => "cancelled",
+            PolicyChange::Reschedule => "rescheduled",
+        }
+    }
+}
+
+pub struct BookingController {
+    db: Database,
+    booking_repository: BookingRepository,
+    consumed_action_repository: ConsumedActionRepository,
+    idempotency_repository: IdempotencyRepository,
+    slot_hold_repository: SlotHoldRepository,
+    settings_repository: CalendarSettingsRepository,
+    availability_repository: AvailabilityRepository,
+    snapshot_repository: AvailabilitySnapshotRepository,
+    event_type_repository: EventTypeRepository,
+    user_repository: UserRepository,
+    notification_repository: NotificationRepository,
+    slot_sources: SlotSources,
+    email_queue: EmailQueue,
+}
+
+impl BookingController {
+    pub fn new(db: Database) -> Self {
+        Self {
+            db: db.clone(),
+            booking_repository: BookingRepository::new(db.clone()),
+            consumed_action_repository: ConsumedActionRepository::new(db.clone()),
+            idempotency_repository: IdempotencyRepository::new(db.clone()),
+            slot_hold_repository: SlotHoldRepository::new(db.clone()),
+            settings_repository: CalendarSettingsRepository::new(db.clone()),
+            availability_repository: AvailabilityRepository::new(db.clone()),
+            snapshot_repository: AvailabilitySnapshotRepository::new(db.clone()),
+            event_type_repository: EventTypeRepository::new(db.clone()),
+            user_repository: UserRepository::new(),
+            notification_repository: NotificationRepository::new(db.clone()),
+            slot_sources: SlotSources::new(db),
+            email_queue: AppState::get().email_queue.clone(),
+        }
+    }
+
+    pub async fn create_booking(
+        &self,
+        req: HttpRequest,
+        data: web::Json<CreateBookingRequest>,
+    ) -> Result<HttpResponse, AppError> {
+        // Validate request data
+        data.validate()
+            .map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+        let data = data.into_inner();
+
+        let event_type_id = ObjectId::parse_str(&data.event_type_id)
+            .map_err(|_| AppError::BadRequest("Invalid event type ID".to_string()))?;
+        let event_type = self.event_type_repository.find_by_id(&event_type_id).await?
+            .ok_or_else(|| AppError::NotFound("Event type not found".to_string()))?;
+        if !event_type.accepts_bookings() {
+            return Err(AppError::BadRequest("Event type is not accepting bookings".to_string()));
+        }
+
+        let settings = self.settings_repository.find_by_user_id(&event_type.user_id).await?
+            .ok_or_else(|| AppError::NotFound("Calendar settings not found".to_string()))?;
+
+        self.book_once(Self::idempotency_key(&req)?, event_type, &settings, data).await
+    }
+
+    /// The client's Idempotency-Key header, if it sent one.
+    fn idempotency_key(req: &HttpRequest) -> Result<Option<String>, AppError> {
+        let Some(value) = req.headers().get(IDEMPOTENCY_KEY_HEADER) else {
+            return Ok(None);
+        };
+        let key = value.to_str().unwrap_or_default().trim();
+        if key.is_empty() || key.len() > IDEMPOTENCY_KEY_MAX_CHARS {
+            return Err(AppError::BadRequest(format!(
+                "Idempotency-Key must be between 1 and {} visible ASCII characters", IDEMPOTENCY_KEY_MAX_CHARS
+            )));
+        }
+        Ok(Some(key.to_string()))
+    }
+
+    /// Books like `book`, except that a replayed idempotency key returns the
+    /// booking its first request made instead of booking again.
+    async fn book_once(
+        &self,
+        idempotency_key: Option<String>,
+        event_type: EventType,
+        settings: &CalendarSettings,
+        data: CreateBookingRequest,
+    ) -> Result<HttpResponse, AppError> {
+        let confirmation_settings = event_type.confirmation_settings.clone();
+        let booked_response = |outcome| Self::booked_response(outcome, confirmation_settings.as_ref());
+        let Some(key) = idempotency_key else {
+            return self.book(event_type, settings, data).await.map(booked_response);
+        };
+
+        // Keys are scoped to the event type, so clients only need them unique per booking page
+        let scope = format!("booking:{}", event_type.id.map(|id| id.to_hex()).unwrap_or_default());
+        let body = serde_json::to_vec(&data).map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        let request_hash: String = Sha256::digest(&body).iter().map(|byte| format!("{:02x}", byte)).collect();
+
+        if let Some(earlier) = self.idempotency_repository.begin(&scope, &key, &request_hash).await? {
+            if earlier.request_hash != request_hash {
+                return Err(AppError::coded(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    "idempotency_key_reused",
+                    "Idempotency-Key was already used with a different request",
+                ));
+            }
+            let Some(booking_id) = earlier.booking_id else {
+                return Err(AppError::coded(
+                    StatusCode::CONFLICT,
+                    "idempotency_key_in_progress",
+                    "A request with this Idempotency-Key is still being processed",
+                ));
+            };
+            let booking = self.booking_repository.find_by_id(&booking_id).await?
+                .ok_or_else(|| AppError::NotFound("Booking not found".to_string()))?;
+
+            // The client may never have seen the first response, so it gets the token again
+            let cancellation_token = booking.cancellation_token.clone();
+            return Ok(HttpResponse::Ok().json(BookingResponse {
+                cancellation_token: Some(cancellation_token),
+                ..BookingResponse::from(booking)
+            }.with_confirmation(confirmation_settings.as_ref())));
+        }
+
+        let result = self.book(event_type, settings, data).await;
+        match &result {
+            Ok(Ok(booking)) => {
+                if let Some(booking_id) = &booking.id
+                    && let Err(e) = self.idempotency_repository.complete(&scope, &key, booking_id).await {
+                    println!("Failed to record idempotency key for booking {}: {}", booking_id.to_hex(), e);
+                }
+            }
+            // Nothing was booked, so a retry should really try again
+            _ => self.idempotency_repository.release(&scope, &key).await,
+        }
+        result.map(booked_response)
+    }
+
+    fn booked_response(outcome: Result<Booking, Vec<SlotConflict>>, confirmation_settings: Option<&ConfirmationSettings>) -> HttpResponse {
+        match outcome {
+            Ok(created) => {
+                // The token is only handed out once, to whoever made the booking
+                let cancellation_token = created.cancellation_token.clone();
+                HttpResponse::Created().json(BookingResponse {
+                    cancellation_token: Some(cancellation_token),
+                    ..BookingResponse::from(created)
+                }.with_confirmation(confirmation_settings))
+            }
+            Err(conflicts) => Self::conflict_response(conflicts, false),
+        }
+    }
+
+    /// Books `event_type` for the invitee, after the caller has checked the
+    /// event type is bookable. Returns the conflicts instead when the slot is taken.
+    async fn book(
+        &self,
+        event_type: EventType,
+        settings: &CalendarSettings,
+        mut data: CreateBookingRequest,
+    ) -> Result<Result<Booking, Vec<SlotConflict>>, AppError> {
+        data.start_time = time_of_day::normalize("start_time", &data.start_time)?;
+        let event_type_id = event_type.id
+            .ok_or_else(|| AppError::InternalServerError("Event type has no id".to_string()))?;
+
+        Self::validate_answers(&event_type.questions, &data.answers)?;
+        Self::validate_tracking(data.tracking.as_ref())?;
+        let guest_emails = Self::normalize_guest_emails(&data.invitee_email, &data.guest_emails)?;
+        let invitee_phone = Self::normalize_invitee_phone(&event_type, data.invitee_phone.clone())?;
+        let hold = match &data.hold_id {
+            Some(hold_id) => Some(self.find_hold(hold_id, &event_type_id, &data.date, &data.start_time).await?),
+            None => None,
+        };
+
+        let (end_time, teams) = match self.free_hosts(&event_type, settings, &data.date, &data.start_time, hold.as_ref()).await? {
+            Ok(free) => free,
+            Err(conflicts) => return Ok(Err(conflicts)),
+        };
+
+        // Fill invitee details and answers into the meeting link
+        let answers: HashMap<String, String> = data.answers
+            .iter()
+            .map(|a| (template::question_key(&a.question), a.answer.to_text()))
+            .collect();
+        let context = TemplateContext {
+            invitee_name: &data.invitee_name,
+            invitee_email: &data.invitee_email,
+            answers: &answers,
+        };
+
+        // Invitees pick one of several locations; a single one needs no choice
+        let chosen_location = match (event_type.locations.as_slice(), data.chosen_location) {
+            ([], Some(_)) => {
+                return Err(AppError::BadRequest("This event type does not offer locations".to_string()));
+            }
+            ([], None) => None,
+            (locations, Some(chosen)) => {
+                if !locations.contains(&chosen) {
+                    return Err(AppError::BadRequest("chosen_location must be one of the event type's locations".to_string()));
+                }
+                Some(chosen)
+            }
+            ([only], None) => Some(only.clone()),
+            (_, None) => {
+                return Err(AppError::BadRequest("chosen_location is required for this event type".to_string()));
+            }
+        };
+        // Only a video location gets a meeting link
+        let meeting_link = match &chosen_location {
+            Some(Location::Video { link: Some(link), .. }) => Some(template::render(link, &context, true)),
+            _ => None,
+        };
+
+        let booking = Booking {
+            id: None,
+            event_type_id,
+            host_user_id: event_type.user_id,  // Set to the assigned hosts when created
+            co_host_user_ids: Vec::new(),
+            invitee_name: data.invitee_name,
+            invitee_email: data.invitee_email,
+            guest_emails,
+            attendees: Vec::new(),
+            invitee_phone,
+            date: data.date,
+            start_time: data.start_time,
+            end_time,
+            status: if event_type.requires_confirmation { BookingStatus::Pending } else { BookingStatus::Confirmed },
+            seat: 0,
+            answers: data.answers,
+            chosen_location,
+            meeting_link,
+            tracking: data.tracking,
+            cancellation_token: Uuid::new_v4().simple().to_string(),
+            management_token: Self::generate_management_token(),
+            cancelled_at: None,
+            cancelled_by: None,
+            cancellation_reason: None,
+            rescheduled_from: None,
+            reschedule_history: Vec::new(),
+            reminders_sent: Vec::new(),
+            schedule_versions: Vec::new(),  // Set with the hosts
+            status_history: Vec::new(),
+            anonymized_at: None,
+            created_at: DateTime::now(),
+            updated_at: DateTime::now(),
+        };
+
+        AppState::get().booking_hooks.before_create(&booking, &event_type).await?;
+
+        let hold_id = hold.as_ref().and_then(|hold| hold.id.as_ref());
+        let mut teams = teams.into_iter().peekable();
+        let mut created = loop {
+            let Some(team) = teams.next() else {
+                return Err(AppError::InternalServerError("Slot has no hosts".to_string()));
+            };
+            let booking = Booking {
+                host_user_id: team[0].user_id,
+                co_host_user_ids: team[1..].iter().map(|host| host.user_id).collect(),
+                schedule_versions: team.iter().filter_map(|host| host.schedule_version).collect(),
+                ..booking.clone()
+            };
+            match self.create_claiming_hold(booking, event_type.capacity(), hold_id).await {
+                Ok(created) => break created,
+                // A concurrent booking took this round-robin host first; the next free one gets it
+                Err(AppError::Coded(_, code, _)) if code == "slot_unavailable" && teams.peek().is_some() => continue,
+                Err(e) => return Err(e),
+            }
+        };
+        let booking_id = created.id
+            .ok_or_else(|| AppError::InternalServerError("Booking has no id".to_string()))?;
+
+        if let Some(link) = self.create_meeting(&created, &event_type).await {
+            created.meeting_link = Some(link);
+        }
+
+        if created.status == BookingStatus::Pending {
+            self.request_approval(&created, &event_type).await;
+        } else {
+            for host_id in created.host_ids() {
+                self.notification_repository.notify(
+                    host_id,
+                    NotificationKind::BookingCreated,
+                    &format!("New booking: {}", event_type.name),
+                    &format!("{} booked {} at {}", created.invitee_name, created.date, created.start_time),
+                    Some(&booking_id),
+                ).await;
+            }
+            self.send_confirmations(&created, &event_type, &settings.timezone).await?;
+        }
+        AppState::get().booking_hooks.after_create(&created, &event_type).await;
+
+        Ok(Ok(created))
+    }
+
+    /// Creates the booking and then uses up the invitee's slot hold, if any,
+    /// which is what makes the hold single use. The hold is only claimed once
+    /// the booking exists, so a failed insert leaves it for another try; a hold
+    /// that expired or was used in the meantime removes the booking again.
+    async fn create_claiming_hold(&self, booking: Booking, capacity: i32, hold_id: Option<&ObjectId>) -> Result<Booking, AppError> {
+        let created = self.create_on_unchanged_schedules(booking, capacity).await?;
+        let Some(hold_id) = hold_id else {
+            return Ok(created);
+        };
+
+        let claimed = self.slot_hold_repository.claim(hold_id).await;
+        if matches!(claimed, Ok(true)) {
+            return Ok(created);
+        }
+        if let Some(booking_id) = &created.id {
+            self.booking_repository.delete(booking_id).await?;
+        }
+        Err(claimed.err().unwrap_or_else(Self::hold_expired))
+    }
+
+    /// Creates the booking, provided none of the hosts' schedules changed
+    /// since the slot was checked against them. They are checked again once
+    /// the booking exists, so an edit that lands mid-booking either shows up
+    /// here or comes after the booking and is checked against it. Daily
+    /// meeting minutes caps are checked again the same way.
+    async fn create_on_unchanged_schedules(&self, booking: Booking, capacity: i32) -> Result<Booking, AppError> {
+        let created = self.booking_repository.create_in_free_seat(booking, capacity).await?;
+        for schedule_version in &created.schedule_versions {
+            if self.availability_repository.is_at_version(schedule_version).await? {
+                continue;
+            }
+            if let Some(booking_id) = &created.id {
+                self.booking_repository.delete(booking_id).await?;
+            }
+            return Err(AppError::coded(
+                StatusCode::CONFLICT,
+                "schedule_changed",
+                "The host's schedule just changed, please pick a time again",
+            ));
+        }
+        // Two bookings checked against the same day at once can go over a daily cap together
+        if self.slot_sources.exceeds_daily_cap(&created).await? {
+            if let Some(booking_id) = &created.id {
+                self.booking_repository.delete(booking_id).await?;
+            }
+            return Err(AppError::coded(
+                StatusCode::CONFLICT,
+                "daily_minutes_cap",
+                "The host's day just filled up, please pick another day",
+            ));
+        }
+        Ok(created)
+    }
+
+    /// Creates a meeting with the chosen location's video provider and stores
+    /// its link on the booking. Best-effort: on failure the booking keeps the
+    /// location's own link, if it has one.
+    async fn create_meeting(&self, booking: &Booking, event_type: &EventType) -> Option<String> {
+        let booking_id = booking.id?;
+        let Some(Location::Video { provider: Some(name), .. }) = Self::booking_location(booking, event_type) else {
+            return None;
+        };
+        let provider = conferencing::provider(name, self.db.clone())?;
+
+        let link = match provider.create_meeting(event_type, booking).await {
+            Ok(link) => link,
+            Err(e) => {
+                println!("Failed to create {} meeting for booking {}: {}", name, booking_id.to_hex(), e);
+                return None;
+            }
+        };
+        if let Err(e) = self.booking_repository.set_meeting_link(&booking_id, &link).await {
+            println!("Failed to save meeting link for booking {}: {}", booking_id.to_hex(), e);
+        }
+        Some(link)
+    }
+
+    /// Tells the hosts a booking is waiting for their answer; any of them may
+    /// give it. Best-effort: failures are logged and the booking keeps its slot either way.
+    async fn request_approval(&self, booking: &Booking, event_type: &EventType) {
+        let Some(booking_id) = booking.id else {
+            return;
+        };
+
+        let secret = AppState::get().action_signing_secret.as_bytes();
+        let expires_at = Utc::now() + Duration::hours(ACTION_LINK_HOURS);
+        let link_token = |action| signed_actions::sign(secret, &ActionClaims {
+            booking_id: booking_id.to_hex(),
+            action,
+            expires_at,
+        });
+
+        for host_id in booking.host_ids() {
+            self.notification_repository.notify(
+                host_id,
+                NotificationKind::BookingRequested,
+                &format!("Booking request: {}", event_type.name),
+                &format!("{} asked to book {} at {}", booking.invitee_name, booking.date, booking.start_time),
+                Some(&booking_id),
+            ).await;
+
+            let host_email = match self.user_repository.find_by_id(&host_id.to_hex()).await {
+                Ok(host) => host.map(|host| host.email),
+                Err(e) => {
+                    println!("Failed to look up host for booking {}: {}", booking_id.to_hex(), e);
+                    None
+                }
+            };
+            let Some(to) = host_email else {
+                continue;
+            };
+            let job = EmailJob::BookingRequested {
+                to,
+                booking_id: booking_id.to_hex(),
+                event_name: event_type.name.clone(),
+                invitee_name: booking.invitee_name.clone(),
+                date: booking.date.clone(),
+                start_time: booking.start_time.clone(),
+                expires_in_hours: AppState::get().pending_booking_ttl_hours,
+                approve_token: link_token(SignedAction::Confirm),
+                decline_token: link_token(SignedAction::Decline),
+            };
+            if let Err(e) = self.email_queue.enqueue(job).await {
+                println!("Failed to queue booking request email for booking {}: {}", booking_id.to_hex(), e);
+            }
+        }
+    }
+
+    /// Sends hosts, attendees and invitee the confirmation with a calendar invitation;
+    /// a failed email does not undo the booking.
+    async fn send_confirmations(&self, booking: &Booking, event_type: &EventType, timezone: &str) -> Result<(), AppError> {
+        let booking_id = booking.id
+            .ok_or_else(|| AppError::InternalServerError("Booking has no id".to_string()))?;
+        let Some(host) = self.user_repository.find_by_id(&booking.host_user_id.to_hex()).await? else {
+            return Ok(());
+        };
+        let co_host_emails = self.co_host_emails(booking).await?;
+
+        let location = Self::location_text(booking, event_type);
+        let ics = Self::booking_ics(
+            booking, &event_type.name, timezone, &host.email, location.as_deref(), IcsMethod::Request,
+        );
+        // Only the invitee gets the links to manage the booking
+        let invitee_token = Some(booking.management_token.clone()).filter(|token| !token.is_empty());
+        let custom_copy = Self::custom_copy(event_type, "confirmation", booking);
+        let hosts = std::iter::once(host.email.clone())
+            .chain(co_host_emails)
+            .chain(booking.attendees.iter().map(|attendee| attendee.email.clone()))
+            .map(|host_email| (host_email, None, None));
+        let attendees = std::iter::once((booking.invitee_email.clone(), invitee_token))
+            .chain(booking.guest_emails.iter().map(|guest_email| (guest_email.clone(), None)))
+            .map(|(to, management_token)| (to, management_token, custom_copy.clone()));
+        for (to, management_token, custom_copy) in hosts.chain(attendees) {
+            let job = EmailJob::BookingConfirmed {
+                to,
+                booking_id: booking_id.to_hex(),
+                event_name: event_type.name.clone(),
+                date: booking.date.clone(),
+                start_time: booking.start_time.clone(),
+                location: location.clone(),
+                ics: ics.clone(),
+                management_token,
+                custom_copy,
+            };
+            if let Err(e) = self.email_queue.enqueue(job).await {
+                println!("Failed to queue confirmation email for booking {}: {}", booking_id.to_hex(), e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The event type's own wording for a `kind` of email to the booking's
+    /// invitee and guests, if the host set one.
+    pub(crate) fn custom_copy(event_type: &EventType, kind: &str, booking: &Booking) -> Option<CustomCopy> {
+        event_type.email_template(kind).map(|template| CustomCopy {
+            template: template.to_string(),
+            invitee_name: booking.invitee_name.clone(),
+        })
+    }
+
+    /// Emails of the booking's co-hosts who still have an account.
+    async fn co_host_emails(&self, booking: &Booking) -> Result<Vec<String>, AppError> {
+        let mut emails = Vec::with_capacity(booking.co_host_user_ids.len());
+        for co_host_id in &booking.co_host_user_ids {
+            if let Some(co_host) = self.user_repository.find_by_id(&co_host_id.to_hex()).await? {
+                emails.push(co_host.email);
+            }
+        }
+        Ok(emails)
+    }
+
+    /// The event types on the host's public page. Secret event types are left
+    /// out; they can still be booked through their own link.
+    pub async fn public_list_event_types(&self, user_id: web::Path<String>) -> Result<HttpResponse, AppError> {
+        let user_id = ObjectId::parse_str(user_id.as_str())
+            .map_err(|_| AppError::NotFound("User not found".to_string()))?;
+        let settings = match self.public_settings(&user_id).await {
+            Ok(settings) => settings,
+            Err(e) => return Self::unavailable_page(e),
+        };
+
+        let event_types = self.event_type_repository.find_listed_by_user_id(&user_id).await?;
+        let response: Vec<PublicEventTypeResponse> = event_types
+            .into_iter()
+            .map(|event_type| PublicEventTypeResponse::new(event_type, settings.timezone.clone()))
+            .collect();
+
+        Ok(HttpResponse::Ok().json(response))
+    }
+
+    /// An event type as shown on the host's public page, including what invitees
+    /// may cancel or reschedule themselves, and the form values the link prefills.
+    pub async fn public_get_event_type(
+        &self,
+        path: web::Path<(String, String)>,
+        query: web::Query<HashMap<String, String>>,
+    ) -> Result<HttpResponse, AppError> {
+        let (user_id, event_type_id) = path.into_inner();
+        let (event_type, settings) = match self.resolve_public_event_type(&user_id, &event_type_id).await {
+            Ok(resolved) => resolved,
+            Err(e) => return Self::unavailable_page(e),
+        };
+        let (prefill, prefill_warnings) = Self::read_prefill(&query, &event_type.questions);
+        Ok(HttpResponse::Ok().json(PublicEventTypeResponse {
+            prefill,
+            prefill_warnings,
+            ..PublicEventTypeResponse::new(event_type, settings.timezone)
+        }))
+    }
+
+    /// What the embedded booking widget of an event type looks like. Hosts
+    /// rarely change it, so it may be cached for a day.
+    pub async fn public_embed_config(&self, path: web::Path<(String, String)>) -> Result<HttpResponse, AppError> {
+        let (user_id, event_type_id) = path.into_inner();
+        let (event_type, _) = match self.resolve_public_event_type(&user_id, &event_type_id).await {
+            Ok(resolved) => resolved,
+            Err(e) => return Self::unavailable_page(e),
+        };
+        Ok(HttpResponse::Ok()
+            .insert_header(header::CacheControl(vec![
+                header::CacheDirective::Public,
+                header::CacheDirective::MaxAge(EMBED_CONFIG_MAX_AGE_SECONDS),
+            ]))
+            .json(PublicEmbedConfigResponse::from(event_type)))
+    }
+
+    /// Open slots on a host's public page, for invitees without an account.
+    pub async fn public_availability(
+        &self,
+        path: web::Path<(String, String)>,
+        query: web::Query<PublicAvailabilityQuery>,
+    ) -> Result<HttpResponse, AppError> {
+        // Validate request data
+        query.validate()
+            .map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+        let (user_id, event_type_id) = path.into_inner();
+        let (event_type, settings) = self.resolve_public_event_type(&user_id, &event_type_id).await?;
+
+        let parse_date = |value: &str| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .map_err(|_| AppError::BadRequest("Invalid date format. Use YYYY-MM-DD".to_string()))
+        };
+        let start_day = parse_date(&query.start_date)?;
+        let end_day = parse_date(&query.end_date)?;
+        if start_day > end_day {
+            return Err(AppError::BadRequest("start_date must not be after end_date".to_string()));
+        }
+        if (end_day - start_day).num_days() >= PUBLIC_AVAILABILITY_MAX_DAYS {
+            return Err(AppError::BadRequest(format!(
+                "Date range must be at most {} days", PUBLIC_AVAILABILITY_MAX_DAYS
+            )));
+        }
+
+        // Slots are generated for the host's days that overlap the invitee's
+        let host_tz = calendar_engine::host_timezone(&settings);
+        let (tz, tz_source) = timezone::resolve_timezone(query.timezone.as_deref(), None, Some(&settings.timezone))?;
+        let (window, (start_day, end_day)) = calendar_engine::viewer_window(start_day, end_day, tz, host_tz);
+        let now = Utc::now().with_timezone(&host_tz).naive_local();
+
+        // Days past the booking horizon cannot have slots, so they are not computed
+        let horizon = AppState::get().booking_horizon;
+        let last_bookable_day = (now + Duration::minutes(horizon.max_notice_minutes(&event_type))).date();
+        let end_day = end_day.min(last_bookable_day);
+        // Nor are days outside the event type's scheduling window
+        let (start_day, end_day) = calendar_engine::clamp_to_scheduling_window(&event_type, start_day, end_day, now.date());
+
+        let availability = self.availability_repository.find_by_id(&event_type.availability_schedule_id).await?
+            .ok_or_else(|| AppError::NotFound("Event type not found".to_string()))?;
+        let owner = HostSchedule::new(event_type.user_id, settings.clone(), availability);
+        let (mut available_slots, BusyTime { booked, seats_held }) = self.slot_sources
+            .team_slots(Some(&event_type), &owner, start_day, end_day, event_type.duration)
+            .await?;
+        // Group event slots stay open until every seat is taken
+        let group_event_type_id = event_type.id.filter(|_| event_type.capacity() > 1);
+
+        // Only offer slots that booking would accept: in the future, within the
+        // event type's booking notice and inside working hours. Round-robin
+        // slots were checked against each host's working hours already
+        let round_robin = event_type.is_round_robin();
+        available_slots.retain(|slot| {
+            let starts_at = parse_date(&slot.date)
+                .map(|date| date.and_time(calendar_engine::parse_start_time(&slot.start_time)));
+            starts_at.is_ok_and(|starts_at| {
+                starts_at > now && calendar_engine::notice_conflict(&event_type, &horizon, starts_at, now).is_none()
+            }) && (round_robin || owner.is_available(&slot.date, &slot.start_time, &slot.end_time, &mut Vec::new()))
+        });
+        if let Some(group_event_type_id) = &group_event_type_id {
+            let mut seats_taken = self.booking_repository
+                .count_seats_taken(group_event_type_id, start_day, end_day)
+                .await?;
+            for (slot, held) in seats_held {
+                *seats_taken.entry(slot).or_default() += held;
+            }
+            calendar_engine::apply_capacity(&mut available_slots, event_type.capacity(), &seats_taken);
+        }
+
+        // Diagnostics are off for almost every host, so this costs nothing on the normal path
+        if settings.diagnostics_until.is_some_and(|until| until > DateTime::now()) {
+            let snapshot = AvailabilitySnapshot {
+                id: None,
+                user_id: event_type.user_id,
+                event_type_id: event_type.id.unwrap_or_default(),
+                inputs_hash: calendar_engine::inputs_hash(&owner.rules, &owner.date_overrides, &settings, &event_type, &booked),
+                window_start: start_day.format("%Y-%m-%d").to_string(),
+                window_end: end_day.format("%Y-%m-%d").to_string(),
+                slots_per_day: calendar_engine::count_slots_per_day(&available_slots),
+                engine_version: calendar_engine::ENGINE_VERSION.to_string(),
+                date: Utc::now().format("%Y-%m-%d").to_string(),
+                computed_at: DateTime::now(),
+            };
+            self.record_snapshot(snapshot).await;
+        }
+
+        calendar_engine::localize_slots(&mut available_slots, host_tz, tz, window);
+
+        let recommended = query.recommend
+            .map(|count| calendar_engine::recommend_slots(&available_slots, &booked, count));
+
+        Ok(HttpResponse::Ok().json(CheckAvailabilityResponse {
+            available_slots,
+            recommended,
+            timezone_resolution: TimezoneResolution::new(tz, tz_source),
+        }))
+    }
+
+    pub async fn public_create_booking(
+        &self,
+        req: HttpRequest,
+        path: web::Path<(String, String)>,
+        data: web::Json<PublicBookingRequest>,
+    ) -> Result<HttpResponse, AppError> {
+        let (user_id, event_type_ref) = path.into_inner();
+        let (event_type, settings) = self.resolve_public_event_type(&user_id, &event_type_ref).await?;
+        let event_type_id = event_type.id.map(|id| id.to_hex()).unwrap_or_default();
+
+        // Prefilled values count as typed in, so they are validated along with the rest
+        let mut data = data.into_inner();
+        if let Some(prefill) = data.prefill.take() {
+            Self::apply_prefill(&mut data, prefill, &event_type.questions);
+        }
+        data.validate()
+            .map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+        let data = data.into_create_request(event_type_id);
+        self.book_once(Self::idempotency_key(&req)?, event_type, &settings, data).await
+    }
+
+    /// Whether `email` has a pending or confirmed booking of the event type
+    /// still to come, so a signup flow need not offer the call again.
+    pub async fn public_existing_booking(
+        &self,
+        path: web::Path<(String, String)>,
+        query: web::Query<ExistingBookingQuery>,
+    ) -> Result<HttpResponse, AppError> {
+        query.validate()
+            .map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+        let (user_id, event_type_ref) = path.into_inner();
+        let (event_type, settings) = self.resolve_public_event_type(&user_id, &event_type_ref).await?;
+        let event_type_id = event_type.id
+            .ok_or_else(|| AppError::InternalServerError("Event type has no id".to_string()))?;
+
+        // Booking dates and times are in the host's timezone
+        let (tz, _) = timezone::resolve_timezone(None, None, Some(settings.timezone.as_str()))?;
+        let now = Utc::now().with_timezone(&tz);
+        let booking = self.booking_repository
+            .find_upcoming_by_invitee(
+                &event_type_id,
+                query.email.trim(),
+                &now.format("%Y-%m-%d").to_string(),
+                &now.format("%H:%M").to_string(),
+            )
+            .await?;
+
+        Ok(HttpResponse::Ok().json(ExistingBookingResponse::new(booking.as_ref())))
+    }
+
+    /// Reserves a slot for the invitee while they fill in the booking form, so
+    /// nobody else can book it until the hold expires or becomes their booking.
+    pub async fn create_slot_hold(
+        &self,
+        path: web::Path<(String, String)>,
+        data: web::Json<CreateSlotHoldRequest>,
+    ) -> Result<HttpResponse, AppError> {
+        let (user_id, event_type_id) = path.into_inner();
+        let (event_type, settings) = self.resolve_public_event_type(&user_id, &event_type_id).await?;
+        let event_type_id = event_type.id
+            .ok_or_else(|| AppError::InternalServerError("Event type has no id".to_string()))?;
+        let start_time = time_of_day::normalize("start_time", &data.start_time)?;
+
+        let (end_time, teams) = match self.free_hosts(&event_type, &settings, &data.date, &start_time, None).await? {
+            Ok(free) => free,
+            Err(conflicts) => return Ok(Self::conflict_response(conflicts, false)),
+        };
+
+        let expires_at = DateTime::from_millis(DateTime::now().timestamp_millis() + SLOT_HOLD_MINUTES * 60 * 1000);
+        let hold = SlotHold {
+            id: None,
+            event_type_id,
+            host_user_id: event_type.user_id,  // Set to the assigned hosts when created
+            co_host_user_ids: Vec::new(),
+            date: data.date.clone(),
+            start_time,
+            end_time,
+            seat: 0,
+            expires_at,
+            created_at: DateTime::now(),
+        };
+        let mut teams = teams.into_iter().peekable();
+        let hold = loop {
+            let Some(team) = teams.next() else {
+                return Err(AppError::InternalServerError("Slot has no hosts".to_string()));
+            };
+            let hold = SlotHold {
+                host_user_id: team[0].user_id,
+                co_host_user_ids: team[1..].iter().map(|host| host.user_id).collect(),
+                ..hold.clone()
+            };
+            match self.slot_hold_repository.create_in_free_seat(hold, event_type.capacity()).await {
+                Ok(created) => break created,
+                // A concurrent hold took this round-robin host first; the next free one gets it
+                Err(AppError::Coded(_, code, _)) if code == "slot_unavailable" && teams.peek().is_some() => continue,
+                Err(e) => return Err(e),
+            }
+        };
+
+        Ok(HttpResponse::Created().json(SlotHoldResponse::from(hold)))
+    }
+
+    /// Finds `hold_id`, checking it is an unexpired hold on this event type's slot.
+    async fn find_hold(&self, hold_id: &str, event_type_id: &ObjectId, date: &str, start_time: &str) -> Result<SlotHold, AppError> {
+        let hold_id = ObjectId::parse_str(hold_id).map_err(|_| Self::hold_expired())?;
+        let hold = self.slot_hold_repository.find_active(&hold_id).await?
+            .ok_or_else(Self::hold_expired)?;
+        if hold.event_type_id != *event_type_id || hold.date != date || hold.start_time != start_time {
+            return Err(AppError::coded(
+                StatusCode::BAD_REQUEST,
+                "hold_mismatch",
+                "The slot hold is for a different event type or time",
+            ));
+        }
+        Ok(hold)
+    }
+
+    fn hold_expired() -> AppError {
+        AppError::coded(StatusCode::GONE, "hold_expired", "The slot hold has expired; pick a time again")
+    }
+
+    /// Stores at most one snapshot per host per sampling interval. Best-effort:
+    /// failures are logged and never fail the availability request.
+    async fn record_snapshot(&self, snapshot: AvailabilitySnapshot) {
+        let since = DateTime::from_millis(
+            DateTime::now().timestamp_millis() - SNAPSHOT_INTERVAL_MINUTES * 60 * 1000
+        );
+        let result = match self.snapshot_repository.exists_since(&snapshot.user_id, since).await {
+            Ok(true) => Ok(()),
+            Ok(false) => self.snapshot_repository.create(snapshot.clone()).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            println!("Failed to store availability snapshot for user {}: {}", snapshot.user_id.to_hex(), e);
+        }
+    }
+
+    /// Finds an event type that can be booked from the host's public page, by
+    /// slug, by an earlier slug or, for links shared before slugs existed, by
+    /// id. Anything not publicly bookable looks like a missing event type.
+    async fn resolve_public_event_type(
+        &self,
+        user_id: &str,
+        event_type_ref: &str,
+    ) -> Result<(EventType, CalendarSettings), AppError> {
+        let not_found = || AppError::NotFound("Event type not found".to_string());
+
+        let user_id = ObjectId::parse_str(user_id).map_err(|_| not_found())?;
+
+        // An event type's current slug wins over another's old one
+        let mut event_type = self.event_type_repository.find_by_user_and_slug(&user_id, event_type_ref).await?;
+        if event_type.is_none() {
+            event_type = self.event_type_repository.find_by_user_and_old_slug(&user_id, event_type_ref).await?;
+        }
+        if event_type.is_none()
+            && let Ok(event_type_id) = ObjectId::parse_str(event_type_ref) {
+            event_type = self.event_type_repository.find_by_id(&event_type_id).await?;
+        }
+        let event_type = event_type
+            .filter(|event_type| event_type.user_id == user_id && event_type.accepts_bookings())
+            .ok_or_else(not_found)?;
+        let settings = self.public_settings(&user_id).await
+            .map_err(|e| match e {
+                AppError::NotFound(_) => not_found(),
+                e => e,
+            })?;
+
+        Ok((event_type, settings))
+    }
+
+    /// The host's settings, provided their public page is switched on.
+    /// Otherwise slots and bookings are refused with the host's message.
+    async fn public_settings(&self, user_id: &ObjectId) -> Result<CalendarSettings, AppError> {
+        let settings = self.settings_repository.find_by_user_id(user_id).await?
+            .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+        if !settings.public_page_enabled {
+            let message = settings.public_page_message
+                .clone()
+                .unwrap_or_else(|| "This booking page is currently unavailable".to_string());
+            return Err(AppError::coded(StatusCode::FORBIDDEN, PUBLIC_PAGE_DISABLED, &message));
+        }
+
+        Ok(settings)
+    }
+
+    /// Page endpoints of a disabled public page still answer, with the host's
+    /// message for the frontend to show. Other errors are passed on.
+    fn unavailable_page(error: AppError) -> Result<HttpResponse, AppError> {
+        match error {
+            AppError::Coded(_, code, message) if code == PUBLIC_PAGE_DISABLED => {
+                Ok(HttpResponse::Ok().json(json!({ "available": false, "message": message })))
+            }
+            error => Err(error),
+        }
+    }
+
+    /// Cancels as the host (bearer token) or as the invitee (cancellation token).
+    pub async fn cancel_booking(
+        &self,
+        claims: Option<web::ReqData<Claims>>,
+        PathObjectId(booking_id): PathObjectId,
+        data: web::Json<CancelBookingRequest>,
+    ) -> Result<HttpResponse, AppError> {
+        // Validate request data
+        data.validate()
+            .map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+        let booking = self.booking_repository.find_by_id(&booking_id).await?
+            .ok_or_else(|| AppError::NotFound("Booking not found".to_string()))?;
+
+        let cancelled_by = Self::authorize(claims.as_ref(), &booking, data.cancellation_token.as_deref())?;
+        let cancelling_host = claims
+            .filter(|_| cancelled_by == "host")
+            .and_then(|claims| ObjectId::parse_str(&claims.sub).ok());
+        self.cancel(booking, cancelled_by, cancelling_host.as_ref(), data.reason.as_deref()).await
+    }
+
+    /// Cancels `booking` once the caller has established who is cancelling it.
+    /// Any host of a collective booking cancels it for everyone; `cancelling_host`
+    /// is the one who did, so they are not told about it.
+    async fn cancel(
+        &self,
+        booking: Booking,
+        cancelled_by: &'static str,
+        cancelling_host: Option<&ObjectId>,
+        reason: Option<&str>,
+    ) -> Result<HttpResponse, AppError> {
+        let booking_id = booking.id
+            .ok_or_else(|| AppError::InternalServerError("Booking has no id".to_string()))?;
+
+        booking.status.transition(BookingStatus::Cancelled)?;
+
+        let event_type = self.event_type_repository.find_by_id(&booking.event_type_id).await?;
+        let settings = self.settings_repository.find_by_user_id(&booking.host_user_id).await?;
+
+        // Booking times are in the host's timezone
+        let (tz, _) = timezone::resolve_timezone(None, None, settings.as_ref().map(|s| s.timezone.as_str()))?;
+        let starts_at = NaiveDate::parse_from_str(&booking.date, "%Y-%m-%d")
+            .map(|date| date.and_time(calendar_engine::parse_start_time(&booking.start_time)))
+            .map_err(|_| AppError::InternalServerError("Stored booking has an invalid date".to_string()))?;
+        let now = Utc::now().with_timezone(&tz).naive_local();
+        if starts_at <= now {
+            return Err(AppError::BadRequest("Cannot cancel a booking that has already started".to_string()));
+        }
+
+        let policy = event_type.as_ref().and_then(|et| et.cancellation_policy.as_ref());
+        Self::check_policy(policy, cancelled_by, starts_at, now, PolicyChange::Cancel)?;
+
+        let reason = reason.map(str::trim).filter(|reason| !reason.is_empty());
+        let cancelled = self.booking_repository.cancel(&booking_id, booking.status, cancelled_by, reason).await?
+            .ok_or_else(|| AppError::Conflict("Booking was changed by another request, reload and try again".to_string()))?;
+
+        // Tell the other parties and any guests; a failed notification does not undo the cancellation
+        let custom_copy = event_type.as_ref().and_then(|et| Self::custom_copy(et, "cancellation", &cancelled));
+        let event_name = event_type.as_ref().map(|et| et.name.clone()).unwrap_or_else(|| "your meeting".to_string());
+        let host_email = self.user_repository.find_by_id(&cancelled.host_user_id.to_hex()).await?
+            .map(|host| host.email);
+        let ics = host_email.as_deref().map(|organizer| {
+            Self::booking_ics(&cancelled, &event_name, tz.name(), organizer, None, IcsMethod::Cancel)
+        });
+        let invitee = Some(cancelled.invitee_email.clone()).filter(|_| cancelled_by == "host");
+        let message = if cancelled_by == "host" {
+            format!("A host cancelled {}'s booking on {} at {}", cancelled.invitee_name, cancelled.date, cancelled.start_time)
+        } else {
+            format!("{} cancelled {} at {}", cancelled.invitee_name, cancelled.date, cancelled.start_time)
+        };
+        let mut other_host_emails = Vec::new();
+        for host_id in cancelled.host_ids().filter(|host_id| Some(*host_id) != cancelling_host) {
+            self.notification_repository.notify(
+                host_id,
+                NotificationKind::BookingCancelled,
+                &format!("Booking cancelled: {}", event_name),
+                &message,
+                Some(&booking_id),
+            ).await;
+
+            let email = if *host_id == cancelled.host_user_id {
+                host_email.clone()
+            } else {
+                self.user_repository.find_by_id(&host_id.to_hex()).await?.map(|host| host.email)
+            };
+            other_host_emails.extend(email);
+        }
+        let attendees = invitee.into_iter()
+            .chain(cancelled.guest_emails.iter().cloned())
+            .map(|to| (to, custom_copy.clone()));
+        let recipients = other_host_emails.into_iter()
+            .chain(cancelled.attendees.iter().map(|attendee| attendee.email.clone()))
+            .map(|to| (to, None))
+            .chain(attendees);
+        for (to, custom_copy) in recipients {
+            let job = EmailJob::BookingCancelled {
+                to,
+                booking_id: booking_id.to_hex(),
+                event_name: event_name.clone(),
+                date: cancelled.date.clone(),
+                start_time: cancelled.start_time.clone(),
+                reason: cancelled.cancellation_reason.clone(),
+                ics: ics.clone(),
+                custom_copy,
+            };
+            if let Err(e) = self.email_queue.enqueue(job).await {
+                println!("Failed to queue cancellation email for booking {}: {}", booking_id.to_hex(), e);
+            }
+        }
+        AppState::get().booking_hooks.after_cancel(&cancelled, event_type.as_ref()).await;
+
+        Ok(HttpResponse::Ok().json(BookingResponse::from(cancelled)))
+    }
+
+    /// Streams the host's bookings between two dates as CSV, one row per booking
+    /// and one column per question asked by any of the host's event types.
+    pub async fn export_bookings(
+        &self,
+        claims: web::ReqData<Claims>,
+        query: web::Query<ExportBookingsQuery>,
+    ) -> Result<HttpResponse, AppError> {
+        if query.format.as_deref().is_some_and(|format| format != "csv") {
+            return Err(AppError::BadRequest("Unsupported export format, use csv".to_string()));
+        }
+        let parse_date = |value: &str| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .map_err(|_| AppError::BadRequest("Invalid date format. Use YYYY-MM-DD".to_string()))
+        };
+        let from = parse_date(&query.from)?;
+        let to = parse_date(&query.to)?;
+        if from > to {
+            return Err(AppError::BadRequest("from must not be after to".to_string()));
+        }
+
+        let user_id = ObjectId::parse_str(&claims.sub)
+            .map_err(|_| AppError::BadRequest("Invalid user ID".to_string()))?;
+        let settings = self.settings_repository.find_by_user_id(&user_id).await?
+            .ok_or_else(|| AppError::NotFound("Calendar settings not found".to_string()))?;
+
+        let event_types = self.event_type_repository.find_by_user_id(&user_id, true).await?;
+        let mut questions: Vec<String> = Vec::new();
+        for question in event_types.iter().flat_map(|event_type| &event_type.questions) {
+            if !questions.contains(&question.label) {
+                questions.push(question.label.clone());
+            }
+        }
+        let event_names: HashMap<ObjectId, String> = event_types
+            .into_iter()
+            .filter_map(|event_type| Some((event_type.id?, event_type.name)))
+            .collect();
+
+        let cursor = self.booking_repository
+            .stream_by_host_in_range(&user_id, &query.from, &query.to)
+            .await?;
+
+        let mut columns: Vec<&str> = vec![
+            "date", "start_time", "end_time", "timezone", "event_type", "invitee_name", "invitee_email", "status", "attendees",
+            "utm_source", "utm_medium", "utm_campaign", "utm_content", "utm_term", "metadata",
+        ];
+        columns.extend(questions.iter().map(String::as_str));
+        let header_row = web::Bytes::from(csv::row(columns));
+
+        // Stored dates and times are already in the host's timezone, so they are only reformatted
+        let rows = cursor.map(move |booking| {
+            booking.map(|booking| {
+                let date = NaiveDate::parse_from_str(&booking.date, "%Y-%m-%d")
+                    .map(|date| date_format::format_date(date, &settings.date_format))
+                    .unwrap_or_else(|_| booking.date.clone());
+                let format_time = |value: &str| {
+                    time_of_day::parse(value)
+                        .map(|time| date_format::format_time(time, &settings.time_format))
+                        .unwrap_or_else(|| value.to_string())
+                };
+                let mut fields = vec![
+                    date,
+                    format_time(&booking.start_time),
+                    format_time(&booking.end_time),
+                    settings.timezone.clone(),
+                    event_names.get(&booking.event_type_id).cloned().unwrap_or_default(),
+                    text::truncate_for_display(&booking.invitee_name, EXPORT_NAME_CHARS).into_owned(),
+                    booking.invitee_email.clone(),
+                    booking.status.to_string(),
+                    booking.attendees.iter().map(|attendee| attendee.email.as_str()).collect::<Vec<_>>().join("; "),
+                ];
+                let tracking = booking.tracking.clone().unwrap_or_default();
+                let utm = |value: Option<String>| {
+                    text::truncate_for_display(&value.unwrap_or_default(), TRACKING_MAX_VALUE_CHARS).into_owned()
+                };
+                let metadata = if tracking.metadata.is_empty() { String::new() } else { json!(tracking.metadata).to_string() };
+                fields.extend([
+                    utm(tracking.utm_source),
+                    utm(tracking.utm_medium),
+                    utm(tracking.utm_campaign),
+                    utm(tracking.utm_content),
+                    utm(tracking.utm_term),
+                    text::truncate_for_display(&metadata, EXPORT_METADATA_CHARS).into_owned(),
+                ]);
+                fields.extend(questions.iter().map(|label| {
+                    booking.answers
+                        .iter()
+                        .find(|answer| &answer.question == label)
+                        .map(|answer| text::truncate_for_display(&answer.answer.to_text(), EXPORT_ANSWER_CHARS).into_owned())
+                        .unwrap_or_default()
+                }));
+                web::Bytes::from(csv::row(fields))
+            })
+        });
+
+        let filename = format!("bookings-{}-to-{}.csv", query.from, query.to);
+        Ok(HttpResponse::Ok()
+            .content_type("text/csv; charset=utf-8")
+            .insert_header((header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)))
+            .streaming(stream::once(async move { Ok(header_row) }).chain(rows)))
+    }
+
+    /// Lets the host record how a past booking went. Cancelling, approving and
+    /// declining have their own endpoints, which also tell the invitee.
+    pub async fn update_status(
+        &self,
+        claims: web::ReqData<Claims>,
+        PathObjectId(booking_id): PathObjectId,
+        data: web::Json<UpdateBookingStatusRequest>,
+    ) -> Result<HttpResponse, AppError> {
+        let booking = self.find_for_host(&claims, &booking_id).await?;
+
+        if data.status == BookingStatus::Cancelled {
+            return Err(AppError::BadRequest("Use the cancel endpoint to cancel a booking".to_string()));
+        }
+        if matches!(data.status, BookingStatus::Confirmed | BookingStatus::Declined) {
+            return Err(AppError::BadRequest("Use the approve or decline endpoint to answer a booking request".to_string()));
+        }
+
+        self.record_outcome(booking, data.status, &format!("host {}", claims.sub)).await
+    }
+
+    /// Flags that the invitee missed a booking that has ended, or with
+    /// `no_show: false` takes a mistaken flag back.
+    pub async fn mark_no_show(
+        &self,
+        claims: web::ReqData<Claims>,
+        PathObjectId(booking_id): PathObjectId,
+        data: web::Json<MarkNoShowRequest>,
+    ) -> Result<HttpResponse, AppError> {
+        let booking = self.find_for_host(&claims, &booking_id).await?;
+        let no_show = data.no_show;
+
+        if no_show {
+            let settings = self.settings_repository.find_by_user_id(&booking.host_user_id).await?;
+            let (tz, _) = timezone::resolve_timezone(None, None, settings.as_ref().map(|s| s.timezone.as_str()))?;
+            let ends_at = NaiveDate::parse_from_str(&booking.date, "%Y-%m-%d")
+                .map(|date| date.and_time(calendar_engine::parse_end_time(&booking.end_time)))
+                .map_err(|_| AppError::InternalServerError("Stored booking has an invalid date".to_string()))?;
+            if ends_at > Utc::now().with_timezone(&tz).naive_local() {
+                return Err(AppError::BadRequest("Cannot mark a booking as no-show before it ends".to_string()));
+            }
+        } else if booking.status != BookingStatus::NoShow {
+            return Err(AppError::BadRequest("Booking is not marked as no-show".to_string()));
+        }
+
+        // A booking that is no longer a no-show took place, like any other past booking
+        let status = if no_show { BookingStatus::NoShow } else { BookingStatus::Completed };
+        self.record_outcome(booking, status, &format!("host {}", claims.sub)).await
+    }
+
+    /// Adds someone to a pending or confirmed booking. On a confirmed booking
+    /// everyone gets the updated invitation, the new attendee included.
+    pub async fn add_attendee(
+        &self,
+        claims: web::ReqData<Claims>,
+        PathObjectId(booking_id): PathObjectId,
+        data: web::Json<AddAttendeeRequest>,
+    ) -> Result<HttpResponse, AppError> {
+        data.validate()
+            .map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+        let booking = self.find_for_host(&claims, &booking_id).await?;
+        if !BookingStatus::SLOT_HOLDING.contains(&booking.status) {
+            return Err(AppError::BadRequest(format!("Cannot add attendees to a {} booking", booking.status)));
+        }
+        let email = data.email.trim().to_lowercase();
+        let already_on_booking = email == booking.invitee_email.to_lowercase()
+            || booking.guest_emails.contains(&email)
+            || booking.attendees.iter().any(|attendee| attendee.email == email);
+        if already_on_booking {
+            return Err(AppError::BadRequest(format!("{} is already on this booking", email)));
+        }
+        if booking.attendees.len() >= MAX_ATTENDEES {
+            return Err(AppError::BadRequest(format!("A booking can have at most {} added attendees", MAX_ATTENDEES)));
+        }
+
+        let attendee = BookingAttendee {
+            email,
+            name: data.name.as_deref().map(str::trim).filter(|name| !name.is_empty()).map(str::to_string),
+            added_at: DateTime::now(),
+        };
+        let updated = self.booking_repository.add_attendee(&booking_id, &attendee).await?
+            .ok_or_else(|| AppError::Conflict("Booking was changed by another request, reload and try again".to_string()))?;
+
+        // A pending booking sends its invitations once the host approves it
+        if updated.status == BookingStatus::Confirmed
+            && let Some(event_type) = self.event_type_repository.find_by_id(&updated.event_type_id).await?
+        {
+            let settings = self.settings_repository.find_by_user_id(&updated.host_user_id).await?;
+            let timezone = settings.map(|settings| settings.timezone).unwrap_or_else(|| "UTC".to_string());
+            self.send_confirmations(&updated, &event_type, &timezone).await?;
+        }
+
+        Ok(HttpResponse::Ok().json(BookingResponse::from(updated)))
+    }
+
+    /// Takes an added attendee off the booking and withdraws their invitation.
+    pub async fn remove_attendee(
+        &self,
+        claims: web::ReqData<Claims>,
+        PathObjectId(booking_id): PathObjectId,
+        path: web::Path<AttendeePath>,
+    ) -> Result<HttpResponse, AppError> {
+        self.find_for_host(&claims, &booking_id).await?;
+        let email = path.email.trim().to_lowercase();
+        let updated = self.booking_repository.remove_attendee(&booking_id, &email).await?
+            .ok_or_else(|| AppError::NotFound("Attendee not found".to_string()))?;
+
+        // A failed email does not undo the removal
+        if updated.status == BookingStatus::Confirmed
+            && let Some(host) = self.user_repository.find_by_id(&updated.host_user_id.to_hex()).await?
+        {
+            let event_name = self.event_type_repository.find_by_id(&updated.event_type_id).await?
+                .map(|event_type| event_type.name)
+                .unwrap_or_else(|| "your meeting".to_string());
+            let settings = self.settings_repository.find_by_user_id(&updated.host_user_id).await?;
+            let timezone = settings.map(|settings| settings.timezone).unwrap_or_else(|| "UTC".to_string());
+            let job = EmailJob::BookingCancelled {
+                to: email,
+                booking_id: booking_id.to_hex(),
+                event_name: event_name.clone(),
+                date: updated.date.clone(),
+                start_time: updated.start_time.clone(),
+                reason: Some("You were removed from this meeting".to_string()),
+                ics: Some(Self::booking_ics(&updated, &event_name, &timezone, &host.email, None, IcsMethod::Cancel)),
+                custom_copy: None,
+            };
+            if let Err(e) = self.email_queue.enqueue(job).await {
+                println!("Failed to queue removal email for booking {}: {}", booking_id.to_hex(), e);
+            }
+        }
+
+        Ok(HttpResponse::Ok().json(BookingResponse::from(updated)))
+    }
+
+    /// One of the host's bookings with its event type, labeled answers and history.
+    pub async fn get_booking(&self, claims: web::ReqData<Claims>, PathObjectId(booking_id): PathObjectId) -> Result<HttpResponse, AppError> {
+        let booking = self.find_for_host(&claims, &booking_id).await?;
+        let event_type = self.event_type_repository.find_by_id(&booking.event_type_id).await?;
+
+        let event_type_summary = event_type.as_ref().map(|event_type| BookingEventTypeSummary {
+            id: booking.event_type_id.to_hex(),
+            name: event_type.name.clone(),
+            duration: event_type.duration,
+            color: event_type.color.clone(),
+            location_type: Self::booking_location(&booking, event_type).map(|location| location.kind().to_string()),
+            location: Self::location_text(&booking, event_type),
+        });
+
+        // Questions may have changed since booking; answers to removed ones come last
+        let questions = event_type.as_ref().map_or(&[][..], |event_type| &event_type.questions);
+        let mut answers: Vec<LabeledAnswer> = questions
+            .iter()
+            .filter_map(|question| {
+                booking.answers.iter().find(|a| a.question == question.label).map(|a| LabeledAnswer {
+                    question: question.label.clone(),
+                    kind: Some(question.kind),
+                    answer: a.answer.clone(),
+                })
+            })
+            .collect();
+        answers.extend(
+            booking.answers
+                .iter()
+                .filter(|a| !questions.iter().any(|question| question.label == a.question))
+                .map(|a| LabeledAnswer { question: a.question.clone(), kind: None, answer: a.answer.clone() }),
+        );
+
+        let mut history = vec![BookingHistoryEntry {
+            action: "created".to_string(),
+            at: Some(booking.created_at.to_string()),
+            by: None,
+            from: None,
+            to: None,
+            reason: None,
+        }];
+        if booking.reschedule_history.is_empty()
+            && let Some(previous) = &booking.rescheduled_from {
+            history.push(BookingHistoryEntry {
+                action: "rescheduled".to_string(),
+                at: None,
+                by: None,
+                from: Some(previous.clone()),
+                to: Some(PreviousSlot {
+                    date: booking.date.clone(),
+                    start_time: booking.start_time.clone(),
+                    end_time: booking.end_time.clone(),
+                }),
+                reason: None,
+            });
+        }
+        history.extend(booking.reschedule_history.iter().map(|record| BookingHistoryEntry {
+            action: "rescheduled".to_string(),
+            at: Some(record.rescheduled_at.to_string()),
+            by: Some(record.rescheduled_by.clone()),
+            from: Some(record.from.clone()),
+            to: Some(record.to.clone()),
+            reason: None,
+        }));
+        // The cancellation has its own entry below, with the reason
+        let status_changes = booking.status_history.iter().filter(|change| change.to != BookingStatus::Cancelled);
+        history.extend(status_changes.map(|change| BookingHistoryEntry {
+            action: change.to.to_string(),
+            at: Some(change.changed_at.to_string()),
+            by: Some(change.changed_by.clone()),
+            from: None,
+            to: None,
+            reason: None,
+        }));
+        if let Some(cancelled_at) = booking.cancelled_at {
+            history.push(BookingHistoryEntry {
+                action: "cancelled".to_string(),
+                at: Some(cancelled_at.to_string()),
+                by: booking.cancelled_by.clone(),
+                from: None,
+                to: None,
+                reason: booking.cancellation_reason.clone(),
+            });
+        }
+
+        Ok(HttpResponse::Ok().json(BookingDetailResponse {
+            booking: BookingResponse::from(booking),
+            event_type: event_type_summary,
+            answers,
+            history,
+        }))
+    }
+
+    /// Booking counts by status for the host, overall and per event type.
+    pub async fn booking_stats(&self, claims: web::ReqData<Claims>) -> Result<HttpResponse, AppError> {
+        let user_id = ObjectId::parse_str(&claims.sub)
+            .map_err(|_| AppError::BadRequest("Invalid user ID".to_string()))?;
+
+        let counts = self.booking_repository.count_by_event_type_and_status(&user_id).await?;
+        let event_type_names: HashMap<ObjectId, String> = self.event_type_repository
+            .find_by_user_id(&user_id, true)
+            .await?
+            .into_iter()
+            .filter_map(|event_type| Some((event_type.id?, event_type.name)))
+            .collect();
+
+        let mut by_status: BTreeMap<String, u64> = BTreeMap::new();
+        let mut per_event_type: BTreeMap<ObjectId, BTreeMap<String, u64>> = BTreeMap::new();
+        for (event_type_id, status, bookings) in counts {
+            *by_status.entry(status.clone()).or_default() += bookings;
+            *per_event_type.entry(event_type_id).or_default().entry(status).or_default() += bookings;
+        }
+
+        let event_types = per_event_type
+            .into_iter()
+            .map(|(event_type_id, by_status)| EventTypeBookingStats {
+                event_type_id: event_type_id.to_hex(),
+                event_type_name: event_type_names.get(&event_type_id).cloned(),
+                no_show_rate: Self::no_show_rate(&by_status),
+                by_status,
+            })
+            .collect();
+
+        Ok(HttpResponse::Ok().json(BookingStatsResponse {
+            total: by_status.values().sum(),
+            no_show_rate: Self::no_show_rate(&by_status),
+            by_status,
+            event_types,
+        }))
+    }
+
+    /// No-shows among the bookings that have taken place, i.e. completed or no-show.
+    fn no_show_rate(by_status: &BTreeMap<String, u64>) -> Option<f64> {
+        let count = |status: BookingStatus| by_status.get(status.as_str()).copied().unwrap_or(0);
+        let no_shows = count(BookingStatus::NoShow);
+        let past = count(BookingStatus::Completed) + no_shows;
+        (past > 0).then(|| no_shows as f64 / past as f64)
+    }
+
+    /// Confirms a booking that was waiting for the host and sends both parties the invitation.
+    pub async fn approve_booking(
+        &self,
+        claims: web::ReqData<Claims>,
+        PathObjectId(booking_id): PathObjectId,
+    ) -> Result<HttpResponse, AppError> {
+        let booking = self.find_for_host(&claims, &booking_id).await?;
+        self.approve(booking, &format!("host {}", claims.sub)).await
+    }
+
+    /// Turns down a booking that was waiting for the host, releasing its slot.
+    pub async fn decline_booking(
+        &self,
+        claims: web::ReqData<Claims>,
+        PathObjectId(booking_id): PathObjectId,
+    ) -> Result<HttpResponse, AppError> {
+        let booking = self.find_for_host(&claims, &booking_id).await?;
+        self.decline(booking, &format!("host {}", claims.sub)).await
+    }
+
+    /// Carries out the host action encoded in a signed link, with the same
+    /// checks as the authenticated endpoints. Each link works once.
+    pub async fn perform_signed_action(&self, token: web::Path<String>) -> Result<HttpResponse, AppError> {
+        let secret = AppState::get().action_signing_secret.as_bytes();
+        let invalid = || AppError::coded(StatusCode::FORBIDDEN, "invalid_action_link", "This link is not valid");
+        let claims = signed_actions::verify(secret, &token, Utc::now()).map_err(|e| match e {
+            SignedActionError::Expired => AppError::coded(StatusCode::GONE, "action_link_expired", "This link has expired"),
+            SignedActionError::Malformed | SignedActionError::BadSignature => invalid(),
+        })?;
+        let booking_id = ObjectId::parse_str(&claims.booking_id).map_err(|_| invalid())?;
+
+        let token_hash = signed_actions::token_hash(&token);
+        let expires_at = DateTime::from_millis(claims.expires_at.timestamp_millis());
+        if !self.consumed_action_repository.claim(&token_hash, &booking_id, claims.action, expires_at).await? {
+            return Err(AppError::coded(StatusCode::GONE, "action_link_used", "This link has already been used"));
+        }
+
+        let result = match self.booking_repository.find_by_id(&booking_id).await {
+            Ok(Some(booking)) => match claims.action {
+                SignedAction::Confirm => self.approve(booking, SIGNED_ACTION_ACTOR).await,
+                SignedAction::Decline => self.decline(booking, SIGNED_ACTION_ACTOR).await,
+                SignedAction::MarkNoShow => self.record_outcome(booking, BookingStatus::NoShow, SIGNED_ACTION_ACTOR).await,
+            },
+            Ok(None) => Err(AppError::NotFound("Booking not found".to_string())),
+            Err(e) => Err(e),
+        };
+
+        // A link whose action was refused stays usable, e.g. after a transient failure
+        if result.is_err() {
+            self.consumed_action_repository.release(&token_hash).await;
+        }
+        result
+    }
+
+    /// Moves a booking that has started to completed or no-show.
+    async fn record_outcome(&self, booking: Booking, status: BookingStatus, actor: &str) -> Result<HttpResponse, AppError> {
+        let booking_id = booking.id
+            .ok_or_else(|| AppError::InternalServerError("Booking has no id".to_string()))?;
+        let next = booking.status.transition(status)?;
+
+        // Attendance can only be recorded once the meeting has started
+        if matches!(next, BookingStatus::Completed | BookingStatus::NoShow) {
+            let settings = self.settings_repository.find_by_user_id(&booking.host_user_id).await?;
+            let (tz, _) = timezone::resolve_timezone(None, None, settings.as_ref().map(|s| s.timezone.as_str()))?;
+            let starts_at = NaiveDate::parse_from_str(&booking.date, "%Y-%m-%d")
+                .map(|date| date.and_time(calendar_engine::parse_start_time(&booking.start_time)))
+                .map_err(|_| AppError::InternalServerError("Stored booking has an invalid date".to_string()))?;
+            if starts_at > Utc::now().with_timezone(&tz).naive_local() {
+                return Err(AppError::BadRequest(format!("Cannot mark a booking as {} before it starts", next)));
+            }
+        }
+
+        let updated = self.booking_repository.update_status(&booking_id, booking.status, next, actor).await?
+            .ok_or_else(|| AppError::Conflict("Booking was changed by another request, reload and try again".to_string()))?;
+
+        Ok(HttpResponse::Ok().json(BookingResponse::from(updated)))
+    }
+
+    async fn approve(&self, booking: Booking, actor: &str) -> Result<HttpResponse, AppError> {
+        let booking_id = booking.id
+            .ok_or_else(|| AppError::InternalServerError("Booking has no id".to_string()))?;
+        Self::ensure_pending(&booking, BookingStatus::Confirmed)?;
+
+        let event_type = self.event_type_repository.find_by_id(&booking.event_type_id).await?
+            .ok_or_else(|| AppError::NotFound("Event type not found".to_string()))?;
+        let settings = self.settings_repository.find_by_user_id(&booking.host_user_id).await?
+            .ok_or_else(|| AppError::NotFound("Calendar settings not found".to_string()))?;
+
+        // Booking times are in the host's timezone
+        let (tz, _) = timezone::resolve_timezone(None, None, Some(&settings.timezone))?;
+        let starts_at = NaiveDate::parse_from_str(&booking.date, "%Y-%m-%d")
+            .map(|date| date.and_time(calendar_engine::parse_start_time(&booking.start_time)))
+            .map_err(|_| AppError::InternalServerError("Stored booking has an invalid date".to_string()))?;
+        if starts_at <= Utc::now().with_timezone(&tz).naive_local() {
+            return Err(AppError::BadRequest("Cannot approve a booking that has already started".to_string()));
+        }
+
+        let approved = self.booking_repository
+            .update_status(&booking_id, BookingStatus::Pending, BookingStatus::Confirmed, actor)
+            .await?
+            .ok_or_else(|| AppError::Conflict("Booking was changed by another request, reload and try again".to_string()))?;
+
+        self.send_confirmations(&approved, &event_type, &settings.timezone).await?;
+
+        Ok(HttpResponse::Ok().json(BookingResponse::from(approved)))
+    }
+
+    async fn decline(&self, booking: Booking, actor: &str) -> Result<HttpResponse, AppError> {
+        let booking_id = booking.id
+            .ok_or_else(|| AppError::InternalServerError("Booking has no id".to_string()))?;
+        Self::ensure_pending(&booking, BookingStatus::Declined)?;
+
+        let declined = self.booking_repository
+            .update_status(&booking_id, BookingStatus::Pending, BookingStatus::Declined, actor)
+            .await?
+            .ok_or_else(|| AppError::Conflict("Booking was changed by another request, reload and try again".to_string()))?;
+
+        // A failed email does not undo the decline
+        let event_name = self.event_type_repository.find_by_id(&declined.event_type_id).await?
+            .map(|event_type| event_type.name)
+            .unwrap_or_else(|| "your meeting".to_string());
+        let job = EmailJob::BookingDeclined {
+            to: declined.invitee_email.clone(),
+            booking_id: booking_id.to_hex(),
+            event_name,
+            date: declined.date.clone(),
+            start_time: declined.start_time.clone(),
+        };
+        if let Err(e) = self.email_queue.enqueue(job).await {
+            println!("Failed to queue decline email for booking {}: {}", booking_id.to_hex(), e);
+        }
+
+        Ok(HttpResponse::Ok().json(BookingResponse::from(declined)))
+    }
+
+    /// Only bookings waiting for the host can be answered; unpaid ones wait for payment first.
+    fn ensure_pending(booking: &Booking, answer: BookingStatus) -> Result<(), AppError> {
+        if booking.status != BookingStatus::Pending {
+            return Err(state::illegal_transition(booking.status, answer));
+        }
+        booking.status.transition(answer).map(|_| ())
+    }
+
+    /// Checks an invitee's cancellation or reschedule against the event type's
+    /// policy for it. The host can always make the change.
+    fn check_policy(
+        policy: Option<&impl NoticePolicy>,
+        changed_by: &str,
+        starts_at: NaiveDateTime,
+        now: NaiveDateTime,
+        change: PolicyChange,
+    ) -> Result<(), AppError> {
+        let Some(policy) = policy.filter(|_| changed_by == "invitee") else {
+            return Ok(());
+        };
+        if !policy.allowed() {
+            return Err(Self::policy_violation(
+                &format!("{}_not_allowed", change.noun()),
+                format!("This booking can only be {} by the host", change.past_tense()),
+                policy.policy_text(),
+            ));
+        }
+        if starts_at - now < Duration::minutes(policy.min_notice_minutes() as i64) {
+            return Err(Self::policy_violation(
+                &format!("{}_notice", change.noun()),
+                format!("Bookings cannot be {} less than {} minutes before the start", change.past_tense(), policy.min_notice_minutes()),
+                policy.policy_text(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// A 400 for an invitee change the event type's policy does not allow,
+    /// followed by the host's own wording of the policy when there is one.
+    fn policy_violation(code: &str, message: String, policy_text: Option<&str>) -> AppError {
+        let message = match policy_text.map(str::trim).filter(|text| !text.is_empty()) {
+            Some(policy_text) => format!("{}. {}", message, policy_text),
+            None => message,
+        };
+        AppError::coded(StatusCode::BAD_REQUEST, code, &message)
+    }
+
+    async fn find_for_host(&self, claims: &Claims, booking_id: &ObjectId) -> Result<Booking, AppError> {
+        let booking = self.booking_repository.find_by_id(booking_id).await?
+            .ok_or_else(|| AppError::NotFound("Booking not found".to_string()))?;
+
+        if !booking.is_hosted_by(&claims.sub) {
+            return Err(AppError::Forbidden("Booking does not belong to user".to_string()));
+        }
+
+        Ok(booking)
+    }
+
+    /// Moves the booking to a new slot as the host or the invitee. The new slot
+    /// goes through the same checks as a new booking, ignoring the booking itself.
+    pub async fn reschedule_booking(
+        &self,
+        claims: Option<web::ReqData<Claims>>,
+        PathObjectId(booking_id): PathObjectId,
+        data: web::Json<RescheduleBookingRequest>,
+    ) -> Result<HttpResponse, AppError> {
+        let data = data.into_inner();
+
+        let booking = self.booking_repository.find_by_id(&booking_id).await?
+            .ok_or_else(|| AppError::NotFound("Booking not found".to_string()))?;
+
+        let rescheduled_by = Self::authorize(claims.as_ref(), &booking, data.cancellation_token.as_deref())?;
+        self.reschedule(booking, rescheduled_by, &data.date, &data.start_time, data.end_time.as_deref()).await
+    }
+
+    /// Moves `booking` once the caller has established who is moving it.
+    async fn reschedule(
+        &self,
+        booking: Booking,
+        rescheduled_by: &'static str,
+        date: &str,
+        start_time: &str,
+        requested_end_time: Option<&str>,
+    ) -> Result<HttpResponse, AppError> {
+        let booking_id = booking.id
+            .ok_or_else(|| AppError::InternalServerError("Booking has no id".to_string()))?;
+        let start_time = time_of_day::normalize("start_time", start_time)?;
+        let requested_end_time = requested_end_time
+            .map(|end_time| time_of_day::normalize("end_time", end_time))
+            .transpose()?;
+
+        if booking.status != BookingStatus::Confirmed {
+            return Err(AppError::BadRequest(format!("Cannot reschedule a {} booking", booking.status)));
+        }
+
+        let event_type = self.event_type_repository.find_by_id(&booking.event_type_id).await?
+            .ok_or_else(|| AppError::NotFound("Event type not found".to_string()))?;
+        let settings = self.settings_repository.find_by_user_id(&booking.host_user_id).await?
+            .ok_or_else(|| AppError::NotFound("Calendar settings not found".to_string()))?;
+
+        // Booking times are in the host's timezone
+        let (tz, _) = timezone::resolve_timezone(None, None, Some(&settings.timezone))?;
+        let starts_at = NaiveDate::parse_from_str(&booking.date, "%Y-%m-%d")
+            .map(|date| date.and_time(calendar_engine::parse_start_time(&booking.start_time)))
+            .map_err(|_| AppError::InternalServerError("Stored booking has an invalid date".to_string()))?;
+        let now = Utc::now().with_timezone(&tz).naive_local();
+        if starts_at <= now {
+            return Err(AppError::BadRequest("Cannot reschedule a booking that has already started".to_string()));
+        }
+
+        Self::check_policy(event_type.reschedule_policy.as_ref(), rescheduled_by, starts_at, now, PolicyChange::Reschedule)?;
+
+        // The booking keeps its hosts, including the one round robin assigned it to
+        let hosts = self.slot_hosts(&event_type, &settings, Some(&booking.host_user_id)).await?;
+        let (end_time, conflicts) = self
+            .check_slot(&event_type, &settings, date, &start_time, &hosts, (Some(&booking_id), None))
+            .await?;
+        if let Some(requested_end) = requested_end_time
+            && requested_end != end_time {
+            return Err(AppError::BadRequest(format!(
+                "end_time must be {} for this event type", end_time
+            )));
+        }
+        if !conflicts.is_empty() {
+            return Ok(Self::conflict_response(conflicts, rescheduled_by == "host"));
+        }
+
+        let previous = PreviousSlot {
+            date: booking.date,
+            start_time: booking.start_time,
+            end_time: booking.end_time,
+        };
+        // Another group attendee may have taken the seat since the slot was checked
+        let taken = self.booking_repository.find_taken_seats(&booking.host_user_id, date, &start_time).await?;
+        let seat = (0..event_type.capacity()).find(|seat| !taken.contains(seat)).unwrap_or(0);
+        let rescheduled = self.booking_repository
+            .reschedule(&booking_id, &previous, date, &start_time, &end_time, seat, rescheduled_by)
+            .await?
+            .ok_or_else(|| AppError::Conflict("Booking was changed by another request, reload and try again".to_string()))?;
+
+        // Both parties and any guests get the new time; a failed notification does not undo the move
+        if rescheduled_by == "invitee" {
+            self.notification_repository.notify(
+                &rescheduled.host_user_id,
+                NotificationKind::BookingRescheduled,
+                &format!("Booking rescheduled: {}", event_type.name),
+                &format!(
+                    "{} moved {} at {} to {} at {}",
+                    rescheduled.invitee_name, previous.date, previous.start_time, rescheduled.date, rescheduled.start_time
+                ),
+                Some(&booking_id),
+            ).await;
+        }
+        let host_email = self.user_repository.find_by_id(&rescheduled.host_user_id.to_hex()).await?
+            .map(|host| host.email);
+        let location = Self::location_text(&rescheduled, &event_type);
+        let ics = host_email.as_deref().map(|organizer| {
+            Self::booking_ics(
+                &rescheduled, &event_type.name, &settings.timezone, organizer, location.as_deref(), IcsMethod::Request,
+            )
+        });
+        let recipients = host_email.iter().cloned()
+            .chain([rescheduled.invitee_email.clone()])
+            .chain(rescheduled.guest_emails.iter().cloned())
+            .chain(rescheduled.attendees.iter().map(|attendee| attendee.email.clone()));
+        for to in recipients {
+            let job = EmailJob::BookingRescheduled {
+                to,
+                booking_id: booking_id.to_hex(),
+                event_name: event_type.name.clone(),
+                previous_date: previous.date.clone(),
+                previous_start_time: previous.start_time.clone(),
+                date: rescheduled.date.clone(),
+                start_time: rescheduled.start_time.clone(),
+                ics: ics.clone(),
+            };
+            if let Err(e) = self.email_queue.enqueue(job).await {
+                println!("Failed to queue reschedule email for booking {}: {}", booking_id.to_hex(), e);
+            }
+        }
+
+        Ok(HttpResponse::Ok().json(BookingResponse::from(rescheduled)))
+    }
+
+    /// The booking behind an invitee's emailed link.
+    pub async fn public_get_booking(&self, token: web::Path<String>) -> Result<HttpResponse, AppError> {
+        let booking = self.find_manageable(&token).await?;
+        Ok(HttpResponse::Ok().json(BookingResponse {
+            tracking: None,
+            ..BookingResponse::from(booking)
+        }))
+    }
+
+    pub async fn public_cancel_booking(
+        &self,
+        token: web::Path<String>,
+        data: web::Json<PublicCancelBookingRequest>,
+    ) -> Result<HttpResponse, AppError> {
+        // Validate request data
+        data.validate()
+            .map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+        let booking = self.find_manageable(&token).await?;
+        self.cancel(booking, "invitee", None, data.reason.as_deref()).await
+    }
+
+    pub async fn public_reschedule_booking(
+        &self,
+        token: web::Path<String>,
+        data: web::Json<PublicRescheduleBookingRequest>,
+    ) -> Result<HttpResponse, AppError> {
+        let booking = self.find_manageable(&token).await?;
+        self.reschedule(booking, "invitee", &data.date, &data.start_time, data.end_time.as_deref()).await
+    }
+
+    /// Finds the booking behind a management token. A booking that can no
+    /// longer be changed answers 410, so the invitee's page can say why.
+    async fn find_manageable(&self, token: &str) -> Result<Booking, AppError> {
+        let booking = self.booking_repository.find_by_management_token(token).await?
+            .ok_or_else(|| AppError::NotFound("Booking not found".to_string()))?;
+
+        let expired = || AppError::coded(StatusCode::GONE, "booking_expired", "This booking has already taken place");
+        match booking.status {
+            BookingStatus::Cancelled => {
+                return Err(AppError::coded(StatusCode::GONE, "booking_cancelled", "This booking has been cancelled"));
+            }
+            BookingStatus::Declined => {
+                return Err(AppError::coded(StatusCode::GONE, "booking_cancelled", "This booking request was declined"));
+            }
+            BookingStatus::Expired => {
+                return Err(AppError::coded(StatusCode::GONE, "booking_cancelled", "This booking expired before it was paid for"));
+            }
+            BookingStatus::Completed | BookingStatus::NoShow => return Err(expired()),
+            BookingStatus::PendingPayment | BookingStatus::Pending | BookingStatus::Confirmed => {}
+        }
+
+        // Booking times are in the host's timezone
+        let settings = self.settings_repository.find_by_user_id(&booking.host_user_id).await?;
+        let (tz, _) = timezone::resolve_timezone(None, None, settings.as_ref().map(|s| s.timezone.as_str()))?;
+        let starts_at = NaiveDate::parse_from_str(&booking.date, "%Y-%m-%d")
+            .map(|date| date.and_time(calendar_engine::parse_start_time(&booking.start_time)))
+            .map_err(|_| AppError::InternalServerError("Stored booking has an invalid date".to_string()))?;
+        if starts_at <= Utc::now().with_timezone(&tz).naive_local() {
+            return Err(expired());
+        }
+
+        Ok(booking)
+    }
+
+    /// Unguessable token for the invitee's emailed links, like refresh tokens.
+    fn generate_management_token() -> String {
+        let mut rng = thread_rng();
+        (0..32)
+            .map(|_| rng.sample(rand::distributions::Alphanumeric) as char)
+            .collect()
+    }
+
+    /// Calendar invitation for `booking`, whose times are local to the host's
+    /// `timezone`. The uid is stable per booking and the sequence is the send
+    /// time, so each update replaces what calendars already hold.
+    fn booking_ics(
+        booking: &Booking,
+        summary: &str,
+        timezone: &str,
+        organizer_email: &str,
+        location: Option<&str>,
+        method: IcsMethod,
+    ) -> String {
+        let date = NaiveDate::parse_from_str(&booking.date, "%Y-%m-%d").unwrap_or_default();
+        let tz: Tz = timezone.parse().unwrap_or(Tz::UTC);
+        let instant = |time: NaiveTime| {
+            let local = date.and_time(time);
+            timezone::local_instant(tz, local).map(|at| at.with_timezone(&Utc)).unwrap_or_else(|| local.and_utc())
+        };
+        let uid = format!("{}@calendly", booking.id.map(|id| id.to_hex()).unwrap_or_default());
+        let summary = text::truncate_for_display(summary, 100);
+        let location = location.map(|location| text::truncate_for_display(location, 500));
+        let attendee_name = text::truncate_for_display(&booking.invitee_name, 100);
+        let attendees: Vec<IcsAttendee> = booking.attendees
+            .iter()
+            .map(|attendee| IcsAttendee { name: attendee.name.as_deref(), email: &attendee.email })
+            .collect();
+
+        ics::render(
+            &IcsEvent {
+                uid: &uid,
+                sequence: Utc::now().timestamp(),
+                starts_at: instant(calendar_engine::parse_start_time(&booking.start_time)),
+                ends_at: instant(calendar_engine::parse_end_time(&booking.end_time)),
+                summary: &summary,
+                location: location.as_deref(),
+                organizer_email,
+                attendee_name: &attendee_name,
+                attendee_email: &booking.invitee_email,
+                guest_emails: &booking.guest_emails,
+                attendees: &attendees,
+            },
+            method,
+        )
+    }
+
+    /// The location a booking takes place at. Bookings made before locations
+    /// were chosen per booking use the event type's first one.
+    fn booking_location<'a>(booking: &'a Booking, event_type: &'a EventType) -> Option<&'a Location> {
+        booking.chosen_location.as_ref().or(event_type.locations.first())
+    }
+
+    /// Where the meeting happens, as shown in emails and calendar invitations.
+    pub(crate) fn location_text(booking: &Booking, event_type: &EventType) -> Option<String> {
+        match Self::booking_location(booking, event_type)? {
+            Location::InPerson { address } if address.is_empty() => Some("In person".to_string()),
+            Location::InPerson { address } => Some(address.clone()),
+            Location::Phone { number: Some(number), who_calls: WhoCalls::Invitee } => Some(format!("Phone call to {}", number)),
+            Location::Phone { .. } => {
+                // The host calls the number the invitee gave
+                let number = event_type.questions
+                    .iter()
+                    .filter(|question| question.kind == QuestionKind::Phone)
+                    .find_map(|question| booking.answers.iter().find(|a| a.question == question.label))
+                    .map(|answer| answer.answer.to_text());
+                Some(match number {
+                    Some(number) => format!("Phone call from the host to {}", number),
+                    None => "Phone call from the host".to_string(),
+                })
+            }
+            Location::Video { .. } => booking.meeting_link.clone(),
+            Location::Custom { note } => Some(note.clone()),
+        }
+    }
+
+    /// Everyone who hosts a booking of `event_type`, owner first: the owner and
+    /// any collective hosts, or for round-robin event types each host that
+    /// bookings can be assigned to, or just `assigned` once one has been.
+    async fn slot_hosts(
+        &self,
+        event_type: &EventType,
+        settings: &CalendarSettings,
+        assigned: Option<&ObjectId>,
+    ) -> Result<Vec<HostSchedule>, AppError> {
+        let availability = self.availability_repository.find_by_id(&event_type.availability_schedule_id).await?
+            .ok_or_else(|| AppError::NotFound("Availability schedule not found".to_string()))?;
+        let owner = HostSchedule::new(event_type.user_id, settings.clone(), availability);
+
+        let others = match assigned {
+            _ if !event_type.is_round_robin() => self.slot_sources.co_host_schedules(event_type, settings).await?,
+            Some(host_id) if *host_id == owner.user_id => return Ok(vec![owner]),
+            Some(host_id) => return self.slot_sources.host_schedules(std::slice::from_ref(host_id), settings).await,
+            None => self.slot_sources.host_schedules(&event_type.hosts, settings).await?,
+        };
+        Ok(std::iter::once(owner).chain(others).collect())
+    }
+
+    /// Who a new booking or hold of the slot can go to, best first, and where
+    /// the slot ends. Round-robin event types offer each free host alone, in
+    /// assignment order, unless `hold` already picked one; other event types
+    /// need all of their hosts together. Returns the conflicts instead when
+    /// nobody can take the slot.
+    async fn free_hosts(
+        &self,
+        event_type: &EventType,
+        settings: &CalendarSettings,
+        date_str: &str,
+        start_time_str: &str,
+        hold: Option<&SlotHold>,
+    ) -> Result<Result<(String, Vec<Vec<HostSchedule>>), Vec<SlotConflict>>, AppError> {
+        let assigned = hold.map(|hold| &hold.host_user_id);
+        let hosts = self.slot_hosts(event_type, settings, assigned).await?;
+        let teams: Vec<Vec<HostSchedule>> = if event_type.is_round_robin() && assigned.is_none() {
+            self.round_robin_order(event_type, date_str, hosts).await?
+                .into_iter()
+                .map(|host| vec![host])
+                .collect()
+        } else {
+            vec![hosts]
+        };
+
+        let excluded = (None, hold.and_then(|hold| hold.id.as_ref()));
+        let mut end_time = String::new();
+        let mut free = Vec::new();
+        let mut first_conflicts = None;
+        for team in teams {
+            let (team_end_time, conflicts) = self
+                .check_slot(event_type, settings, date_str, start_time_str, &team, excluded)
+                .await?;
+            end_time = team_end_time;
+            if conflicts.is_empty() {
+                free.push(team);
+            } else {
+                first_conflicts.get_or_insert(conflicts);
+            }
+        }
+
+        if free.is_empty() {
+            return Ok(Err(first_conflicts.unwrap_or_default()));
+        }
+        Ok(Ok((end_time, free)))
+    }
+
+    /// Orders the hosts of a round-robin event type by who should get its
+    /// next booking on `date_str`, as its round-robin strategy says.
+    async fn round_robin_order(
+        &self,
+        event_type: &EventType,
+        date_str: &str,
+        mut hosts: Vec<HostSchedule>,
+    ) -> Result<Vec<HostSchedule>, AppError> {
+        let event_type_id = event_type.id
+            .ok_or_else(|| AppError::InternalServerError("Event type has no id".to_string()))?;
+        let last_assigned = self.booking_repository.find_last_created_by_host(&event_type_id).await?;
+        // Hosts never assigned a booking sort first
+        let assigned_at = |host: &HostSchedule| last_assigned.get(&host.user_id).copied();
+
+        match event_type.round_robin_strategy {
+            RoundRobinStrategy::LeastBooked => {
+                let date = NaiveDate::parse_from_str(date_str, "%Y-%m-%d")
+                    .map_err(|_| AppError::BadRequest("Invalid date format. Use YYYY-MM-DD".to_string()))?;
+                let week_start = date - Duration::days(date.weekday().num_days_from_monday() as i64);
+                let week_end = week_start + Duration::days(6);
+                let host_ids: Vec<ObjectId> = hosts.iter().map(|host| host.user_id).collect();
+                let booked = self.booking_repository
+                    .count_holding_by_hosts_in_range(
+                        &host_ids,
+                        &week_start.format("%Y-%m-%d").to_string(),
+                        &week_end.format("%Y-%m-%d").to_string(),
+                    )
+                    .await?;
+                // The sort is stable, so full ties keep the listed order
+                hosts.sort_by_key(|host| (booked.get(&host.user_id).copied().unwrap_or(0), assigned_at(host)));
+            }
+            RoundRobinStrategy::Rotation => {
+                let last = hosts.iter()
+                    .enumerate()
+                    .filter_map(|(i, host)| assigned_at(host).map(|at| (at, i)))
+                    .max()
+                    .map(|(_, i)| i);
+                if let Some(last) = last {
+                    hosts.rotate_left(last + 1);
+                }
+            }
+        }
+
+        Ok(hosts)
+    }
+
+    /// Works out where a booking of `event_type` starting at `date` and
+    /// `start_time` (host timezone) ends, and why that slot cannot be booked
+    /// with all of `hosts`, as `slot_hosts` returns them. `excluded` leaves a
+    /// booking out of the overlap check, e.g. the one being moved, and a slot
+    /// hold, e.g. the one being turned into a booking.
+    async fn check_slot(
+        &self,
+        event_type: &EventType,
+        settings: &CalendarSettings,
+        date_str: &str,
+        start_time_str: &str,
+        hosts: &[HostSchedule],
+        excluded: (Option<&ObjectId>, Option<&ObjectId>),
+    ) -> Result<(String, Vec<SlotConflict>), AppError> {
+        let date = NaiveDate::parse_from_str(date_str, "%Y-%m-%d")
+            .map_err(|_| AppError::BadRequest("Invalid date format. Use YYYY-MM-DD".to_string()))?;
+        let start_time = time_of_day::parse(start_time_str)
+            .ok_or_else(|| AppError::BadRequest(time_of_day::invalid_message("start_time", start_time_str)))?;
+
+        // Dates and times are in the host's timezone
+        let (tz, _) = timezone::resolve_timezone(None, None, Some(&settings.timezone))?;
+        let now = Utc::now().with_timezone(&tz).naive_local();
+        if date.and_time(start_time) <= now {
+            return Err(AppError::BadRequest("Cannot book a time in the past".to_string()));
+        }
+
+        let day_config = calendar_engine::resolve_day_config(event_type, &calendar_engine::day_of_week(date));
+        let buffer_time = day_config.buffer_time.unwrap_or_else(|| settings.buffer_time.clone());
+
+        let (end_time, wrapped) = start_time.overflowing_add_signed(Duration::minutes(day_config.duration as i64));
+        if wrapped != 0 {
+            return Err(AppError::BadRequest("Booking must end on the same day it starts".to_string()));
+        }
+        let end_time_str = end_time.format("%H:%M").to_string();
+
+        let mut conflicts: Vec<SlotConflict> = calendar_engine::notice_conflict(
+            event_type,
+            &AppState::get().booking_horizon,
+            date.and_time(start_time),
+            now,
+        )
+            .into_iter()
+            .chain(calendar_engine::scheduling_window_conflict(event_type, date, now.date()))
+            .collect();
+
+        // Every host must be free for the whole booking; only the first one's
+        // bookings can share a group event slot
+        let slot = (start_time, end_time);
+        let mut seats_taken = 0;
+        for (i, host) in hosts.iter().enumerate() {
+            host.is_available(date_str, start_time_str, &end_time_str, &mut conflicts);
+            let checked: Vec<ObjectId> = hosts[..i].iter().map(|host| host.user_id).collect();
+            let host_seats_taken = self.slot_sources
+                .host_conflicts(host, Some(event_type), date_str, slot, &buffer_time, excluded, &checked, &mut conflicts)
+                .await?;
+            if i == 0 {
+                seats_taken = host_seats_taken;
+            }
+        }
+        if seats_taken >= event_type.capacity() {
+            conflicts.push(SlotConflict::new("slot_full", "All places in this time slot are taken"));
+        }
+
+        Ok((end_time_str, conflicts))
+    }
+
+    /// Checks each guest email and returns them lowercased, without duplicates
+    /// or the invitee's own address.
+    fn normalize_guest_emails(invitee_email: &str, guest_emails: &[String]) -> Result<Vec<String>, AppError> {
+        let invitee_email = invitee_email.trim().to_lowercase();
+        let mut normalized: Vec<String> = Vec::with_capacity(guest_emails.len());
+        for guest_email in guest_emails {
+            let guest_email = guest_email.trim().to_lowercase();
+            if guest_email.len() > 254 || !guest_email.validate_email() {
+                return Err(AppError::ValidationError(format!(
+                    "Invalid guest email '{}'", text::truncate_for_display(&guest_email, 254)
+                )));
+            }
+            if guest_email != invitee_email && !normalized.contains(&guest_email) {
+                normalized.push(guest_email);
+            }
+        }
+        Ok(normalized)
+    }
+
+    /// Keeps tracking data small: every UTM value and metadata value within
+    /// the value limit, and a bounded number of short metadata keys.
+    fn validate_tracking(tracking: Option<&BookingTracking>) -> Result<(), AppError> {
+        let Some(tracking) = tracking else {
+            return Ok(());
+        };
+
+        let utm_fields = [
+            ("utm_source", &tracking.utm_source),
+            ("utm_medium", &tracking.utm_medium),
+            ("utm_campaign", &tracking.utm_campaign),
+            ("utm_content", &tracking.utm_content),
+            ("utm_term", &tracking.utm_term),
+        ];
+        for (field, value) in utm_fields {
+            if value.as_ref().is_some_and(|value| value.chars().count() > TRACKING_MAX_VALUE_CHARS) {
+                return Err(AppError::ValidationError(format!(
+                    "tracking.{} must be at most {} characters", field, TRACKING_MAX_VALUE_CHARS
+                )));
+            }
+        }
+
+        if tracking.metadata.len() > TRACKING_MAX_METADATA_KEYS {
+            return Err(AppError::ValidationError(format!(
+                "tracking.metadata may have at most {} keys, got {}", TRACKING_MAX_METADATA_KEYS, tracking.metadata.len()
+            )));
+        }
+        for (key, value) in &tracking.metadata {
+            if key.is_empty() || key.chars().count() > TRACKING_MAX_KEY_CHARS {
+                return Err(AppError::ValidationError(format!(
+                    "tracking.metadata key '{}' must be between 1 and {} characters",
+                    text::truncate_for_display(key, TRACKING_MAX_KEY_CHARS), TRACKING_MAX_KEY_CHARS
+                )));
+            }
+            if value.chars().count() > TRACKING_MAX_VALUE_CHARS {
+                return Err(AppError::ValidationError(format!(
+                    "tracking.metadata value for '{}' must be at most {} characters", key, TRACKING_MAX_VALUE_CHARS
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Answers 409 with why the slot is taken. Only the host learns which
+    /// booking, rule or time range is in the way.
+    fn conflict_response(conflicts: Vec<SlotConflict>, for_host: bool) -> HttpResponse {
+        let conflicts: Vec<SlotConflict> = if for_host {
+            conflicts
+        } else {
+            conflicts.into_iter().map(SlotConflict::without_details).collect()
+        };
+        HttpResponse::Conflict().json(json!({
+            "error": "Conflict",
+            "code": "slot_unavailable",
+            "message": "Requested time slot is not available",
+            "conflicts": conflicts
+        }))
+    }
+
+    /// Required questions need a non-empty answer, each answer must fit its
+    /// question's kind, and answers must not name questions the event type does not ask.
+    fn validate_answers(questions: &[Question], answers: &[BookingAnswer]) -> Result<(), AppError> {
+        let mut problems = Vec::new();
+
+        let missing: Vec<&str> = questions
+            .iter()
+            .filter(|question| question.required)
+            .filter(|question| !answers.iter().any(|a| a.question == question.label && !a.answer.is_blank()))
+            .map(|question| question.label.as_str())
+            .collect();
+        if !missing.is_empty() {
+            problems.push(format!("Missing answers for: {}", missing.join(", ")));
+        }
+
+        let mut unknown = Vec::new();
+        for (i, answer) in answers.iter().enumerate() {
+            if answers[..i].iter().any(|a| a.question == answer.question) {
+                problems.push(format!("Question answered more than once: {}", answer.question));
+            }
+
+            let Some(question) = questions.iter().find(|q| q.label == answer.question) else {
+                unknown.push(answer.question.as_str());
+                continue;
+            };
+            if answer.answer.is_blank() {
+                continue;
+            }
+            if let Err(problem) = Self::check_answer(question, &answer.answer) {
+                problems.push(format!("{}: {}", question.label, problem));
+            }
+        }
+        if !unknown.is_empty() {
+            problems.push(format!("Unknown questions: {}", unknown.join(", ")));
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(AppError::ValidationError(problems.join("; ")))
+        }
+    }
+
+    /// The invitee's trimmed phone number, which must be one. Event types
+    /// that send SMS reminders need it.
+    fn normalize_invitee_phone(event_type: &EventType, invitee_phone: Option<String>) -> Result<Option<String>, AppError> {
+        let invitee_phone = invitee_phone
+            .map(|phone| phone.trim().to_string())
+            .filter(|phone| !phone.is_empty());
+        match &invitee_phone {
+            Some(phone) if !validation::is_phone_number(phone) => {
+                Err(AppError::ValidationError("invitee_phone must be a phone number".to_string()))
+            }
+            None if event_type.sends_sms_reminders() => {
+                Err(AppError::ValidationError("invitee_phone is required because this event type sends SMS reminders".to_string()))
+            }
+            _ => Ok(invitee_phone),
+        }
+    }
+
+    /// Reads the prefill parameters of a public booking link and keeps the
+    /// values that would pass as form input. Anything else is left out with a
+    /// warning rather than refusing the page.
+    fn read_prefill(params: &HashMap<String, String>, questions: &[Question]) -> (Prefill, Vec<String>) {
+        let mut prefill = Prefill::default();
+        let mut warnings = Vec::new();
+        let mut params: Vec<(&String, &String)> = params.iter().collect();
+        params.sort();
+
+        for (key, value) in params {
+            let value = value.trim();
+            if value.is_empty() {
+                continue;
+            }
+            let problem = match key.as_str() {
+                "name" if value.chars().count() > 100 => Some("must be at most 100 characters"),
+                "email" if value.len() > 254 || !value.validate_email() => Some("must be an email address"),
+                "phone" if value.len() > 30 || !validation::is_phone_number(value) => Some("must be a phone number"),
+                "name" => prefill.name.replace(value.to_string()).and(None),
+                "email" => prefill.email.replace(value.to_string()).and(None),
+                "phone" => prefill.phone.replace(value.to_string()).and(None),
+                _ => match key.strip_prefix(PREFILL_ANSWER_PREFIX) {
+                    None => Some("is not a prefill parameter"),
+                    Some(question_key) => match Self::question_by_key(questions, question_key) {
+                        None => Some("matches none of the event type's questions"),
+                        Some(question) => Self::check_answer(question, &Self::prefill_answer(question, value))
+                            .map(|()| prefill.answers.insert(question_key.to_string(), value.to_string()))
+                            .err(),
+                    },
+                },
+            };
+            if let Some(problem) = problem {
+                warnings.push(format!("{} {}", key, problem));
+            }
+        }
+        (prefill, warnings)
+    }
+
+    /// Fills in what the invitee left empty from the link's prefill. Answers
+    /// to questions the event type does not ask are ignored.
+    fn apply_prefill(data: &mut PublicBookingRequest, prefill: Prefill, questions: &[Question]) {
+        if data.invitee_name.trim().is_empty()
+            && let Some(name) = prefill.name {
+            data.invitee_name = name;
+        }
+        if data.invitee_email.trim().is_empty()
+            && let Some(email) = prefill.email {
+            data.invitee_email = email;
+        }
+        if data.invitee_phone.is_none() {
+            data.invitee_phone = prefill.phone;
+        }
+        for (key, value) in prefill.answers {
+            let Some(question) = Self::question_by_key(questions, &key) else {
+                continue;
+            };
+            if !data.answers.iter().any(|answer| answer.question == question.label) {
+                data.answers.push(BookingAnswer { question: question.label.clone(), answer: Self::prefill_answer(question, &value) });
+            }
+        }
+    }
+
+    fn question_by_key<'a>(questions: &'a [Question], key: &str) -> Option<&'a Question> {
+        questions.iter().find(|question| template::question_key(&question.label) == key)
+    }
+
+    /// A prefilled answer as the form would send it; checkbox options are comma separated.
+    fn prefill_answer(question: &Question, value: &str) -> AnswerValue {
+        match question.kind {
+            QuestionKind::Checkbox => AnswerValue::Choices(
+                value.split(',').map(str::trim).filter(|choice| !choice.is_empty()).map(str::to_string).collect(),
+            ),
+            _ => AnswerValue::Text(value.trim().to_string()),
+        }
+    }
+
+    fn check_answer(question: &Question, answer: &AnswerValue) -> Result<(), &'static str> {
+        const MAX_ANSWER_CHARS: usize = 2000;
+
+        if answer.to_text().chars().count() > MAX_ANSWER_CHARS {
+            return Err("answer must be at most 2000 characters");
+        }
+
+        match (question.kind, answer) {
+            (QuestionKind::Checkbox, AnswerValue::Choices(choices)) => {
+                if !choices.iter().all(|choice| question.options.contains(choice)) {
+                    return Err("answers must be among the question's options");
+                }
+                if choices.iter().enumerate().any(|(i, choice)| choices[..i].contains(choice)) {
+                    return Err("options must not repeat");
+                }
+                Ok(())
+            }
+            (QuestionKind::Checkbox, AnswerValue::Text(_)) => Err("answer must be a list of options"),
+            (_, AnswerValue::Choices(_)) => Err("answer must be text"),
+            (QuestionKind::Radio, AnswerValue::Text(text)) if !question.options.contains(text) => {
+                Err("answer must be one of the question's options")
+            }
+            (QuestionKind::Text, AnswerValue::Text(text)) if text.contains('\n') => {
+                Err("answer must be a single line")
+            }
+            (QuestionKind::Phone, AnswerValue::Text(text)) if !validation::is_phone_number(text.trim()) => {
+                Err("answer must be a phone number")
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn authorize(
+        claims: Option<&web::ReqData<Claims>>,
+        booking: &Booking,
+        cancellation_token: Option<&str>,
+    ) -> Result<&'static str, AppError> {
+        let is_host = claims.is_some_and(|claims| booking.is_hosted_by(&claims.sub));
+        let is_invitee = !booking.cancellation_token.is_empty()
+            && cancellation_token == Some(booking.cancellation_token.as_str());
+
+        match (is_host, is_invitee, claims.is_some()) {
+            (true, _, _) => Ok("host"),
+            (false, true, _) => Ok("invitee"),
+            (false, false, true) => Err(AppError::Forbidden("Booking does not belong to user".to_string())),
+            (false, false, false) => Err(AppError::Unauthorized(
+                "Sign in as the host or provide the cancellation token".to_string()
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{test as actix_test, FromRequest, HttpMessage};
+
+    use super::*;
+    use crate::modules::booking::booking_model::{AnswerValue, BookingAnswer, BookingTracking};
+    use crate::modules::calendar::calendar_model::{CancellationPolicy, EmbedSettings, HostAssignment, Question, QuestionKind, ReschedulePolicy};
+    use crate::modules::calendar::calendar_schema::ConflictRange;
+    use crate::test_support::{self, with_database};
+
+    fn booking_request(event_type_id: &ObjectId, invitee: &str, date: &str, start_time: &str) -> CreateBookingRequest {
+        CreateBookingRequest {
+            event_type_id: event_type_id.to_hex(),
+            invitee_name: invitee.to_string(),
+            invitee_email: format!("{}@example.com", invitee),
+            invitee_phone: None,
+            guest_emails: Vec::new(),
+            date: date.to_string(),
+            start_time: start_time.to_string(),
+            answers: Vec::new(),
+            chosen_location: None,
+            tracking: None,
+            hold_id: None,
+        }
+    }
+
+    fn claims_of(user_id: &ObjectId) -> web::ReqData<Claims> {
+        let req = actix_test::TestRequest::default().to_http_request();
+        req.extensions_mut().insert(Claims {
+            sub: user_id.to_hex(),
+            exp: 0,
+            iat: 0,
+            email: "host@example.com".to_string(),
+            role: "member".to_string(),
+        });
+        web::ReqData::<Claims>::extract(&req).into_inner().unwrap()
+    }
+
+    async fn conflict_body(for_host: bool) -> serde_json::Value {
+        let conflict = SlotConflict {
+            booking_id: Some(ObjectId::new().to_hex()),
+            rule_id: Some("rule".to_string()),
+            range: Some(ConflictRange { start: "10:00".to_string(), end: "10:30".to_string() }),
+            ..SlotConflict::new("booking_overlap", "Time slot overlaps an existing booking")
+        };
+        let response = BookingController::conflict_response(vec![conflict], for_host);
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let body = actix_web::body::to_bytes(response.into_body()).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[actix_web::test]
+    async fn disabled_page_answers_with_the_hosts_message() {
+        let error = AppError::coded(StatusCode::FORBIDDEN, PUBLIC_PAGE_DISABLED, "Back in May");
+        let response = BookingController::unavailable_page(error).unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = actix_web::body::to_bytes(response.into_body()).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body, json!({ "available": false, "message": "Back in May" }));
+
+        let error = BookingController::unavailable_page(AppError::NotFound("Event type not found".to_string()));
+        assert!(matches!(error, Err(AppError::NotFound(_))));
+    }
+
+    #[actix_web::test]
+    async fn conflicts_hide_their_source_from_everyone_but_the_host() {
+        let body = conflict_body(false).await;
+        assert_eq!(body["conflicts"][0], json!({
+            "code": "booking_overlap",
+            "message": "Time slot overlaps an existing booking",
+        }));
+
+        let body = conflict_body(true).await;
+        assert!(body["conflicts"][0]["booking_id"].is_string());
+        assert_eq!(body["conflicts"][0]["rule_id"], "rule");
+        assert_eq!(body["conflicts"][0]["range"], json!({ "start": "10:00", "end": "10:30" }));
+    }
+
+    fn prefill_questions() -> Vec<Question> {
+        let question = |label: &str, kind, options: &[&str]| Question {
+            label: label.to_string(),
+            kind,
+            required: false,
+            options: options.iter().map(|option| option.to_string()).collect(),
+        };
+        vec![
+            question("Company", QuestionKind::Text, &[]),
+            question("Phone number", QuestionKind::Phone, &[]),
+            question("Topics", QuestionKind::Checkbox, &["Pricing", "Demo"]),
+        ]
+    }
+
+    #[test]
+    fn link_prefill_keeps_valid_values_and_warns_about_the_rest() {
+        let params: HashMap<String, String> = [
+            ("name", " Jane "),
+            ("email", "not-an-email"),
+            ("phone", "+49 30 1234567"),
+            ("a_company", "Acme"),
+            ("a_topics", "Pricing, Demo"),
+            ("a_budget", "Large"),
+            ("a_phone_number", "call me"),
+            ("utm_source", "newsletter"),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect();
+
+        let (prefill, warnings) = BookingController::read_prefill(&params, &prefill_questions());
+
+        assert_eq!(prefill.name.as_deref(), Some("Jane"));
+        assert_eq!(prefill.email, None);
+        assert_eq!(prefill.phone.as_deref(), Some("+49 30 1234567"));
+        assert_eq!(prefill.answers.keys().collect::<Vec<_>>(), ["company", "topics"]);
+        assert_eq!(warnings, [
+            "a_budget matches none of the event type's questions",
+            "a_phone_number answer must be a phone number",
+            "email must be an email address",
+            "utm_source is not a prefill parameter",
+        ]);
+    }
+
+    #[test]
+    fn prefill_only_fills_what_the_invitee_left_empty() {
+        let mut data: PublicBookingRequest = serde_json::from_value(json!({
+            "invitee_email": "ivy@example.com",
+            "date": "2026-03-02",
+            "start_time": "10:00",
+            "answers": [{ "question": "Company", "answer": "Typed in" }],
+        }))
+        .unwrap();
+        let prefill: Prefill = serde_json::from_value(json!({
+            "name": "Jane",
+            "email": "jane@example.com",
+            "answers": { "company": "Acme", "topics": "Demo", "budget": "Large" },
+        }))
+        .unwrap();
+
+        BookingController::apply_prefill(&mut data, prefill, &prefill_questions());
+
+        assert_eq!(data.invitee_name, "Jane");
+        assert_eq!(data.invitee_email, "ivy@example.com");
+        let answers: Vec<(&str, String)> = data.answers.iter().map(|a| (a.question.as_str(), a.answer.to_text())).collect();
+        assert_eq!(answers, [("Company", "Typed in".to_string()), ("Topics", "Demo".to_string())]);
+        assert!(data.validate().is_ok());
+    }
+
+    fn notice_policy(allowed: bool, min_notice_minutes: i32) -> CancellationPolicy {
+        CancellationPolicy { allowed, min_notice_minutes, policy_text: Some("Call us instead.".to_string()) }
+    }
+
+    fn error_code(result: Result<(), AppError>) -> String {
+        match result {
+            Err(AppError::Coded(400, code, _)) => code,
+            other => panic!("Expected a policy violation, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn invitees_may_change_a_booking_exactly_at_the_notice() {
+        let now = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap().and_hms_opt(9, 0, 0).unwrap();
+        let policy = notice_policy(true, 60);
+        let check = |starts_at| BookingController::check_policy(Some(&policy), "invitee", starts_at, now, PolicyChange::Cancel);
+
+        assert!(check(now + Duration::minutes(60)).is_ok());
+        assert_eq!(error_code(check(now + Duration::minutes(59))), "cancellation_notice");
+    }
+
+    #[test]
+    fn disallowed_changes_are_refused_with_the_hosts_wording() {
+        let now = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap().and_hms_opt(9, 0, 0).unwrap();
+        let policy = ReschedulePolicy { allowed: false, min_notice_minutes: 0, policy_text: Some("Call us instead.".to_string()) };
+
+        let refused = BookingController::check_policy(Some(&policy), "invitee", now + Duration::days(7), now, PolicyChange::Reschedule);
+        let Err(AppError::Coded(400, code, message)) = refused else {
+            panic!("Expected a policy violation");
+        };
+        assert_eq!(code, "reschedule_not_allowed");
+        assert_eq!(message, "This booking can only be rescheduled by the host. Call us instead.");
+    }
+
+    #[test]
+    fn hosts_are_not_bound_by_the_policy() {
+        let now = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap().and_hms_opt(9, 0, 0).unwrap();
+        let soon = now + Duration::minutes(5);
+
+        for policy in [notice_policy(false, 0), notice_policy(true, 60)] {
+            assert!(BookingController::check_policy(Some(&policy), "host", soon, now, PolicyChange::Cancel).is_ok());
+        }
+        assert!(BookingController::check_policy(None::<&CancellationPolicy>, "invitee", soon, now, PolicyChange::Cancel).is_ok());
+    }
+
+    #[test]
+    fn export_cuts_oversized_invitee_data() {
+        with_database(|db| async move {
+            let timezone = "Europe/Berlin";
+            let (settings, schedule) = test_support::create_host(&db, timezone).await;
+            let host = settings.user_id;
+            let question = Question { label: "Notes".to_string(), kind: QuestionKind::Textarea, required: false, options: Vec::new() };
+            let event_type = EventTypeRepository::new(db.clone())
+                .create(EventType { questions: vec![question], ..test_support::event_type(&host, &schedule) })
+                .await
+                .unwrap();
+            let date = test_support::date_in(timezone, 3);
+
+            // Stored before the booking form limited these
+            let metadata = (0..100).map(|i| (format!("key-{}", i), "v".repeat(200))).collect();
+            let booking = Booking {
+                invitee_name: "n".repeat(1000),
+                answers: vec![BookingAnswer { question: "Notes".to_string(), answer: AnswerValue::Text("a".repeat(10_000)) }],
+                tracking: Some(BookingTracking { metadata, ..Default::default() }),
+                ..test_support::booking(&event_type.id.unwrap(), &host, &date, "10:00")
+            };
+            BookingRepository::new(db.clone()).create_in_free_seat(booking, 1).await.unwrap();
+
+            let controller = BookingController::new(db.clone());
+            let query = web::Query(ExportBookingsQuery { from: date.clone(), to: date, format: None });
+            let response = controller.export_bookings(claims_of(&host), query).await.unwrap();
+            let body = actix_web::body::to_bytes(response.into_body()).await.unwrap();
+            let body = String::from_utf8(body.to_vec()).unwrap();
+
+            assert!(body.contains(&format!("{}…", "n".repeat(EXPORT_NAME_CHARS - 1))));
+            assert!(!body.contains(&"n".repeat(EXPORT_NAME_CHARS)));
+            assert!(body.contains(&format!("{}…", "a".repeat(EXPORT_ANSWER_CHARS - 1))));
+            assert!(!body.contains(&"a".repeat(EXPORT_ANSWER_CHARS)));
+            assert!(body.chars().count() < EXPORT_NAME_CHARS + EXPORT_ANSWER_CHARS + EXPORT_METADATA_CHARS + 500);
+        });
+    }
+
+    #[test]
+    fn embed_config_is_cached_for_a_day() {
+        with_database(|db| async move {
+            let (settings, schedule) = test_support::create_host(&db, "Europe/Berlin").await;
+            let host = settings.user_id;
+            let embed_settings = EmbedSettings {
+                hide_event_details: true,
+                background_color: "#ffffff".to_string(),
+                text_color: "#111111".to_string(),
+                hide_gdpr_banner: false,
+            };
+            let event_type = EventTypeRepository::new(db.clone())
+                .create(EventType { embed_settings: Some(embed_settings), ..test_support::event_type(&host, &schedule) })
+                .await
+                .unwrap();
+
+            let controller = BookingController::new(db.clone());
+            let path = web::Path::from((host.to_hex(), event_type.slug.clone()));
+            let response = controller.public_embed_config(path).await.unwrap();
+
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(response.headers().get(header::CACHE_CONTROL).unwrap(), "public, max-age=86400");
+            let body = actix_web::body::to_bytes(response.into_body()).await.unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(body["slug"], json!(event_type.slug));
+            assert_eq!(body["embed_settings"]["hide_event_details"], json!(true));
+            assert_eq!(body["embed_settings"]["background_color"], json!("#ffffff"));
+        });
+    }
+
+    #[test]
+    fn secret_event_types_are_left_off_the_public_page_but_open_by_link() {
+        with_database(|db| async move {
+            let (settings, schedule) = test_support::create_host(&db, "Europe/Berlin").await;
+            let host = settings.user_id;
+            let event_type_repository = EventTypeRepository::new(db.clone());
+            let listed = event_type_repository.create(test_support::event_type(&host, &schedule)).await.unwrap();
+            let secret = event_type_repository
+                .create(EventType { is_secret: true, ..test_support::event_type(&host, &schedule) })
+                .await
+                .unwrap();
+            let controller = BookingController::new(db.clone());
+
+            let response = controller.public_list_event_types(web::Path::from(host.to_hex())).await.unwrap();
+            let body = actix_web::body::to_bytes(response.into_body()).await.unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            let slugs: Vec<&str> = body.as_array().unwrap().iter().filter_map(|event_type| event_type["slug"].as_str()).collect();
+            assert_eq!(slugs, [listed.slug.as_str()]);
+
+            let path = web::Path::from((host.to_hex(), secret.slug.clone()));
+            let response = controller.public_get_event_type(path, web::Query(HashMap::new())).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = actix_web::body::to_bytes(response.into_body()).await.unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(body["slug"], json!(secret.slug));
+        });
+    }
+
+    #[test]
+    fn booking_is_refused_when_the_schedule_changes_before_commit() {
+        with_database(|db| async move {
+            let timezone = "Europe/Berlin";
+            let (settings, schedule) = test_support::create_host(&db, timezone).await;
+            let host = settings.user_id;
+            let event_type = EventTypeRepository::new(db.clone())
+                .create(test_support::event_type(&host, &schedule))
+                .await
+                .unwrap();
+            let event_type_id = event_type.id.unwrap();
+            let controller = BookingController::new(db.clone());
+            let date = test_support::date_in(timezone, 3);
+            let checked = Booking {
+                schedule_versions: schedule.schedule_version().into_iter().collect(),
+                ..test_support::booking(&event_type_id, &host, &date, "10:00")
+            };
+
+            // The host saves their schedule after the slot was checked against it
+            let availability_repository = AvailabilityRepository::new(db.clone());
+            let schedule_id = schedule.id.unwrap();
+            let updated = availability_repository.update(&schedule_id, schedule).await.unwrap();
+            assert!(updated.is_some());
+
+            let refused = controller.create_on_unchanged_schedules(checked, 1).await;
+            assert!(matches!(refused, Err(AppError::Coded(409, code, _)) if code == "schedule_changed"));
+            let taken = controller.booking_repository.find_taken_seats(&host, &date, "10:00").await.unwrap();
+            assert!(taken.is_empty());
+
+            let current = availability_repository.find_by_id(&schedule_id).await.unwrap().unwrap();
+            let checked = Booking {
+                schedule_versions: current.schedule_version().into_iter().collect(),
+                ..test_support::booking(&event_type_id, &host, &date, "10:00")
+            };
+            let created = controller.create_on_unchanged_schedules(checked, 1).await.unwrap();
+            assert!(created.id.is_some());
+        });
+    }
+
+    #[test]
+    fn hold_is_only_used_up_by_a_created_booking() {
+        with_database(|db| async move {
+            let timezone = "Europe/Berlin";
+            let (settings, schedule) = test_support::create_host(&db, timezone).await;
+            let host = settings.user_id;
+            let event_type = EventTypeRepository::new(db.clone())
+                .create(test_support::event_type(&host, &schedule))
+                .await
+                .unwrap();
+            let event_type_id = event_type.id.unwrap();
+            let controller = BookingController::new(db.clone());
+            let date = test_support::date_in(timezone, 3);
+            let hold = SlotHold {
+                id: None,
+                event_type_id,
+                host_user_id: host,
+                co_host_user_ids: Vec::new(),
+                date: date.clone(),
+                start_time: "10:00".to_string(),
+                end_time: "10:30".to_string(),
+                seat: 0,
+                expires_at: DateTime::from_millis(DateTime::now().timestamp_millis() + 10 * 60 * 1000),
+                created_at: DateTime::now(),
+            };
+            let hold = controller.slot_hold_repository.create_in_free_seat(hold, 1).await.unwrap();
+            let hold_id = hold.id.unwrap();
+
+            // The insert fails because the schedule changed after the slot was checked
+            let availability_repository = AvailabilityRepository::new(db.clone());
+            let schedule_id = schedule.id.unwrap();
+            let stale = Booking {
+                schedule_versions: schedule.schedule_version().into_iter().collect(),
+                ..test_support::booking(&event_type_id, &host, &date, "10:00")
+            };
+            availability_repository.update(&schedule_id, schedule).await.unwrap();
+            let refused = controller.create_claiming_hold(stale, 1, Some(&hold_id)).await;
+            assert!(matches!(refused, Err(AppError::Coded(409, code, _)) if code == "schedule_changed"));
+            assert!(controller.slot_hold_repository.find_active(&hold_id).await.unwrap().is_some());
+
+            let current = availability_repository.find_by_id(&schedule_id).await.unwrap().unwrap();
+            let checked = Booking {
+                schedule_versions: current.schedule_version().into_iter().collect(),
+                ..test_support::booking(&event_type_id, &host, &date, "10:00")
+            };
+            let created = controller.create_claiming_hold(checked.clone(), 1, Some(&hold_id)).await.unwrap();
+            assert!(created.id.is_some());
+            assert!(controller.slot_hold_repository.find_active(&hold_id).await.unwrap().is_none());
+
+            // A second booking on the used hold is taken back out again
+            controller.booking_repository.delete(&created.id.unwrap()).await.unwrap();
+            let refused = controller.create_claiming_hold(checked, 1, Some(&hold_id)).await;
+            assert!(matches!(refused, Err(AppError::Coded(410, code, _)) if code == "hold_expired"));
+            let taken = controller.booking_repository.find_taken_seats(&host, &date, "10:00").await.unwrap();
+            assert!(taken.is_empty());
+        });
+    }
+
+    #[test]
+    fn simultaneous_round_robin_bookings_go_to_different_hosts() {
+        with_database(|db| async move {
+            let timezone = "Europe/Berlin";
+            let (owner_settings, owner_schedule) = test_support::create_host(&db, timezone).await;
+            let (host_settings, _) = test_support::create_host(&db, timezone).await;
+            let owner = owner_settings.user_id;
+            let host = host_settings.user_id;
+            test_support::accept_host_invite(&db, &owner, &host).await;
+
+            let event_type = EventTypeRepository::new(db.clone())
+                .create(EventType {
+                    hosts: vec![host],
+                    host_assignment: HostAssignment::RoundRobin,
+                    ..test_support::event_type(&owner, &owner_schedule)
+                })
+                .await
+                .unwrap();
+            let event_type_id = event_type.id.unwrap();
+            let controller = BookingController::new(db.clone());
+            let date = test_support::date_in(timezone, 3);
+
+            let book = |invitee: &'static str| {
+                let controller = &controller;
+                let owner_settings = &owner_settings;
+                let date = date.clone();
+                async move {
+                    let event_type = controller.event_type_repository.find_by_id(&event_type_id).await.unwrap().unwrap();
+                    controller.book(event_type, owner_settings, booking_request(&event_type_id, invitee, &date, "10:00")).await
+                }
+            };
+            let (first, second) = tokio::join!(book("first"), book("second"));
+            let first = first.unwrap().expect("First booking conflicted");
+            let second = second.unwrap().expect("Second booking conflicted");
+
+            assert_ne!(first.host_user_id, second.host_user_id);
+            let mut hosts = [first.host_user_id, second.host_user_id];
+            hosts.sort();
+            let mut expected = [owner, host];
+            expected.sort();
+            assert_eq!(hosts, expected);
+        });
+    }
+
+    #[test]
+    fn host_adds_and_removes_attendees() {
+        with_database(|db| async move {
+            let timezone = "Europe/Berlin";
+            let (settings, schedule) = test_support::create_host(&db, timezone).await;
+            let host = settings.user_id;
+            let event_type = EventTypeRepository::new(db.clone())
+                .create(test_support::event_type(&host, &schedule))
+                .await
+                .unwrap();
+            let controller = BookingController::new(db.clone());
+            let date = test_support::date_in(timezone, 3);
+            let booking = Booking {
+                guest_emails: vec!["guest@example.com".to_string()],
+                ..test_support::booking(&event_type.id.unwrap(), &host, &date, "10:00")
+            };
+            let booking_id = controller.booking_repository.create_in_free_seat(booking, 1).await.unwrap().id.unwrap();
+            let add = |email: &str| {
+                let request = AddAttendeeRequest { email: email.to_string(), name: Some(" Sam ".to_string()) };
+                controller.add_attendee(claims_of(&host), PathObjectId(booking_id), web::Json(request))
+            };
+
+            let response = add("Sam@Example.com").await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let stored = controller.booking_repository.find_by_id(&booking_id).await.unwrap().unwrap();
+            assert_eq!(stored.attendees.len(), 1);
+            assert_eq!((stored.attendees[0].email.as_str(), stored.attendees[0].name.as_deref()), ("sam@example.com", Some("Sam")));
+
+            // Nobody is on the booking twice, whatever the case of their email
+            for email in ["SAM@example.com", "Ivy@Example.com", "guest@example.com"] {
+                assert!(matches!(add(email).await, Err(AppError::BadRequest(_))), "{}", email);
+            }
+            assert!(matches!(add("not-an-email").await, Err(AppError::ValidationError(_))));
+
+            for n in 2..=MAX_ATTENDEES {
+                add(&format!("attendee{}@example.com", n)).await.unwrap();
+            }
+            assert!(matches!(add("one-too-many@example.com").await, Err(AppError::BadRequest(_))));
+
+            let someone_else = claims_of(&ObjectId::new());
+            let request = AddAttendeeRequest { email: "other@example.com".to_string(), name: None };
+            let forbidden = controller.add_attendee(someone_else, PathObjectId(booking_id), web::Json(request)).await;
+            assert!(matches!(forbidden, Err(AppError::Forbidden(_))));
+
+            let remove = |email: &str| {
+                let path = web::Path::from(AttendeePath { email: email.to_string() });
+                controller.remove_attendee(claims_of(&host), PathObjectId(booking_id), path)
+            };
+            remove("Sam@example.com").await.unwrap();
+            assert!(matches!(remove("sam@example.com").await, Err(AppError::NotFound(_))));
+            let stored = controller.booking_repository.find_by_id(&booking_id).await.unwrap().unwrap();
+            assert_eq!(stored.attendees.len(), MAX_ATTENDEES - 1);
+            assert!(stored.attendees.iter().all(|attendee| attendee.email != "sam@example.com"));
+        });
+    }
+
+    #[test]
+    fn existing_booking_lookup_ignores_case_and_cancelled_bookings() {
+        with_database(|db| async move {
+            let timezone = "Europe/Berlin";
+            let (settings, schedule) = test_support::create_host(&db, timezone).await;
+            let host = settings.user_id;
+            let event_type = EventTypeRepository::new(db.clone())
+                .create(test_support::event_type(&host, &schedule))
+                .await
+                .unwrap();
+            let event_type_id = event_type.id.unwrap();
+            let controller = BookingController::new(db.clone());
+            let lookup = |email: &str| {
+                let path = web::Path::from((host.to_hex(), event_type.slug.clone()));
+                let query = web::Query(ExistingBookingQuery { email: email.to_string() });
+                async {
+                    let response = controller.public_existing_booking(path, query).await.unwrap();
+                    let body = actix_web::body::to_bytes(response.into_body()).await.unwrap();
+                    serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+                }
+            };
+
+            let past = Booking {
+                invitee_email: "Pat@Example.com".to_string(),
+                ..test_support::booking(&event_type_id, &host, &test_support::date_in(timezone, -3), "10:00")
+            };
+            controller.booking_repository.create_in_free_seat(past, 1).await.unwrap();
+            assert_eq!(lookup("pat@example.com").await, json!({ "has_upcoming_booking": false }));
+
+            let upcoming = Booking {
+                invitee_email: "Pat@Example.com".to_string(),
+                management_token: "t".repeat(28) + "Wxyz",
+                ..test_support::booking(&event_type_id, &host, &test_support::date_in(timezone, 3), "10:00")
+            };
+            let upcoming = controller.booking_repository.create_in_free_seat(upcoming, 1).await.unwrap();
+            // Nothing but the flag and the hint, however the email is typed
+            assert_eq!(lookup("PAT@example.COM").await, json!({ "has_upcoming_booking": true, "management_hint": "Wxyz" }));
+            assert_eq!(lookup("someone@example.com").await, json!({ "has_upcoming_booking": false }));
+
+            controller.booking_repository
+                .cancel(&upcoming.id.unwrap(), BookingStatus::Confirmed, "invitee", None)
+                .await
+                .unwrap();
+            assert_eq!(lookup("pat@example.com").await, json!({ "has_upcoming_booking": false }));
+        });
+    }
+}
@@ -0,0 +1,1321 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::NaiveDate;
+use actix_web::http::StatusCode;
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime, Document},
+    error::{Error as MongoError, ErrorKind, WriteFailure},
+    options::{Collation, CollationStrength, FindOneAndUpdateOptions, FindOneOptions, FindOptions, IndexOptions, ReturnDocument},
+    Collection, Cursor, Database, IndexModel,
+};
+use futures::TryStreamExt;
+use crate::errors::error::AppError;
+use crate::modules::calendar::calendar_engine::{self, BookedWindow};
+use crate::modules::calendar::calendar_model::{BufferTime, EventType};
+use mongodb::bson;
+use crate::modules::booking::booking_model::{Booking, BookingAttendee, BookingStatus, MAX_ATTENDEES, ConsumedAction, IdempotencyRecord, PreviousSlot, RescheduleRecord, RetentionRun, SlotHold, StatusChange};
+use crate::utils::signed_actions::SignedAction;
+use crate::utils::text;
+
+/// Server error code for a unique index violation.
+const DUPLICATE_KEY_CODE: i32 = 11000;
+/// Server error codes for dropping an index or collection that does not exist.
+const NAMESPACE_NOT_FOUND_CODE: i32 = 26;
+const INDEX_NOT_FOUND_CODE: i32 = 27;
+/// Slot indexes from before pending bookings held their slot, and from before group events had seats.
+const LEGACY_SLOT_INDEXES: [&str; 2] = ["host_user_id_1_date_1_start_time_1", "slot_hold"];
+
+/// Stands in for the invitee's name once retention has removed it.
+pub const ANONYMIZED_NAME: &str = "Anonymized invitee";
+
+pub struct BookingRepository {
+    collection: Collection<Booking>,
+}
+
+impl BookingRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection("bookings");
+        Self { collection }
+    }
+
+    /// Holds each seat at a start time of a host to one pending or confirmed
+    /// booking, so two concurrent requests for the same seat cannot both
+    /// succeed. One-on-one events only have seat 0. Safe to call on every start.
+    pub async fn ensure_indexes(&self) -> Result<(), AppError> {
+        // The old indexes have no seat key, so they would reject a second group attendee
+        for legacy_index in LEGACY_SLOT_INDEXES {
+            if let Err(e) = self.collection.drop_index(legacy_index, None).await {
+                let missing = matches!(
+                    e.kind.as_ref(),
+                    ErrorKind::Command(command_error)
+                        if matches!(command_error.code, NAMESPACE_NOT_FOUND_CODE | INDEX_NOT_FOUND_CODE)
+                );
+                if !missing {
+                    return Err(AppError::DatabaseError(e.to_string()));
+                }
+            }
+        }
+
+        // Bookings from before seats existed take seat 0, so they still block their slot
+        self.collection
+            .update_many(doc! { "seat": { "$exists": false } }, doc! { "$set": { "seat": 0 } }, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let index = IndexModel::builder()
+            .keys(doc! { "host_user_id": 1, "date": 1, "start_time": 1, "seat": 1 })
+            .options(
+                IndexOptions::builder()
+                    .name("slot_seat".to_string())
+                    .unique(true)
+                    .partial_filter_expression(doc! { "status": slot_holding() })
+                    .build(),
+            )
+            .build();
+        // The same for co-hosts of collective events, between those events
+        let co_host_index = IndexModel::builder()
+            .keys(doc! { "co_host_user_ids": 1, "date": 1, "start_time": 1, "seat": 1 })
+            .options(
+                IndexOptions::builder()
+                    .name("co_host_slot_seat".to_string())
+                    .unique(true)
+                    .partial_filter_expression(doc! {
+                        "status": slot_holding(),
+                        "co_host_user_ids.0": { "$exists": true },
+                    })
+                    .build(),
+            )
+            .build();
+
+        // Bookings from before management tokens existed have an empty one
+        let token_index = IndexModel::builder()
+            .keys(doc! { "management_token": 1 })
+            .options(
+                IndexOptions::builder()
+                    .unique(true)
+                    .partial_filter_expression(doc! { "management_token": { "$gt": "" } })
+                    .build(),
+            )
+            .build();
+
+        // The jobs and retention scan all hosts' bookings by date
+        let date_index = IndexModel::builder()
+            .keys(doc! { "date": 1 })
+            .options(IndexOptions::builder().name("date".to_string()).build())
+            .build();
+
+        // Invitee lookups ignore the case the email was typed in
+        let invitee_index = IndexModel::builder()
+            .keys(doc! { "event_type_id": 1, "invitee_email": 1, "date": 1 })
+            .options(
+                IndexOptions::builder()
+                    .name("event_type_invitee_email".to_string())
+                    .collation(email_collation())
+                    .build(),
+            )
+            .build();
+
+        self.collection
+            .create_indexes([index, co_host_index, token_index, date_index, invitee_index], None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Books the first free seat of the slot, retrying when a concurrent
+    /// request takes the same seat first. Fails once all `capacity` seats are held.
+    pub async fn create_in_free_seat(&self, booking: Booking, capacity: i32) -> Result<Booking, AppError> {
+        let mut booking = booking;
+        booking.created_at = DateTime::now();
+        booking.updated_at = DateTime::now();
+
+        for _ in 0..capacity {
+            let taken = self.find_taken_seats(&booking.host_user_id, &booking.date, &booking.start_time).await?;
+            let Some(seat) = (0..capacity).find(|seat| !taken.contains(seat)) else {
+                break;
+            };
+            booking.seat = seat;
+
+            match self.collection.insert_one(&booking, None).await {
+                Ok(result) => {
+                    booking.id = Some(result.inserted_id.as_object_id().unwrap());
+                    return Ok(booking);
+                }
+                Err(e) if is_duplicate_key(&e) => continue,
+                Err(e) => return Err(AppError::DatabaseError(e.to_string())),
+            }
+        }
+
+        Err(slot_unavailable())
+    }
+
+    /// Seats held by pending and confirmed bookings of the host at a start time.
+    pub async fn find_taken_seats(&self, host_user_id: &ObjectId, date: &str, start_time: &str) -> Result<Vec<i32>, AppError> {
+        let filter = doc! {
+            "host_user_id": host_user_id,
+            "date": date,
+            "start_time": start_time,
+            "status": slot_holding(),
+        };
+
+        let mut seats = Vec::new();
+        let mut cursor = self.collection
+            .find(filter, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        while let Some(booking) = cursor.try_next().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))? {
+            seats.push(booking.seat);
+        }
+
+        Ok(seats)
+    }
+
+    pub async fn find_by_id(&self, id: &ObjectId) -> Result<Option<Booking>, AppError> {
+        self.collection
+            .find_one(doc! { "_id": id }, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// Removes a booking that was just created and must not stand.
+    pub async fn delete(&self, id: &ObjectId) -> Result<(), AppError> {
+        self.collection
+            .delete_one(doc! { "_id": id }, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        Ok(())
+    }
+
+    pub async fn find_by_management_token(&self, token: &str) -> Result<Option<Booking>, AppError> {
+        if token.is_empty() {
+            return Ok(None);
+        }
+        self.collection
+            .find_one(doc! { "management_token": token }, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// Cancels the booking if it is still in status `from`, returning `None` otherwise.
+    pub async fn cancel(&self, id: &ObjectId, from: BookingStatus, cancelled_by: &str, reason: Option<&str>) -> Result<Option<Booking>, AppError> {
+        let now = DateTime::now();
+        let record = StatusChange {
+            from,
+            to: BookingStatus::Cancelled,
+            changed_by: cancelled_by.to_string(),
+            changed_at: now,
+        };
+        let record_doc = bson::to_bson(&record)
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+
+        self.collection
+            .find_one_and_update(
+                doc! { "_id": id, "status": from.as_str() },
+                doc! {
+                    "$set": {
+                        "status": BookingStatus::Cancelled.as_str(),
+                        "cancelled_at": now,
+                        "cancelled_by": cancelled_by,
+                        "cancellation_reason": reason,
+                        "updated_at": now,
+                    },
+                    "$push": { "status_history": record_doc },
+                },
+                options
+            )
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// Moves the booking from status `from` to `to`, returning `None` if its
+    /// status changed in the meantime. Callers check the transition first.
+    /// The change is recorded in the booking's status history with `changed_by`.
+    pub async fn update_status(
+        &self,
+        id: &ObjectId,
+        from: BookingStatus,
+        to: BookingStatus,
+        changed_by: &str,
+    ) -> Result<Option<Booking>, AppError> {
+        let record = StatusChange {
+            from,
+            to,
+            changed_by: changed_by.to_string(),
+            changed_at: DateTime::now(),
+        };
+        let record_doc = bson::to_bson(&record)
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+
+        self.collection
+            .find_one_and_update(
+                doc! { "_id": id, "status": from.as_str() },
+                doc! {
+                    "$set": { "status": to.as_str(), "updated_at": DateTime::now() },
+                    "$push": { "status_history": record_doc },
+                },
+                options
+            )
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// Adds `attendee` to a pending or confirmed booking. `None` when the
+    /// booking moved on, already has the email or is full, so two hosts adding
+    /// at once cannot go past `MAX_ATTENDEES`.
+    pub async fn add_attendee(&self, id: &ObjectId, attendee: &BookingAttendee) -> Result<Option<Booking>, AppError> {
+        let attendee_doc = bson::to_bson(attendee)
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+
+        self.collection
+            .find_one_and_update(
+                doc! {
+                    "_id": id,
+                    "status": slot_holding(),
+                    "attendees.email": { "$ne": &attendee.email },
+                    format!("attendees.{}", MAX_ATTENDEES - 1): { "$exists": false },
+                },
+                doc! {
+                    "$push": { "attendees": attendee_doc },
+                    "$set": { "updated_at": DateTime::now() },
+                },
+                options
+            )
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// Takes the attendee with `email` off the booking; `None` when there is none.
+    pub async fn remove_attendee(&self, id: &ObjectId, email: &str) -> Result<Option<Booking>, AppError> {
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+
+        self.collection
+            .find_one_and_update(
+                doc! { "_id": id, "attendees.email": email },
+                doc! {
+                    "$pull": { "attendees": { "email": email } },
+                    "$set": { "updated_at": DateTime::now() },
+                },
+                options
+            )
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    pub async fn set_meeting_link(&self, id: &ObjectId, meeting_link: &str) -> Result<(), AppError> {
+        self.collection
+            .update_one(
+                doc! { "_id": id },
+                doc! { "$set": { "meeting_link": meeting_link, "updated_at": DateTime::now() } },
+                None
+            )
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Confirmed bookings of any host on or before a YYYY-MM-DD date.
+    pub async fn find_confirmed_until(&self, date: &str) -> Result<Vec<Booking>, AppError> {
+        let filter = doc! {
+            "date": { "$lte": date },
+            "status": BookingStatus::Confirmed.as_str(),
+        };
+
+        let mut bookings = Vec::new();
+        let mut cursor = self.collection
+            .find(filter, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        while let Some(booking) = cursor.try_next().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))? {
+            bookings.push(booking);
+        }
+
+        Ok(bookings)
+    }
+
+    /// Confirmed bookings between two YYYY-MM-DD dates, inclusive.
+    pub async fn find_confirmed_between(&self, start_date: &str, end_date: &str) -> Result<Vec<Booking>, AppError> {
+        let filter = doc! {
+            "date": { "$gte": start_date, "$lte": end_date },
+            "status": BookingStatus::Confirmed.as_str(),
+        };
+
+        let mut bookings = Vec::new();
+        let mut cursor = self.collection
+            .find(filter, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        while let Some(booking) = cursor.try_next().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))? {
+            bookings.push(booking);
+        }
+
+        Ok(bookings)
+    }
+
+    /// Marks the reminders at `offsets` for a confirmed booking at `date` and `start_time`
+    /// as sent. Returns false if another worker sent any of them first, or the booking was
+    /// cancelled or moved.
+    pub async fn claim_reminders(&self, id: &ObjectId, date: &str, start_time: &str, offsets: &[i32]) -> Result<bool, AppError> {
+        let result = self.collection
+            .update_one(
+                doc! {
+                    "_id": id,
+                    "status": BookingStatus::Confirmed.as_str(),
+                    "date": date,
+                    "start_time": start_time,
+                    "reminders_sent": { "$nin": offsets },
+                },
+                doc! { "$addToSet": { "reminders_sent": { "$each": offsets } } },
+                None
+            )
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(result.modified_count == 1)
+    }
+
+    /// Bookings created since `since`, counted per UTC day (YYYY-MM-DD).
+    /// Days without bookings are left out.
+    pub async fn count_created_per_day(&self, since: DateTime) -> Result<BTreeMap<String, u64>, AppError> {
+        let pipeline = vec![
+            doc! { "$match": { "created_at": { "$gte": since } } },
+            doc! { "$group": {
+                "_id": { "$dateToString": { "format": "%Y-%m-%d", "date": "$created_at" } },
+                "bookings": { "$sum": 1 },
+            } },
+        ];
+
+        let mut cursor = self.collection
+            .aggregate(pipeline, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let mut counts = BTreeMap::new();
+        while let Some(row) = cursor.try_next().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))? {
+            let Ok(day) = row.get_str("_id") else { continue };
+            let bookings = match row.get("bookings") {
+                Some(bson::Bson::Int32(n)) => *n as u64,
+                Some(bson::Bson::Int64(n)) => *n as u64,
+                _ => 0,
+            };
+            counts.insert(day.to_string(), bookings);
+        }
+
+        Ok(counts)
+    }
+
+    /// The host's bookings counted per event type and status.
+    pub async fn count_by_event_type_and_status(&self, host_user_id: &ObjectId) -> Result<Vec<(ObjectId, String, u64)>, AppError> {
+        let pipeline = vec![
+            doc! { "$match": { "host_user_id": host_user_id } },
+            doc! { "$group": {
+                "_id": { "event_type_id": "$event_type_id", "status": "$status" },
+                "bookings": { "$sum": 1 },
+            } },
+        ];
+
+        let mut cursor = self.collection
+            .aggregate(pipeline, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let mut counts = Vec::new();
+        while let Some(row) = cursor.try_next().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))? {
+            let Ok(group) = row.get_document("_id") else { continue };
+            let (Ok(event_type_id), Ok(status)) = (group.get_object_id("event_type_id"), group.get_str("status")) else {
+                continue;
+            };
+            let bookings = match row.get("bookings") {
+                Some(bson::Bson::Int32(n)) => *n as u64,
+                Some(bson::Bson::Int64(n)) => *n as u64,
+                _ => 0,
+            };
+            counts.push((event_type_id, status.to_string(), bookings));
+        }
+
+        Ok(counts)
+    }
+
+    /// Pending and confirmed bookings between two YYYY-MM-DD dates, inclusive,
+    /// counted per host for each of `host_user_ids` who has any, as host or co-host.
+    pub async fn count_holding_by_hosts_in_range(
+        &self,
+        host_user_ids: &[ObjectId],
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<HashMap<ObjectId, u64>, AppError> {
+        let pipeline = vec![
+            doc! { "$match": {
+                "$or": [
+                    { "host_user_id": { "$in": host_user_ids } },
+                    { "co_host_user_ids": { "$in": host_user_ids } },
+                ],
+                "date": { "$gte": start_date, "$lte": end_date },
+                "status": slot_holding(),
+            } },
+            doc! { "$project": {
+                "hosts": { "$concatArrays": [["$host_user_id"], { "$ifNull": ["$co_host_user_ids", []] }] },
+            } },
+            doc! { "$unwind": "$hosts" },
+            doc! { "$match": { "hosts": { "$in": host_user_ids } } },
+            doc! { "$group": { "_id": "$hosts", "bookings": { "$sum": 1 } } },
+        ];
+
+        let mut cursor = self.collection
+            .aggregate(pipeline, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let mut counts = HashMap::new();
+        while let Some(row) = cursor.try_next().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))? {
+            let Ok(host_user_id) = row.get_object_id("_id") else { continue };
+            let bookings = match row.get("bookings") {
+                Some(bson::Bson::Int32(n)) => *n as u64,
+                Some(bson::Bson::Int64(n)) => *n as u64,
+                _ => 0,
+            };
+            counts.insert(host_user_id, bookings);
+        }
+
+        Ok(counts)
+    }
+
+    /// When each host was last given a booking of the event type, whatever
+    /// has happened to it since.
+    pub async fn find_last_created_by_host(&self, event_type_id: &ObjectId) -> Result<HashMap<ObjectId, DateTime>, AppError> {
+        let pipeline = vec![
+            doc! { "$match": { "event_type_id": event_type_id } },
+            doc! { "$group": { "_id": "$host_user_id", "last_created_at": { "$max": "$created_at" } } },
+        ];
+
+        let mut cursor = self.collection
+            .aggregate(pipeline, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let mut last_created = HashMap::new();
+        while let Some(row) = cursor.try_next().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))? {
+            let (Ok(host_user_id), Ok(created_at)) = (row.get_object_id("_id"), row.get_datetime("last_created_at")) else {
+                continue;
+            };
+            last_created.insert(host_user_id, *created_at);
+        }
+
+        Ok(last_created)
+    }
+
+    /// Pending bookings of any host created before `cutoff`.
+    pub async fn find_pending_created_before(&self, cutoff: DateTime) -> Result<Vec<Booking>, AppError> {
+        let filter = doc! {
+            "status": BookingStatus::Pending.as_str(),
+            "created_at": { "$lt": cutoff },
+        };
+
+        let mut bookings = Vec::new();
+        let mut cursor = self.collection
+            .find(filter, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        while let Some(booking) = cursor.try_next().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))? {
+            bookings.push(booking);
+        }
+
+        Ok(bookings)
+    }
+
+    /// Bookings of any host before a YYYY-MM-DD date that still hold invitee details.
+    pub async fn count_unanonymized_before(&self, date: &str) -> Result<u64, AppError> {
+        self.collection
+            .count_documents(unanonymized_before(date), None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// Up to `limit` ids of the bookings `count_unanonymized_before` counts, oldest first.
+    pub async fn find_unanonymized_ids_before(&self, date: &str, limit: i64) -> Result<Vec<ObjectId>, AppError> {
+        let options = FindOptions::builder()
+            .projection(doc! { "_id": 1 })
+            .sort(doc! { "date": 1, "_id": 1 })
+            .limit(limit)
+            .build();
+
+        let mut ids = Vec::new();
+        let mut cursor = self.collection
+            .clone_with_type::<Document>()
+            .find(unanonymized_before(date), options)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        while let Some(document) = cursor.try_next().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))? {
+            if let Ok(id) = document.get_object_id("_id") {
+                ids.push(id);
+            }
+        }
+
+        Ok(ids)
+    }
+
+    /// Replaces what identifies the invitee on the given bookings. Dates,
+    /// times, statuses, event types and tracking stay, so stats still add up.
+    pub async fn anonymize(&self, ids: &[ObjectId]) -> Result<u64, AppError> {
+        let now = DateTime::now();
+        let result = self.collection
+            .update_many(
+                doc! { "_id": { "$in": ids }, "anonymized_at": null },
+                doc! {
+                    "$set": {
+                        "invitee_name": ANONYMIZED_NAME,
+                        "invitee_email": "",
+                        "guest_emails": [],
+                        "attendees": [],
+                        "invitee_phone": null,
+                        "answers": [],
+                        "cancellation_reason": null,
+                        "anonymized_at": now,
+                        "updated_at": now,
+                    },
+                },
+                None
+            )
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(result.modified_count)
+    }
+
+    /// Moves a confirmed booking away from `previous` into `seat` of the new
+    /// slot, returning `None` if it was cancelled or moved by someone else in the meantime.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn reschedule(
+        &self,
+        id: &ObjectId,
+        previous: &PreviousSlot,
+        date: &str,
+        start_time: &str,
+        end_time: &str,
+        seat: i32,
+        rescheduled_by: &str,
+    ) -> Result<Option<Booking>, AppError> {
+        let previous_doc = bson::to_bson(previous)
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        let record = RescheduleRecord {
+            from: previous.clone(),
+            to: PreviousSlot {
+                date: date.to_string(),
+                start_time: start_time.to_string(),
+                end_time: end_time.to_string(),
+            },
+            rescheduled_by: rescheduled_by.to_string(),
+            rescheduled_at: DateTime::now(),
+        };
+        let record_doc = bson::to_bson(&record)
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+
+        self.collection
+            .find_one_and_update(
+                doc! {
+                    "_id": id,
+                    "status": BookingStatus::Confirmed.as_str(),
+                    "date": &previous.date,
+                    "start_time": &previous.start_time,
+                },
+                doc! {
+                    "$set": {
+                        "date": date,
+                        "start_time": start_time,
+                        "end_time": end_time,
+                        "seat": seat,
+                        "rescheduled_from": previous_doc,
+                        "updated_at": DateTime::now(),
+                    },
+                    "$unset": { "reminders_sent": "" },
+                    "$push": { "reschedule_history": record_doc },
+                },
+                options
+            )
+            .await
+            .map_err(slot_write_error)
+    }
+
+    /// Pending and confirmed bookings of the host on a YYYY-MM-DD date.
+    pub async fn find_holding_by_host_and_date(&self, host_user_id: &ObjectId, date: &str) -> Result<Vec<Booking>, AppError> {
+        let mut filter = hosted_by(host_user_id);
+        filter.extend(doc! {
+            "date": date,
+            "status": slot_holding(),
+        });
+
+        let mut bookings = Vec::new();
+        let mut cursor = self.collection
+            .find(filter, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        while let Some(booking) = cursor.try_next().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))? {
+            bookings.push(booking);
+        }
+
+        Ok(bookings)
+    }
+
+    /// The invitee's next pending or confirmed booking of the event type that
+    /// starts after `date` at `time`, whatever the case of the stored email.
+    pub async fn find_upcoming_by_invitee(
+        &self,
+        event_type_id: &ObjectId,
+        email: &str,
+        date: &str,
+        time: &str,
+    ) -> Result<Option<Booking>, AppError> {
+        let options = FindOneOptions::builder()
+            .collation(email_collation())
+            .sort(doc! { "date": 1, "start_time": 1 })
+            .build();
+
+        self.collection
+            .find_one(
+                doc! {
+                    "event_type_id": event_type_id,
+                    "invitee_email": email,
+                    "status": slot_holding(),
+                    "$or": [
+                        { "date": { "$gt": date } },
+                        { "date": date, "start_time": { "$gt": time } },
+                    ],
+                },
+                options
+            )
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// Every booking the user hosts or co-hosts, and every booking made with
+    /// their email as the invitee, in date and start time order. For data exports.
+    pub async fn find_by_user(&self, user_id: &ObjectId, email: &str) -> Result<Vec<Booking>, AppError> {
+        let invitee = doc! { "invitee_email": { "$regex": format!("^{}$", text::escape_regex(email)), "$options": "i" } };
+        let options = FindOptions::builder()
+            .sort(doc! { "date": 1, "start_time": 1 })
+            .build();
+
+        self.collection
+            .find(doc! { "$or": [hosted_by(user_id), invitee] }, options)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?
+            .try_collect()
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// All of the host's bookings between two YYYY-MM-DD dates, inclusive, in
+    /// date and start time order. Returned as a cursor so large exports stream.
+    pub async fn stream_by_host_in_range(&self, host_user_id: &ObjectId, start_date: &str, end_date: &str) -> Result<Cursor<Booking>, AppError> {
+        let mut filter = hosted_by(host_user_id);
+        filter.extend(doc! {
+            "date": { "$gte": start_date, "$lte": end_date },
+        });
+        let options = FindOptions::builder()
+            .sort(doc! { "date": 1, "start_time": 1 })
+            .build();
+
+        self.collection
+            .find(filter, options)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// Pending and confirmed bookings of the host between two YYYY-MM-DD dates, inclusive.
+    pub async fn find_holding_by_host_in_range(&self, host_user_id: &ObjectId, start_date: &str, end_date: &str) -> Result<Vec<Booking>, AppError> {
+        let mut filter = hosted_by(host_user_id);
+        filter.extend(doc! {
+            "date": { "$gte": start_date, "$lte": end_date },
+            "status": slot_holding(),
+        });
+
+        let mut bookings = Vec::new();
+        let mut cursor = self.collection
+            .find(filter, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        while let Some(booking) = cursor.try_next().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))? {
+            bookings.push(booking);
+        }
+
+        Ok(bookings)
+    }
+
+    /// Time taken by the host's pending and confirmed bookings, keyed by YYYY-MM-DD date.
+    /// Bookings of `except_event_type` are left out; group events count their seats instead.
+    /// Each booking is padded by its event type's buffer from `event_types`, or `default_buffer`.
+    pub async fn find_booked_windows(
+        &self,
+        host_user_id: &ObjectId,
+        start_day: NaiveDate,
+        end_day: NaiveDate,
+        except_event_type: Option<&ObjectId>,
+        event_types: &HashMap<ObjectId, EventType>,
+        default_buffer: &BufferTime,
+    ) -> Result<HashMap<String, Vec<BookedWindow>>, AppError> {
+        let bookings = self
+            .find_holding_by_host_in_range(
+                host_user_id,
+                &start_day.format("%Y-%m-%d").to_string(),
+                &end_day.format("%Y-%m-%d").to_string(),
+            )
+            .await?;
+
+        let mut booked: HashMap<String, Vec<BookedWindow>> = HashMap::new();
+        for booking in bookings {
+            if except_event_type == Some(&booking.event_type_id) {
+                continue;
+            }
+            let window = calendar_engine::booked_window(
+                event_types.get(&booking.event_type_id),
+                &booking.date,
+                &booking.start_time,
+                &booking.end_time,
+                default_buffer,
+            );
+            booked.entry(booking.date).or_default().push(window);
+        }
+
+        Ok(booked)
+    }
+
+    /// Pending and confirmed bookings of an event type per slot between two
+    /// dates, inclusive, keyed by (YYYY-MM-DD, HH:mm).
+    pub async fn count_seats_taken(
+        &self,
+        event_type_id: &ObjectId,
+        start_day: NaiveDate,
+        end_day: NaiveDate,
+    ) -> Result<HashMap<(String, String), u32>, AppError> {
+        let filter = doc! {
+            "event_type_id": event_type_id,
+            "date": {
+                "$gte": start_day.format("%Y-%m-%d").to_string(),
+                "$lte": end_day.format("%Y-%m-%d").to_string(),
+            },
+            "status": slot_holding(),
+        };
+
+        let mut cursor = self.collection
+            .find(filter, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let mut taken: HashMap<(String, String), u32> = HashMap::new();
+        while let Some(booking) = cursor.try_next().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))? {
+            *taken.entry((booking.date, booking.start_time)).or_default() += 1;
+        }
+
+        Ok(taken)
+    }
+}
+
+pub struct ConsumedActionRepository {
+    collection: Collection<ConsumedAction>,
+}
+
+impl ConsumedActionRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection("consumed_actions");
+        Self { collection }
+    }
+
+    /// One record per link, removed once the link has expired. Safe to call on every start.
+    pub async fn ensure_indexes(&self) -> Result<(), AppError> {
+        let hash_index = IndexModel::builder()
+            .keys(doc! { "token_hash": 1 })
+            .options(IndexOptions::builder().unique(true).build())
+            .build();
+        let expiry_index = IndexModel::builder()
+            .keys(doc! { "expires_at": 1 })
+            .options(IndexOptions::builder().expire_after(std::time::Duration::ZERO).build())
+            .build();
+
+        self.collection
+            .create_indexes([hash_index, expiry_index], None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Marks a link as used. Returns false if it already was.
+    pub async fn claim(
+        &self,
+        token_hash: &str,
+        booking_id: &ObjectId,
+        action: SignedAction,
+        expires_at: DateTime,
+    ) -> Result<bool, AppError> {
+        let consumed = ConsumedAction {
+            id: None,
+            token_hash: token_hash.to_string(),
+            booking_id: *booking_id,
+            action,
+            expires_at,
+            consumed_at: DateTime::now(),
+        };
+
+        match self.collection.insert_one(consumed, None).await {
+            Ok(_) => Ok(true),
+            Err(e) if is_duplicate_key(&e) => Ok(false),
+            Err(e) => Err(AppError::DatabaseError(e.to_string())),
+        }
+    }
+
+    /// Makes a link usable again after its action was refused. Best effort.
+    pub async fn release(&self, token_hash: &str) {
+        if let Err(e) = self.collection.delete_one(doc! { "token_hash": token_hash }, None).await {
+            println!("Failed to release action link {}: {}", token_hash, e);
+        }
+    }
+}
+
+/// How long a replayed idempotency key returns the original booking.
+const IDEMPOTENCY_KEY_TTL: std::time::Duration = std::time::Duration::from_secs(24 * 60 * 60);
+
+pub struct IdempotencyRepository {
+    collection: Collection<IdempotencyRecord>,
+}
+
+impl IdempotencyRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection("idempotency_keys");
+        Self { collection }
+    }
+
+    /// One record per key and scope, removed a day after it was first used. Safe to call on every start.
+    pub async fn ensure_indexes(&self) -> Result<(), AppError> {
+        let key_index = IndexModel::builder()
+            .keys(doc! { "scope": 1, "key": 1 })
+            .options(IndexOptions::builder().unique(true).build())
+            .build();
+        let expiry_index = IndexModel::builder()
+            .keys(doc! { "created_at": 1 })
+            .options(IndexOptions::builder().expire_after(IDEMPOTENCY_KEY_TTL).build())
+            .build();
+
+        self.collection
+            .create_indexes([key_index, expiry_index], None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Records the first use of a key. Returns the earlier record instead when
+    /// the key was already used.
+    pub async fn begin(&self, scope: &str, key: &str, request_hash: &str) -> Result<Option<IdempotencyRecord>, AppError> {
+        let record = IdempotencyRecord {
+            id: None,
+            scope: scope.to_string(),
+            key: key.to_string(),
+            request_hash: request_hash.to_string(),
+            booking_id: None,
+            created_at: DateTime::now(),
+        };
+
+        match self.collection.insert_one(record, None).await {
+            Ok(_) => Ok(None),
+            Err(e) if is_duplicate_key(&e) => self.collection
+                .find_one(doc! { "scope": scope, "key": key }, None)
+                .await
+                .map_err(|e| AppError::DatabaseError(e.to_string())),
+            Err(e) => Err(AppError::DatabaseError(e.to_string())),
+        }
+    }
+
+    /// Stores the booking the key's request created.
+    pub async fn complete(&self, scope: &str, key: &str, booking_id: &ObjectId) -> Result<(), AppError> {
+        self.collection
+            .update_one(doc! { "scope": scope, "key": key }, doc! { "$set": { "booking_id": booking_id } }, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Forgets a key whose request created nothing, so a retry runs again. Best effort.
+    pub async fn release(&self, scope: &str, key: &str) {
+        if let Err(e) = self.collection.delete_one(doc! { "scope": scope, "key": key }, None).await {
+            println!("Failed to release idempotency key for {}: {}", scope, e);
+        }
+    }
+}
+
+pub struct SlotHoldRepository {
+    collection: Collection<SlotHold>,
+}
+
+impl SlotHoldRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection("slot_holds");
+        Self { collection }
+    }
+
+    /// One hold per seat at a start time of a host, removed once it expires.
+    /// Safe to call on every start.
+    pub async fn ensure_indexes(&self) -> Result<(), AppError> {
+        let seat_index = IndexModel::builder()
+            .keys(doc! { "host_user_id": 1, "date": 1, "start_time": 1, "seat": 1 })
+            .options(IndexOptions::builder().name("hold_seat".to_string()).unique(true).build())
+            .build();
+        let co_host_seat_index = IndexModel::builder()
+            .keys(doc! { "co_host_user_ids": 1, "date": 1, "start_time": 1, "seat": 1 })
+            .options(
+                IndexOptions::builder()
+                    .name("co_host_hold_seat".to_string())
+                    .unique(true)
+                    .partial_filter_expression(doc! { "co_host_user_ids.0": { "$exists": true } })
+                    .build(),
+            )
+            .build();
+        let expiry_index = IndexModel::builder()
+            .keys(doc! { "expires_at": 1 })
+            .options(IndexOptions::builder().expire_after(std::time::Duration::ZERO).build())
+            .build();
+
+        self.collection
+            .create_indexes([seat_index, co_host_seat_index, expiry_index], None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Inserts the hold into the lowest free seat of its slot, out of
+    /// `capacity`. Fails with a 409 when every seat is held.
+    pub async fn create_in_free_seat(&self, hold: SlotHold, capacity: i32) -> Result<SlotHold, AppError> {
+        let mut hold = hold;
+        let slot = doc! { "host_user_id": hold.host_user_id, "date": &hold.date, "start_time": &hold.start_time };
+
+        // The database removes expired holds about once a minute; until then they would keep their seat
+        let mut expired = slot.clone();
+        expired.insert("expires_at", doc! { "$lte": DateTime::now() });
+        self.collection
+            .delete_many(expired, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        for _ in 0..capacity {
+            let mut taken = Vec::new();
+            let mut cursor = self.collection
+                .find(slot.clone(), None)
+                .await
+                .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+            while let Some(held) = cursor.try_next().await
+                .map_err(|e| AppError::DatabaseError(e.to_string()))? {
+                taken.push(held.seat);
+            }
+            let Some(seat) = (0..capacity).find(|seat| !taken.contains(seat)) else {
+                break;
+            };
+            hold.seat = seat;
+
+            match self.collection.insert_one(&hold, None).await {
+                Ok(result) => {
+                    hold.id = Some(result.inserted_id.as_object_id().unwrap());
+                    return Ok(hold);
+                }
+                Err(e) if is_duplicate_key(&e) => continue,
+                Err(e) => return Err(AppError::DatabaseError(e.to_string())),
+            }
+        }
+
+        Err(slot_unavailable())
+    }
+
+    /// A hold that has not expired yet.
+    pub async fn find_active(&self, id: &ObjectId) -> Result<Option<SlotHold>, AppError> {
+        self.collection
+            .find_one(doc! { "_id": id, "expires_at": { "$gt": DateTime::now() } }, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// Removes an unexpired hold so it can become a booking. Returns false if it
+    /// expired or was already used.
+    pub async fn claim(&self, id: &ObjectId) -> Result<bool, AppError> {
+        let result = self.collection
+            .delete_one(doc! { "_id": id, "expires_at": { "$gt": DateTime::now() } }, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        Ok(result.deleted_count == 1)
+    }
+
+    /// Unexpired holds on the host's slots between two YYYY-MM-DD dates, inclusive.
+    pub async fn find_active_by_host_in_range(
+        &self,
+        host_user_id: &ObjectId,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<Vec<SlotHold>, AppError> {
+        let mut filter = hosted_by(host_user_id);
+        filter.extend(doc! {
+            "date": { "$gte": start_date, "$lte": end_date },
+            "expires_at": { "$gt": DateTime::now() },
+        });
+
+        let mut holds = Vec::new();
+        let mut cursor = self.collection
+            .find(filter, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        while let Some(hold) = cursor.try_next().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))? {
+            holds.push(hold);
+        }
+
+        Ok(holds)
+    }
+
+    /// Adds the time taken by unexpired holds to `booked`, the way
+    /// `BookingRepository::find_booked_windows` does for bookings, and returns
+    /// the seats they take per slot of `group_event_type`.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn add_held_windows(
+        &self,
+        booked: &mut HashMap<String, Vec<BookedWindow>>,
+        host_user_id: &ObjectId,
+        start_day: NaiveDate,
+        end_day: NaiveDate,
+        group_event_type: Option<&ObjectId>,
+        event_types: &HashMap<ObjectId, EventType>,
+        default_buffer: &BufferTime,
+    ) -> Result<HashMap<(String, String), u32>, AppError> {
+        let holds = self
+            .find_active_by_host_in_range(
+                host_user_id,
+                &start_day.format("%Y-%m-%d").to_string(),
+                &end_day.format("%Y-%m-%d").to_string(),
+            )
+            .await?;
+
+        let mut seats_held: HashMap<(String, String), u32> = HashMap::new();
+        for hold in holds {
+            if group_event_type == Some(&hold.event_type_id) {
+                *seats_held.entry((hold.date, hold.start_time)).or_default() += 1;
+                continue;
+            }
+            let window = calendar_engine::booked_window(
+                event_types.get(&hold.event_type_id),
+                &hold.date,
+                &hold.start_time,
+                &hold.end_time,
+                default_buffer,
+            );
+            booked.entry(hold.date).or_default().push(window);
+        }
+
+        Ok(seats_held)
+    }
+}
+
+pub struct RetentionRunRepository {
+    collection: Collection<RetentionRun>,
+}
+
+impl RetentionRunRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection("retention_runs");
+        Self { collection }
+    }
+
+    /// Newest runs first. Safe to call on every start.
+    pub async fn ensure_indexes(&self) -> Result<(), AppError> {
+        let index = IndexModel::builder()
+            .keys(doc! { "started_at": -1 })
+            .build();
+
+        self.collection
+            .create_index(index, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    pub async fn create(&self, mut run: RetentionRun) -> Result<RetentionRun, AppError> {
+        let result = self.collection
+            .insert_one(&run, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        run.id = result.inserted_id.as_object_id();
+        Ok(run)
+    }
+}
+
+/// Bookings before a YYYY-MM-DD date that retention has not anonymized yet.
+fn unanonymized_before(date: &str) -> Document {
+    doc! { "date": { "$lt": date }, "anonymized_at": null }
+}
+
+/// Compares emails ignoring case. Queries must pass it to use the invitee email index.
+fn email_collation() -> Collation {
+    Collation::builder().locale("en").strength(CollationStrength::Secondary).build()
+}
+
+/// Matches the statuses in `BookingStatus::SLOT_HOLDING`.
+fn slot_holding() -> Document {
+    let statuses: Vec<&str> = BookingStatus::SLOT_HOLDING.iter().map(|status| status.as_str()).collect();
+    doc! { "$in": statuses }
+}
+
+/// Matches bookings and holds the user hosts, alone or as a co-host of a collective event.
+fn hosted_by(host_user_id: &ObjectId) -> Document {
+    doc! { "$or": [{ "host_user_id": host_user_id }, { "co_host_user_ids": host_user_id }] }
+}
+
+fn is_duplicate_key(e: &MongoError) -> bool {
+    match e.kind.as_ref() {
+        ErrorKind::Write(WriteFailure::WriteError(write_error)) => write_error.code == DUPLICATE_KEY_CODE,
+        ErrorKind::Command(command_error) => command_error.code == DUPLICATE_KEY_CODE,
+        _ => false,
+    }
+}
+
+fn slot_unavailable() -> AppError {
+    AppError::coded(StatusCode::CONFLICT, "slot_unavailable", "Slot is no longer available")
+}
+
+/// Maps a lost race for a slot to a 409; anything else stays a database error.
+fn slot_write_error(e: MongoError) -> AppError {
+    if is_duplicate_key(&e) {
+        slot_unavailable()
+    } else {
+        AppError::DatabaseError(e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, with_database};
+
+    fn is_slot_unavailable(result: &Result<Booking, AppError>) -> bool {
+        matches!(result, Err(AppError::Coded(409, code, _)) if code == "slot_unavailable")
+    }
+
+    #[test]
+    fn concurrent_bookings_of_one_slot_create_one_booking() {
+        with_database(|db| async move {
+            let repository = BookingRepository::new(db.clone());
+            let (event_type_id, host) = (ObjectId::new(), ObjectId::new());
+            let date = test_support::date_in("UTC", 3);
+            let booking = || test_support::booking(&event_type_id, &host, &date, "10:00");
+
+            let (first, second) = tokio::join!(
+                repository.create_in_free_seat(booking(), 1),
+                repository.create_in_free_seat(booking(), 1),
+            );
+
+            assert_eq!([&first, &second].iter().filter(|result| result.is_ok()).count(), 1);
+            assert!(is_slot_unavailable(&first) || is_slot_unavailable(&second));
+            assert_eq!(repository.find_taken_seats(&host, &date, "10:00").await.unwrap(), [0]);
+        });
+    }
+
+    #[test]
+    fn the_last_two_seats_go_to_two_of_three_concurrent_bookings() {
+        with_database(|db| async move {
+            let repository = BookingRepository::new(db.clone());
+            let (event_type_id, host) = (ObjectId::new(), ObjectId::new());
+            let date = test_support::date_in("UTC", 3);
+            let booking = || test_support::booking(&event_type_id, &host, &date, "10:00");
+            let capacity = 3;
+            repository.create_in_free_seat(booking(), capacity).await.unwrap();
+
+            let (first, second, third) = tokio::join!(
+                repository.create_in_free_seat(booking(), capacity),
+                repository.create_in_free_seat(booking(), capacity),
+                repository.create_in_free_seat(booking(), capacity),
+            );
+            let results = [first, second, third];
+
+            assert_eq!(results.iter().filter(|result| result.is_ok()).count(), 2);
+            assert_eq!(results.iter().filter(|result| is_slot_unavailable(result)).count(), 1);
+            let mut seats = repository.find_taken_seats(&host, &date, "10:00").await.unwrap();
+            seats.sort();
+            assert_eq!(seats, [0, 1, 2]);
+        });
+    }
+
+    #[test]
+    fn a_status_change_records_who_made_it() {
+        with_database(|db| async move {
+            let repository = BookingRepository::new(db.clone());
+            let (event_type_id, host) = (ObjectId::new(), ObjectId::new());
+            let mut booking = test_support::booking(&event_type_id, &host, &test_support::date_in("UTC", 3), "10:00");
+            booking.status = BookingStatus::Pending;
+            let id = repository.create_in_free_seat(booking, 1).await.unwrap().id.unwrap();
+
+            let approved = repository
+                .update_status(&id, BookingStatus::Pending, BookingStatus::Confirmed, "signed-action")
+                .await
+                .unwrap()
+                .unwrap();
+            let stale = repository
+                .update_status(&id, BookingStatus::Pending, BookingStatus::Declined, "host")
+                .await
+                .unwrap();
+
+            assert!(stale.is_none());
+            assert_eq!(approved.status_history.len(), 1);
+            let change = &approved.status_history[0];
+            assert_eq!((change.from, change.to), (BookingStatus::Pending, BookingStatus::Confirmed));
+            assert_eq!(change.changed_by, "signed-action");
+        });
+    }
+
+    #[test]
+    fn an_action_link_is_claimed_once_until_released() {
+        with_database(|db| async move {
+            let repository = ConsumedActionRepository::new(db.clone());
+            let booking_id = ObjectId::new();
+            let token_hash = format!("test-{}", ObjectId::new().to_hex());
+            let expires_at = DateTime::from_millis(DateTime::now().timestamp_millis() + 60_000);
+            let claim = || repository.claim(&token_hash, &booking_id, SignedAction::Confirm, expires_at);
+
+            assert!(claim().await.unwrap());
+            assert!(!claim().await.unwrap());
+
+            repository.release(&token_hash).await;
+            assert!(claim().await.unwrap());
+        });
+    }
+}
@@ -0,0 +1,468 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::time::Duration;
+
+use chrono::{Duration as ChronoDuration, Months, NaiveDate, Utc};
+use chrono_tz::Tz;
+use mongodb::bson::{oid::ObjectId, DateTime};
+use mongodb::Database;
+
+use crate::app::AppState;
+use crate::errors::error::AppError;
+use crate::modules::booking::booking_controller::BookingController;
+use crate::modules::booking::booking_crud::{BookingRepository, RetentionRunRepository};
+use crate::modules::booking::booking_model::{Booking, BookingStatus, RetentionRun};
+use crate::modules::calendar::calendar_crud::{CalendarSettingsRepository, EventTypeRepository};
+use crate::modules::calendar::calendar_engine;
+use crate::modules::calendar::calendar_model::{EventType, ReminderChannel};
+use crate::modules::notification::notification_crud::MessageLogRepository;
+use crate::services::email_queue::{EmailJob, EmailQueue};
+use crate::services::notification_channel::{self, ChannelMetrics, NotificationChannel, Recipient, ReminderMessage};
+use crate::utils::timezone;
+
+const COMPLETION_INTERVAL: Duration = Duration::from_secs(15 * 60);
+const PENDING_EXPIRY_INTERVAL: Duration = Duration::from_secs(5 * 60);
+const REMINDER_INTERVAL: Duration = Duration::from_secs(60);
+const RETENTION_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+/// Recorded in a booking's status history for changes the jobs make.
+const JOB_ACTOR: &str = "system";
+/// Bookings anonymized per update, so a first run over years of data does not hold one long write.
+const RETENTION_BATCH_SIZE: i64 = 500;
+/// Longest reminder offset an event type can set, in days, plus a day for timezones.
+const REMINDER_LOOKAHEAD_DAYS: i64 = 8;
+
+/// Marks confirmed bookings completed once they have ended in the host's
+/// timezone. Runs for the lifetime of the server.
+pub async fn run_completion(db: Database) {
+    let booking_repository = BookingRepository::new(db.clone());
+    let settings_repository = CalendarSettingsRepository::new(db);
+
+    loop {
+        match complete_past_bookings(&booking_repository, &settings_repository).await {
+            Ok(0) => {}
+            Ok(completed) => println!("Marked {} past bookings as completed", completed),
+            Err(e) => eprintln!("Failed to complete past bookings: {}", e),
+        }
+        tokio::time::sleep(COMPLETION_INTERVAL).await;
+    }
+}
+
+async fn complete_past_bookings(
+    booking_repository: &BookingRepository,
+    settings_repository: &CalendarSettingsRepository,
+) -> Result<usize, AppError> {
+    // Tomorrow in UTC covers every timezone that is already past midnight
+    let latest_date = (Utc::now() + ChronoDuration::days(1)).format("%Y-%m-%d").to_string();
+    let bookings = booking_repository.find_confirmed_until(&latest_date).await?;
+
+    let mut timezones: HashMap<ObjectId, Tz> = HashMap::new();
+    let mut completed = 0;
+    for booking in bookings {
+        let Some(id) = booking.id else {
+            continue;
+        };
+        // One host's broken settings must not hold up everyone else's bookings
+        let tz = match host_timezone(&mut timezones, settings_repository, &booking.host_user_id).await {
+            Ok(tz) => tz,
+            Err(e) => {
+                eprintln!("Failed to complete booking {}: {}", id.to_hex(), e);
+                continue;
+            }
+        };
+
+        let Ok(date) = NaiveDate::parse_from_str(&booking.date, "%Y-%m-%d") else {
+            continue;
+        };
+        let ends_at = date.and_time(calendar_engine::parse_end_time(&booking.end_time));
+        if ends_at > Utc::now().with_timezone(&tz).naive_local() {
+            continue;
+        }
+
+        // A concurrent cancel or status change wins; the booking is simply skipped
+        match booking_repository.update_status(&id, BookingStatus::Confirmed, BookingStatus::Completed, JOB_ACTOR).await {
+            Ok(Some(_)) => completed += 1,
+            Ok(None) => {}
+            Err(e) => eprintln!("Failed to complete booking {}: {}", id.to_hex(), e),
+        }
+    }
+
+    Ok(completed)
+}
+
+/// Declines pending bookings the host has not answered within `ttl_hours`,
+/// releasing their slots. Runs for the lifetime of the server.
+pub async fn run_pending_expiry(db: Database, email_queue: EmailQueue, ttl_hours: u64) {
+    let booking_repository = BookingRepository::new(db.clone());
+    let event_type_repository = EventTypeRepository::new(db);
+
+    loop {
+        match decline_expired_requests(&booking_repository, &event_type_repository, &email_queue, ttl_hours).await {
+            Ok(0) => {}
+            Ok(declined) => println!("Declined {} unanswered booking requests", declined),
+            Err(e) => eprintln!("Failed to decline unanswered booking requests: {}", e),
+        }
+        tokio::time::sleep(PENDING_EXPIRY_INTERVAL).await;
+    }
+}
+
+async fn decline_expired_requests(
+    booking_repository: &BookingRepository,
+    event_type_repository: &EventTypeRepository,
+    email_queue: &EmailQueue,
+    ttl_hours: u64,
+) -> Result<usize, AppError> {
+    let ttl_millis = i64::try_from(ttl_hours.saturating_mul(60 * 60 * 1000)).unwrap_or(i64::MAX);
+    let cutoff = DateTime::from_millis(DateTime::now().timestamp_millis().saturating_sub(ttl_millis));
+    let bookings = booking_repository.find_pending_created_before(cutoff).await?;
+
+    let mut event_names: HashMap<ObjectId, String> = HashMap::new();
+    let mut declined = 0;
+    for booking in bookings {
+        let Some(id) = booking.id else {
+            continue;
+        };
+        // The host may have answered in the meantime; their answer wins
+        let booking = match booking_repository.update_status(&id, BookingStatus::Pending, BookingStatus::Declined, JOB_ACTOR).await {
+            Ok(Some(booking)) => booking,
+            Ok(None) => continue,
+            Err(e) => {
+                eprintln!("Failed to decline booking {}: {}", id.to_hex(), e);
+                continue;
+            }
+        };
+        declined += 1;
+
+        let event_name = match event_names.get(&booking.event_type_id) {
+            Some(name) => name.clone(),
+            // The booking is already declined, so a failed lookup still sends the email
+            None => match event_type_repository.find_by_id(&booking.event_type_id).await {
+                Ok(event_type) => {
+                    let name = event_type
+                        .map(|event_type| event_type.name)
+                        .unwrap_or_else(|| "your meeting".to_string());
+                    event_names.insert(booking.event_type_id, name.clone());
+                    name
+                }
+                Err(e) => {
+                    eprintln!("Failed to look up event type for booking {}: {}", id.to_hex(), e);
+                    "your meeting".to_string()
+                }
+            },
+        };
+        let job = EmailJob::BookingDeclined {
+            to: booking.invitee_email.clone(),
+            booking_id: id.to_hex(),
+            event_name,
+            date: booking.date.clone(),
+            start_time: booking.start_time.clone(),
+        };
+        if let Err(e) = email_queue.enqueue(job).await {
+            eprintln!("Failed to queue decline email for booking {}: {}", id.to_hex(), e);
+        }
+    }
+
+    Ok(declined)
+}
+
+/// Reminds invitees and their guests shortly before confirmed bookings
+/// start, on each channel the event type's reminders name. Runs for the
+/// lifetime of the server; a failed pass is logged and retried on the next tick.
+pub async fn run_reminders(db: Database, email_queue: EmailQueue) {
+    let booking_repository = BookingRepository::new(db.clone());
+    let event_type_repository = EventTypeRepository::new(db.clone());
+    let settings_repository = CalendarSettingsRepository::new(db.clone());
+    let message_log_repository = MessageLogRepository::new(db);
+    let channels = notification_channel::reminder_channels(email_queue);
+    let metrics = AppState::get().channel_metrics.clone();
+
+    loop {
+        let reminded = send_due_reminders(
+            &booking_repository,
+            &event_type_repository,
+            &settings_repository,
+            &message_log_repository,
+            &channels,
+            &metrics,
+        ).await;
+        match reminded {
+            Ok(0) => {}
+            Ok(reminded) => println!("Sent reminders for {} upcoming bookings", reminded),
+            Err(e) => eprintln!("Failed to send booking reminders: {}", e),
+        }
+        tokio::time::sleep(REMINDER_INTERVAL).await;
+    }
+}
+
+async fn send_due_reminders(
+    booking_repository: &BookingRepository,
+    event_type_repository: &EventTypeRepository,
+    settings_repository: &CalendarSettingsRepository,
+    message_log_repository: &MessageLogRepository,
+    channels: &[Box<dyn NotificationChannel>],
+    metrics: &ChannelMetrics,
+) -> Result<usize, AppError> {
+    // Dates are in each host's timezone, so pad the UTC range by a day on each side
+    let today = Utc::now().date_naive();
+    let bookings = booking_repository
+        .find_confirmed_between(
+            &(today - ChronoDuration::days(1)).format("%Y-%m-%d").to_string(),
+            &(today + ChronoDuration::days(REMINDER_LOOKAHEAD_DAYS)).format("%Y-%m-%d").to_string(),
+        )
+        .await?;
+
+    let mut timezones: HashMap<ObjectId, Tz> = HashMap::new();
+    let mut event_types: HashMap<ObjectId, Option<EventType>> = HashMap::new();
+    let mut reminded = 0;
+    for booking in bookings {
+        let Some(id) = booking.id else {
+            continue;
+        };
+        let Ok(date) = NaiveDate::parse_from_str(&booking.date, "%Y-%m-%d") else {
+            continue;
+        };
+        let event_type = match event_types.entry(booking.event_type_id) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => match event_type_repository.find_by_id(&booking.event_type_id).await {
+                Ok(event_type) => entry.insert(event_type),
+                Err(e) => {
+                    eprintln!("Failed to look up event type for booking {}: {}", id.to_hex(), e);
+                    continue;
+                }
+            },
+        };
+        let Some(event_type) = event_type.as_ref() else {
+            continue;
+        };
+
+        let tz = match host_timezone(&mut timezones, settings_repository, &booking.host_user_id).await {
+            Ok(tz) => tz,
+            Err(e) => {
+                eprintln!("Failed to remind booking {}: {}", id.to_hex(), e);
+                continue;
+            }
+        };
+        let starts_at = date.and_time(calendar_engine::parse_start_time(&booking.start_time));
+        let now = Utc::now().with_timezone(&tz).naive_local();
+        if starts_at <= now {
+            continue;
+        }
+        // A booking made late may have several reminders due at once; one message per channel covers them all
+        let due: Vec<i32> = event_type.reminder_offsets()
+            .into_iter()
+            .filter(|offset| !booking.reminders_sent.contains(offset))
+            .filter(|&offset| starts_at - now <= ChronoDuration::minutes(offset.into()))
+            .collect();
+        let Some(&offset_minutes) = due.iter().min() else {
+            continue;
+        };
+        let mut wanted: Vec<ReminderChannel> = Vec::new();
+        for channel in due.iter().flat_map(|&offset| event_type.reminder_channels(offset)) {
+            if !wanted.contains(&channel) {
+                wanted.push(channel);
+            }
+        }
+
+        // Claiming first means a restart or a second server never sends it twice
+        match booking_repository.claim_reminders(&id, &booking.date, &booking.start_time, &due).await {
+            Ok(true) => {}
+            Ok(false) => continue,
+            Err(e) => {
+                eprintln!("Failed to claim reminders for booking {}: {}", id.to_hex(), e);
+                continue;
+            }
+        }
+        reminded += 1;
+
+        let message = ReminderMessage {
+            booking_id: id.to_hex(),
+            event_name: event_type.name.clone(),
+            date: booking.date.clone(),
+            start_time: booking.start_time.clone(),
+            location: BookingController::location_text(&booking, event_type),
+            offset_minutes,
+            management_token: Some(booking.management_token.clone()).filter(|token| !token.is_empty()),
+            custom_copy: BookingController::custom_copy(event_type, "reminder", &booking),
+        };
+        let recipients = reminder_recipients(&booking, &wanted);
+        let log = notification_channel::dispatch_reminder(channels, &recipients, &message, metrics).await;
+        message_log_repository.record(&log).await;
+    }
+
+    Ok(reminded)
+}
+
+/// Who a booking's reminder goes to on each of the `wanted` channels: the
+/// invitee and guests by email, and the invitee's phone, if any, by SMS.
+fn reminder_recipients(booking: &Booking, wanted: &[ReminderChannel]) -> Vec<Recipient> {
+    let mut recipients = Vec::new();
+    for &channel in wanted {
+        match channel {
+            ReminderChannel::Email => {
+                recipients.push(Recipient { channel, to: booking.invitee_email.clone(), is_invitee: true });
+                recipients.extend(
+                    booking.guest_emails.iter().map(|to| Recipient { channel, to: to.clone(), is_invitee: false }),
+                );
+            }
+            ReminderChannel::Sms => recipients.extend(
+                booking.invitee_phone.iter().map(|to| Recipient { channel, to: to.clone(), is_invitee: true }),
+            ),
+        }
+    }
+    recipients
+}
+
+/// Removes invitee details from bookings older than `months`, once a day.
+/// Runs for the lifetime of the server.
+pub async fn run_retention(db: Database, months: u32) {
+    let booking_repository = BookingRepository::new(db.clone());
+    let run_repository = RetentionRunRepository::new(db);
+
+    loop {
+        match apply_retention(&booking_repository, &run_repository, months, false, JOB_ACTOR).await {
+            Ok(run) if run.anonymized == 0 => {}
+            Ok(run) => println!("Anonymized {} bookings before {}", run.anonymized, run.cutoff_date),
+            Err(e) => eprintln!("Failed to apply booking retention: {}", e),
+        }
+        tokio::time::sleep(RETENTION_INTERVAL).await;
+    }
+}
+
+/// Anonymizes bookings dated more than `months` before today in batches and
+/// records the run. A dry run only counts them.
+pub async fn apply_retention(
+    booking_repository: &BookingRepository,
+    run_repository: &RetentionRunRepository,
+    months: u32,
+    dry_run: bool,
+    triggered_by: &str,
+) -> Result<RetentionRun, AppError> {
+    let started_at = DateTime::now();
+    let cutoff_date = retention_cutoff(Utc::now().date_naive(), months).format("%Y-%m-%d").to_string();
+    let matched = booking_repository.count_unanonymized_before(&cutoff_date).await?;
+
+    let mut anonymized = 0;
+    if !dry_run {
+        loop {
+            let ids = booking_repository.find_unanonymized_ids_before(&cutoff_date, RETENTION_BATCH_SIZE).await?;
+            if ids.is_empty() {
+                break;
+            }
+            anonymized += booking_repository.anonymize(&ids).await?;
+            println!("Retention: anonymized {} of {} bookings before {}", anonymized, matched, cutoff_date);
+        }
+    }
+
+    run_repository.create(RetentionRun {
+        id: None,
+        cutoff_date,
+        months,
+        dry_run,
+        matched,
+        anonymized,
+        triggered_by: triggered_by.to_string(),
+        started_at,
+        finished_at: DateTime::now(),
+    }).await
+}
+
+/// The first date retention keeps: `months` before `today`, or the end of
+/// that month when it is shorter.
+fn retention_cutoff(today: NaiveDate, months: u32) -> NaiveDate {
+    today.checked_sub_months(Months::new(months)).unwrap_or(NaiveDate::MIN)
+}
+
+/// The host's calendar timezone, looked up once per pass.
+async fn host_timezone(
+    timezones: &mut HashMap<ObjectId, Tz>,
+    settings_repository: &CalendarSettingsRepository,
+    host_user_id: &ObjectId,
+) -> Result<Tz, AppError> {
+    if let Some(tz) = timezones.get(host_user_id) {
+        return Ok(*tz);
+    }
+    let settings = settings_repository.find_by_user_id(host_user_id).await?;
+    let (tz, _) = timezone::resolve_timezone(None, None, settings.as_ref().map(|s| s.timezone.as_str()))?;
+    timezones.insert(*host_user_id, tz);
+    Ok(tz)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::booking::booking_crud::ANONYMIZED_NAME;
+    use crate::test_support;
+
+    #[test]
+    fn a_host_with_broken_settings_does_not_stop_other_completions() {
+        test_support::with_database(|db| async move {
+            let booking_repository = BookingRepository::new(db.clone());
+            let settings_repository = CalendarSettingsRepository::new(db.clone());
+            // Stored before timezones were validated
+            let (broken, _) = test_support::create_host(&db, "Not/A_Zone").await;
+            let (working, _) = test_support::create_host(&db, "UTC").await;
+            let date = test_support::date_in("UTC", -2);
+            let mut ids = Vec::new();
+            for host in [broken.user_id, working.user_id] {
+                let booking = test_support::booking(&ObjectId::new(), &host, &date, "10:00");
+                ids.push(booking_repository.create_in_free_seat(booking, 1).await.unwrap().id.unwrap());
+            }
+
+            complete_past_bookings(&booking_repository, &settings_repository).await.unwrap();
+
+            let status = |id| {
+                let booking_repository = &booking_repository;
+                async move { booking_repository.find_by_id(&id).await.unwrap().unwrap().status }
+            };
+            assert_eq!(status(ids[0]).await, BookingStatus::Confirmed);
+            assert_eq!(status(ids[1]).await, BookingStatus::Completed);
+        });
+    }
+
+    #[test]
+    fn retention_keeps_whole_months_and_clamps_to_shorter_ones() {
+        let date = |value| NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap();
+
+        assert_eq!(retention_cutoff(date("2026-10-18"), 24), date("2024-10-18"));
+        assert_eq!(retention_cutoff(date("2026-03-31"), 1), date("2026-02-28"));
+    }
+
+    #[test]
+    fn a_dry_run_only_counts_and_a_real_run_keeps_what_stats_need() {
+        test_support::with_database(|db| async move {
+            let booking_repository = BookingRepository::new(db.clone());
+            let run_repository = RetentionRunRepository::new(db.clone());
+            let (host, _) = test_support::create_host(&db, "UTC").await;
+            let event_type_id = ObjectId::new();
+            let mut old = test_support::booking(&event_type_id, &host.user_id, &test_support::date_in("UTC", -800), "10:00");
+            old.invitee_phone = Some("+15550100".to_string());
+            old.guest_emails = vec!["guest@example.com".to_string()];
+            let old = booking_repository.create_in_free_seat(old, 1).await.unwrap();
+            let recent = test_support::booking(&event_type_id, &host.user_id, &test_support::date_in("UTC", -10), "10:00");
+            let recent = booking_repository.create_in_free_seat(recent, 1).await.unwrap();
+            let stats_before = booking_repository.count_by_event_type_and_status(&host.user_id).await.unwrap();
+
+            let dry_run = apply_retention(&booking_repository, &run_repository, 24, true, "admin tester").await.unwrap();
+
+            assert_eq!((dry_run.matched, dry_run.anonymized), (1, 0));
+            assert!(dry_run.id.is_some());
+            let untouched = booking_repository.find_by_id(&old.id.unwrap()).await.unwrap().unwrap();
+            assert_eq!(untouched.invitee_email, old.invitee_email);
+
+            let run = apply_retention(&booking_repository, &run_repository, 24, false, "admin tester").await.unwrap();
+
+            assert_eq!((run.matched, run.anonymized), (1, 1));
+            let anonymized = booking_repository.find_by_id(&old.id.unwrap()).await.unwrap().unwrap();
+            assert_eq!(anonymized.invitee_name, ANONYMIZED_NAME);
+            assert_eq!(anonymized.invitee_email, "");
+            assert!(anonymized.invitee_phone.is_none() && anonymized.guest_emails.is_empty());
+            assert!(anonymized.anonymized_at.is_some());
+            assert_eq!((anonymized.date, anonymized.status), (old.date, old.status));
+            let kept = booking_repository.find_by_id(&recent.id.unwrap()).await.unwrap().unwrap();
+            assert_eq!(kept.invitee_email, recent.invitee_email);
+            assert_eq!(booking_repository.count_by_event_type_and_status(&host.user_id).await.unwrap(), stats_before);
+
+            let again = apply_retention(&booking_repository, &run_repository, 24, false, "admin tester").await.unwrap();
+            assert_eq!((again.matched, again.anonymized), (0, 0));
+        });
+    }
+}
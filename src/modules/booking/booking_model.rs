@@ -0,0 +1,253 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use mongodb::bson::{DateTime, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+use crate::modules::calendar::calendar_model::{Location, ScheduleVersion};
+use crate::utils::signed_actions::SignedAction;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BookingStatus {
+    PendingPayment,  // Waiting for the invitee to pay; see `state` for the ordering with approval
+    Pending,  // Waiting for the host's approval
+    Confirmed,
+    Cancelled,
+    Completed,
+    NoShow,
+    Declined,
+    Expired,  // Never paid for
+}
+
+impl BookingStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BookingStatus::PendingPayment => "pending_payment",
+            BookingStatus::Pending => "pending",
+            BookingStatus::Confirmed => "confirmed",
+            BookingStatus::Cancelled => "cancelled",
+            BookingStatus::Completed => "completed",
+            BookingStatus::NoShow => "no_show",
+            BookingStatus::Declined => "declined",
+            BookingStatus::Expired => "expired",
+        }
+    }
+
+    /// Statuses in which a booking keeps its slot from being booked again.
+    pub const SLOT_HOLDING: [BookingStatus; 2] = [BookingStatus::Pending, BookingStatus::Confirmed];
+}
+
+impl fmt::Display for BookingStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An invitee's answer to one of the event type's questions.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BookingAnswer {
+    pub question: String,  // The question's label
+    pub answer: AnswerValue,
+}
+
+/// Checkbox questions are answered with a list of options, everything else with text.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum AnswerValue {
+    Text(String),
+    Choices(Vec<String>),
+}
+
+impl AnswerValue {
+    pub fn is_blank(&self) -> bool {
+        match self {
+            AnswerValue::Text(text) => text.trim().is_empty(),
+            AnswerValue::Choices(choices) => choices.is_empty(),
+        }
+    }
+
+    /// The answer as shown in meeting links and emails.
+    pub fn to_text(&self) -> String {
+        match self {
+            AnswerValue::Text(text) => text.clone(),
+            AnswerValue::Choices(choices) => choices.join(", "),
+        }
+    }
+}
+
+/// Which campaign led the invitee to book, as passed on by the booking page.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct BookingTracking {
+    pub utm_source: Option<String>,
+    pub utm_medium: Option<String>,
+    pub utm_campaign: Option<String>,
+    pub utm_content: Option<String>,
+    pub utm_term: Option<String>,
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>,  // Free-form key/value pairs
+}
+
+/// Where a booking was before its most recent reschedule.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PreviousSlot {
+    pub date: String,
+    pub start_time: String,
+    pub end_time: String,
+}
+
+/// One move of a booking to another slot.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RescheduleRecord {
+    pub from: PreviousSlot,
+    pub to: PreviousSlot,
+    pub rescheduled_by: String,  // "host" or "invitee"
+    pub rescheduled_at: DateTime,
+}
+
+/// One status transition, kept so approvals, declines and outcomes can be
+/// traced back to whoever made them.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StatusChange {
+    pub from: BookingStatus,
+    pub to: BookingStatus,
+    pub changed_by: String,  // "host <user id>", "signed-action" or "system"
+    pub changed_at: DateTime,
+}
+
+/// Most attendees a host can add to one booking.
+pub const MAX_ATTENDEES: usize = 5;
+
+/// Someone the host added to the booking after it was made, e.g. a colleague
+/// who should join the call.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BookingAttendee {
+    pub email: String,  // Lowercase
+    pub name: Option<String>,
+    pub added_at: DateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Booking {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub event_type_id: ObjectId,
+    pub host_user_id: ObjectId,
+    #[serde(default)]
+    pub co_host_user_ids: Vec<ObjectId>,  // The collective event type's other hosts, who share the booking
+    pub invitee_name: String,
+    pub invitee_email: String,
+    #[serde(default)]
+    pub guest_emails: Vec<String>,  // Colleagues the invitee copied in; lowercase, no duplicates
+    #[serde(default)]
+    pub attendees: Vec<BookingAttendee>,  // Added by the host, at most MAX_ATTENDEES
+    #[serde(default)]
+    pub invitee_phone: Option<String>,  // Where SMS reminders go; required when the event type sends them
+    pub date: String,        // YYYY-MM-DD in the host's timezone
+    pub start_time: String,  // Format: "HH:mm"
+    pub end_time: String,    // Format: "HH:mm"
+    pub status: BookingStatus,
+    #[serde(default)]
+    pub seat: i32,  // Place within a group event slot; 0 for one-on-one events
+    pub answers: Vec<BookingAnswer>,
+    #[serde(default)]
+    pub chosen_location: Option<Location>,  // Set when the event type offers location options
+    pub meeting_link: Option<String>,  // Event type link with placeholders filled in
+    #[serde(default)]
+    pub tracking: Option<BookingTracking>,
+    #[serde(default)]
+    pub cancellation_token: String,  // Lets the invitee cancel without an account
+    #[serde(default)]
+    pub management_token: String,  // Authenticates the invitee's emailed links; empty on older bookings
+    #[serde(default)]
+    pub cancelled_at: Option<DateTime>,
+    #[serde(default)]
+    pub cancelled_by: Option<String>,  // "host" or "invitee"
+    #[serde(default)]
+    pub cancellation_reason: Option<String>,
+    #[serde(default)]
+    pub rescheduled_from: Option<PreviousSlot>,
+    #[serde(default)]
+    pub reschedule_history: Vec<RescheduleRecord>,  // Oldest first; older bookings only have rescheduled_from
+    #[serde(default)]
+    pub reminders_sent: Vec<i32>,  // Offsets in minutes already sent; cleared on reschedule
+    #[serde(default)]
+    pub schedule_versions: Vec<ScheduleVersion>,  // The hosts' schedules the slot was checked against
+    #[serde(default)]
+    pub status_history: Vec<StatusChange>,  // Oldest first
+    #[serde(default)]
+    pub anonymized_at: Option<DateTime>,  // Set once retention removed the invitee's details
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+impl Booking {
+    /// Everyone hosting the booking: the event type's owner, then any co-hosts.
+    pub fn host_ids(&self) -> impl Iterator<Item = &ObjectId> {
+        std::iter::once(&self.host_user_id).chain(&self.co_host_user_ids)
+    }
+
+    pub fn is_hosted_by(&self, user_id: &str) -> bool {
+        self.host_ids().any(|host_id| host_id.to_hex() == user_id)
+    }
+}
+
+/// One pass of the retention job or an admin's retention run. A dry run
+/// only counts what a real run would anonymize.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RetentionRun {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub cutoff_date: String,  // YYYY-MM-DD; bookings on earlier dates are anonymized
+    pub months: u32,
+    pub dry_run: bool,
+    pub matched: u64,  // Bookings still holding invitee details before the cutoff
+    pub anonymized: u64,
+    pub triggered_by: String,  // "admin <user id>" or "system"
+    pub started_at: DateTime,
+    pub finished_at: DateTime,
+}
+
+/// A signed action link that has been used. Kept until the link would have
+/// expired anyway, so each link works once.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConsumedAction {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub token_hash: String,  // Hex SHA-256 of the link token
+    pub booking_id: ObjectId,
+    pub action: SignedAction,
+    pub expires_at: DateTime,
+    pub consumed_at: DateTime,
+}
+
+/// A slot reserved for an invitee while they fill in the booking form. Counts
+/// as busy until it expires, when the database removes it, or becomes a booking.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SlotHold {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub event_type_id: ObjectId,
+    pub host_user_id: ObjectId,
+    #[serde(default)]
+    pub co_host_user_ids: Vec<ObjectId>,  // Held for them too, as for bookings
+    pub date: String,        // YYYY-MM-DD in the host's timezone
+    pub start_time: String,  // Format: "HH:mm"
+    pub end_time: String,    // Format: "HH:mm"
+    pub seat: i32,  // Place within a group event slot; 0 for one-on-one events
+    pub expires_at: DateTime,
+    pub created_at: DateTime,
+}
+
+/// A booking request made with an Idempotency-Key header. Replays of the key
+/// return `booking_id` instead of booking again. Removed after a day.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IdempotencyRecord {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub scope: String,         // What the key applies to, e.g. the event type being booked
+    pub key: String,
+    pub request_hash: String,  // Hex SHA-256 of the request body
+    pub booking_id: Option<ObjectId>,  // None while the first request is still running
+    pub created_at: DateTime,
+}
@@ -0,0 +1,237 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use actix_web::{web, HttpRequest, Scope};
+use crate::modules::booking::booking_controller::BookingController;
+use crate::modules::booking::booking_schema::{
+    AddAttendeeRequest, AttendeePath, CancelBookingRequest, CreateBookingRequest, CreateSlotHoldRequest, ExistingBookingQuery, ExportBookingsQuery, MarkNoShowRequest, PublicAvailabilityQuery, PublicBookingRequest,
+    PublicCancelBookingRequest, PublicRescheduleBookingRequest, RescheduleBookingRequest, UpdateBookingStatusRequest,
+};
+use crate::modules::user::user_schema::Claims;
+use crate::errors::error::AppError;
+use crate::errors::error_handler::method_not_allowed;
+use crate::middleware::auth::{AuthMiddleware, OptionalAuthMiddleware};
+use crate::middleware::rate_limit::RateLimit;
+use crate::utils::object_id::PathObjectId;
+use crate::app::AppState;
+
+/// Availability lookups allowed per client IP per minute on public booking pages.
+const PUBLIC_AVAILABILITY_REQUESTS_PER_MINUTE: u32 = 60;
+/// Bookings allowed per client IP per minute on public booking pages.
+const PUBLIC_BOOKING_REQUESTS_PER_MINUTE: u32 = 10;
+/// Management token requests allowed per client IP per minute; keeps token guessing slow.
+const PUBLIC_MANAGE_REQUESTS_PER_MINUTE: u32 = 20;
+/// Existing booking lookups allowed per client IP per minute; keeps probing who has meetings slow.
+const PUBLIC_LOOKUP_REQUESTS_PER_MINUTE: u32 = 10;
+/// Signed action link uses allowed per client IP per minute.
+const ACTION_REQUESTS_PER_MINUTE: u32 = 20;
+
+/// Must be registered before `calendar_routes`, whose "/calendar" scope would
+/// otherwise swallow these paths.
+pub fn booking_routes() -> Result<Scope, AppError> {
+    let controller = web::Data::new(BookingController::new(AppState::get().db.clone()));
+
+    Ok(web::scope("/calendar/bookings")
+        .app_data(controller.clone())
+        .service(
+            web::resource("")
+                .default_service(method_not_allowed("POST"))
+                .wrap(AuthMiddleware)
+                .route(web::post().to(|req: HttpRequest, data: web::Json<CreateBookingRequest>, controller: web::Data<BookingController>| {
+                    async move { controller.create_booking(req, data).await }
+                }))
+        )
+        .service(
+            web::resource("/export")
+                .default_service(method_not_allowed("GET"))
+                .wrap(AuthMiddleware)
+                .route(web::get().to(|claims: web::ReqData<Claims>, query: web::Query<ExportBookingsQuery>, controller: web::Data<BookingController>| {
+                    async move { controller.export_bookings(claims, query).await }
+                }))
+        )
+        .service(
+            web::resource("/stats")
+                .default_service(method_not_allowed("GET"))
+                .wrap(AuthMiddleware)
+                .route(web::get().to(|claims: web::ReqData<Claims>, controller: web::Data<BookingController>| {
+                    async move { controller.booking_stats(claims).await }
+                }))
+        )
+        .service(
+            web::resource("/{id}")
+                .default_service(method_not_allowed("GET"))
+                .wrap(AuthMiddleware)
+                .route(web::get().to(|claims: web::ReqData<Claims>, id: PathObjectId, controller: web::Data<BookingController>| {
+                    async move { controller.get_booking(claims, id).await }
+                }))
+        )
+        .service(
+            web::resource("/{id}/cancel")
+                .default_service(method_not_allowed("POST"))
+                .wrap(OptionalAuthMiddleware)
+                .route(web::post().to(|claims: Option<web::ReqData<Claims>>, id: PathObjectId, data: web::Json<CancelBookingRequest>, controller: web::Data<BookingController>| {
+                    async move { controller.cancel_booking(claims, id, data).await }
+                }))
+        )
+        .service(
+            web::resource("/{id}/reschedule")
+                .default_service(method_not_allowed("POST"))
+                .wrap(OptionalAuthMiddleware)
+                .route(web::post().to(|claims: Option<web::ReqData<Claims>>, id: PathObjectId, data: web::Json<RescheduleBookingRequest>, controller: web::Data<BookingController>| {
+                    async move { controller.reschedule_booking(claims, id, data).await }
+                }))
+        )
+        .service(
+            web::resource("/{id}/status")
+                .default_service(method_not_allowed("PATCH"))
+                .wrap(AuthMiddleware)
+                .route(web::patch().to(|claims: web::ReqData<Claims>, id: PathObjectId, data: web::Json<UpdateBookingStatusRequest>, controller: web::Data<BookingController>| {
+                    async move { controller.update_status(claims, id, data).await }
+                }))
+        )
+        .service(
+            web::resource("/{id}/no-show")
+                .default_service(method_not_allowed("POST"))
+                .wrap(AuthMiddleware)
+                .route(web::post().to(|claims: web::ReqData<Claims>, id: PathObjectId, data: web::Json<MarkNoShowRequest>, controller: web::Data<BookingController>| {
+                    async move { controller.mark_no_show(claims, id, data).await }
+                }))
+        )
+        .service(
+            web::resource("/{id}/attendees")
+                .default_service(method_not_allowed("POST"))
+                .wrap(AuthMiddleware)
+                .route(web::post().to(|claims: web::ReqData<Claims>, id: PathObjectId, data: web::Json<AddAttendeeRequest>, controller: web::Data<BookingController>| {
+                    async move { controller.add_attendee(claims, id, data).await }
+                }))
+        )
+        .service(
+            web::resource("/{id}/attendees/{email}")
+                .default_service(method_not_allowed("DELETE"))
+                .wrap(AuthMiddleware)
+                .route(web::delete().to(|claims: web::ReqData<Claims>, id: PathObjectId, path: web::Path<AttendeePath>, controller: web::Data<BookingController>| {
+                    async move { controller.remove_attendee(claims, id, path).await }
+                }))
+        )
+        .service(
+            web::resource("/{id}/approve")
+                .default_service(method_not_allowed("POST"))
+                .wrap(AuthMiddleware)
+                .route(web::post().to(|claims: web::ReqData<Claims>, id: PathObjectId, controller: web::Data<BookingController>| {
+                    async move { controller.approve_booking(claims, id).await }
+                }))
+        )
+        .service(
+            web::resource("/{id}/decline")
+                .default_service(method_not_allowed("POST"))
+                .wrap(AuthMiddleware)
+                .route(web::post().to(|claims: web::ReqData<Claims>, id: PathObjectId, controller: web::Data<BookingController>| {
+                    async move { controller.decline_booking(claims, id).await }
+                }))
+        ))
+}
+
+/// Must be registered after `public_analytics_routes`, since this "/public"
+/// scope would otherwise swallow "/public/analytics".
+pub fn public_booking_routes() -> Result<Scope, AppError> {
+    let controller = web::Data::new(BookingController::new(AppState::get().db.clone()));
+
+    Ok(web::scope("/public")
+        .app_data(controller.clone())
+        .service(
+            web::resource("/bookings/{token}")
+                .default_service(method_not_allowed("GET"))
+                .wrap(RateLimit::new("public_booking_manage", PUBLIC_MANAGE_REQUESTS_PER_MINUTE, Duration::from_secs(60)))
+                .route(web::get().to(|token: web::Path<String>, controller: web::Data<BookingController>| {
+                    async move { controller.public_get_booking(token).await }
+                }))
+        )
+        .service(
+            web::resource("/bookings/{token}/cancel")
+                .default_service(method_not_allowed("POST"))
+                .wrap(RateLimit::new("public_booking_manage", PUBLIC_MANAGE_REQUESTS_PER_MINUTE, Duration::from_secs(60)))
+                .route(web::post().to(|token: web::Path<String>, data: web::Json<PublicCancelBookingRequest>, controller: web::Data<BookingController>| {
+                    async move { controller.public_cancel_booking(token, data).await }
+                }))
+        )
+        .service(
+            web::resource("/bookings/{token}/reschedule")
+                .default_service(method_not_allowed("POST"))
+                .wrap(RateLimit::new("public_booking_manage", PUBLIC_MANAGE_REQUESTS_PER_MINUTE, Duration::from_secs(60)))
+                .route(web::post().to(|token: web::Path<String>, data: web::Json<PublicRescheduleBookingRequest>, controller: web::Data<BookingController>| {
+                    async move { controller.public_reschedule_booking(token, data).await }
+                }))
+        )
+        .service(
+            web::resource("/{user_id}")
+                .default_service(method_not_allowed("GET"))
+                .wrap(RateLimit::new("public_booking_availability", PUBLIC_AVAILABILITY_REQUESTS_PER_MINUTE, Duration::from_secs(60)))
+                .route(web::get().to(|user_id: web::Path<String>, controller: web::Data<BookingController>| {
+                    async move { controller.public_list_event_types(user_id).await }
+                }))
+        )
+        .service(
+            web::resource("/{user_id}/{event_type_id}")
+                .default_service(method_not_allowed("GET"))
+                .wrap(RateLimit::new("public_booking_availability", PUBLIC_AVAILABILITY_REQUESTS_PER_MINUTE, Duration::from_secs(60)))
+                .route(web::get().to(|path: web::Path<(String, String)>, query: web::Query<HashMap<String, String>>, controller: web::Data<BookingController>| {
+                    async move { controller.public_get_event_type(path, query).await }
+                }))
+        )
+        .service(
+            web::resource("/{user_id}/{event_type_id}/embed-config")
+                .default_service(method_not_allowed("GET"))
+                .wrap(RateLimit::new("public_booking_availability", PUBLIC_AVAILABILITY_REQUESTS_PER_MINUTE, Duration::from_secs(60)))
+                .route(web::get().to(|path: web::Path<(String, String)>, controller: web::Data<BookingController>| {
+                    async move { controller.public_embed_config(path).await }
+                }))
+        )
+        .service(
+            web::resource("/{user_id}/{event_type_id}/availability")
+                .default_service(method_not_allowed("GET"))
+                .wrap(RateLimit::new("public_booking_availability", PUBLIC_AVAILABILITY_REQUESTS_PER_MINUTE, Duration::from_secs(60)))
+                .route(web::get().to(|path: web::Path<(String, String)>, query: web::Query<PublicAvailabilityQuery>, controller: web::Data<BookingController>| {
+                    async move { controller.public_availability(path, query).await }
+                }))
+        )
+        .service(
+            web::resource("/{user_id}/{event_type_id}/holds")
+                .default_service(method_not_allowed("POST"))
+                .wrap(RateLimit::new("public_booking_hold", PUBLIC_BOOKING_REQUESTS_PER_MINUTE, Duration::from_secs(60)))
+                .route(web::post().to(|path: web::Path<(String, String)>, data: web::Json<CreateSlotHoldRequest>, controller: web::Data<BookingController>| {
+                    async move { controller.create_slot_hold(path, data).await }
+                }))
+        )
+        .service(
+            web::resource("/{user_id}/{event_type_id}/bookings")
+                .default_service(method_not_allowed("POST"))
+                .wrap(RateLimit::new("public_booking_create", PUBLIC_BOOKING_REQUESTS_PER_MINUTE, Duration::from_secs(60)))
+                .route(web::post().to(|req: HttpRequest, path: web::Path<(String, String)>, data: web::Json<PublicBookingRequest>, controller: web::Data<BookingController>| {
+                    async move { controller.public_create_booking(req, path, data).await }
+                }))
+        )
+        .service(
+            web::resource("/{user_id}/{event_type_id}/existing-booking")
+                .default_service(method_not_allowed("GET"))
+                .wrap(RateLimit::new("public_existing_booking", PUBLIC_LOOKUP_REQUESTS_PER_MINUTE, Duration::from_secs(60)))
+                .route(web::get().to(|path: web::Path<(String, String)>, query: web::Query<ExistingBookingQuery>, controller: web::Data<BookingController>| {
+                    async move { controller.public_existing_booking(path, query).await }
+                }))
+        ))
+}
+
+/// Host actions carried out through signed links from emails, without a session.
+pub fn action_routes() -> Result<Scope, AppError> {
+    let controller = web::Data::new(BookingController::new(AppState::get().db.clone()));
+
+    Ok(web::scope("/actions")
+        .app_data(controller.clone())
+        .service(
+            web::resource("/{token}")
+                .default_service(method_not_allowed("POST"))
+                .wrap(RateLimit::new("signed_action", ACTION_REQUESTS_PER_MINUTE, Duration::from_secs(60)))
+                .route(web::post().to(|token: web::Path<String>, controller: web::Data<BookingController>| {
+                    async move { controller.perform_signed_action(token).await }
+                }))
+        ))
+}
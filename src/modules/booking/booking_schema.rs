@@ -0,0 +1,583 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+use crate::modules::booking::booking_model::{AnswerValue, Booking, BookingAnswer, BookingAttendee, BookingStatus, BookingTracking, PreviousSlot, SlotHold};
+use crate::modules::calendar::calendar_model::{CancellationPolicy, ConfirmationSettings, EmbedSettings, EventType, Location, Question, QuestionKind, ReschedulePolicy, SchedulingWindow};
+use crate::utils::markdown;
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreateBookingRequest {
+    #[validate(length(min = 1, max = 24, message = "Event type ID must be between 1 and 24 characters"))]
+    pub event_type_id: String,
+    #[validate(length(min = 1, max = 100, message = "Invitee name must be between 1 and 100 characters"))]
+    pub invitee_name: String,
+    #[validate(email(message = "Invalid invitee email"), length(max = 254, message = "Invitee email must be at most 254 characters"))]
+    pub invitee_email: String,
+    #[serde(default)]
+    #[validate(length(max = 10, message = "At most 10 guest emails are allowed"))]
+    pub guest_emails: Vec<String>,
+    #[validate(length(max = 30, message = "Invitee phone must be at most 30 characters"))]
+    pub invitee_phone: Option<String>,  // Required when the event type sends SMS reminders
+    pub date: String,        // YYYY-MM-DD format
+    pub start_time: String,  // HH:mm format; the end follows from the event duration
+    #[serde(default)]
+    pub answers: Vec<BookingAnswer>,
+    pub chosen_location: Option<Location>,  // Required when the event type offers more than one location
+    pub tracking: Option<BookingTracking>,
+    #[validate(length(max = 24, message = "Hold ID must be at most 24 characters"))]
+    pub hold_id: Option<String>,  // A slot hold on the same slot, turned into this booking
+}
+
+/// Booking made by an invitee on a host's public page; the event type comes from the path.
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct PublicBookingRequest {
+    #[serde(default)]
+    #[validate(length(min = 1, max = 100, message = "Invitee name must be between 1 and 100 characters"))]
+    pub invitee_name: String,  // May be left out when the prefill has it
+    #[serde(default)]
+    #[validate(email(message = "Invalid invitee email"), length(max = 254, message = "Invitee email must be at most 254 characters"))]
+    pub invitee_email: String,
+    #[serde(default)]
+    #[validate(length(max = 10, message = "At most 10 guest emails are allowed"))]
+    pub guest_emails: Vec<String>,
+    #[validate(length(max = 30, message = "Invitee phone must be at most 30 characters"))]
+    pub invitee_phone: Option<String>,  // Required when the event type sends SMS reminders
+    pub date: String,        // YYYY-MM-DD format
+    pub start_time: String,  // HH:mm format
+    #[serde(default)]
+    pub answers: Vec<BookingAnswer>,
+    pub chosen_location: Option<Location>,
+    pub tracking: Option<BookingTracking>,  // UTM parameters and metadata from the booking page
+    #[validate(length(max = 24, message = "Hold ID must be at most 24 characters"))]
+    pub hold_id: Option<String>,  // From POST .../holds when the invitee picked the slot
+    pub prefill: Option<Prefill>,  // As echoed by the event type page; fills in whatever the form left empty
+}
+
+/// Booking form values carried in a public booking link, such as
+/// `?name=Jane&email=jane@example.com&a_company=Acme`. Answers are keyed by
+/// question key, the question's label in snake case.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Prefill {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phone: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub answers: BTreeMap<String, String>,  // Comma-separated options for checkbox questions
+}
+
+impl Prefill {
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none() && self.email.is_none() && self.phone.is_none() && self.answers.is_empty()
+    }
+}
+
+impl PublicBookingRequest {
+    pub fn into_create_request(self, event_type_id: String) -> CreateBookingRequest {
+        CreateBookingRequest {
+            event_type_id,
+            invitee_name: self.invitee_name,
+            invitee_email: self.invitee_email,
+            guest_emails: self.guest_emails,
+            invitee_phone: self.invitee_phone,
+            date: self.date,
+            start_time: self.start_time,
+            answers: self.answers,
+            chosen_location: self.chosen_location,
+            tracking: self.tracking,
+            hold_id: self.hold_id,
+        }
+    }
+}
+
+/// Reserves a slot on a host's public page while the invitee fills in the form.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreateSlotHoldRequest {
+    pub date: String,        // YYYY-MM-DD format
+    pub start_time: String,  // HH:mm format
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SlotHoldResponse {
+    pub hold_id: String,
+    pub event_type_id: String,
+    pub date: String,
+    pub start_time: String,
+    pub end_time: String,
+    pub expires_at: String,
+}
+
+impl From<SlotHold> for SlotHoldResponse {
+    fn from(hold: SlotHold) -> Self {
+        Self {
+            hold_id: hold.id.unwrap().to_hex(),
+            event_type_id: hold.event_type_id.to_hex(),
+            date: hold.date,
+            start_time: hold.start_time,
+            end_time: hold.end_time,
+            expires_at: hold.expires_at.to_string(),
+        }
+    }
+}
+
+/// What a host's public booking page shows about an event type before booking.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PublicEventTypeResponse {
+    pub id: String,
+    pub name: String,
+    pub slug: String,  // Use in place of the id in public URLs; the current one even when found by an old one
+    pub description_html: Option<String>,
+    pub duration: i32,
+    pub color: String,
+    pub locations: Vec<Location>,  // Pass one back as chosen_location when there are several
+    pub questions: Vec<Question>,
+    pub requires_confirmation: bool,
+    pub requires_invitee_phone: bool,  // Set when reminders go out by SMS
+    pub max_attendees: Option<i32>,
+    pub cancellation_policy: Option<CancellationPolicy>,  // What invitees may change themselves
+    pub reschedule_policy: Option<ReschedulePolicy>,
+    pub scheduling_window: Option<SchedulingWindow>,  // Dates outside it have no slots
+    pub embed_settings: Option<EmbedSettings>,
+    pub timezone: String,  // The host's; slot dates and times are in it
+    #[serde(default, skip_serializing_if = "Prefill::is_empty")]
+    pub prefill: Prefill,  // The valid values of the link's prefill parameters
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub prefill_warnings: Vec<String>,  // Prefill parameters that were ignored, and why
+}
+
+impl PublicEventTypeResponse {
+    pub fn new(event_type: EventType, timezone: String) -> Self {
+        let requires_invitee_phone = event_type.sends_sms_reminders();
+        Self {
+            id: event_type.id.unwrap().to_hex(),
+            name: event_type.name,
+            slug: event_type.slug,
+            description_html: event_type.description.as_deref().map(markdown::render_markdown),
+            duration: event_type.duration,
+            color: event_type.color,
+            locations: event_type.locations,
+            questions: event_type.questions,
+            requires_confirmation: event_type.requires_confirmation,
+            requires_invitee_phone,
+            max_attendees: event_type.max_attendees,
+            cancellation_policy: event_type.cancellation_policy,
+            reschedule_policy: event_type.reschedule_policy,
+            scheduling_window: event_type.scheduling_window,
+            embed_settings: event_type.embed_settings,
+            timezone,
+            prefill: Prefill::default(),
+            prefill_warnings: Vec::new(),
+        }
+    }
+}
+
+/// How the booking widget of an event type looks when embedded in another site.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PublicEmbedConfigResponse {
+    pub id: String,
+    pub slug: String,
+    pub color: String,
+    pub embed_settings: Option<EmbedSettings>,  // None uses the widget's defaults
+}
+
+impl From<EventType> for PublicEmbedConfigResponse {
+    fn from(event_type: EventType) -> Self {
+        Self {
+            id: event_type.id.unwrap().to_hex(),
+            slug: event_type.slug,
+            color: event_type.color,
+            embed_settings: event_type.embed_settings,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct PublicAvailabilityQuery {
+    pub start_date: String,  // YYYY-MM-DD format, a day in `timezone`
+    pub end_date: String,    // YYYY-MM-DD format, a day in `timezone`
+    pub timezone: Option<String>,  // IANA name of the invitee; defaults to the host's
+    #[validate(range(min = 1, max = 10, message = "Recommend must be between 1 and 10"))]
+    pub recommend: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct ExistingBookingQuery {
+    #[validate(email(message = "Invalid email"), length(max = 254, message = "Email must be at most 254 characters"))]
+    pub email: String,
+}
+
+/// Whether an invitee already booked, and nothing about the booking itself.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExistingBookingResponse {
+    pub has_upcoming_booking: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub management_hint: Option<String>,  // Last 4 characters of the management token, to match it to their email
+}
+
+impl ExistingBookingResponse {
+    pub fn new(booking: Option<&Booking>) -> Self {
+        let management_hint = booking
+            .map(|booking| booking.management_token.as_str())
+            .filter(|token| token.len() >= 4)
+            .map(|token| token[token.len() - 4..].to_string());
+        Self {
+            has_upcoming_booking: booking.is_some(),
+            management_hint,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExportBookingsQuery {
+    pub from: String,            // YYYY-MM-DD format
+    pub to: String,              // YYYY-MM-DD format
+    pub format: Option<String>,  // Only "csv" for now, the default
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CancelBookingRequest {
+    #[validate(length(max = 500, message = "Reason must be at most 500 characters"))]
+    pub reason: Option<String>,
+    #[validate(length(max = 64, message = "Cancellation token must be at most 64 characters"))]
+    pub cancellation_token: Option<String>,  // Required when not signed in as the host
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct RescheduleBookingRequest {
+    pub date: String,        // YYYY-MM-DD format
+    pub start_time: String,  // HH:mm format
+    pub end_time: Option<String>,  // HH:mm; must match the event duration when given
+    #[validate(length(max = 64, message = "Cancellation token must be at most 64 characters"))]
+    pub cancellation_token: Option<String>,  // Required when not signed in as the host
+}
+
+/// Cancellation through the invitee's emailed link; the token in the path authenticates it.
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct PublicCancelBookingRequest {
+    #[validate(length(max = 500, message = "Reason must be at most 500 characters"))]
+    pub reason: Option<String>,
+}
+
+/// Reschedule through the invitee's emailed link; the token in the path authenticates it.
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct PublicRescheduleBookingRequest {
+    pub date: String,        // YYYY-MM-DD format
+    pub start_time: String,  // HH:mm format
+    pub end_time: Option<String>,  // HH:mm; must match the event duration when given
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct AddAttendeeRequest {
+    #[validate(email(message = "Invalid attendee email"), length(max = 254, message = "Attendee email must be at most 254 characters"))]
+    pub email: String,
+    #[validate(length(min = 1, max = 100, message = "Attendee name must be between 1 and 100 characters"))]
+    pub name: Option<String>,
+}
+
+/// The attendee segment of `/bookings/{id}/attendees/{email}`.
+#[derive(Debug, Deserialize)]
+pub struct AttendeePath {
+    pub email: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MarkNoShowRequest {
+    #[serde(default = "default_no_show")]
+    pub no_show: bool,  // Defaults to true, so `{}` flags the booking; false undoes a mistaken flag
+}
+
+fn default_no_show() -> bool {
+    true
+}
+
+/// Booking counts for one of the host's event types.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EventTypeBookingStats {
+    pub event_type_id: String,
+    pub event_type_name: Option<String>,  // None once the event type is deleted
+    pub by_status: BTreeMap<String, u64>,
+    pub no_show_rate: Option<f64>,  // Share of past bookings the invitee missed; None before any
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BookingStatsResponse {
+    pub total: u64,
+    pub by_status: BTreeMap<String, u64>,
+    pub no_show_rate: Option<f64>,
+    pub event_types: Vec<EventTypeBookingStats>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateBookingStatusRequest {
+    pub status: BookingStatus,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BookingResponse {
+    pub id: String,
+    pub event_type_id: String,
+    pub host_user_id: String,
+    pub co_host_user_ids: Vec<String>,
+    pub invitee_name: String,
+    pub invitee_email: String,
+    pub guest_emails: Vec<String>,
+    pub attendees: Vec<AttendeeResponse>,
+    pub invitee_phone: Option<String>,
+    pub date: String,
+    pub start_time: String,
+    pub end_time: String,
+    pub status: BookingStatus,
+    pub answers: Vec<BookingAnswer>,
+    pub chosen_location: Option<Location>,
+    pub meeting_link: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tracking: Option<BookingTracking>,  // Left out of the invitee's management view
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cancellation_token: Option<String>,  // Only returned when the booking is created
+    pub cancelled_at: Option<String>,
+    pub cancelled_by: Option<String>,
+    pub cancellation_reason: Option<String>,
+    pub rescheduled_from: Option<PreviousSlot>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confirmation_settings: Option<ConfirmationSettings>,  // Only returned when the booking is made
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redirect_url: Option<String>,  // Where to send the invitee, with the booking details added if asked for
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl BookingResponse {
+    /// Adds what the invitee should see next, from the event type's confirmation settings.
+    pub fn with_confirmation(self, settings: Option<&ConfirmationSettings>) -> Self {
+        let redirect_url = settings
+            .and_then(|settings| settings.redirect_url.as_deref().map(|url| (url, settings.pass_booking_details_as_query)))
+            .map(|(url, pass_details)| {
+                if !pass_details {
+                    return url.to_string();
+                }
+                let details = [
+                    ("booking_id", self.id.as_str()),
+                    ("date", self.date.as_str()),
+                    ("start_time", self.start_time.as_str()),
+                    ("end_time", self.end_time.as_str()),
+                ]
+                    .iter()
+                    .map(|(name, value)| format!("{}={}", name, urlencoding::encode(value)))
+                    .collect::<Vec<_>>()
+                    .join("&");
+                // The query goes before any fragment
+                let (base, fragment) = url.split_once('#').map_or((url, None), |(base, fragment)| (base, Some(fragment)));
+                let separator = if base.contains('?') { "&" } else { "?" };
+                match fragment {
+                    Some(fragment) => format!("{}{}{}#{}", base, separator, details, fragment),
+                    None => format!("{}{}{}", base, separator, details),
+                }
+            });
+        Self {
+            confirmation_settings: settings.cloned(),
+            redirect_url,
+            ..self
+        }
+    }
+}
+
+impl From<Booking> for BookingResponse {
+    fn from(booking: Booking) -> Self {
+        Self {
+            id: booking.id.unwrap().to_hex(),
+            event_type_id: booking.event_type_id.to_hex(),
+            host_user_id: booking.host_user_id.to_hex(),
+            co_host_user_ids: booking.co_host_user_ids.iter().map(|id| id.to_hex()).collect(),
+            invitee_name: booking.invitee_name,
+            invitee_email: booking.invitee_email,
+            guest_emails: booking.guest_emails,
+            attendees: booking.attendees.into_iter().map(AttendeeResponse::from).collect(),
+            invitee_phone: booking.invitee_phone,
+            date: booking.date,
+            start_time: booking.start_time,
+            end_time: booking.end_time,
+            status: booking.status,
+            answers: booking.answers,
+            chosen_location: booking.chosen_location,
+            meeting_link: booking.meeting_link,
+            tracking: booking.tracking,
+            cancellation_token: None,
+            cancelled_at: booking.cancelled_at.map(|dt| dt.to_string()),
+            cancelled_by: booking.cancelled_by,
+            cancellation_reason: booking.cancellation_reason,
+            rescheduled_from: booking.rescheduled_from,
+            confirmation_settings: None,
+            redirect_url: None,
+            created_at: booking.created_at.to_string(),
+            updated_at: booking.updated_at.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AttendeeResponse {
+    pub email: String,
+    pub name: Option<String>,
+    pub added_at: String,
+}
+
+impl From<BookingAttendee> for AttendeeResponse {
+    fn from(attendee: BookingAttendee) -> Self {
+        Self {
+            email: attendee.email,
+            name: attendee.name,
+            added_at: attendee.added_at.to_string(),
+        }
+    }
+}
+
+/// The parts of the event type a booking detail page shows.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BookingEventTypeSummary {
+    pub id: String,
+    pub name: String,
+    pub duration: i32,
+    pub color: String,
+    pub location_type: Option<String>,  // Kind of the chosen location, e.g. "video"
+    pub location: Option<String>,  // Where this booking takes place, e.g. its meeting link
+}
+
+/// An answer next to the question it belongs to, in the event type's question order.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LabeledAnswer {
+    pub question: String,
+    pub kind: Option<QuestionKind>,  // None when the question was removed from the event type
+    pub answer: AnswerValue,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BookingHistoryEntry {
+    pub action: String,  // "created", "rescheduled", "cancelled" or the status the booking moved to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub at: Option<String>,  // Missing for reschedules made before history was kept
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub by: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<PreviousSlot>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<PreviousSlot>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Everything a dashboard needs to show one booking.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BookingDetailResponse {
+    pub booking: BookingResponse,
+    pub event_type: Option<BookingEventTypeSummary>,  // None once the event type is deleted
+    pub answers: Vec<LabeledAnswer>,
+    pub history: Vec<BookingHistoryEntry>,  // Oldest first
+}
+
+#[cfg(test)]
+mod tests {
+    use mongodb::bson::oid::ObjectId;
+    use serde::de::DeserializeOwned;
+    use serde_json::json;
+
+    use super::*;
+    use crate::test_support;
+
+    fn invalid_fields<T: DeserializeOwned + Validate>(body: serde_json::Value) -> Vec<String> {
+        let request: T = serde_json::from_value(body).unwrap();
+        let errors = request.validate().unwrap_err();
+        let mut fields: Vec<String> = errors.field_errors().keys().map(|field| field.to_string()).collect();
+        fields.sort();
+        fields
+    }
+
+    fn oversized_invitee() -> serde_json::Value {
+        json!({
+            "invitee_name": "n".repeat(101),
+            "invitee_email": format!("{}@example.com", "e".repeat(250)),
+            "invitee_phone": "1".repeat(31),
+            "date": "2024-06-03",
+            "start_time": "10:00",
+            "hold_id": "h".repeat(25),
+        })
+    }
+
+    #[test]
+    fn oversized_booking_fields_are_named_in_the_errors() {
+        let invitee_fields = ["hold_id", "invitee_email", "invitee_name", "invitee_phone"];
+
+        assert_eq!(invalid_fields::<PublicBookingRequest>(oversized_invitee()), invitee_fields);
+
+        let mut body = oversized_invitee();
+        body["event_type_id"] = json!("i".repeat(25));
+        assert_eq!(
+            invalid_fields::<CreateBookingRequest>(body),
+            ["event_type_id", "hold_id", "invitee_email", "invitee_name", "invitee_phone"],
+        );
+    }
+
+    #[test]
+    fn oversized_cancel_and_reschedule_fields_are_named_in_the_errors() {
+        let reason = "r".repeat(501);
+        let token = "t".repeat(65);
+
+        assert_eq!(
+            invalid_fields::<CancelBookingRequest>(json!({ "reason": reason, "cancellation_token": token })),
+            ["cancellation_token", "reason"],
+        );
+        assert_eq!(invalid_fields::<PublicCancelBookingRequest>(json!({ "reason": reason })), ["reason"]);
+        assert_eq!(
+            invalid_fields::<RescheduleBookingRequest>(json!({
+                "date": "2024-06-03",
+                "start_time": "10:00",
+                "cancellation_token": token,
+            })),
+            ["cancellation_token"],
+        );
+    }
+
+    #[test]
+    fn attendee_needs_a_valid_email_and_a_short_name() {
+        assert_eq!(
+            invalid_fields::<AddAttendeeRequest>(json!({ "email": "not-an-email", "name": "n".repeat(101) })),
+            ["email", "name"],
+        );
+        let request: AddAttendeeRequest = serde_json::from_value(json!({ "email": "sam@example.com" })).unwrap();
+        assert!(request.validate().is_ok());
+    }
+
+    #[test]
+    fn existing_booking_lookup_only_tells_whether_there_is_one() {
+        let booking = Booking {
+            management_token: "a".repeat(28) + "Wxyz",
+            ..test_support::booking(&ObjectId::new(), &ObjectId::new(), "2026-03-09", "10:00")
+        };
+
+        let found = serde_json::to_value(ExistingBookingResponse::new(Some(&booking))).unwrap();
+        assert_eq!(found, json!({ "has_upcoming_booking": true, "management_hint": "Wxyz" }));
+
+        let none = serde_json::to_value(ExistingBookingResponse::new(None)).unwrap();
+        assert_eq!(none, json!({ "has_upcoming_booking": false }));
+
+        // Bookings made before management tokens existed have no hint to give
+        let old = Booking { management_token: String::new(), ..booking };
+        let old = serde_json::to_value(ExistingBookingResponse::new(Some(&old))).unwrap();
+        assert_eq!(old, json!({ "has_upcoming_booking": true }));
+    }
+}
@@ -0,0 +1,7 @@
+pub mod booking_model;
+pub mod booking_schema;
+pub mod booking_crud;
+pub mod booking_controller;
+pub mod booking_router;
+pub mod booking_jobs;
+pub mod state;
@@ -0,0 +1,106 @@
+//! The booking status machine. Every endpoint and job that changes a
+//! booking's status checks the change here, so the allowed transitions live
+//! in one table.
+//!
+//! When an event type takes payment and also needs the host's approval,
+//! payment comes first: a booking goes from pending_payment to pending, and
+//! the host only ever answers bookings that are paid for.
+
+use actix_web::http::StatusCode;
+
+use crate::errors::error::AppError;
+use crate::modules::booking::booking_model::BookingStatus;
+
+impl BookingStatus {
+    /// Cancelled, declined and expired are final; completed and no-show can
+    /// be swapped to correct a mistake.
+    pub fn can_transition_to(self, next: BookingStatus) -> bool {
+        use BookingStatus::*;
+        matches!(
+            (self, next),
+            (PendingPayment, Pending | Confirmed | Cancelled | Expired)
+                | (Pending, Confirmed | Cancelled | Declined)
+                | (Confirmed, Cancelled | Completed | NoShow)
+                | (Completed, NoShow)
+                | (NoShow, Completed)
+        )
+    }
+
+    /// `next` if the booking may move there, or a 409 naming the status it is in.
+    pub fn transition(self, next: BookingStatus) -> Result<BookingStatus, AppError> {
+        if self.can_transition_to(next) {
+            Ok(next)
+        } else {
+            Err(illegal_transition(self, next))
+        }
+    }
+}
+
+pub fn illegal_transition(current: BookingStatus, next: BookingStatus) -> AppError {
+    AppError::coded(
+        StatusCode::CONFLICT,
+        "invalid_status_transition",
+        &format!("Booking is {}, so it cannot become {}", current, next),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use BookingStatus::*;
+
+    const STATUSES: [BookingStatus; 8] = [PendingPayment, Pending, Confirmed, Cancelled, Declined, Expired, Completed, NoShow];
+
+    #[test]
+    fn only_the_listed_transitions_are_allowed() {
+        let allowed = [
+            (PendingPayment, Pending),
+            (PendingPayment, Confirmed),
+            (PendingPayment, Cancelled),
+            (PendingPayment, Expired),
+            (Pending, Confirmed),
+            (Pending, Cancelled),
+            (Pending, Declined),
+            (Confirmed, Cancelled),
+            (Confirmed, Completed),
+            (Confirmed, NoShow),
+            (Completed, NoShow),
+            (NoShow, Completed),
+        ];
+
+        for from in STATUSES {
+            for next in STATUSES {
+                let expected = allowed.contains(&(from, next));
+                assert_eq!(from.can_transition_to(next), expected, "{} -> {}", from, next);
+                match from.transition(next) {
+                    Ok(status) => assert_eq!(status, next),
+                    Err(AppError::Coded(status, code, message)) => {
+                        assert!(!expected, "{} -> {}", from, next);
+                        assert_eq!((status, code.as_str()), (409, "invalid_status_transition"));
+                        assert!(message.starts_with(&format!("Booking is {},", from)), "{}", message);
+                    }
+                    Err(e) => panic!("unexpected error {:?}", e),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn payment_comes_before_approval() {
+        // Paid goes to the host when approval is needed, else straight to confirmed
+        assert!(PendingPayment.can_transition_to(Pending) && PendingPayment.can_transition_to(Confirmed));
+        // A paid booking waiting for the host never goes back to waiting for payment
+        assert!(!Pending.can_transition_to(PendingPayment));
+        assert!(!Confirmed.can_transition_to(PendingPayment));
+        // Only a booking the host was asked about can be declined; unpaid ones expire
+        assert!(!PendingPayment.can_transition_to(Declined));
+        assert!(!Pending.can_transition_to(Expired));
+    }
+
+    #[test]
+    fn final_statuses_have_no_way_out() {
+        for from in [Cancelled, Declined, Expired] {
+            assert!(STATUSES.iter().all(|&next| !from.can_transition_to(next)), "{}", from);
+        }
+    }
+}
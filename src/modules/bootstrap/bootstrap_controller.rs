@@ -0,0 +1,115 @@
+use actix_web::{web, HttpResponse};
+use mongodb::bson::oid::ObjectId;
+use mongodb::Database;
+
+use crate::app::AppState;
+use crate::errors::error::AppError;
+use crate::modules::bootstrap::bootstrap_schema::BootstrapResponse;
+use crate::modules::calendar::calendar_crud::{AvailabilityRepository, CalendarSettingsRepository, EventTypeRepository};
+use crate::modules::calendar::calendar_schema::{AvailabilityResponse, CalendarSettingsResponse, EventTypeResponse};
+use crate::modules::notification::notification_crud::NotificationRepository;
+use crate::modules::user::user_crud::UserRepository;
+use crate::modules::user::user_schema::{Claims, UserResponse};
+
+pub struct BootstrapController {
+    user_repository: UserRepository,
+    settings_repository: CalendarSettingsRepository,
+    availability_repository: AvailabilityRepository,
+    event_type_repository: EventTypeRepository,
+    notification_repository: NotificationRepository,
+}
+
+impl BootstrapController {
+    pub fn new(db: Database) -> Self {
+        Self {
+            user_repository: UserRepository::new(),
+            settings_repository: CalendarSettingsRepository::new(db.clone()),
+            availability_repository: AvailabilityRepository::new(db.clone()),
+            event_type_repository: EventTypeRepository::new(db.clone()),
+            notification_repository: NotificationRepository::new(db),
+        }
+    }
+
+    /// Missing optional resources come back empty or null; only a missing
+    /// user or a database error fails the whole response.
+    pub async fn bootstrap(
+        &self,
+        claims: web::ReqData<Claims>,
+    ) -> Result<HttpResponse, AppError> {
+        let claims = claims.into_inner();
+        let user_id = ObjectId::parse_str(&claims.sub)
+            .map_err(|_| AppError::BadRequest("Invalid user ID".to_string()))?;
+
+        let (user, settings, availabilities, event_types, unread_notifications) = futures::join!(
+            self.user_repository.find_by_id(&claims.sub),
+            self.settings_repository.find_by_user_id(&user_id),
+            self.availability_repository.find_all_by_user_id(&user_id, false),
+            self.event_type_repository.find_by_user_id(&user_id, false),
+            self.notification_repository.count_unread(&user_id),
+        );
+
+        let user = user?
+            .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+        Ok(HttpResponse::Ok().json(BootstrapResponse {
+            user: UserResponse::from(user),
+            settings: settings?.map(CalendarSettingsResponse::from),
+            availabilities: availabilities?.into_iter().map(AvailabilityResponse::from).collect(),
+            event_types: event_types?.into_iter().map(EventTypeResponse::from).collect(),
+            feature_flags: AppState::get().features.enabled_names(),
+            unread_notifications: unread_notifications?,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{test as actix_test, FromRequest, HttpMessage};
+
+    use super::*;
+    use crate::modules::user::user_model::User;
+    use crate::test_support::with_database;
+
+    fn claims_of(user_id: &ObjectId) -> web::ReqData<Claims> {
+        let req = actix_test::TestRequest::default().to_http_request();
+        req.extensions_mut().insert(Claims {
+            sub: user_id.to_hex(),
+            exp: 0,
+            iat: 0,
+            email: "host@example.com".to_string(),
+            role: "member".to_string(),
+        });
+        web::ReqData::<Claims>::extract(&req).into_inner().unwrap()
+    }
+
+    #[test]
+    fn new_user_gets_empty_resources_instead_of_errors() {
+        with_database(|db| async move {
+            let email = format!("{}@example.com", ObjectId::new().to_hex());
+            let user = UserRepository::new()
+                .create(User::new(email.clone(), "hash".to_string(), "Host".to_string()))
+                .await
+                .unwrap();
+            let controller = BootstrapController::new(db.clone());
+
+            let response = controller.bootstrap(claims_of(&user.id.unwrap())).await.unwrap();
+            let body = actix_web::body::to_bytes(response.into_body()).await.unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(body["user"]["email"], email);
+            assert!(body["settings"].is_null());
+            assert_eq!(body["availabilities"], serde_json::json!([]));
+            assert_eq!(body["event_types"], serde_json::json!([]));
+            assert_eq!(body["unread_notifications"], 0);
+            assert!(body["feature_flags"].is_array());
+        });
+    }
+
+    #[test]
+    fn missing_user_fails_the_response() {
+        with_database(|db| async move {
+            let controller = BootstrapController::new(db.clone());
+            let result = controller.bootstrap(claims_of(&ObjectId::new())).await;
+            assert!(matches!(result, Err(AppError::NotFound(_))));
+        });
+    }
+}
@@ -0,0 +1,22 @@
+use actix_web::{web, Scope};
+use crate::modules::bootstrap::bootstrap_controller::BootstrapController;
+use crate::modules::user::user_schema::Claims;
+use crate::errors::error::AppError;
+use crate::errors::error_handler::method_not_allowed;
+use crate::middleware::auth::AuthMiddleware;
+use crate::app::AppState;
+
+pub fn bootstrap_routes() -> Result<Scope, AppError> {
+    let controller = web::Data::new(BootstrapController::new(AppState::get().db.clone()));
+
+    Ok(web::scope("/bootstrap")
+        .app_data(controller.clone())
+        .service(
+            web::resource("")
+                .default_service(method_not_allowed("GET"))
+                .wrap(AuthMiddleware)
+                .route(web::get().to(|claims: web::ReqData<Claims>, controller: web::Data<BootstrapController>| {
+                    async move { controller.bootstrap(claims).await }
+                }))
+        ))
+}
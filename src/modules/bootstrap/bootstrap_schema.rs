@@ -0,0 +1,15 @@
+use serde::Serialize;
+use crate::modules::calendar::calendar_schema::{AvailabilityResponse, CalendarSettingsResponse, EventTypeResponse};
+use crate::modules::user::user_schema::UserResponse;
+
+/// Everything the dashboard needs on load. Embedded objects use the same
+/// response types as their standalone endpoints.
+#[derive(Debug, Serialize)]
+pub struct BootstrapResponse {
+    pub user: UserResponse,
+    pub settings: Option<CalendarSettingsResponse>,  // null until the user creates settings
+    pub availabilities: Vec<AvailabilityResponse>,
+    pub event_types: Vec<EventTypeResponse>,
+    pub feature_flags: Vec<&'static str>,
+    pub unread_notifications: u64,
+}
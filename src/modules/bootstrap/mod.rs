@@ -0,0 +1,3 @@
+pub mod bootstrap_schema;
+pub mod bootstrap_controller;
+pub mod bootstrap_router;
@@ -1,37 +1,73 @@
+use std::collections::HashMap;
+
 use actix_web::{web, HttpResponse};
 use mongodb::Database;
 use validator::Validate;
 use serde_json::json;
+use chrono::{Duration, NaiveDate, Utc};
 use mongodb::bson::{oid::ObjectId, DateTime};
-use chrono::{NaiveTime, Duration};
 
+use crate::app::AppState;
+use crate::config::features::{self, Feature};
 use crate::errors::error::AppError;
+use crate::services::conferencing;
+use crate::utils::markdown;
+use crate::utils::object_id::PathObjectId;
+use crate::utils::template;
+use crate::utils::text;
+use crate::utils::time_of_day;
+use crate::utils::validation;
+use crate::utils::timezone::{self, TimezoneResolution};
 use crate::modules::user::user_schema::Claims;
-use crate::modules::calendar::calendar_crud::{CalendarSettingsRepository, AvailabilityRepository, EventTypeRepository};
-use crate::modules::calendar::calendar_model::{CalendarSettings, Availability, AvailabilityRule, EventType, BufferTime};
+use crate::modules::booking::booking_crud::BookingRepository;
+use crate::modules::notification::notification_crud::NotificationRepository;
+use crate::modules::notification::notification_model::NotificationKind;
+use crate::modules::calendar::calendar_crud::{CalendarSettingsRepository, AvailabilityRepository, EventTypeFilter, EventTypeRepository, EventTypeSort, HostInviteRepository, SortDirection};
+use crate::modules::user::user_crud::UserRepository;
+use crate::modules::calendar::calendar_engine;
+use crate::modules::calendar::calendar_slots::{BusyTime, HostSchedule, SlotSources};
+use crate::modules::calendar::calendar_model::{CalendarSettings, Availability, AvailabilityRule, CancellationPolicy, ConfirmationSettings, DateOverride, ReschedulePolicy, SchedulingWindow, TimeSlot, DayOverride, EmbedSettings, EventType, EventTypeTranslation, HostInviteStatus, Location, Question, QuestionKind, Reminder, WhoCalls, AVAILABILITY_RESTORE_DAYS, DEFAULT_SCHEDULE_NAME, EMAIL_TEMPLATE_KINDS, MAX_REMINDER_OFFSET_MINUTES};
 use crate::modules::calendar::calendar_schema::{
     CreateCalendarSettingsRequest, CalendarSettingsResponse,
     CreateAvailabilityRequest, AvailabilityResponse, CheckAvailabilityRequest, 
-    CheckAvailabilityResponse, AvailableTimeSlot,
-    CreateEventTypeRequest, EventTypeResponse, CheckTimeSlotRequest, CheckTimeSlotResponse,
-    UpdateAvailabilityRequest, UpdateEventTypeRequest
+    CheckAvailabilityResponse, AffectedBooking, WithAffectedBookings,
+    CreateEventTypeRequest, CreateHostInviteRequest, EventTypeResponse, HostInviteResponse, CheckTimeSlotRequest, CheckTimeSlotResponse, SlotConflict,
+    DateOverridePath, DeleteEventTypeQuery, ListAvailabilityQuery, ListEventTypesQuery, ReorderEventTypesRequest, SetDateOverrideRequest, UpdateAvailabilityRequest, UpdateEventTypeRequest
 };
 
+/// How far ahead availability changes are checked against existing bookings.
+const AFFECTED_BOOKINGS_HORIZON_DAYS: i64 = 90;
+
 pub struct CalendarController {
     settings_repository: CalendarSettingsRepository,
     availability_repository: AvailabilityRepository,
     event_type_repository: EventTypeRepository,
+    booking_repository: BookingRepository,
+    notification_repository: NotificationRepository,
+    host_invite_repository: HostInviteRepository,
+    user_repository: UserRepository,
+    slot_sources: SlotSources,
 }
 
 impl CalendarController {
     pub fn new(db: Database) -> Self {
         let settings_repository = CalendarSettingsRepository::new(db.clone());
         let availability_repository = AvailabilityRepository::new(db.clone());
-        let event_type_repository = EventTypeRepository::new(db);
+        let event_type_repository = EventTypeRepository::new(db.clone());
+        let booking_repository = BookingRepository::new(db.clone());
+        let notification_repository = NotificationRepository::new(db.clone());
+        let host_invite_repository = HostInviteRepository::new(db.clone());
+        let user_repository = UserRepository::new();
+        let slot_sources = SlotSources::new(db);
         Self { 
             settings_repository, 
             availability_repository,
-            event_type_repository 
+            event_type_repository,
+            booking_repository,
+            notification_repository,
+            host_invite_repository,
+            user_repository,
+            slot_sources
         }
     }
 
@@ -53,12 +89,17 @@ impl CalendarController {
             id: None,
             user_id,
             timezone: data.timezone.clone(),
-            working_hours: data.working_hours.clone(),
+            working_hours: Self::normalize_working_hours(&data.working_hours)?,
             buffer_time: data.buffer_time.clone(),
             default_meeting_duration: data.default_meeting_duration,
+            slot_interval: data.slot_interval,
+            max_meeting_minutes_per_day: data.max_meeting_minutes_per_day,
             calendar_name: data.calendar_name.clone(),
             date_format: data.date_format.clone(),
             time_format: data.time_format.clone(),
+            public_page_enabled: data.public_page_enabled.unwrap_or(true),
+            public_page_message: data.public_page_message.as_deref().map(markdown::sanitize_html),
+            diagnostics_until: None,
             created_at: DateTime::now(),
             updated_at: DateTime::now(),
         };
@@ -67,19 +108,7 @@ impl CalendarController {
         let created_settings = self.settings_repository.create(&user_id, settings).await?;
 
         // Convert to response
-        let response = CalendarSettingsResponse {
-            id: created_settings.id.unwrap().to_hex(),
-            user_id: created_settings.user_id.to_hex(),
-            timezone: created_settings.timezone,
-            working_hours: created_settings.working_hours,
-            buffer_time: created_settings.buffer_time,
-            default_meeting_duration: created_settings.default_meeting_duration,
-            calendar_name: created_settings.calendar_name,
-            date_format: created_settings.date_format,
-            time_format: created_settings.time_format,
-            created_at: created_settings.created_at.to_string(),
-            updated_at: created_settings.updated_at.to_string(),
-        };
+        let response = CalendarSettingsResponse::from(created_settings);
 
         Ok(HttpResponse::Created().json(response))
     }
@@ -106,33 +135,36 @@ impl CalendarController {
             id: existing_settings.id,
             user_id,
             timezone: data.timezone.clone(),
-            working_hours: data.working_hours.clone(),
+            working_hours: Self::normalize_working_hours(&data.working_hours)?,
             buffer_time: data.buffer_time.clone(),
             default_meeting_duration: data.default_meeting_duration,
+            slot_interval: data.slot_interval,
+            max_meeting_minutes_per_day: data.max_meeting_minutes_per_day,
             calendar_name: data.calendar_name.clone(),
             date_format: data.date_format.clone(),
             time_format: data.time_format.clone(),
+            public_page_enabled: data.public_page_enabled.unwrap_or(existing_settings.public_page_enabled),
+            public_page_message: match &data.public_page_message {
+                Some(message) => Some(markdown::sanitize_html(message)),
+                None => existing_settings.public_page_message,
+            },
+            diagnostics_until: existing_settings.diagnostics_until,
             created_at: existing_settings.created_at,
             updated_at: DateTime::now(),
         };
 
+        // Working hours may have shrunk around existing bookings
+        let affected_bookings = self.find_affected_bookings(&user_id, &settings, None).await?;
+
         // Update in database
         let updated_settings = self.settings_repository.update(&existing_settings.id.unwrap(), settings).await?
             .ok_or_else(|| AppError::NotFound("Failed to update calendar settings".to_string()))?;
+        self.notify_affected_bookings(&user_id, existing_settings.id.as_ref(), &affected_bookings).await;
 
         // Convert to response
-        let response = CalendarSettingsResponse {
-            id: updated_settings.id.unwrap().to_hex(),
-            user_id: updated_settings.user_id.to_hex(),
-            timezone: updated_settings.timezone,
-            working_hours: updated_settings.working_hours,
-            buffer_time: updated_settings.buffer_time,
-            default_meeting_duration: updated_settings.default_meeting_duration,
-            calendar_name: updated_settings.calendar_name,
-            date_format: updated_settings.date_format,
-            time_format: updated_settings.time_format,
-            created_at: updated_settings.created_at.to_string(),
-            updated_at: updated_settings.updated_at.to_string(),
+        let response = WithAffectedBookings {
+            inner: CalendarSettingsResponse::from(updated_settings),
+            affected_bookings,
         };
 
         Ok(HttpResponse::Ok().json(response))
@@ -192,16 +224,25 @@ impl CalendarController {
                 rule.is_recurring,
                 rule.recurrence_pattern.clone(),
                 rule.slots.clone(),
-            ).map_err(|e| AppError::ValidationError(e))?;
+                rule.priority.unwrap_or(0),
+            ).map_err(AppError::ValidationError)?;
             processed_rules.push(processed_rule);
         }
 
+        // A user's first schedule is the one used when no event type picks another
+        let is_default = self.availability_repository.find_default_by_user_id(&user_id).await?.is_none();
+
         // Create new availability
         let availability = Availability {
             id: None,
             user_id,
             calendar_settings_id,
+            name: data.name.clone().unwrap_or_else(|| DEFAULT_SCHEDULE_NAME.to_string()),
+            is_default,
             rules: processed_rules,
+            date_overrides: Self::normalize_date_overrides(data.date_overrides.as_deref().unwrap_or_default())?,
+            version: 0,
+            deleted_at: None,
             created_at: DateTime::now(),
             updated_at: DateTime::now(),
         };
@@ -210,14 +251,7 @@ impl CalendarController {
         let created = self.availability_repository.create(availability).await?;
 
         // Convert to response
-        let response = AvailabilityResponse {
-            id: created.id.unwrap().to_hex(),
-            user_id: created.user_id.to_hex(),
-            calendar_settings_id: created.calendar_settings_id.to_hex(),
-            rules: created.rules,
-            created_at: created.created_at.to_string(),
-            updated_at: created.updated_at.to_string(),
-        };
+        let response = AvailabilityResponse::from(created);
 
         Ok(HttpResponse::Created().json(response))
     }
@@ -239,100 +273,93 @@ impl CalendarController {
         let settings = self.settings_repository.find_by_user_id(&user_id).await?
             .ok_or_else(|| AppError::NotFound("Calendar settings not found".to_string()))?;
 
+        let (tz, tz_source) = timezone::resolve_timezone(
+            data.timezone.as_deref(),
+            None,
+            Some(&settings.timezone),
+        )?;
+
         // Parse dates
         let start_date = DateTime::parse_rfc3339_str(&data.start_date)
             .map_err(|_| AppError::BadRequest("Invalid start date format".to_string()))?;
         let end_date = DateTime::parse_rfc3339_str(&data.end_date)
             .map_err(|_| AppError::BadRequest("Invalid end date format".to_string()))?;
 
-        // Get user's availability
-        let availabilities = self.availability_repository
-            .find_available_slots(&user_id, start_date, end_date)
-            .await?;
-
-        // Process available slots
-        let mut available_slots = Vec::new();
-        for availability in availabilities {
-            for rule in availability.rules {
-                if let Some(mut slots) = self.process_availability_rule(
-                    rule, 
-                    &start_date, 
-                    &end_date, 
-                    data.duration,
-                    &settings.buffer_time
-                ) {
-                    available_slots.append(&mut slots);
+        // Slot length and buffer come from the event type when one is given, and may vary by weekday
+        let event_type = match &data.event_type_id {
+            Some(event_type_id) => {
+                let event_type_id = ObjectId::parse_str(event_type_id)
+                    .map_err(|_| AppError::BadRequest("Invalid event type ID".to_string()))?;
+                let event_type = self.event_type_repository.find_by_id(&event_type_id).await?
+                    .ok_or_else(|| AppError::NotFound("Event type not found".to_string()))?;
+                if event_type.user_id != user_id {
+                    return Err(AppError::Forbidden("Event type does not belong to user".to_string()));
                 }
+                Some(event_type)
             }
+            None => None,
+        };
+        if event_type.is_none() && data.duration.is_none() {
+            return Err(AppError::BadRequest("Either duration or event_type_id is required".to_string()));
         }
+        let schedule = self.schedule_for(&user_id, event_type.as_ref()).await?;
 
-        // Sort slots by date and start time
-        available_slots.sort_by(|a, b| {
-            a.date.cmp(&b.date).then(a.start_time.cmp(&b.start_time))
-        });
-
-        Ok(HttpResponse::Ok().json(CheckAvailabilityResponse {
-            available_slots,
-        }))
-    }
-
-    fn process_availability_rule(
-        &self,
-        rule: AvailabilityRule,
-        start_date: &DateTime,
-        end_date: &DateTime,
-        duration: i32,
-        buffer_time: &BufferTime,
-    ) -> Option<Vec<AvailableTimeSlot>> {
-        let mut available_slots = Vec::new();
-        let start_date = chrono::DateTime::from_timestamp_millis(start_date.timestamp_millis())
-            .map(|dt| dt.date_naive())
-            .unwrap_or_default();
-        let end_date = chrono::DateTime::from_timestamp_millis(end_date.timestamp_millis())
-            .map(|dt| dt.date_naive())
+        // The range covers whole days in the requester's timezone; slots are
+        // generated for the host's days it overlaps and stamped with the requester's times
+        let host_tz = calendar_engine::host_timezone(&settings);
+        let requested_day = |date: &DateTime| chrono::DateTime::from_timestamp_millis(date.timestamp_millis())
+            .map(|instant| instant.with_timezone(&tz).date_naive())
             .unwrap_or_default();
-        let mut current_date = start_date;
-
-        while current_date <= end_date {
-            let day_of_week = current_date.format("%A").to_string().to_lowercase();
-            
-            // Find matching slots for the current day
-            for slot in rule.slots.iter() {
-                if slot.day_of_week != day_of_week || !slot.is_available {
-                    continue;
-                }
-
-                // Parse slot times
-                let slot_start = NaiveTime::parse_from_str(&slot.start_time, "%H:%M")
-                    .unwrap_or_else(|_| NaiveTime::from_hms_opt(0, 0, 0).unwrap());
-                let slot_end = NaiveTime::parse_from_str(&slot.end_time, "%H:%M")
-                    .unwrap_or_else(|_| NaiveTime::from_hms_opt(23, 59, 59).unwrap());
-
-                // Calculate available time slots considering duration and buffer times
-                let mut current_time = slot_start;
-                let total_duration = duration + buffer_time.before + buffer_time.after;
-
-                while current_time + Duration::minutes(total_duration as i64) <= slot_end {
-                    // Add buffer before
-                    let actual_start = current_time + Duration::minutes(buffer_time.before as i64);
-                    let actual_end = actual_start + Duration::minutes(duration as i64);
-
-                    available_slots.push(AvailableTimeSlot {
-                        date: current_date.format("%Y-%m-%d").to_string(),
-                        start_time: actual_start.format("%H:%M").to_string(),
-                        end_time: actual_end.format("%H:%M").to_string(),
-                    });
-
-                    // Move to next slot including buffer after
-                    current_time = actual_end + Duration::minutes(buffer_time.after as i64);
-                }
+        let (window, (start_day, end_day)) = calendar_engine::viewer_window(
+            requested_day(&start_date),
+            requested_day(&end_date),
+            tz,
+            host_tz,
+        );
+        // Days outside the event type's scheduling window have no slots; today is the host's
+        let (start_day, end_day) = match &event_type {
+            Some(event_type) => {
+                let today = Utc::now().with_timezone(&host_tz).date_naive();
+                calendar_engine::clamp_to_scheduling_window(event_type, start_day, end_day, today)
             }
+            None => (start_day, end_day),
+        };
 
-            // Move to next day
-            current_date = current_date.succ_opt().unwrap_or(end_date);
+        // Confirmed bookings take their time out of the generated slots; group
+        // event slots stay open until every seat is taken
+        let owner = HostSchedule::new(user_id, settings.clone(), schedule);
+        let (mut available_slots, BusyTime { booked, seats_held }) = self.slot_sources
+            .team_slots(event_type.as_ref(), &owner, start_day, end_day, data.duration.unwrap_or_default())
+            .await?;
+        let group_event_type_id = event_type.as_ref()
+            .filter(|event_type| event_type.capacity() > 1)
+            .and_then(|event_type| event_type.id);
+
+        // Booking notice is measured from now in the host's timezone, whatever zone the caller asked for
+        if let Some(event_type) = &event_type {
+            let now = Utc::now().with_timezone(&host_tz).naive_local();
+            calendar_engine::retain_within_notice(&mut available_slots, event_type, &AppState::get().booking_horizon, now);
+        }
+        if let (Some(event_type), Some(group_event_type_id)) = (&event_type, &group_event_type_id) {
+            let mut seats_taken = self.booking_repository
+                .count_seats_taken(group_event_type_id, start_day, end_day)
+                .await?;
+            for (slot, held) in seats_held {
+                *seats_taken.entry(slot).or_default() += held;
+            }
+            calendar_engine::apply_capacity(&mut available_slots, event_type.capacity(), &seats_taken);
         }
 
-        Some(available_slots)
+        calendar_engine::localize_slots(&mut available_slots, host_tz, tz, window);
+
+        let recommended = data.recommend
+            .map(|count| calendar_engine::recommend_slots(&available_slots, &booked, count));
+
+        Ok(HttpResponse::Ok().json(CheckAvailabilityResponse {
+            available_slots,
+            recommended,
+            timezone_resolution: TimezoneResolution::new(tz, tz_source),
+        }))
     }
 
     pub async fn create_event_type(
@@ -348,21 +375,22 @@ impl CalendarController {
         let user_id = ObjectId::parse_str(&claims.sub)
             .map_err(|_| AppError::BadRequest("Invalid user ID".to_string()))?;
 
-        // Validate location type
-        let valid_location_types = vec!["in_person", "phone", "video"];
-        if !valid_location_types.contains(&data.location_type.as_str()) {
-            return Err(AppError::BadRequest("Invalid location type".to_string()));
-        }
-
-        // Validate meeting link for video type
-        if data.location_type == "video" && data.meeting_link.is_none() {
-            return Err(AppError::BadRequest("Meeting link is required for video events".to_string()));
-        }
+        Self::validate_questions(&data.questions)?;
+        Self::validate_locations(&data.locations, &data.questions)?;
 
         // Validate color format
-        if !data.color.starts_with('#') || data.color.len() != 7 {
-            return Err(AppError::BadRequest("Invalid color format. Use hex color code (e.g., #FF0000)".to_string()));
-        }
+        Self::validate_color("color", &data.color)?;
+
+        Self::validate_booking_notice(data.min_booking_notice, data.max_booking_notice)?;
+        Self::validate_cancellation_policy(data.cancellation_policy.as_ref())?;
+        Self::validate_reschedule_policy(data.reschedule_policy.as_ref())?;
+        Self::validate_scheduling_window(data.scheduling_window.as_ref())?;
+        Self::validate_embed_settings(data.embed_settings.as_ref())?;
+        Self::validate_confirmation_settings(data.confirmation_settings.as_ref())?;
+        Self::validate_day_overrides(data.day_overrides.as_ref())?;
+        Self::validate_translations(data.translations.as_ref())?;
+        Self::validate_email_templates(data.email_templates.as_ref())?;
+        Self::validate_reminders(data.reminders.as_deref())?;
 
         // Validate availability schedule exists and belongs to user
         let availability_id = ObjectId::parse_str(&data.availability_schedule_id)
@@ -375,50 +403,415 @@ impl CalendarController {
             return Err(AppError::Forbidden("Availability schedule does not belong to user".to_string()));
         }
 
+        let hosts = self.resolve_hosts(&user_id, &data.hosts).await?;
+        Self::validate_collective(&hosts, data.max_attendees)?;
+
+        let slug = self.event_type_repository.unique_slug(&user_id, &data.name).await?;
+        let position = self.event_type_repository.next_position(&user_id).await?;
+
         // Create new event type
         let event_type = EventType {
             id: None,
             user_id,
             name: data.name.clone(),
+            slug,
+            slug_history: Vec::new(),
             description: data.description.clone(),
             duration: data.duration,
             color: data.color.clone(),
-            location_type: data.location_type.clone(),
-            meeting_link: data.meeting_link.clone(),
+            locations: data.locations.clone(),
             questions: data.questions.clone(),
             availability_schedule_id: availability_id,
             buffer_time: data.buffer_time.clone(),
             min_booking_notice: data.min_booking_notice,
             max_booking_notice: data.max_booking_notice,
+            slot_interval: data.slot_interval,
+            cancellation_policy: data.cancellation_policy.clone(),
+            reschedule_policy: data.reschedule_policy.clone(),
+            scheduling_window: data.scheduling_window.clone(),
+            embed_settings: data.embed_settings.clone(),
+            confirmation_settings: data.confirmation_settings.clone(),
+            day_overrides: data.day_overrides.clone(),
+            translations: data.translations.clone(),
+            email_templates: data.email_templates.clone(),
+            requires_confirmation: data.requires_confirmation,
+            max_attendees: data.max_attendees.filter(|&max_attendees| max_attendees > 1),
+            reminders: data.reminders.as_deref().map(Self::normalize_reminders),
+            hosts,
+            host_assignment: data.host_assignment,
+            round_robin_strategy: data.round_robin_strategy,
+            is_secret: data.is_secret,
+            position,
             is_active: data.is_active,
+            archived_at: None,
             created_at: DateTime::now(),
             updated_at: DateTime::now(),
         };
 
         // Save to database
         let created = self.event_type_repository.create(event_type).await?;
+        self.event_type_repository.drop_old_slug(&user_id, &created.slug).await?;
 
-        // Convert to response
-        let response = EventTypeResponse {
-            id: created.id.unwrap().to_hex(),
-            user_id: created.user_id.to_hex(),
-            name: created.name,
-            description: created.description,
-            duration: created.duration,
-            color: created.color,
-            location_type: created.location_type,
-            meeting_link: created.meeting_link,
-            questions: created.questions,
-            availability_schedule_id: created.availability_schedule_id.to_hex(),
-            buffer_time: created.buffer_time,
-            min_booking_notice: created.min_booking_notice,
-            max_booking_notice: created.max_booking_notice,
-            is_active: created.is_active,
-            created_at: created.created_at.to_string(),
-            updated_at: created.updated_at.to_string(),
+        Ok(HttpResponse::Created().json(EventTypeResponse::from(created)))
+    }
+
+    /// Parses the hosts of an event type besides its owner and checks each can host
+    /// with the owner: invitation accepted, calendar set up, with availability,
+    /// in the same timezone.
+    async fn resolve_hosts(&self, owner_id: &ObjectId, hosts: &[String]) -> Result<Vec<ObjectId>, AppError> {
+        let mut host_ids: Vec<ObjectId> = Vec::with_capacity(hosts.len());
+        for host in hosts {
+            let host_id = ObjectId::parse_str(host)
+                .map_err(|_| AppError::BadRequest(format!("Invalid host ID: {}", host)))?;
+            if host_id == *owner_id {
+                return Err(AppError::ValidationError("The owner always hosts and must not be listed in hosts".to_string()));
+            }
+            if host_ids.contains(&host_id) {
+                return Err(AppError::ValidationError(format!("Duplicate host ID: {}", host)));
+            }
+            host_ids.push(host_id);
+        }
+        if host_ids.is_empty() {
+            return Ok(host_ids);
+        }
+
+        let owner_settings = self.settings_repository.find_by_user_id(owner_id).await?
+            .ok_or_else(|| AppError::NotFound("Calendar settings not found".to_string()))?;
+        self.slot_sources.check_new_hosts(&host_ids, &owner_settings).await?;
+
+        Ok(host_ids)
+    }
+
+    /// A booking of an event type with hosts ties up a host, so its slots have one seat.
+    fn validate_collective(hosts: &[ObjectId], max_attendees: Option<i32>) -> Result<(), AppError> {
+        if !hosts.is_empty() && max_attendees.is_some_and(|max_attendees| max_attendees > 1) {
+            return Err(AppError::ValidationError("Event types with hosts cannot have more than one attendee".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Working hours accept 12-hour input but are always stored as 24-hour HH:mm.
+    fn normalize_working_hours(working_hours: &HashMap<String, Vec<TimeSlot>>) -> Result<HashMap<String, Vec<TimeSlot>>, AppError> {
+        working_hours
+            .iter()
+            .map(|(day, slots)| {
+                let slots = slots
+                    .iter()
+                    .map(|slot| Ok(TimeSlot {
+                        start: time_of_day::normalize("working hours start", &slot.start)?,
+                        end: time_of_day::normalize("working hours end", &slot.end)?,
+                    }))
+                    .collect::<Result<Vec<_>, AppError>>()?;
+                Ok((day.clone(), slots))
+            })
+            .collect()
+    }
+
+    fn validate_booking_notice(min_notice: Option<i32>, max_notice: Option<i32>) -> Result<(), AppError> {
+        if min_notice.is_some_and(|minutes| minutes < 0) {
+            return Err(AppError::ValidationError("Minimum booking notice must not be negative".to_string()));
+        }
+        let hard_max_minutes = AppState::get().booking_horizon.hard_max_minutes();
+        if let Some(minutes) = max_notice
+            && !(0..=hard_max_minutes).contains(&(minutes as i64)) {
+            return Err(AppError::ValidationError(format!(
+                "Maximum booking notice must be between 0 and {} minutes", hard_max_minutes
+            )));
+        }
+        Ok(())
+    }
+
+    fn validate_reminders(reminders: Option<&[Reminder]>) -> Result<(), AppError> {
+        let Some(reminders) = reminders else {
+            return Ok(());
+        };
+        for (index, reminder) in reminders.iter().enumerate() {
+            if !(1..=MAX_REMINDER_OFFSET_MINUTES).contains(&reminder.offset_minutes) {
+                return Err(AppError::ValidationError(format!(
+                    "Reminder offset must be between 1 and {} minutes", MAX_REMINDER_OFFSET_MINUTES
+                )));
+            }
+            if reminders[..index].iter().any(|earlier| earlier.offset_minutes == reminder.offset_minutes) {
+                return Err(AppError::ValidationError(format!(
+                    "Duplicate reminder offset {}", reminder.offset_minutes
+                )));
+            }
+            if reminder.channels.is_empty() {
+                return Err(AppError::ValidationError(format!(
+                    "Reminder at {} minutes needs at least one channel", reminder.offset_minutes
+                )));
+            }
+            if reminder.channels.iter().enumerate().any(|(i, channel)| reminder.channels[..i].contains(channel)) {
+                return Err(AppError::ValidationError(format!(
+                    "Reminder at {} minutes lists a channel twice", reminder.offset_minutes
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Stores reminders longest offset first, the order they fire in.
+    fn normalize_reminders(reminders: &[Reminder]) -> Vec<Reminder> {
+        let mut reminders = reminders.to_vec();
+        reminders.sort_by_key(|reminder| std::cmp::Reverse(reminder.offset_minutes));
+        reminders
+    }
+
+    fn validate_cancellation_policy(policy: Option<&CancellationPolicy>) -> Result<(), AppError> {
+        if let Some(policy) = policy
+            && !(0..=525_600).contains(&policy.min_notice_minutes) {
+            return Err(AppError::ValidationError(
+                "Cancellation notice must be between 0 and 525600 minutes".to_string()
+            ));
+        }
+        Ok(())
+    }
+
+    fn validate_reschedule_policy(policy: Option<&ReschedulePolicy>) -> Result<(), AppError> {
+        if let Some(policy) = policy
+            && !(0..=525_600).contains(&policy.min_notice_minutes) {
+            return Err(AppError::ValidationError(
+                "Reschedule notice must be between 0 and 525600 minutes".to_string()
+            ));
+        }
+        Ok(())
+    }
+
+    fn validate_scheduling_window(window: Option<&SchedulingWindow>) -> Result<(), AppError> {
+        match window {
+            None => {}
+            Some(SchedulingWindow::Rolling { days }) => {
+                let hard_max_days = AppState::get().booking_horizon.hard_max_days;
+                if !(1..=hard_max_days).contains(&(*days as i64)) {
+                    return Err(AppError::ValidationError(format!(
+                        "Rolling scheduling window must be between 1 and {} days", hard_max_days
+                    )));
+                }
+            }
+            Some(SchedulingWindow::Range { start, end }) => {
+                let parse = |value: &str| NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                    .map_err(|_| AppError::ValidationError("Scheduling window dates must be YYYY-MM-DD".to_string()));
+                if parse(start)? > parse(end)? {
+                    return Err(AppError::ValidationError(
+                        "Scheduling window start must not be after its end".to_string()
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_color(field: &str, value: &str) -> Result<(), AppError> {
+        if !validation::is_hex_color(value) {
+            return Err(AppError::BadRequest(format!(
+                "Invalid {} format. Use hex color code (e.g., #FF0000)", field
+            )));
+        }
+        Ok(())
+    }
+
+    fn validate_day_overrides(overrides: Option<&HashMap<String, DayOverride>>) -> Result<(), AppError> {
+        const WEEKDAYS: [&str; 7] = ["monday", "tuesday", "wednesday", "thursday", "friday", "saturday", "sunday"];
+
+        for (day, day_override) in overrides.into_iter().flatten() {
+            if !WEEKDAYS.contains(&day.as_str()) {
+                return Err(AppError::ValidationError(format!("Invalid day in day_overrides: {}", day)));
+            }
+            if let Some(duration) = day_override.duration
+                && !(15..=480).contains(&duration) {
+                return Err(AppError::ValidationError(
+                    "Duration must be between 15 and 480 minutes".to_string()
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Locations must be distinct and complete for their kind. Video links
+    /// must be http(s) URLs whose placeholders name one of `questions`, and a
+    /// phone location where the host calls needs a phone question to call.
+    fn validate_locations(locations: &[Location], questions: &[Question]) -> Result<(), AppError> {
+        let too_long = |value: &str, max: usize| value.chars().count() > max;
+
+        for (index, location) in locations.iter().enumerate() {
+            if locations[..index].contains(location) {
+                return Err(AppError::BadRequest("Duplicate entry in locations".to_string()));
+            }
+            match location {
+                Location::InPerson { address } => {
+                    if address.trim().is_empty() || too_long(address, 500) {
+                        return Err(AppError::ValidationError("In person addresses must be between 1 and 500 characters".to_string()));
+                    }
+                }
+                Location::Phone { number, who_calls: WhoCalls::Invitee } => {
+                    if !number.as_deref().is_some_and(validation::is_phone_number) {
+                        return Err(AppError::ValidationError(
+                            "Phone locations the invitee calls need a valid phone number".to_string()
+                        ));
+                    }
+                }
+                Location::Phone { number, who_calls: WhoCalls::Host } => {
+                    if number.is_some() {
+                        return Err(AppError::BadRequest(
+                            "Phone locations the host calls take the number from the invitee, not a number".to_string()
+                        ));
+                    }
+                    if !questions.iter().any(|question| question.kind == QuestionKind::Phone) {
+                        return Err(AppError::ValidationError(
+                            "Phone locations the host calls need a phone question".to_string()
+                        ));
+                    }
+                }
+                Location::Video { provider, link } => {
+                    if provider.as_deref().is_some_and(|provider| too_long(provider, 50)) {
+                        return Err(AppError::ValidationError("Video providers must be at most 50 characters".to_string()));
+                    }
+                    // Providers that create a meeting per booking need no link; one given is the fallback
+                    let Some(link) = link.as_deref() else {
+                        if provider.as_deref().is_some_and(conferencing::is_known_provider) {
+                            continue;
+                        }
+                        return Err(AppError::BadRequest("Meeting link is required for video locations".to_string()));
+                    };
+                    if too_long(link, 2048) || !validation::is_http_url(link) {
+                        return Err(AppError::ValidationError(
+                            "Meeting links must be http or https URLs of at most 2048 characters".to_string()
+                        ));
+                    }
+                    template::validate_placeholders(link, questions.iter().map(|q| q.label.as_str()))
+                        .map_err(AppError::ValidationError)?;
+                }
+                Location::Custom { note } => {
+                    if note.trim().is_empty() || too_long(note, 500) {
+                        return Err(AppError::ValidationError("Custom location notes must be between 1 and 500 characters".to_string()));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Labels must be present and distinct; choice questions need at least
+    /// two distinct options and other kinds take none.
+    fn validate_questions(questions: &[Question]) -> Result<(), AppError> {
+        const MAX_QUESTIONS: usize = 20;
+        const MAX_OPTIONS: usize = 20;
+
+        if questions.len() > MAX_QUESTIONS {
+            return Err(AppError::ValidationError(format!("At most {} questions are allowed", MAX_QUESTIONS)));
+        }
+
+        for (index, question) in questions.iter().enumerate() {
+            let label = question.label.trim();
+            if label.is_empty() || label.chars().count() > 200 {
+                return Err(AppError::ValidationError("Question labels must be between 1 and 200 characters".to_string()));
+            }
+            if questions[..index].iter().any(|q| template::question_key(&q.label) == template::question_key(label)) {
+                return Err(AppError::ValidationError(format!("Duplicate question: {}", label)));
+            }
+
+            if question.kind.has_options() {
+                if question.options.len() < 2 || question.options.len() > MAX_OPTIONS {
+                    return Err(AppError::ValidationError(format!(
+                        "Question \"{}\" must have between 2 and {} options", label, MAX_OPTIONS
+                    )));
+                }
+                for (option_index, option) in question.options.iter().enumerate() {
+                    if option.trim().is_empty() {
+                        return Err(AppError::ValidationError(format!("Question \"{}\" has an empty option", label)));
+                    }
+                    if question.options[..option_index].contains(option) {
+                        return Err(AppError::ValidationError(format!("Question \"{}\" has duplicate options", label)));
+                    }
+                }
+            } else if !question.options.is_empty() {
+                return Err(AppError::ValidationError(format!(
+                    "Only radio and checkbox questions can have options (\"{}\")", label
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_translations(translations: Option<&HashMap<String, EventTypeTranslation>>) -> Result<(), AppError> {
+        const MAX_LOCALES: usize = 20;
+
+        let Some(translations) = translations else {
+            return Ok(());
         };
+        if translations.len() > MAX_LOCALES {
+            return Err(AppError::ValidationError(format!("At most {} translations are allowed", MAX_LOCALES)));
+        }
 
-        Ok(HttpResponse::Created().json(response))
+        for (locale, translation) in translations {
+            if !validation::is_locale_code(locale) {
+                return Err(AppError::ValidationError(format!("Invalid locale in translations: {}", locale)));
+            }
+            if translation.name.trim().is_empty() || translation.name.chars().count() > 100 {
+                return Err(AppError::ValidationError(format!(
+                    "Translated name for {} must be between 1 and 100 characters", locale
+                )));
+            }
+            if translation.description.as_ref().is_some_and(|d| d.chars().count() > 5000) {
+                return Err(AppError::ValidationError(format!(
+                    "Translated description for {} must be at most 5000 characters", locale
+                )));
+            }
+            if translation.confirmation_email_extra.as_ref().is_some_and(|e| e.chars().count() > 1000) {
+                return Err(AppError::ValidationError(format!(
+                    "Translated confirmation email text for {} must be at most 1000 characters", locale
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_email_templates(email_templates: Option<&HashMap<String, String>>) -> Result<(), AppError> {
+        let Some(email_templates) = email_templates else {
+            return Ok(());
+        };
+
+        for (kind, email_template) in email_templates {
+            if !EMAIL_TEMPLATE_KINDS.contains(&kind.as_str()) {
+                return Err(AppError::ValidationError(format!(
+                    "Invalid email template: {}. Use one of: {}", kind, EMAIL_TEMPLATE_KINDS.join(", ")
+                )));
+            }
+            if email_template.trim().is_empty() || email_template.chars().count() > 5000 {
+                return Err(AppError::ValidationError(format!(
+                    "The {} email template must be between 1 and 5000 characters", kind
+                )));
+            }
+            template::validate_known_placeholders(email_template, template::EMAIL_PLACEHOLDERS)
+                .map_err(|e| AppError::ValidationError(format!("The {} email template has {}", kind, e.to_lowercase())))?;
+        }
+        Ok(())
+    }
+
+    fn validate_embed_settings(settings: Option<&EmbedSettings>) -> Result<(), AppError> {
+        if let Some(settings) = settings {
+            Self::validate_color("background_color", &settings.background_color)?;
+            Self::validate_color("text_color", &settings.text_color)?;
+        }
+        Ok(())
+    }
+
+    fn validate_confirmation_settings(settings: Option<&ConfirmationSettings>) -> Result<(), AppError> {
+        let Some(settings) = settings else {
+            return Ok(());
+        };
+        if let Some(redirect_url) = &settings.redirect_url
+            && (redirect_url.chars().count() > 2048
+                || !redirect_url.starts_with("https://")
+                || !validation::is_http_url(redirect_url)) {
+            return Err(AppError::ValidationError(
+                "Redirect URL must be an https URL of at most 2048 characters".to_string()
+            ));
+        }
+        if settings.custom_message.as_ref().is_some_and(|message| message.chars().count() > 2000) {
+            return Err(AppError::ValidationError("Custom message must be at most 2000 characters".to_string()));
+        }
+        Ok(())
     }
 
     pub async fn get_settings(
@@ -432,19 +825,7 @@ impl CalendarController {
         let settings = self.settings_repository.find_by_user_id(&user_id).await?
             .ok_or_else(|| AppError::NotFound("Calendar settings not found".to_string()))?;
 
-        let response = CalendarSettingsResponse {
-            id: settings.id.unwrap().to_hex(),
-            user_id: settings.user_id.to_hex(),
-            timezone: settings.timezone,
-            working_hours: settings.working_hours,
-            buffer_time: settings.buffer_time,
-            default_meeting_duration: settings.default_meeting_duration,
-            calendar_name: settings.calendar_name,
-            date_format: settings.date_format,
-            time_format: settings.time_format,
-            created_at: settings.created_at.to_string(),
-            updated_at: settings.updated_at.to_string(),
-        };
+        let response = CalendarSettingsResponse::from(settings);
 
         Ok(HttpResponse::Ok().json(response))
     }
@@ -462,40 +843,63 @@ impl CalendarController {
         let settings = self.settings_repository.find_by_user_id(&user_id).await?
             .ok_or_else(|| AppError::NotFound("Calendar settings not found".to_string()))?;
 
-        // Get user's availability
-        let availability = self.availability_repository.find_by_user_id(&user_id).await?
-            .ok_or_else(|| AppError::NotFound("Availability not found".to_string()))?;
+        let event_type = match &data.event_type_id {
+            Some(event_type_id) => {
+                let event_type_id = ObjectId::parse_str(event_type_id)
+                    .map_err(|_| AppError::BadRequest("Invalid event type ID".to_string()))?;
+                let event_type = self.event_type_repository.find_by_id(&event_type_id).await?
+                    .ok_or_else(|| AppError::NotFound("Event type not found".to_string()))?;
+                if event_type.user_id != user_id {
+                    return Err(AppError::Forbidden("Event type does not belong to user".to_string()));
+                }
+                Some(event_type)
+            }
+            None => None,
+        };
+        let availability = self.schedule_for(&user_id, event_type.as_ref()).await?;
+
+        let date = NaiveDate::parse_from_str(&data.date, "%Y-%m-%d")
+            .map_err(|_| AppError::BadRequest("Invalid date format. Use YYYY-MM-DD".to_string()))?;
+        let start_time = time_of_day::normalize("start_time", &data.start_time)?;
+        let end_time = time_of_day::normalize("end_time", &data.end_time)?;
 
         // Check if the time slot is available
         let mut conflicts = Vec::new();
-        let is_available = self.is_slot_available(
-            &data.date,
-            &data.start_time,
-            &data.end_time,
-            &settings,
-            &availability,
-            &mut conflicts,
-        );
+        let buffer_time = event_type.as_ref()
+            .and_then(|event_type| calendar_engine::resolve_day_config(event_type, &calendar_engine::day_of_week(date)).buffer_time)
+            .unwrap_or_else(|| settings.buffer_time.clone());
+        let host = HostSchedule::new(user_id, settings, availability);
+        let within_schedule = host.is_available(&data.date, &start_time, &end_time, &mut conflicts);
+
+        // The host's own bookings and held slots take their time too
+        let slot = (calendar_engine::parse_start_time(&start_time), calendar_engine::parse_end_time(&end_time));
+        let seats_taken = self.slot_sources
+            .host_conflicts(&host, event_type.as_ref(), &data.date, slot, &buffer_time, (None, None), &[], &mut conflicts)
+            .await?;
+        if event_type.as_ref().is_some_and(|event_type| seats_taken >= event_type.capacity()) {
+            conflicts.push(SlotConflict::new("slot_full", "All places in this time slot are taken"));
+        }
 
         Ok(HttpResponse::Ok().json(CheckTimeSlotResponse {
-            is_available,
-            conflicts: if conflicts.is_empty() { None } else { Some(conflicts) },
+            is_available: within_schedule && conflicts.is_empty(),
+            messages: conflicts.iter().map(|c| c.message.clone()).collect(),
+            conflicts,
         }))
     }
 
     pub async fn update_availability(
         &self,
         claims: web::ReqData<Claims>,
-        id: web::Path<String>,
+        PathObjectId(availability_id): PathObjectId,
         data: web::Json<UpdateAvailabilityRequest>,
     ) -> Result<HttpResponse, AppError> {
+        data.validate()
+            .map_err(|e| AppError::ValidationError(e.to_string()))?;
+
         let claims = claims.into_inner();
         let user_id = ObjectId::parse_str(&claims.sub)
             .map_err(|_| AppError::BadRequest("Invalid user ID".to_string()))?;
 
-        let availability_id = ObjectId::parse_str(&*id)
-            .map_err(|_| AppError::BadRequest("Invalid availability ID".to_string()))?;
-
         // Check if availability exists and belongs to user
         let existing = self.availability_repository.find_by_id(&availability_id).await?
             .ok_or_else(|| AppError::NotFound("Availability not found".to_string()))?;
@@ -507,48 +911,167 @@ impl CalendarController {
         // Process rules
         let mut processed_rules = Vec::new();
         for rule in &data.rules {
-            let processed_rule = AvailabilityRule::new(
+            let mut processed_rule = AvailabilityRule::new(
                 &rule.start_date,
                 rule.end_date.as_deref(),
                 rule.is_recurring,
                 rule.recurrence_pattern.clone(),
                 rule.slots.clone(),
-            ).map_err(|e| AppError::ValidationError(e))?;
+                rule.priority.unwrap_or(0),
+            ).map_err(AppError::ValidationError)?;
+
+            // Keep the id of the rule being replaced so clients can keep referencing it
+            if let Some(rule_id) = &rule.rule_id {
+                if !existing.rules.iter().any(|r| &r.rule_id == rule_id) {
+                    return Err(AppError::BadRequest(format!("Unknown rule_id: {}", rule_id)));
+                }
+                if processed_rules.iter().any(|r: &AvailabilityRule| &r.rule_id == rule_id) {
+                    return Err(AppError::BadRequest(format!("Duplicate rule_id: {}", rule_id)));
+                }
+                processed_rule.rule_id = rule_id.clone();
+            }
             processed_rules.push(processed_rule);
         }
 
         // Update availability
         let mut updated = existing;
+        if let Some(name) = &data.name {
+            updated.name = name.clone();
+        }
         updated.rules = processed_rules;
+        if let Some(date_overrides) = &data.date_overrides {
+            updated.date_overrides = Self::normalize_date_overrides(date_overrides)?;
+        }
+
+        self.save_availability(&user_id, &availability_id, updated).await
+    }
+
+    pub async fn set_date_override(
+        &self,
+        claims: web::ReqData<Claims>,
+        PathObjectId(availability_id): PathObjectId,
+        path: web::Path<DateOverridePath>,
+        data: web::Json<SetDateOverrideRequest>,
+    ) -> Result<HttpResponse, AppError> {
+        let claims = claims.into_inner();
+        let user_id = ObjectId::parse_str(&claims.sub)
+            .map_err(|_| AppError::BadRequest("Invalid user ID".to_string()))?;
+
+        let existing = self.availability_repository.find_by_id(&availability_id).await?
+            .ok_or_else(|| AppError::NotFound("Availability not found".to_string()))?;
+
+        if existing.user_id != user_id {
+            return Err(AppError::Forbidden("Availability does not belong to user".to_string()));
+        }
+
+        let date_override = DateOverride { date: path.into_inner().date, slots: data.into_inner().slots };
+        let mut updated = existing;
+        updated.date_overrides.retain(|existing| existing.date != date_override.date);
+        updated.date_overrides.push(date_override);
+        updated.date_overrides = Self::normalize_date_overrides(&updated.date_overrides)?;
+
+        self.save_availability(&user_id, &availability_id, updated).await
+    }
+
+    pub async fn delete_date_override(
+        &self,
+        claims: web::ReqData<Claims>,
+        PathObjectId(availability_id): PathObjectId,
+        path: web::Path<DateOverridePath>,
+    ) -> Result<HttpResponse, AppError> {
+        let claims = claims.into_inner();
+        let user_id = ObjectId::parse_str(&claims.sub)
+            .map_err(|_| AppError::BadRequest("Invalid user ID".to_string()))?;
+
+        let existing = self.availability_repository.find_by_id(&availability_id).await?
+            .ok_or_else(|| AppError::NotFound("Availability not found".to_string()))?;
+
+        if existing.user_id != user_id {
+            return Err(AppError::Forbidden("Availability does not belong to user".to_string()));
+        }
+
+        let date = path.into_inner().date;
+        let mut updated = existing;
+        let before = updated.date_overrides.len();
+        updated.date_overrides.retain(|existing| existing.date != date);
+        if updated.date_overrides.len() == before {
+            return Err(AppError::NotFound(format!("No date override on {}", date)));
+        }
+
+        self.save_availability(&user_id, &availability_id, updated).await
+    }
+
+    /// Stores an edited schedule and reports the upcoming bookings it leaves
+    /// outside availability.
+    async fn save_availability(
+        &self,
+        user_id: &ObjectId,
+        availability_id: &ObjectId,
+        mut updated: Availability,
+    ) -> Result<HttpResponse, AppError> {
         updated.updated_at = DateTime::now();
 
-        let result = self.availability_repository.update(&availability_id, updated).await?
-            .ok_or_else(|| AppError::NotFound("Failed to update availability".to_string()))?;
+        let result = self.availability_repository.update(availability_id, updated).await?
+            .ok_or_else(|| AppError::Conflict("Availability was modified by another request, reload and try again".to_string()))?;
+
+        let affected_bookings = match self.settings_repository.find_by_user_id(user_id).await? {
+            Some(settings) => {
+                self.find_affected_bookings(user_id, &settings, Some(&result)).await?
+            }
+            None => Vec::new(),
+        };
+        self.notify_affected_bookings(user_id, Some(availability_id), &affected_bookings).await;
 
-        let response = AvailabilityResponse {
-            id: result.id.unwrap().to_hex(),
-            user_id: result.user_id.to_hex(),
-            calendar_settings_id: result.calendar_settings_id.to_hex(),
-            rules: result.rules,
-            created_at: result.created_at.to_string(),
-            updated_at: result.updated_at.to_string(),
+        let response = WithAffectedBookings {
+            inner: AvailabilityResponse::from(result),
+            affected_bookings,
         };
 
         Ok(HttpResponse::Ok().json(response))
     }
 
+    /// Date overrides are stored sorted by date with 24-hour HH:mm windows,
+    /// at most one per date.
+    fn normalize_date_overrides(date_overrides: &[DateOverride]) -> Result<Vec<DateOverride>, AppError> {
+        let mut normalized: Vec<DateOverride> = Vec::with_capacity(date_overrides.len());
+        for date_override in date_overrides {
+            let date = NaiveDate::parse_from_str(&date_override.date, "%Y-%m-%d")
+                .map_err(|_| AppError::ValidationError(format!(
+                    "Invalid date override date '{}': expected YYYY-MM-DD", date_override.date
+                )))?
+                .format("%Y-%m-%d")
+                .to_string();
+            if normalized.iter().any(|existing| existing.date == date) {
+                return Err(AppError::ValidationError(format!("Duplicate date override for {}", date)));
+            }
+
+            let mut slots = Vec::with_capacity(date_override.slots.len());
+            for slot in &date_override.slots {
+                let start = time_of_day::normalize("date override start", &slot.start)?;
+                let end = time_of_day::normalize("date override end", &slot.end)?;
+                if start >= end {
+                    return Err(AppError::ValidationError(format!(
+                        "Date override on {} has a window that does not end after it starts", date
+                    )));
+                }
+                slots.push(TimeSlot { start, end });
+            }
+            slots.sort_by(|a, b| a.start.cmp(&b.start));
+            normalized.push(DateOverride { date, slots });
+        }
+        normalized.sort_by(|a, b| a.date.cmp(&b.date));
+        Ok(normalized)
+    }
+
     pub async fn delete_availability(
         &self,
         claims: web::ReqData<Claims>,
-        id: web::Path<String>,
+        PathObjectId(availability_id): PathObjectId,
     ) -> Result<HttpResponse, AppError> {
         let claims = claims.into_inner();
         let user_id = ObjectId::parse_str(&claims.sub)
             .map_err(|_| AppError::BadRequest("Invalid user ID".to_string()))?;
 
-        let availability_id = ObjectId::parse_str(&*id)
-            .map_err(|_| AppError::BadRequest("Invalid availability ID".to_string()))?;
-
         // Check if availability exists and belongs to user
         let existing = self.availability_repository.find_by_id(&availability_id).await?
             .ok_or_else(|| AppError::NotFound("Availability not found".to_string()))?;
@@ -557,153 +1080,394 @@ impl CalendarController {
             return Err(AppError::Forbidden("Availability does not belong to user".to_string()));
         }
 
-        // Delete availability
-        self.availability_repository.delete(&availability_id).await?
+        // Event types booking against this schedule would silently lose all availability
+        let references = self.event_type_repository
+            .count_by_availability_schedule_id(&availability_id)
+            .await?;
+        if references > 0 {
+            return Err(AppError::Conflict(format!(
+                "Availability is used by {} event type(s); reassign them before deleting", references
+            )));
+        }
+
+        // Slot checks without an event type need a default to fall back on
+        if existing.is_default && self.availability_repository.find_all_by_user_id(&user_id, false).await?.len() > 1 {
+            return Err(AppError::Conflict(
+                "Availability is the default schedule; make another schedule the default before deleting it".to_string()
+            ));
+        }
+
+        // Soft delete so the schedule can be restored
+        self.availability_repository.soft_delete(&availability_id).await?
             .ok_or_else(|| AppError::NotFound("Failed to delete availability".to_string()))?;
 
         Ok(HttpResponse::Ok().json(json!({
-            "message": "Availability deleted successfully"
+            "message": format!(
+                "Availability deleted successfully. It can be restored within {} days", AVAILABILITY_RESTORE_DAYS
+            )
         })))
     }
 
-    fn is_slot_available(
+    pub async fn restore_availability(
         &self,
-        date: &str,
-        start_time: &str,
-        end_time: &str,
-        settings: &CalendarSettings,
-        availability: &Availability,
-        conflicts: &mut Vec<String>,
-    ) -> bool {
-        // Check if date is within working hours
-        let day_of_week = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
-            .ok()
-            .and_then(|d| Some(d.format("%A").to_string().to_lowercase()))
+        claims: web::ReqData<Claims>,
+        PathObjectId(availability_id): PathObjectId,
+    ) -> Result<HttpResponse, AppError> {
+        let claims = claims.into_inner();
+        let user_id = ObjectId::parse_str(&claims.sub)
+            .map_err(|_| AppError::BadRequest("Invalid user ID".to_string()))?;
+
+        let existing = self.availability_repository.find_by_id_including_deleted(&availability_id).await?
+            .ok_or_else(|| AppError::NotFound("Availability not found".to_string()))?;
+
+        if existing.user_id != user_id {
+            return Err(AppError::Forbidden("Availability does not belong to user".to_string()));
+        }
+
+        if existing.deleted_at.is_none() {
+            return Err(AppError::BadRequest("Availability is not deleted".to_string()));
+        }
+
+        let restored = self.availability_repository.restore(&availability_id).await?
+            .ok_or_else(|| AppError::NotFound("Availability can no longer be restored".to_string()))?;
+
+        Ok(HttpResponse::Ok().json(AvailabilityResponse::from(restored)))
+    }
+
+    pub async fn list_availability(
+        &self,
+        claims: web::ReqData<Claims>,
+        query: web::Query<ListAvailabilityQuery>,
+    ) -> Result<HttpResponse, AppError> {
+        let claims = claims.into_inner();
+        let user_id = ObjectId::parse_str(&claims.sub)
+            .map_err(|_| AppError::BadRequest("Invalid user ID".to_string()))?;
+
+        let availabilities = self.availability_repository
+            .find_all_by_user_id(&user_id, query.include_deleted)
+            .await?;
+
+        let mut references = self.event_types_by_schedule(&user_id).await?;
+        let response: Vec<AvailabilityResponse> = availabilities
+            .into_iter()
+            .map(|availability| {
+                let event_type_ids = availability.id
+                    .and_then(|id| references.remove(&id))
+                    .unwrap_or_default();
+                AvailabilityResponse::from(availability).with_event_types(event_type_ids)
+            })
+            .collect();
+
+        Ok(HttpResponse::Ok().json(response))
+    }
+
+    pub async fn get_availability(
+        &self,
+        claims: web::ReqData<Claims>,
+        PathObjectId(availability_id): PathObjectId,
+    ) -> Result<HttpResponse, AppError> {
+        let claims = claims.into_inner();
+        let user_id = ObjectId::parse_str(&claims.sub)
+            .map_err(|_| AppError::BadRequest("Invalid user ID".to_string()))?;
+
+        let existing = self.availability_repository.find_by_id(&availability_id).await?
+            .ok_or_else(|| AppError::NotFound("Availability not found".to_string()))?;
+
+        if existing.user_id != user_id {
+            return Err(AppError::Forbidden("Availability does not belong to user".to_string()));
+        }
+
+        let event_type_ids = self.event_types_by_schedule(&user_id).await?
+            .remove(&availability_id)
             .unwrap_or_default();
 
-        if let Some(working_hours) = settings.working_hours.get(&day_of_week) {
-            if working_hours.is_empty() {
-                conflicts.push("No working hours set for this day".to_string());
-                return false;
-            }
+        Ok(HttpResponse::Ok().json(AvailabilityResponse::from(existing).with_event_types(event_type_ids)))
+    }
 
-            // Check if time slot is within working hours
-            let slot_start = NaiveTime::parse_from_str(start_time, "%H:%M")
-                .unwrap_or_else(|_| NaiveTime::from_hms_opt(0, 0, 0).unwrap());
-            let slot_end = NaiveTime::parse_from_str(end_time, "%H:%M")
-                .unwrap_or_else(|_| NaiveTime::from_hms_opt(23, 59, 59).unwrap());
+    pub async fn set_default_availability(
+        &self,
+        claims: web::ReqData<Claims>,
+        PathObjectId(availability_id): PathObjectId,
+    ) -> Result<HttpResponse, AppError> {
+        let claims = claims.into_inner();
+        let user_id = ObjectId::parse_str(&claims.sub)
+            .map_err(|_| AppError::BadRequest("Invalid user ID".to_string()))?;
 
-            let is_within_working_hours = working_hours.iter().any(|wh| {
-                let wh_start = NaiveTime::parse_from_str(&wh.start, "%H:%M")
-                    .unwrap_or_else(|_| NaiveTime::from_hms_opt(0, 0, 0).unwrap());
-                let wh_end = NaiveTime::parse_from_str(&wh.end, "%H:%M")
-                    .unwrap_or_else(|_| NaiveTime::from_hms_opt(23, 59, 59).unwrap());
-                slot_start >= wh_start && slot_end <= wh_end
-            });
+        let existing = self.availability_repository.find_by_id(&availability_id).await?
+            .ok_or_else(|| AppError::NotFound("Availability not found".to_string()))?;
 
-            if !is_within_working_hours {
-                conflicts.push("Time slot is outside working hours".to_string());
-                return false;
+        if existing.user_id != user_id {
+            return Err(AppError::Forbidden("Availability does not belong to user".to_string()));
+        }
+
+        let updated = self.availability_repository.set_default(&user_id, &availability_id).await?
+            .ok_or_else(|| AppError::NotFound("Availability not found".to_string()))?;
+
+        Ok(HttpResponse::Ok().json(AvailabilityResponse::from(updated)))
+    }
+
+    /// The schedule slots are checked against: the event type's when given,
+    /// otherwise the user's default.
+    async fn schedule_for(&self, user_id: &ObjectId, event_type: Option<&EventType>) -> Result<Availability, AppError> {
+        let schedule = match event_type {
+            Some(event_type) => self.availability_repository.find_by_id(&event_type.availability_schedule_id).await?,
+            None => self.availability_repository.find_default_by_user_id(user_id).await?,
+        };
+        schedule.ok_or_else(|| AppError::NotFound("Availability not found".to_string()))
+    }
+
+    /// Ids of the user's event types, archived ones included, keyed by the
+    /// schedule they book against. These are what block deleting a schedule.
+    async fn event_types_by_schedule(&self, user_id: &ObjectId) -> Result<HashMap<ObjectId, Vec<String>>, AppError> {
+        let mut references: HashMap<ObjectId, Vec<String>> = HashMap::new();
+        for event_type in self.event_type_repository.find_by_user_id(user_id, true).await? {
+            if let Some(id) = event_type.id {
+                references.entry(event_type.availability_schedule_id).or_default().push(id.to_hex());
             }
-        } else {
-            conflicts.push("No working hours set for this day".to_string());
-            return false;
         }
+        Ok(references)
+    }
 
-        // Check if time slot is within availability rules
-        let is_within_availability = availability.rules.iter().any(|rule| {
-            self.is_slot_available_in_rule(rule, date, start_time, end_time)
-        });
+    /// Upcoming confirmed bookings of the host that would fail the slot check
+    /// under `settings`, with `changed_schedule` standing in for its stored rules.
+    /// Leaves the host a dashboard notice when an edit stranded bookings.
+    async fn notify_affected_bookings(
+        &self,
+        user_id: &ObjectId,
+        related_id: Option<&ObjectId>,
+        affected_bookings: &[AffectedBooking],
+    ) {
+        if affected_bookings.is_empty() {
+            return;
+        }
+
+        self.notification_repository.notify(
+            user_id,
+            NotificationKind::AffectedBookings,
+            "Bookings outside your availability",
+            &format!(
+                "{} upcoming booking(s) now fall outside your availability",
+                affected_bookings.len()
+            ),
+            related_id,
+        ).await;
+    }
 
-        if !is_within_availability {
-            conflicts.push("Time slot is not available in your schedule".to_string());
-            return false;
+    async fn find_affected_bookings(
+        &self,
+        user_id: &ObjectId,
+        settings: &CalendarSettings,
+        changed_schedule: Option<&Availability>,
+    ) -> Result<Vec<AffectedBooking>, AppError> {
+        let (tz, _) = timezone::resolve_timezone(None, None, Some(&settings.timezone))?;
+        let today = Utc::now().with_timezone(&tz).date_naive();
+        let horizon = today + Duration::days(AFFECTED_BOOKINGS_HORIZON_DAYS);
+
+        let bookings = self.booking_repository
+            .find_holding_by_host_in_range(
+                user_id,
+                &today.format("%Y-%m-%d").to_string(),
+                &horizon.format("%Y-%m-%d").to_string(),
+            )
+            .await?;
+        if bookings.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let schedule_ids: HashMap<ObjectId, ObjectId> = self.event_type_repository
+            .find_by_user_id(user_id, true)
+            .await?
+            .into_iter()
+            .filter_map(|event_type| Some((event_type.id?, event_type.availability_schedule_id)))
+            .collect();
+
+        let mut schedules: HashMap<ObjectId, (Vec<AvailabilityRule>, Vec<DateOverride>)> = HashMap::new();
+        if let Some(schedule) = changed_schedule
+            && let Some(schedule_id) = schedule.id
+        {
+            schedules.insert(schedule_id, (schedule.rules.clone(), schedule.date_overrides.clone()));
         }
 
-        true
+        let mut affected = Vec::new();
+        for booking in bookings {
+            // Bookings whose event type or schedule is gone have nothing to check against
+            let Some(schedule_id) = schedule_ids.get(&booking.event_type_id) else {
+                continue;
+            };
+            if !schedules.contains_key(schedule_id) {
+                let schedule = self.availability_repository.find_by_id(schedule_id).await?
+                    .map(|availability| (availability.rules, availability.date_overrides))
+                    .unwrap_or_default();
+                schedules.insert(*schedule_id, schedule);
+            }
+
+            let (rules, date_overrides) = &schedules[schedule_id];
+            let mut conflicts = Vec::new();
+            if !calendar_engine::is_slot_available(
+                &booking.date,
+                &booking.start_time,
+                &booking.end_time,
+                settings,
+                rules,
+                date_overrides,
+                &mut conflicts,
+            ) {
+                affected.push(AffectedBooking {
+                    booking_id: booking.id.map(|id| id.to_hex()).unwrap_or_default(),
+                    date: booking.date,
+                    start_time: booking.start_time,
+                    invitee_name: booking.invitee_name,
+                });
+            }
+        }
+
+        Ok(affected)
     }
 
-    fn is_slot_available_in_rule(
+    pub async fn list_event_types(
         &self,
-        rule: &AvailabilityRule,
-        date: &str,
-        start_time: &str,
-        end_time: &str,
-    ) -> bool {
-        // Check if date is within rule's date range
-        let slot_date = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
-            .ok()
-            .and_then(|d| Some(d.and_hms_opt(0, 0, 0).unwrap()))
-            .unwrap_or_default();
+        claims: web::ReqData<Claims>,
+        query: web::Query<ListEventTypesQuery>,
+    ) -> Result<HttpResponse, AppError> {
+        query.validate()
+            .map_err(|e| AppError::ValidationError(e.to_string()))?;
 
-        let rule_start = chrono::DateTime::from_timestamp_millis(rule.start_date.timestamp_millis())
-            .map(|dt| dt.date_naive().and_hms_opt(0, 0, 0).unwrap())
-            .unwrap_or_default();
-        let rule_end = rule.end_date
-            .map(|d| chrono::DateTime::from_timestamp_millis(d.timestamp_millis())
-                .map(|dt| dt.date_naive().and_hms_opt(0, 0, 0).unwrap())
-                .unwrap_or_default())
-            .unwrap_or_else(|| chrono::NaiveDateTime::MAX);
-
-        if slot_date < rule_start || slot_date > rule_end {
-            return false;
-        }
-
-        // Check if time slot matches any availability slot
-        let day_of_week = slot_date.format("%A").to_string().to_lowercase();
-        let slot_start = NaiveTime::parse_from_str(start_time, "%H:%M")
-            .unwrap_or_else(|_| NaiveTime::from_hms_opt(0, 0, 0).unwrap());
-        let slot_end = NaiveTime::parse_from_str(end_time, "%H:%M")
-            .unwrap_or_else(|_| NaiveTime::from_hms_opt(23, 59, 59).unwrap());
-
-        rule.slots.iter().any(|slot| {
-            slot.day_of_week == day_of_week &&
-            slot.is_available &&
-            NaiveTime::parse_from_str(&slot.start_time, "%H:%M")
-                .map(|s| s <= slot_start)
-                .unwrap_or(false) &&
-            NaiveTime::parse_from_str(&slot.end_time, "%H:%M")
-                .map(|e| e >= slot_end)
-                .unwrap_or(false)
+        let claims = claims.into_inner();
+        let user_id = ObjectId::parse_str(&claims.sub)
+            .map_err(|_| AppError::BadRequest("Invalid user ID".to_string()))?;
+
+        let filter = Self::event_type_filter(query.into_inner())?;
+        let event_types = self.event_type_repository.find_matching(&user_id, &filter).await?;
+
+        let response: Vec<EventTypeResponse> = event_types.into_iter().map(EventTypeResponse::from).collect();
+
+        Ok(HttpResponse::Ok().json(response))
+    }
+
+    fn event_type_filter(query: ListEventTypesQuery) -> Result<EventTypeFilter, AppError> {
+        const LOCATION_KINDS: [&str; 4] = ["in_person", "phone", "video", "custom"];
+
+        if let Some(kind) = &query.location_type
+            && !LOCATION_KINDS.contains(&kind.as_str()) {
+            return Err(AppError::ValidationError(format!(
+                "Unknown location_type '{}', expected one of: {}", kind, LOCATION_KINDS.join(", ")
+            )));
+        }
+        if let (Some(min), Some(max)) = (query.duration_min, query.duration_max)
+            && min > max {
+            return Err(AppError::ValidationError("duration_min must not be greater than duration_max".to_string()));
+        }
+
+        let direction = match query.direction.as_deref() {
+            None => SortDirection::Ascending,
+            Some(value) => SortDirection::parse(value).ok_or_else(|| AppError::ValidationError(format!(
+                "Unknown direction '{}', expected asc or desc", value
+            )))?,
+        };
+        // A direction on its own applies to the user's order
+        let sort = match (query.sort.as_deref(), &query.direction) {
+            (None, None) => None,
+            (None, Some(_)) => Some((EventTypeSort::Position, direction)),
+            (Some(value), _) => Some((
+                EventTypeSort::parse(value).ok_or_else(|| AppError::ValidationError(format!(
+                    "Unknown sort field '{}', expected name, created_at or position", value
+                )))?,
+                direction,
+            )),
+        };
+
+        Ok(EventTypeFilter {
+            include_archived: query.include_archived,
+            search: query.q,
+            is_active: query.is_active,
+            location_kind: query.location_type,
+            duration_min: query.duration_min,
+            duration_max: query.duration_max,
+            sort,
         })
     }
 
-    pub async fn list_event_types(
+    /// Puts the user's event types in the given order. Event types left out of
+    /// the request keep their relative order after the listed ones.
+    pub async fn reorder_event_types(
         &self,
         claims: web::ReqData<Claims>,
+        data: web::Json<ReorderEventTypesRequest>,
     ) -> Result<HttpResponse, AppError> {
+        data.validate()
+            .map_err(|e| AppError::ValidationError(e.to_string()))?;
+
         let claims = claims.into_inner();
         let user_id = ObjectId::parse_str(&claims.sub)
             .map_err(|_| AppError::BadRequest("Invalid user ID".to_string()))?;
 
-        let event_types = self.event_type_repository.find_by_user_id(&user_id).await?;
-
-        let response: Vec<EventTypeResponse> = event_types.into_iter().map(|et| EventTypeResponse {
-            id: et.id.unwrap().to_hex(),
-            user_id: et.user_id.to_hex(),
-            name: et.name,
-            description: et.description,
-            duration: et.duration,
-            color: et.color,
-            location_type: et.location_type,
-            meeting_link: et.meeting_link,
-            questions: et.questions,
-            availability_schedule_id: et.availability_schedule_id.to_hex(),
-            buffer_time: et.buffer_time,
-            min_booking_notice: et.min_booking_notice,
-            max_booking_notice: et.max_booking_notice,
-            is_active: et.is_active,
-            created_at: et.created_at.to_string(),
-            updated_at: et.updated_at.to_string(),
-        }).collect();
+        let mut listed = Vec::with_capacity(data.event_type_ids.len());
+        for id in &data.event_type_ids {
+            let id = ObjectId::parse_str(id)
+                .map_err(|_| AppError::BadRequest(format!("Invalid event type ID: {}", id)))?;
+            if listed.contains(&id) {
+                return Err(AppError::ValidationError(format!("Duplicate event type ID: {}", id.to_hex())));
+            }
+            listed.push(id);
+        }
+
+        // Every ID is checked before anything is written; archived event types keep their position
+        let event_types = self.event_type_repository.find_by_user_id(&user_id, false).await?;
+        let owned: Vec<ObjectId> = event_types.iter().filter_map(|event_type| event_type.id).collect();
+        if let Some(foreign) = listed.iter().find(|id| !owned.contains(id)) {
+            return Err(AppError::Forbidden(format!("Event type {} does not belong to user", foreign.to_hex())));
+        }
+
+        let unlisted: Vec<ObjectId> = owned.into_iter().filter(|id| !listed.contains(id)).collect();
+        let ordered = [listed, unlisted].concat();
+        self.event_type_repository.set_positions(&user_id, &ordered).await?;
+
+        let event_types = self.event_type_repository.find_by_user_id(&user_id, false).await?;
+        let response: Vec<EventTypeResponse> = event_types.into_iter().map(EventTypeResponse::from).collect();
 
         Ok(HttpResponse::Ok().json(response))
     }
 
+    pub async fn get_event_type(
+        &self,
+        claims: web::ReqData<Claims>,
+        PathObjectId(event_type_id): PathObjectId,
+    ) -> Result<HttpResponse, AppError> {
+        let claims = claims.into_inner();
+        let user_id = ObjectId::parse_str(&claims.sub)
+            .map_err(|_| AppError::BadRequest("Invalid user ID".to_string()))?;
+
+        // Check if event type exists and belongs to user
+        let event_type = self.event_type_repository.find_by_id(&event_type_id).await?
+            .ok_or_else(|| AppError::NotFound("Event type not found".to_string()))?;
+
+        if event_type.user_id != user_id {
+            return Err(AppError::Forbidden("Event type does not belong to user".to_string()));
+        }
+
+        Ok(HttpResponse::Ok().json(EventTypeResponse::from(event_type)))
+    }
+
+    pub async fn get_event_type_by_slug(
+        &self,
+        claims: web::ReqData<Claims>,
+        slug: web::Path<String>,
+    ) -> Result<HttpResponse, AppError> {
+        let claims = claims.into_inner();
+        let user_id = ObjectId::parse_str(&claims.sub)
+            .map_err(|_| AppError::BadRequest("Invalid user ID".to_string()))?;
+
+        // Slugs are only unique per user, so another user's event type is simply not found
+        let event_type = self.event_type_repository.find_by_user_and_slug(&user_id, &slug).await?
+            .ok_or_else(|| AppError::NotFound("Event type not found".to_string()))?;
+
+        Ok(HttpResponse::Ok().json(EventTypeResponse::from(event_type)))
+    }
+
     pub async fn update_event_type(
         &self,
         claims: web::ReqData<Claims>,
-        id: web::Path<String>,
+        PathObjectId(event_type_id): PathObjectId,
         data: web::Json<UpdateEventTypeRequest>,
     ) -> Result<HttpResponse, AppError> {
         // Validate request data
@@ -714,9 +1478,6 @@ impl CalendarController {
         let user_id = ObjectId::parse_str(&claims.sub)
             .map_err(|_| AppError::BadRequest("Invalid user ID".to_string()))?;
 
-        let event_type_id = ObjectId::parse_str(&*id)
-            .map_err(|_| AppError::BadRequest("Invalid event type ID".to_string()))?;
-
         // Check if event type exists and belongs to user
         let existing = self.event_type_repository.find_by_id(&event_type_id).await?
             .ok_or_else(|| AppError::NotFound("Event type not found".to_string()))?;
@@ -725,78 +1486,105 @@ impl CalendarController {
             return Err(AppError::Forbidden("Event type does not belong to user".to_string()));
         }
 
-        // Validate location type if provided
-        if let Some(location_type) = &data.location_type {
-            let valid_location_types = vec!["in_person", "phone", "video"];
-            if !valid_location_types.contains(&location_type.as_str()) {
-                return Err(AppError::BadRequest("Invalid location type".to_string()));
-            }
+        // Validate color format if provided
+        if let Some(color) = &data.color {
+            Self::validate_color("color", color)?;
+        }
 
-            // Validate meeting link for video type
-            if location_type == "video" && data.meeting_link.is_none() {
-                return Err(AppError::BadRequest("Meeting link is required for video events".to_string()));
+        if let Some(slug) = &data.slug
+            && *slug != existing.slug {
+            if !text::is_slug(slug) {
+                return Err(AppError::ValidationError(format!(
+                    "Slug must be 1 to {} lowercase letters, digits or single hyphens", text::SLUG_MAX_CHARS
+                )));
+            }
+            if self.event_type_repository.find_by_user_and_slug(&user_id, slug).await?.is_some() {
+                return Err(AppError::ValidationError("Slug is already used by another of your event types".to_string()));
             }
         }
 
-        // Validate color format if provided
-        if let Some(color) = &data.color {
-            if !color.starts_with('#') || color.len() != 7 {
-                return Err(AppError::BadRequest("Invalid color format. Use hex color code (e.g., #FF0000)".to_string()));
-            }
+        Self::validate_booking_notice(data.min_booking_notice, data.max_booking_notice)?;
+        Self::validate_cancellation_policy(data.cancellation_policy.as_ref().and_then(Option::as_ref))?;
+        Self::validate_reschedule_policy(data.reschedule_policy.as_ref().and_then(Option::as_ref))?;
+        Self::validate_scheduling_window(data.scheduling_window.as_ref())?;
+        Self::validate_embed_settings(data.embed_settings.as_ref())?;
+        Self::validate_confirmation_settings(data.confirmation_settings.as_ref())?;
+        Self::validate_day_overrides(data.day_overrides.as_ref())?;
+        Self::validate_translations(data.translations.as_ref())?;
+        Self::validate_email_templates(data.email_templates.as_ref())?;
+        Self::validate_reminders(data.reminders.as_deref())?;
+        if let Some(questions) = &data.questions {
+            Self::validate_questions(questions)?;
+        }
+
+        // Locations are checked against the questions as they will be after the update
+        if data.locations.is_some() || data.questions.is_some() {
+            Self::validate_locations(
+                data.locations.as_ref().unwrap_or(&existing.locations),
+                data.questions.as_ref().unwrap_or(&existing.questions),
+            )?;
         }
 
+        let hosts = match &data.hosts {
+            Some(hosts) => Some(self.resolve_hosts(&user_id, hosts).await?),
+            None => None,
+        };
+        Self::validate_collective(
+            hosts.as_ref().unwrap_or(&existing.hosts),
+            data.max_attendees.or(existing.max_attendees),
+        )?;
+
         // Update event type
         let mut updated = existing;
         if let Some(name) = &data.name { updated.name = name.clone(); }
+        if let Some(slug) = &data.slug { updated.change_slug(slug.clone()); }
         if let Some(description) = &data.description { updated.description = Some(description.clone()); }
         if let Some(duration) = data.duration { updated.duration = duration; }
         if let Some(color) = &data.color { updated.color = color.clone(); }
-        if let Some(location_type) = &data.location_type { updated.location_type = location_type.clone(); }
-        if let Some(meeting_link) = &data.meeting_link { updated.meeting_link = Some(meeting_link.clone()); }
+        if let Some(locations) = &data.locations { updated.locations = locations.clone(); }
         if let Some(questions) = &data.questions { updated.questions = questions.clone(); }
         if let Some(buffer_time) = &data.buffer_time { updated.buffer_time = Some(buffer_time.clone()); }
         if let Some(min_booking_notice) = data.min_booking_notice { updated.min_booking_notice = Some(min_booking_notice); }
         if let Some(max_booking_notice) = data.max_booking_notice { updated.max_booking_notice = Some(max_booking_notice); }
+        if let Some(slot_interval) = data.slot_interval { updated.slot_interval = Some(slot_interval); }
+        if let Some(cancellation_policy) = &data.cancellation_policy { updated.cancellation_policy = cancellation_policy.clone(); }
+        if let Some(reschedule_policy) = &data.reschedule_policy { updated.reschedule_policy = reschedule_policy.clone(); }
+        if let Some(scheduling_window) = &data.scheduling_window { updated.scheduling_window = Some(scheduling_window.clone()); }
+        if let Some(embed_settings) = &data.embed_settings { updated.embed_settings = Some(embed_settings.clone()); }
+        if let Some(confirmation_settings) = &data.confirmation_settings { updated.confirmation_settings = Some(confirmation_settings.clone()); }
+        if let Some(day_overrides) = &data.day_overrides { updated.day_overrides = Some(day_overrides.clone()); }
+        if let Some(translations) = &data.translations { updated.translations = Some(translations.clone()); }
+        if let Some(email_templates) = &data.email_templates { updated.email_templates = Some(email_templates.clone()); }
+        if let Some(requires_confirmation) = data.requires_confirmation { updated.requires_confirmation = requires_confirmation; }
+        if let Some(max_attendees) = data.max_attendees { updated.max_attendees = Some(max_attendees).filter(|&n| n > 1); }
+        if let Some(reminders) = &data.reminders { updated.reminders = Some(Self::normalize_reminders(reminders)); }
+        if let Some(hosts) = hosts { updated.hosts = hosts; }
+        if let Some(host_assignment) = data.host_assignment { updated.host_assignment = host_assignment; }
+        if let Some(round_robin_strategy) = data.round_robin_strategy { updated.round_robin_strategy = round_robin_strategy; }
+        if let Some(is_secret) = data.is_secret { updated.is_secret = is_secret; }
         if let Some(is_active) = data.is_active { updated.is_active = is_active; }
         updated.updated_at = DateTime::now();
 
+        let slug = updated.slug.clone();
         let result = self.event_type_repository.update(&event_type_id, updated).await?
             .ok_or_else(|| AppError::NotFound("Failed to update event type".to_string()))?;
+        self.event_type_repository.drop_old_slug(&user_id, &slug).await?;
 
-        let response = EventTypeResponse {
-            id: result.id.unwrap().to_hex(),
-            user_id: result.user_id.to_hex(),
-            name: result.name,
-            description: result.description,
-            duration: result.duration,
-            color: result.color,
-            location_type: result.location_type,
-            meeting_link: result.meeting_link,
-            questions: result.questions,
-            availability_schedule_id: result.availability_schedule_id.to_hex(),
-            buffer_time: result.buffer_time,
-            min_booking_notice: result.min_booking_notice,
-            max_booking_notice: result.max_booking_notice,
-            is_active: result.is_active,
-            created_at: result.created_at.to_string(),
-            updated_at: result.updated_at.to_string(),
-        };
-
-        Ok(HttpResponse::Ok().json(response))
+        Ok(HttpResponse::Ok().json(EventTypeResponse::from(result)))
     }
 
+    /// Archives the event type, or removes it for good with `permanent=true`.
+    /// Archiving keeps past bookings able to show what was booked.
     pub async fn delete_event_type(
         &self,
         claims: web::ReqData<Claims>,
-        id: web::Path<String>,
+        PathObjectId(event_type_id): PathObjectId,
+        query: web::Query<DeleteEventTypeQuery>,
     ) -> Result<HttpResponse, AppError> {
         let claims = claims.into_inner();
         let user_id = ObjectId::parse_str(&claims.sub)
             .map_err(|_| AppError::BadRequest("Invalid user ID".to_string()))?;
 
-        let event_type_id = ObjectId::parse_str(&*id)
-            .map_err(|_| AppError::BadRequest("Invalid event type ID".to_string()))?;
-
         // Check if event type exists and belongs to user
         let existing = self.event_type_repository.find_by_id(&event_type_id).await?
             .ok_or_else(|| AppError::NotFound("Event type not found".to_string()))?;
@@ -805,12 +1593,393 @@ impl CalendarController {
             return Err(AppError::Forbidden("Event type does not belong to user".to_string()));
         }
 
-        // Delete event type
-        self.event_type_repository.delete(&event_type_id).await?
-            .ok_or_else(|| AppError::NotFound("Failed to delete event type".to_string()))?;
+        if query.permanent {
+            self.event_type_repository.delete(&event_type_id).await?
+                .ok_or_else(|| AppError::NotFound("Failed to delete event type".to_string()))?;
+
+            return Ok(HttpResponse::Ok().json(json!({
+                "message": "Event type deleted permanently"
+            })));
+        }
+
+        if existing.archived_at.is_some() {
+            return Err(AppError::BadRequest("Event type is already archived".to_string()));
+        }
+        self.event_type_repository.archive(&event_type_id).await?
+            .ok_or_else(|| AppError::NotFound("Failed to archive event type".to_string()))?;
 
         Ok(HttpResponse::Ok().json(json!({
-            "message": "Event type deleted successfully"
+            "message": "Event type archived successfully. It can be restored at any time"
         })))
     }
-}
\ No newline at end of file
+
+    pub async fn restore_event_type(
+        &self,
+        claims: web::ReqData<Claims>,
+        PathObjectId(event_type_id): PathObjectId,
+    ) -> Result<HttpResponse, AppError> {
+        let claims = claims.into_inner();
+        let user_id = ObjectId::parse_str(&claims.sub)
+            .map_err(|_| AppError::BadRequest("Invalid user ID".to_string()))?;
+
+        let existing = self.event_type_repository.find_by_id(&event_type_id).await?
+            .ok_or_else(|| AppError::NotFound("Event type not found".to_string()))?;
+
+        if existing.user_id != user_id {
+            return Err(AppError::Forbidden("Event type does not belong to user".to_string()));
+        }
+
+        if existing.archived_at.is_none() {
+            return Err(AppError::BadRequest("Event type is not archived".to_string()));
+        }
+
+        let restored = self.event_type_repository.restore(&event_type_id).await?
+            .ok_or_else(|| AppError::NotFound("Event type not found".to_string()))?;
+
+        Ok(HttpResponse::Ok().json(EventTypeResponse::from(restored)))
+    }
+
+    /// Asks another user to co-host the caller's event types. The answer is
+    /// the same whether or not the email belongs to a user. Host invitations
+    /// are part of teams, so they 404 until that feature is enabled.
+    pub async fn create_host_invite(
+        &self,
+        claims: web::ReqData<Claims>,
+        data: web::Json<CreateHostInviteRequest>,
+    ) -> Result<HttpResponse, AppError> {
+        features::require_feature(Feature::Teams)?;
+        data.validate()
+            .map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+        let claims = claims.into_inner();
+        let user_id = ObjectId::parse_str(&claims.sub)
+            .map_err(|_| AppError::BadRequest("Invalid user ID".to_string()))?;
+
+        let host = self.user_repository.find_by_email(data.email.trim()).await?;
+        if let Some(host) = host
+            && let Some(host_id) = host.id.filter(|host_id| *host_id != user_id)
+            && let Some(invite) = self.host_invite_repository.invite(&user_id, &host_id).await?
+            && invite.status == HostInviteStatus::Pending
+        {
+            let owner_name = self.user_repository.find_by_id(&claims.sub).await?
+                .map(|owner| owner.name)
+                .unwrap_or_default();
+            self.notification_repository.notify(
+                &host_id,
+                NotificationKind::HostInvite,
+                "Host invitation",
+                &format!("{} invited you to host their event types", owner_name),
+                invite.id.as_ref(),
+            ).await;
+        }
+
+        Ok(HttpResponse::Accepted().json(json!({
+            "message": "If that email belongs to a user, they have been invited to host your event types"
+        })))
+    }
+
+    pub async fn list_host_invites(
+        &self,
+        claims: web::ReqData<Claims>,
+    ) -> Result<HttpResponse, AppError> {
+        features::require_feature(Feature::Teams)?;
+        let claims = claims.into_inner();
+        let user_id = ObjectId::parse_str(&claims.sub)
+            .map_err(|_| AppError::BadRequest("Invalid user ID".to_string()))?;
+
+        let mut response = Vec::new();
+        for invite in self.host_invite_repository.find_by_user_id(&user_id).await? {
+            let received = invite.host_user_id == user_id;
+            // Owners only learn who an email belongs to once that user agrees
+            let other_name = if received {
+                self.user_repository.find_by_id(&invite.owner_user_id.to_hex()).await?.map(|owner| owner.name)
+            } else if invite.status == HostInviteStatus::Accepted {
+                self.user_repository.find_by_id(&invite.host_user_id.to_hex()).await?.map(|host| host.name)
+            } else {
+                None
+            };
+            response.push(HostInviteResponse::new(invite, received, other_name));
+        }
+
+        Ok(HttpResponse::Ok().json(response))
+    }
+
+    pub async fn respond_to_host_invite(
+        &self,
+        claims: web::ReqData<Claims>,
+        PathObjectId(invite_id): PathObjectId,
+        status: HostInviteStatus,
+    ) -> Result<HttpResponse, AppError> {
+        features::require_feature(Feature::Teams)?;
+        let claims = claims.into_inner();
+        let user_id = ObjectId::parse_str(&claims.sub)
+            .map_err(|_| AppError::BadRequest("Invalid user ID".to_string()))?;
+
+        let invite = self.host_invite_repository.respond(&invite_id, &user_id, status).await?
+            .ok_or_else(|| AppError::NotFound("Host invitation not found".to_string()))?;
+
+        Ok(HttpResponse::Ok().json(HostInviteResponse::new(invite, true, None)))
+    }
+
+    /// Either side can withdraw. Event types still listing the host stop
+    /// offering slots until the owner removes them.
+    pub async fn delete_host_invite(
+        &self,
+        claims: web::ReqData<Claims>,
+        PathObjectId(invite_id): PathObjectId,
+    ) -> Result<HttpResponse, AppError> {
+        features::require_feature(Feature::Teams)?;
+        let claims = claims.into_inner();
+        let user_id = ObjectId::parse_str(&claims.sub)
+            .map_err(|_| AppError::BadRequest("Invalid user ID".to_string()))?;
+
+        let invite = self.host_invite_repository.find_by_id(&invite_id).await?
+            .filter(|invite| invite.owner_user_id == user_id || invite.host_user_id == user_id)
+            .ok_or_else(|| AppError::NotFound("Host invitation not found".to_string()))?;
+
+        self.host_invite_repository.delete(&invite_id).await?;
+
+        Ok(HttpResponse::Ok().json(json!({
+            "message": format!("Host invitation {} deleted", invite.id.map(|id| id.to_hex()).unwrap_or_default())
+        })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{test as actix_test, FromRequest, HttpMessage};
+
+    use super::*;
+    use crate::modules::booking::booking_controller::BookingController;
+    use crate::modules::booking::booking_crud::BookingRepository;
+    use crate::test_support::{self, with_database};
+
+    fn claims_of(user_id: &ObjectId) -> web::ReqData<Claims> {
+        let req = actix_test::TestRequest::default().to_http_request();
+        req.extensions_mut().insert(Claims {
+            sub: user_id.to_hex(),
+            exp: 0,
+            iat: 0,
+            email: "host@example.com".to_string(),
+            role: "member".to_string(),
+        });
+        web::ReqData::<Claims>::extract(&req).into_inner().unwrap()
+    }
+
+    #[test]
+    fn host_invitations_are_missing_while_teams_is_disabled() {
+        with_database(|db| async move {
+            // The test state enables no features
+            let controller = CalendarController::new(db.clone());
+            let user_id = ObjectId::new();
+            let invite_id = || PathObjectId(ObjectId::new());
+
+            let results = [
+                controller.list_host_invites(claims_of(&user_id)).await,
+                controller
+                    .create_host_invite(claims_of(&user_id), web::Json(CreateHostInviteRequest { email: "host@example.com".to_string() }))
+                    .await,
+                controller.respond_to_host_invite(claims_of(&user_id), invite_id(), HostInviteStatus::Accepted).await,
+                controller.delete_host_invite(claims_of(&user_id), invite_id()).await,
+            ];
+
+            for result in results {
+                assert!(matches!(result, Err(AppError::NotFound(message)) if message == "Resource not found"));
+            }
+        });
+    }
+
+    #[test]
+    fn check_time_slot_names_the_overlapping_booking() {
+        with_database(|db| async move {
+            let timezone = "Europe/Berlin";
+            let (settings, schedule) = test_support::create_host(&db, timezone).await;
+            let host = settings.user_id;
+            let event_type = EventTypeRepository::new(db.clone())
+                .create(test_support::event_type(&host, &schedule))
+                .await
+                .unwrap();
+            let date = test_support::date_in(timezone, 3);
+            let booking = BookingRepository::new(db.clone())
+                .create_in_free_seat(test_support::booking(&event_type.id.unwrap(), &host, &date, "10:00"), 1)
+                .await
+                .unwrap();
+
+            let controller = CalendarController::new(db.clone());
+            let check = |start_time: &str, end_time: &str| CheckTimeSlotRequest {
+                date: date.clone(),
+                start_time: start_time.to_string(),
+                end_time: end_time.to_string(),
+                event_type_id: None,
+            };
+            let response = controller.check_time_slot(claims_of(&host), web::Json(check("10:15", "10:45"))).await.unwrap();
+            let body = actix_web::body::to_bytes(response.into_body()).await.unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+            assert_eq!(body, json!({
+                "is_available": false,
+                "conflicts": [{
+                    "code": "booking_overlap",
+                    "message": "Time slot overlaps an existing booking",
+                    "booking_id": booking.id.unwrap().to_hex(),
+                    "range": { "start": "10:00", "end": "10:30" },
+                }],
+                "messages": ["Time slot overlaps an existing booking"],
+            }));
+
+            let response = controller.check_time_slot(claims_of(&host), web::Json(check("11:00", "11:30"))).await.unwrap();
+            let body = actix_web::body::to_bytes(response.into_body()).await.unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(body["is_available"], json!(true));
+        });
+    }
+
+    #[test]
+    fn policy_notice_is_bounded_to_a_year_inclusive() {
+        let cancellation = |min_notice_minutes| CancellationPolicy { allowed: true, min_notice_minutes, policy_text: None };
+        let reschedule = |min_notice_minutes| ReschedulePolicy { allowed: true, min_notice_minutes, policy_text: None };
+
+        for minutes in [0, 525_600] {
+            assert!(CalendarController::validate_cancellation_policy(Some(&cancellation(minutes))).is_ok());
+            assert!(CalendarController::validate_reschedule_policy(Some(&reschedule(minutes))).is_ok());
+        }
+        for minutes in [-1, 525_601] {
+            assert!(matches!(
+                CalendarController::validate_cancellation_policy(Some(&cancellation(minutes))),
+                Err(AppError::ValidationError(_))
+            ));
+            assert!(matches!(
+                CalendarController::validate_reschedule_policy(Some(&reschedule(minutes))),
+                Err(AppError::ValidationError(_))
+            ));
+        }
+        assert!(CalendarController::validate_cancellation_policy(None).is_ok());
+    }
+
+    #[test]
+    fn day_overrides_need_a_weekday_and_a_sensible_duration() {
+        let overrides = |day: &str, duration| HashMap::from([
+            (day.to_string(), DayOverride { duration, buffer_time: None }),
+        ]);
+
+        for valid in [overrides("monday", Some(15)), overrides("sunday", Some(480)), overrides("friday", None)] {
+            assert!(CalendarController::validate_day_overrides(Some(&valid)).is_ok());
+        }
+        for invalid in [overrides("Monday", Some(30)), overrides("funday", None), overrides("monday", Some(14)), overrides("monday", Some(481))] {
+            assert!(matches!(CalendarController::validate_day_overrides(Some(&invalid)), Err(AppError::ValidationError(_))));
+        }
+        assert!(CalendarController::validate_day_overrides(None).is_ok());
+    }
+
+    #[test]
+    fn translations_need_a_locale_code_and_bounded_text() {
+        let translation = |name: &str| EventTypeTranslation {
+            name: name.to_string(),
+            description: None,
+            confirmation_email_extra: None,
+        };
+        let validate = |translations: HashMap<String, EventTypeTranslation>| {
+            CalendarController::validate_translations(Some(&translations))
+        };
+
+        assert!(validate(HashMap::from([("de".to_string(), translation("Kennenlernen"))])).is_ok());
+        assert!(validate(HashMap::from([("pt-BR".to_string(), translation(&"n".repeat(100)))])).is_ok());
+        assert!(CalendarController::validate_translations(None).is_ok());
+
+        let too_long = EventTypeTranslation {
+            description: Some("d".repeat(5001)),
+            ..translation("Intro")
+        };
+        let too_long_email = EventTypeTranslation {
+            confirmation_email_extra: Some("e".repeat(1001)),
+            ..translation("Intro")
+        };
+        let too_many: HashMap<String, EventTypeTranslation> = ('a'..='u')
+            .map(|c| (format!("{}{}", c, c), translation("Intro")))
+            .collect();
+        for invalid in [
+            HashMap::from([("german".to_string(), translation("Intro"))]),
+            HashMap::from([("de".to_string(), translation("  "))]),
+            HashMap::from([("de".to_string(), translation(&"n".repeat(101)))]),
+            HashMap::from([("de".to_string(), too_long)]),
+            HashMap::from([("de".to_string(), too_long_email)]),
+            too_many,
+        ] {
+            assert!(matches!(validate(invalid), Err(AppError::ValidationError(_))));
+        }
+    }
+
+    #[test]
+    fn bookings_left_outside_an_edited_schedule_are_reported() {
+        with_database(|db| async move {
+            let timezone = "Europe/Berlin";
+            let (settings, schedule) = test_support::create_host(&db, timezone).await;
+            let host = settings.user_id;
+            let event_type = EventTypeRepository::new(db.clone())
+                .create(test_support::event_type(&host, &schedule))
+                .await
+                .unwrap();
+            let controller = CalendarController::new(db.clone());
+            let date = test_support::date_in(timezone, 3);
+            let booking = BookingRepository::new(db.clone())
+                .create_in_free_seat(test_support::booking(&event_type.id.unwrap(), &host, &date, "10:00"), 1)
+                .await
+                .unwrap();
+
+            let unchanged = controller.find_affected_bookings(&host, &settings, Some(&schedule)).await.unwrap();
+            assert!(unchanged.is_empty());
+
+            // The host blocks the booking's day; the booking stays but is reported
+            let blocked = Availability {
+                date_overrides: vec![DateOverride { date: date.clone(), slots: Vec::new() }],
+                ..schedule
+            };
+            let affected = controller.find_affected_bookings(&host, &settings, Some(&blocked)).await.unwrap();
+            assert_eq!(affected.len(), 1);
+            assert_eq!(affected[0].booking_id, booking.id.unwrap().to_hex());
+            assert_eq!((affected[0].date.as_str(), affected[0].start_time.as_str()), (date.as_str(), "10:00"));
+        });
+    }
+
+    #[test]
+    fn old_slugs_lead_to_the_event_type_until_another_takes_them() {
+        with_database(|db| async move {
+            let (settings, schedule) = test_support::create_host(&db, "Europe/Berlin").await;
+            let host = settings.user_id;
+            let repository = EventTypeRepository::new(db.clone());
+            let intro = repository
+                .create(EventType { slug: "intro".to_string(), ..test_support::event_type(&host, &schedule) })
+                .await
+                .unwrap();
+            let other = repository.create(test_support::event_type(&host, &schedule)).await.unwrap();
+            let controller = CalendarController::new(db.clone());
+            let rename = |event_type: &EventType, slug: &str| {
+                let request: UpdateEventTypeRequest = serde_json::from_value(json!({ "slug": slug })).unwrap();
+                controller.update_event_type(claims_of(&host), PathObjectId(event_type.id.unwrap()), web::Json(request))
+            };
+            let public_id_and_slug = |slug: &str| {
+                let path = web::Path::from((host.to_hex(), slug.to_string()));
+                let db = db.clone();
+                async move {
+                    let response = BookingController::new(db).public_get_event_type(path, web::Query(HashMap::new())).await.unwrap();
+                    let body = actix_web::body::to_bytes(response.into_body()).await.unwrap();
+                    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+                    (body["id"].as_str().unwrap_or_default().to_string(), body["slug"].clone())
+                }
+            };
+
+            rename(&intro, "welcome").await.unwrap();
+            let (id, slug) = public_id_and_slug("intro").await;
+            assert_eq!(id, intro.id.unwrap().to_hex());
+            assert_eq!(slug, json!("welcome"));
+
+            // The event type that has the slug now wins, and the old owner lets it go
+            rename(&other, "intro").await.unwrap();
+            let (id, slug) = public_id_and_slug("intro").await;
+            assert_eq!(id, other.id.unwrap().to_hex());
+            assert_eq!(slug, json!("intro"));
+            let intro = repository.find_by_id(&intro.id.unwrap()).await.unwrap().unwrap();
+            assert!(intro.slug_history.is_empty());
+        });
+    }
+}
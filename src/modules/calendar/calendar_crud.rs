@@ -1,10 +1,25 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
 use mongodb::{
-    bson::{doc, oid::ObjectId, DateTime},
-    Collection, Database,
+    bson::{self, doc, oid::ObjectId, Bson, DateTime, Document},
+    error::{Error as MongoError, ErrorKind, WriteFailure},
+    options::{CreateCollectionOptions, FindOneAndReplaceOptions, FindOneAndUpdateOptions, FindOneOptions, FindOptions, IndexOptions, ReturnDocument},
+    Collection, Database, IndexModel,
 };
 use futures::TryStreamExt;
 use crate::errors::error::AppError;
-use crate::modules::calendar::calendar_model::{CalendarSettings, Availability, EventType};
+use crate::utils::text;
+use crate::modules::calendar::calendar_model::{CalendarSettings, Availability, AvailabilitySnapshot, EventType, HostInvite, HostInviteStatus, Location, ScheduleVersion, AVAILABILITY_RESTORE_DAYS};
+
+/// Size of the capped snapshot collection; the oldest snapshots are dropped first.
+const SNAPSHOTS_CAPACITY_BYTES: u64 = 64 * 1024 * 1024;
+/// Server error code for creating a collection that already exists.
+const NAMESPACE_EXISTS_CODE: i32 = 48;
+/// Server error code for a unique index violation.
+const DUPLICATE_KEY_CODE: i32 = 11000;
+/// How often switching the default schedule is retried when a concurrent switch wins.
+const SET_DEFAULT_ATTEMPTS: usize = 3;
 
 
 pub struct CalendarSettingsRepository {
@@ -65,6 +80,43 @@ impl CalendarSettingsRepository {
             .await
             .map_err(|e| AppError::DatabaseError(e.to_string()))
     }
+
+    /// Turns availability diagnostics on until `until`, or off with `None`.
+    /// Number of distinct users with calendar settings.
+    pub async fn count_users(&self) -> Result<u64, AppError> {
+        let pipeline = vec![
+            doc! { "$group": { "_id": "$user_id" } },
+            doc! { "$count": "users" },
+        ];
+
+        let mut cursor = self.collection
+            .aggregate(pipeline, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let row = cursor.try_next().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        Ok(match row.as_ref().and_then(|row| row.get("users")) {
+            Some(Bson::Int32(n)) => *n as u64,
+            Some(Bson::Int64(n)) => *n as u64,
+            _ => 0,
+        })
+    }
+
+    pub async fn set_diagnostics_until(&self, user_id: &ObjectId, until: Option<DateTime>) -> Result<Option<CalendarSettings>, AppError> {
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+
+        self.collection
+            .find_one_and_update(
+                doc! { "user_id": user_id },
+                doc! { "$set": { "diagnostics_until": until, "updated_at": DateTime::now() } },
+                options
+            )
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
 }
 
 
@@ -86,66 +138,170 @@ impl AvailabilityRepository {
         availability.created_at = DateTime::now();
         availability.updated_at = DateTime::now();
 
-        let result = self.collection
-            .insert_one(&availability, None)
-            .await
+        let result = match self.collection.insert_one(&availability, None).await {
+            // A concurrent request created the user's default schedule first
+            Err(e) if availability.is_default && is_duplicate_key(&e) => {
+                availability.is_default = false;
+                self.collection.insert_one(&availability, None).await
+            }
+            result => result,
+        }
             .map_err(|e| AppError::DatabaseError(e.to_string()))?;
 
         availability.id = Some(result.inserted_id.as_object_id().unwrap());
         Ok(availability)
     }
 
-    pub async fn find_by_user_id(&self, user_id: &ObjectId) -> Result<Option<Availability>, AppError> {
+    /// Purges soft-deleted schedules once the restore window has passed, and
+    /// allows one default schedule per user. Safe to call on every start.
+    pub async fn ensure_indexes(&self) -> Result<(), AppError> {
+        self.clear_extra_defaults().await?;
+        self.backfill_rule_ids().await?;
+
+        let purge_after = Duration::from_secs(AVAILABILITY_RESTORE_DAYS * 24 * 60 * 60);
+        let index = IndexModel::builder()
+            .keys(doc! { "deleted_at": 1 })
+            .options(IndexOptions::builder().expire_after(purge_after).build())
+            .build();
+        let default_index = IndexModel::builder()
+            .keys(doc! { "user_id": 1 })
+            .options(
+                IndexOptions::builder()
+                    .name("one_default_per_user".to_string())
+                    .unique(true)
+                    .partial_filter_expression(doc! { "is_default": true })
+                    .build(),
+            )
+            .build();
+
         self.collection
-            .find_one(doc! { "user_id": user_id }, None)
+            .create_indexes([index, default_index], None)
             .await
-            .map_err(|e| AppError::DatabaseError(e.to_string()))
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(())
     }
 
-    pub async fn find_by_calendar_settings_id(&self, calendar_settings_id: &ObjectId) -> Result<Option<Availability>, AppError> {
+    /// Unflags all but the oldest default schedule of each user, which is the
+    /// one reads resolved to, so the unique index can be built over schedules
+    /// from before it existed.
+    async fn clear_extra_defaults(&self) -> Result<(), AppError> {
+        let pipeline = vec![
+            doc! { "$match": { "is_default": true } },
+            doc! { "$sort": { "_id": 1 } },
+            doc! { "$group": { "_id": "$user_id", "ids": { "$push": "$_id" } } },
+            doc! { "$match": { "ids.1": { "$exists": true } } },
+        ];
+        let mut cursor = self.collection
+            .aggregate(pipeline, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        let mut extra = Vec::new();
+        while let Some(group) = cursor.try_next().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))? {
+            if let Ok(ids) = group.get_array("ids") {
+                extra.extend(ids.iter().skip(1).cloned());
+            }
+        }
+        if extra.is_empty() {
+            return Ok(());
+        }
+
         self.collection
-            .find_one(doc! { "calendar_settings_id": calendar_settings_id }, None)
+            .update_many(doc! { "_id": { "$in": extra } }, doc! { "$set": { "is_default": false } }, None)
             .await
-            .map_err(|e| AppError::DatabaseError(e.to_string()))
-    }
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
 
-    pub async fn update(&self, id: &ObjectId, availability: Availability) -> Result<Option<Availability>, AppError> {
-        let mut availability = availability;
-        availability.updated_at = DateTime::now();
+        Ok(())
+    }
 
-        let result = self.collection
-            .find_one_and_replace(
-                doc! { "_id": id },
-                &availability,
-                None
-            )
+    /// Gives an id to every rule stored before rule ids existed. Schedules
+    /// written in the meantime are left to get theirs on their next update.
+    async fn backfill_rule_ids(&self) -> Result<(), AppError> {
+        let filter = doc! {
+            "rules": { "$elemMatch": { "$or": [{ "rule_id": { "$exists": false } }, { "rule_id": "" }] } },
+        };
+        let mut cursor = self.collection
+            .find(filter, None)
             .await
             .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        let mut unassigned = Vec::new();
+        while let Some(availability) = cursor.try_next().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))? {
+            unassigned.push(availability);
+        }
 
-        Ok(result)
+        for mut availability in unassigned {
+            let Some(id) = availability.id else {
+                continue;
+            };
+            for rule in &mut availability.rules {
+                rule.ensure_rule_id();
+            }
+            let rules = bson::to_bson(&availability.rules)
+                .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+            // Documents written before versioning have no field, which reads as 0
+            let version = if availability.version == 0 {
+                doc! { "$in": [0_i64, Bson::Null] }
+            } else {
+                doc! { "$eq": availability.version }
+            };
+            self.collection
+                .update_one(doc! { "_id": id, "version": version }, doc! { "$set": { "rules": rules } }, None)
+                .await
+                .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        }
+
+        Ok(())
     }
 
-    pub async fn delete(&self, id: &ObjectId) -> Result<Option<Availability>, AppError> {
+    /// The user's default schedule. Users whose schedules predate the default
+    /// flag, or who deleted their default, fall back to their oldest schedule.
+    pub async fn find_default_by_user_id(&self, user_id: &ObjectId) -> Result<Option<Availability>, AppError> {
+        let options = FindOneOptions::builder()
+            .sort(doc! { "is_default": -1, "_id": 1 })
+            .build();
+
         self.collection
-            .find_one_and_delete(doc! { "_id": id }, None)
+            .find_one(doc! { "user_id": user_id, "deleted_at": null }, options)
             .await
             .map_err(|e| AppError::DatabaseError(e.to_string()))
     }
 
-    pub async fn find_available_slots(&self, user_id: &ObjectId, start_date: DateTime, end_date: DateTime) -> Result<Vec<Availability>, AppError> {
-        let filter = doc! {
-            "user_id": user_id,
-            "$or": [
-                {
-                    "rules.start_date": { "$lte": end_date },
-                    "rules.end_date": { "$gte": start_date }
-                },
-                {
-                    "rules.start_date": { "$lte": end_date },
-                    "rules.end_date": null,
-                    "rules.is_recurring": true
-                }
-            ]
+    /// Makes `id` the user's default schedule. A unique index allows one
+    /// default per user, so the previous default is unflagged first; when a
+    /// concurrent switch flags its own schedule in between, this one retries.
+    pub async fn set_default(&self, user_id: &ObjectId, id: &ObjectId) -> Result<Option<Availability>, AppError> {
+        let flag = |is_default: bool| vec![doc! { "$set": {
+            "is_default": is_default,
+            "version": { "$add": [{ "$ifNull": ["$version", 0_i64] }, 1_i64] },
+            "updated_at": DateTime::now(),
+        } }];
+
+        for _ in 0..SET_DEFAULT_ATTEMPTS {
+            self.collection
+                .update_many(doc! { "user_id": user_id, "is_default": true, "_id": { "$ne": id } }, flag(false), None)
+                .await
+                .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+            match self.collection
+                .update_one(doc! { "_id": id, "user_id": user_id, "deleted_at": null }, flag(true), None)
+                .await
+            {
+                Ok(_) => return self.find_by_id(id).await,
+                Err(e) if is_duplicate_key(&e) => continue,
+                Err(e) => return Err(AppError::DatabaseError(e.to_string())),
+            }
+        }
+
+        Err(AppError::Conflict("The default schedule is being changed by another request, try again".to_string()))
+    }
+
+    pub async fn find_all_by_user_id(&self, user_id: &ObjectId, include_deleted: bool) -> Result<Vec<Availability>, AppError> {
+        let filter = if include_deleted {
+            doc! { "user_id": user_id }
+        } else {
+            doc! { "user_id": user_id, "deleted_at": null }
         };
 
         let mut availabilities = Vec::new();
@@ -162,7 +318,108 @@ impl AvailabilityRepository {
         Ok(availabilities)
     }
 
+    #[allow(dead_code)]
+    pub async fn find_by_calendar_settings_id(&self, calendar_settings_id: &ObjectId) -> Result<Option<Availability>, AppError> {
+        self.collection
+            .find_one(doc! { "calendar_settings_id": calendar_settings_id, "deleted_at": null }, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// Replaces the document only if it is still at `availability.version`,
+    /// returning the updated document or `None` if it changed in the meantime.
+    pub async fn update(&self, id: &ObjectId, availability: Availability) -> Result<Option<Availability>, AppError> {
+        let mut availability = availability;
+        let expected_version = availability.version;
+        availability.version += 1;
+        availability.updated_at = DateTime::now();
+
+        // Rules stored before rule ids existed get one on their first write
+        for rule in &mut availability.rules {
+            rule.ensure_rule_id();
+        }
+
+        // Documents written before versioning have no field, which reads as 0
+        let version_filter = if expected_version == 0 {
+            doc! { "$in": [0_i64, Bson::Null] }
+        } else {
+            doc! { "$eq": expected_version }
+        };
+
+        let options = FindOneAndReplaceOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+
+        let result = self.collection
+            .find_one_and_replace(
+                doc! { "_id": id, "version": version_filter },
+                &availability,
+                options
+            )
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(result)
+    }
+
+    /// Whether the schedule is still at the version a decision was based on.
+    pub async fn is_at_version(&self, schedule_version: &ScheduleVersion) -> Result<bool, AppError> {
+        // Documents written before versioning have no field, which reads as 0
+        let version_filter = if schedule_version.version == 0 {
+            doc! { "$in": [0_i64, Bson::Null] }
+        } else {
+            doc! { "$eq": schedule_version.version }
+        };
+        let count = self.collection
+            .count_documents(doc! { "_id": schedule_version.availability_id, "version": version_filter }, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        Ok(count > 0)
+    }
+
+    /// Marks the schedule deleted; the TTL index purges it after the restore window.
+    pub async fn soft_delete(&self, id: &ObjectId) -> Result<Option<Availability>, AppError> {
+        let now = DateTime::now();
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+
+        self.collection
+            .find_one_and_update(
+                doc! { "_id": id, "deleted_at": null },
+                doc! { "$set": { "deleted_at": now, "is_default": false, "updated_at": now }, "$inc": { "version": 1_i64 } },
+                options
+            )
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// Undoes a soft delete that is still inside the restore window.
+    pub async fn restore(&self, id: &ObjectId) -> Result<Option<Availability>, AppError> {
+        let window_ms = (AVAILABILITY_RESTORE_DAYS * 24 * 60 * 60 * 1000) as i64;
+        let cutoff = DateTime::from_millis(DateTime::now().timestamp_millis() - window_ms);
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+
+        self.collection
+            .find_one_and_update(
+                doc! { "_id": id, "deleted_at": { "$gte": cutoff } },
+                doc! { "$set": { "deleted_at": Bson::Null, "updated_at": DateTime::now() }, "$inc": { "version": 1_i64 } },
+                options
+            )
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
     pub async fn find_by_id(&self, id: &ObjectId) -> Result<Option<Availability>, AppError> {
+        self.collection
+            .find_one(doc! { "_id": id, "deleted_at": null }, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    pub async fn find_by_id_including_deleted(&self, id: &ObjectId) -> Result<Option<Availability>, AppError> {
         self.collection
             .find_one(doc! { "_id": id }, None)
             .await
@@ -170,6 +427,66 @@ impl AvailabilityRepository {
     }
 }
 
+/// Narrows and orders a user's event types. The default is every event type
+/// that is not archived, in the user's order.
+#[derive(Debug, Default)]
+pub struct EventTypeFilter {
+    pub include_archived: bool,
+    pub search: Option<String>,  // Case-insensitive substring of the name or description
+    pub is_active: Option<bool>,
+    pub location_kind: Option<String>,  // Event types offering a location of this kind
+    pub duration_min: Option<i32>,
+    pub duration_max: Option<i32>,
+    pub sort: Option<(EventTypeSort, SortDirection)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventTypeSort {
+    Name,
+    CreatedAt,
+    Position,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortDirection {
+    Ascending,
+    Descending,
+}
+
+impl EventTypeSort {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "name" => Some(Self::Name),
+            "created_at" => Some(Self::CreatedAt),
+            "position" => Some(Self::Position),
+            _ => None,
+        }
+    }
+
+    /// Ties fall back to creation order, so pages of equal keys are stable.
+    fn sort_document(self, direction: SortDirection) -> Document {
+        let order = match direction {
+            SortDirection::Ascending => 1,
+            SortDirection::Descending => -1,
+        };
+        match self {
+            Self::Name => doc! { "name": order, "created_at": 1, "_id": 1 },
+            Self::CreatedAt => doc! { "created_at": order, "_id": order },
+            Self::Position => doc! { "position": order, "created_at": 1, "_id": 1 },
+        }
+    }
+}
+
+impl SortDirection {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "asc" => Some(Self::Ascending),
+            "desc" => Some(Self::Descending),
+            _ => None,
+        }
+    }
+}
+
 pub struct EventTypeRepository {
     collection: Collection<EventType>,
 }
@@ -180,6 +497,168 @@ impl EventTypeRepository {
         Self { collection }
     }
 
+    /// Keeps slugs unique per user and old slugs quick to find, and brings event types from before slugs
+    /// and location lists up to date. Safe to call on every start.
+    pub async fn ensure_indexes(&self) -> Result<(), AppError> {
+        self.migrate_locations().await?;
+
+        let mut cursor = self.collection
+            .find(doc! { "$or": [{ "slug": { "$exists": false } }, { "slug": "" }] }, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        let mut unslugged = Vec::new();
+        while let Some(event_type) = cursor.try_next().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))? {
+            unslugged.push(event_type);
+        }
+        for event_type in unslugged {
+            let Some(id) = event_type.id else {
+                continue;
+            };
+            let slug = self.unique_slug(&event_type.user_id, &event_type.name).await?;
+            self.collection
+                .update_one(doc! { "_id": id }, doc! { "$set": { "slug": slug } }, None)
+                .await
+                .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        }
+
+        let index = IndexModel::builder()
+            .keys(doc! { "user_id": 1, "slug": 1 })
+            .options(
+                IndexOptions::builder()
+                    .name("user_slug".to_string())
+                    .unique(true)
+                    .partial_filter_expression(doc! { "slug": { "$gt": "" } })
+                    .build(),
+            )
+            .build();
+
+        self.collection
+            .create_index(index, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let history_index = IndexModel::builder()
+            .keys(doc! { "user_id": 1, "slug_history": 1 })
+            .options(IndexOptions::builder().name("user_slug_history".to_string()).build())
+            .build();
+        self.collection
+            .create_index(history_index, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Turns the `location_type`, `meeting_link` and `location_options` fields
+    /// event types had before location lists into `locations`.
+    async fn migrate_locations(&self) -> Result<(), AppError> {
+        let raw = self.collection.clone_with_type::<Document>();
+        let mut cursor = raw
+            .find(doc! { "locations": { "$exists": false } }, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        let mut legacy = Vec::new();
+        while let Some(document) = cursor.try_next().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))? {
+            legacy.push(document);
+        }
+
+        for document in legacy {
+            let Ok(id) = document.get_object_id("_id") else {
+                continue;
+            };
+            let meeting_link = document.get_str("meeting_link").ok().map(str::to_string);
+            let options: Vec<Location> = document.get_array("location_options")
+                .ok()
+                .and_then(|options| bson::from_bson(Bson::Array(options.clone())).ok())
+                .unwrap_or_default();
+
+            let locations: Vec<Location> = if options.is_empty() {
+                let location_type = document.get_str("location_type").unwrap_or("video");
+                vec![Location::from_legacy(location_type, meeting_link, None)]
+            } else {
+                // Video options without their own link used the event type's
+                options
+                    .into_iter()
+                    .map(|option| match option {
+                        Location::Video { provider, link: None } => Location::Video { provider, link: meeting_link.clone() },
+                        option => option,
+                    })
+                    .collect()
+            };
+            let locations = bson::to_bson(&locations)
+                .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+
+            self.collection
+                .update_one(
+                    doc! { "_id": id },
+                    doc! {
+                        "$set": { "locations": locations },
+                        "$unset": { "location_type": "", "meeting_link": "", "location_options": "" },
+                    },
+                    None,
+                )
+                .await
+                .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        }
+
+        Ok(())
+    }
+
+    /// A slug for `name` that none of the user's event types has yet: the
+    /// slugified name, or the name with "-2", "-3", ... appended.
+    pub async fn unique_slug(&self, user_id: &ObjectId, name: &str) -> Result<String, AppError> {
+        let mut base = text::slugify(name);
+        if base.is_empty() {
+            base = "event".to_string();
+        }
+        // Leave room for the suffix within the length limit
+        base.truncate(text::SLUG_MAX_CHARS - 4);
+        let base = base.trim_end_matches('-').to_string();
+
+        // Archived event types keep their slug for when they are restored
+        let taken: Vec<String> = self.find_by_user_id(user_id, true).await?
+            .into_iter()
+            .map(|event_type| event_type.slug)
+            .collect();
+        let slug = std::iter::once(base.clone())
+            .chain((2..).map(|n| format!("{}-{}", base, n)))
+            .find(|slug| !taken.contains(slug))
+            .unwrap_or(base);
+        Ok(slug)
+    }
+
+    pub async fn find_by_user_and_slug(&self, user_id: &ObjectId, slug: &str) -> Result<Option<EventType>, AppError> {
+        self.collection
+            .find_one(doc! { "user_id": user_id, "slug": slug }, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// The user's event type that used to have `slug`, for links shared
+    /// before it was renamed.
+    pub async fn find_by_user_and_old_slug(&self, user_id: &ObjectId, slug: &str) -> Result<Option<EventType>, AppError> {
+        self.collection
+            .find_one(doc! { "user_id": user_id, "slug_history": slug }, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// Takes `slug` out of the history of the user's other event types once
+    /// one of them has it, so old links stop pointing elsewhere.
+    pub async fn drop_old_slug(&self, user_id: &ObjectId, slug: &str) -> Result<(), AppError> {
+        self.collection
+            .update_many(
+                doc! { "user_id": user_id, "slug_history": slug },
+                doc! { "$pull": { "slug_history": slug } },
+                None,
+            )
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        Ok(())
+    }
+
     pub async fn create(&self, event_type: EventType) -> Result<EventType, AppError> {
         let mut event_type = event_type;
         event_type.created_at = DateTime::now();
@@ -188,16 +667,67 @@ impl EventTypeRepository {
         let result = self.collection
             .insert_one(&event_type, None)
             .await
-            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+            .map_err(slug_write_error)?;
 
         event_type.id = Some(result.inserted_id.as_object_id().unwrap());
         Ok(event_type)
     }
 
-    pub async fn find_by_user_id(&self, user_id: &ObjectId) -> Result<Vec<EventType>, AppError> {
+    pub async fn find_by_user_id(&self, user_id: &ObjectId, include_archived: bool) -> Result<Vec<EventType>, AppError> {
+        self.find_matching(user_id, &EventTypeFilter { include_archived, ..Default::default() }).await
+    }
+
+    /// The user's event types that pass every condition set in `filter`.
+    pub async fn find_matching(&self, user_id: &ObjectId, filter: &EventTypeFilter) -> Result<Vec<EventType>, AppError> {
+        let mut query = doc! { "user_id": user_id };
+        if !filter.include_archived {
+            query.insert("archived_at", Bson::Null);
+        }
+        if let Some(search) = &filter.search {
+            let pattern = doc! { "$regex": text::escape_regex(search), "$options": "i" };
+            query.insert("$or", vec![doc! { "name": pattern.clone() }, doc! { "description": pattern }]);
+        }
+        if let Some(is_active) = filter.is_active {
+            query.insert("is_active", is_active);
+        }
+        if let Some(kind) = &filter.location_kind {
+            query.insert("locations.kind", kind);
+        }
+        let mut duration = Document::new();
+        if let Some(min) = filter.duration_min {
+            duration.insert("$gte", min);
+        }
+        if let Some(max) = filter.duration_max {
+            duration.insert("$lte", max);
+        }
+        if !duration.is_empty() {
+            query.insert("duration", duration);
+        }
+
+        let options = match filter.sort {
+            Some((field, direction)) => FindOptions::builder().sort(field.sort_document(direction)).build(),
+            None => Self::list_order(),
+        };
+
+        let mut event_types = Vec::new();
+        let mut cursor = self.collection
+            .find(query, options)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        while let Some(event_type) = cursor.try_next().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))? {
+            event_types.push(event_type);
+        }
+
+        Ok(event_types)
+    }
+
+    /// The user's active event types that are neither secret nor archived, for their public page.
+    pub async fn find_listed_by_user_id(&self, user_id: &ObjectId) -> Result<Vec<EventType>, AppError> {
         let mut event_types = Vec::new();
         let mut cursor = self.collection
-            .find(doc! { "user_id": user_id }, None)
+            .find(doc! { "user_id": user_id, "is_active": true, "is_secret": { "$ne": true }, "archived_at": null }, Self::list_order())
             .await
             .map_err(|e| AppError::DatabaseError(e.to_string()))?;
 
@@ -209,6 +739,51 @@ impl EventTypeRepository {
         Ok(event_types)
     }
 
+    /// The order the user chose, with ties (such as event types from before
+    /// ordering existed) oldest first.
+    fn list_order() -> FindOptions {
+        FindOptions::builder()
+            .sort(doc! { "position": 1, "created_at": 1, "_id": 1 })
+            .build()
+    }
+
+    /// A position after all of the user's event types.
+    pub async fn next_position(&self, user_id: &ObjectId) -> Result<i32, AppError> {
+        let options = FindOneOptions::builder()
+            .sort(doc! { "position": -1 })
+            .build();
+        let last = self.collection
+            .find_one(doc! { "user_id": user_id }, options)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        Ok(last.map_or(0, |event_type| event_type.position + 1))
+    }
+
+    /// Gives each of the user's event types in `ordered_ids` its index as
+    /// position, in a single write.
+    pub async fn set_positions(&self, user_id: &ObjectId, ordered_ids: &[ObjectId]) -> Result<(), AppError> {
+        let pipeline = vec![doc! {
+            "$set": {
+                "position": { "$indexOfArray": [ordered_ids, "$_id"] },
+                "updated_at": DateTime::now(),
+            }
+        }];
+        self.collection
+            .update_many(doc! { "user_id": user_id, "_id": { "$in": ordered_ids } }, pipeline, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        Ok(())
+    }
+
+    /// The user's event types keyed by ID, for looking up the event type of
+    /// each booking. Includes archived ones, which past bookings still point at.
+    pub async fn find_map_by_user_id(&self, user_id: &ObjectId) -> Result<HashMap<ObjectId, EventType>, AppError> {
+        Ok(self.find_by_user_id(user_id, true).await?
+            .into_iter()
+            .filter_map(|event_type| event_type.id.map(|id| (id, event_type)))
+            .collect())
+    }
+
     pub async fn find_by_id(&self, id: &ObjectId) -> Result<Option<EventType>, AppError> {
         self.collection
             .find_one(doc! { "_id": id }, None)
@@ -216,6 +791,35 @@ impl EventTypeRepository {
             .map_err(|e| AppError::DatabaseError(e.to_string()))
     }
 
+    pub async fn count_all(&self) -> Result<u64, AppError> {
+        self.collection
+            .count_documents(doc! {}, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    pub async fn count_by_availability_schedule_id(&self, availability_schedule_id: &ObjectId) -> Result<u64, AppError> {
+        self.collection
+            .count_documents(doc! { "availability_schedule_id": availability_schedule_id }, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    pub async fn find_active_by_ids(&self, ids: &[ObjectId]) -> Result<Vec<EventType>, AppError> {
+        let mut event_types = Vec::new();
+        let mut cursor = self.collection
+            .find(doc! { "_id": { "$in": ids }, "is_active": true, "archived_at": null }, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        while let Some(event_type) = cursor.try_next().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))? {
+            event_types.push(event_type);
+        }
+
+        Ok(event_types)
+    }
+
     pub async fn update(&self, id: &ObjectId, event_type: EventType) -> Result<Option<EventType>, AppError> {
         let mut event_type = event_type;
         event_type.updated_at = DateTime::now();
@@ -227,15 +831,358 @@ impl EventTypeRepository {
                 None
             )
             .await
-            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+            .map_err(slug_write_error)?;
 
         Ok(result)
     }
 
+    /// Hides the event type from lists and booking while keeping it for the
+    /// bookings that point at it.
+    pub async fn archive(&self, id: &ObjectId) -> Result<Option<EventType>, AppError> {
+        let now = DateTime::now();
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+
+        self.collection
+            .find_one_and_update(
+                doc! { "_id": id, "archived_at": null },
+                doc! { "$set": { "archived_at": now, "updated_at": now } },
+                options
+            )
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    pub async fn restore(&self, id: &ObjectId) -> Result<Option<EventType>, AppError> {
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+
+        self.collection
+            .find_one_and_update(
+                doc! { "_id": id, "archived_at": { "$ne": null } },
+                doc! { "$set": { "archived_at": Bson::Null, "updated_at": DateTime::now() } },
+                options
+            )
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
     pub async fn delete(&self, id: &ObjectId) -> Result<Option<EventType>, AppError> {
         self.collection
             .find_one_and_delete(doc! { "_id": id }, None)
             .await
             .map_err(|e| AppError::DatabaseError(e.to_string()))
     }
-}
\ No newline at end of file
+}
+pub struct AvailabilitySnapshotRepository {
+    db: Database,
+    collection: Collection<AvailabilitySnapshot>,
+}
+
+impl AvailabilitySnapshotRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection("availability_snapshots");
+        Self { db, collection }
+    }
+
+    /// Creates the capped collection and its lookup index. Safe to call on every start.
+    pub async fn ensure_indexes(&self) -> Result<(), AppError> {
+        let options = CreateCollectionOptions::builder()
+            .capped(true)
+            .size(SNAPSHOTS_CAPACITY_BYTES)
+            .build();
+
+        if let Err(e) = self.db.create_collection("availability_snapshots", options).await {
+            let exists = matches!(e.kind.as_ref(), ErrorKind::Command(command_error) if command_error.code == NAMESPACE_EXISTS_CODE);
+            if !exists {
+                return Err(AppError::DatabaseError(e.to_string()));
+            }
+        }
+
+        let index = IndexModel::builder()
+            .keys(doc! { "user_id": 1, "computed_at": -1 })
+            .build();
+
+        self.collection
+            .create_index(index, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    pub async fn create(&self, snapshot: AvailabilitySnapshot) -> Result<(), AppError> {
+        self.collection
+            .insert_one(&snapshot, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Whether a snapshot was stored for the user at or after `since`.
+    pub async fn exists_since(&self, user_id: &ObjectId, since: DateTime) -> Result<bool, AppError> {
+        let snapshot = self.collection
+            .find_one(doc! { "user_id": user_id, "computed_at": { "$gte": since } }, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(snapshot.is_some())
+    }
+
+    /// Snapshots of the user computed on YYYY-MM-DD dates in `dates`, oldest first.
+    pub async fn find_by_user_id_and_dates(&self, user_id: &ObjectId, dates: &[String]) -> Result<Vec<AvailabilitySnapshot>, AppError> {
+        let options = FindOptions::builder()
+            .sort(doc! { "computed_at": 1 })
+            .build();
+
+        let mut snapshots = Vec::new();
+        let mut cursor = self.collection
+            .find(doc! { "user_id": user_id, "date": { "$in": dates } }, options)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        while let Some(snapshot) = cursor.try_next().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))? {
+            snapshots.push(snapshot);
+        }
+
+        Ok(snapshots)
+    }
+}
+
+pub struct HostInviteRepository {
+    collection: Collection<HostInvite>,
+}
+
+impl HostInviteRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection("host_invites");
+        Self { collection }
+    }
+
+    /// One invitation per owner and host. Safe to call on every start.
+    pub async fn ensure_indexes(&self) -> Result<(), AppError> {
+        let index = IndexModel::builder()
+            .keys(doc! { "owner_user_id": 1, "host_user_id": 1 })
+            .options(IndexOptions::builder().unique(true).build())
+            .build();
+
+        self.collection
+            .create_index(index, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Invites `host_user_id` unless the owner already has. An earlier answer
+    /// stands; the host deletes the invitation to be asked again.
+    pub async fn invite(&self, owner_user_id: &ObjectId, host_user_id: &ObjectId) -> Result<Option<HostInvite>, AppError> {
+        let now = DateTime::now();
+        let options = FindOneAndUpdateOptions::builder()
+            .upsert(true)
+            .return_document(ReturnDocument::After)
+            .build();
+
+        self.collection
+            .find_one_and_update(
+                doc! { "owner_user_id": owner_user_id, "host_user_id": host_user_id },
+                doc! { "$setOnInsert": {
+                    "status": bson::to_bson(&HostInviteStatus::Pending).map_err(|e| AppError::InternalServerError(e.to_string()))?,
+                    "created_at": now,
+                    "updated_at": now,
+                } },
+                options
+            )
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    pub async fn find_by_id(&self, id: &ObjectId) -> Result<Option<HostInvite>, AppError> {
+        self.collection
+            .find_one(doc! { "_id": id }, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// Invitations the user sent or received, newest first.
+    pub async fn find_by_user_id(&self, user_id: &ObjectId) -> Result<Vec<HostInvite>, AppError> {
+        let options = FindOptions::builder()
+            .sort(doc! { "created_at": -1 })
+            .build();
+
+        let mut invites = Vec::new();
+        let mut cursor = self.collection
+            .find(doc! { "$or": [{ "owner_user_id": user_id }, { "host_user_id": user_id }] }, options)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        while let Some(invite) = cursor.try_next().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))? {
+            invites.push(invite);
+        }
+
+        Ok(invites)
+    }
+
+    /// Records the invited host's answer.
+    pub async fn respond(&self, id: &ObjectId, host_user_id: &ObjectId, status: HostInviteStatus) -> Result<Option<HostInvite>, AppError> {
+        let status = bson::to_bson(&status).map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+
+        self.collection
+            .find_one_and_update(
+                doc! { "_id": id, "host_user_id": host_user_id },
+                doc! { "$set": { "status": status, "updated_at": DateTime::now() } },
+                options
+            )
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    pub async fn delete(&self, id: &ObjectId) -> Result<(), AppError> {
+        self.collection
+            .delete_one(doc! { "_id": id }, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// The hosts among `host_user_ids` that accepted the owner's invitation.
+    pub async fn accepted_hosts(&self, owner_user_id: &ObjectId, host_user_ids: &[ObjectId]) -> Result<Vec<ObjectId>, AppError> {
+        let accepted = bson::to_bson(&HostInviteStatus::Accepted).map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        let mut hosts = Vec::new();
+        let mut cursor = self.collection
+            .find(
+                doc! { "owner_user_id": owner_user_id, "host_user_id": { "$in": host_user_ids }, "status": accepted },
+                None
+            )
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        while let Some(invite) = cursor.try_next().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))? {
+            hosts.push(invite.host_user_id);
+        }
+
+        Ok(hosts)
+    }
+}
+
+fn is_duplicate_key(e: &MongoError) -> bool {
+    match e.kind.as_ref() {
+        ErrorKind::Write(WriteFailure::WriteError(write_error)) => write_error.code == DUPLICATE_KEY_CODE,
+        ErrorKind::Command(command_error) => command_error.code == DUPLICATE_KEY_CODE,
+        _ => false,
+    }
+}
+
+/// Another event type of the same user got the slug first; anything else stays a database error.
+fn slug_write_error(e: MongoError) -> AppError {
+    if is_duplicate_key(&e) {
+        AppError::ValidationError("Slug is already used by another of your event types".to_string())
+    } else {
+        AppError::DatabaseError(e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, with_database};
+
+    /// Another schedule of the same host, with the same rules.
+    fn schedule_like(existing: &Availability, name: &str, is_default: bool) -> Availability {
+        Availability {
+            id: None,
+            user_id: existing.user_id,
+            calendar_settings_id: existing.calendar_settings_id,
+            name: name.to_string(),
+            is_default,
+            rules: existing.rules.clone(),
+            date_overrides: Vec::new(),
+            version: 0,
+            deleted_at: None,
+            created_at: DateTime::now(),
+            updated_at: DateTime::now(),
+        }
+    }
+
+    #[test]
+    fn concurrent_default_switches_leave_one_default() {
+        with_database(|db| async move {
+            let (_, first) = test_support::create_host(&db, "Europe/Berlin").await;
+            let repository = AvailabilityRepository::new(db.clone());
+            let second = repository
+                .create(schedule_like(&first, "Evenings", false))
+                .await
+                .unwrap();
+            let third = repository
+                .create(schedule_like(&first, "Weekends", false))
+                .await
+                .unwrap();
+            let user_id = first.user_id;
+            let (second_id, third_id) = (second.id.unwrap(), third.id.unwrap());
+
+            let (a, b) = tokio::join!(
+                repository.set_default(&user_id, &second_id),
+                repository.set_default(&user_id, &third_id),
+            );
+            a.unwrap().unwrap();
+            b.unwrap().unwrap();
+
+            let defaults: Vec<_> = repository
+                .find_all_by_user_id(&user_id, false)
+                .await
+                .unwrap()
+                .into_iter()
+                .filter(|availability| availability.is_default)
+                .collect();
+            assert_eq!(defaults.len(), 1);
+            assert_ne!(defaults[0].id, first.id);
+        });
+    }
+
+    #[test]
+    fn startup_gives_legacy_rules_an_id() {
+        with_database(|db| async move {
+            let (_, availability) = test_support::create_host(&db, "Europe/Berlin").await;
+            let id = availability.id.unwrap();
+            db.collection::<Document>("availability")
+                .update_one(doc! { "_id": id }, doc! { "$unset": { "rules.$[].rule_id": "" } }, None)
+                .await
+                .unwrap();
+
+            let repository = AvailabilityRepository::new(db.clone());
+            repository.ensure_indexes().await.unwrap();
+
+            let stored = repository.find_by_id(&id).await.unwrap().unwrap();
+            assert!(!stored.rules.is_empty());
+            assert!(stored.rules.iter().all(|rule| !rule.rule_id.is_empty()));
+            assert_eq!(stored.version, availability.version);
+        });
+    }
+
+    #[test]
+    fn a_second_default_is_created_unflagged() {
+        with_database(|db| async move {
+            let (_, first) = test_support::create_host(&db, "Europe/Berlin").await;
+            let repository = AvailabilityRepository::new(db.clone());
+
+            let second = repository
+                .create(schedule_like(&first, "Evenings", true))
+                .await
+                .unwrap();
+
+            assert!(!second.is_default);
+            let default = repository.find_default_by_user_id(&first.user_id).await.unwrap().unwrap();
+            assert_eq!(default.id, first.id);
+        });
+    }
+}
@@ -0,0 +1,1413 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use chrono_tz::Tz;
+use mongodb::bson::DateTime;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::modules::calendar::calendar_model::{AvailabilityRule, BufferTime, DateOverride, CalendarSettings, EventType, SchedulingWindow};
+use crate::modules::calendar::calendar_schema::{AvailableTimeSlot, SlotConflict};
+use crate::utils::recurrence;
+use crate::utils::time_of_day;
+use crate::utils::timezone;
+
+/// Recorded on availability snapshots. Bump it whenever slot generation changes,
+/// so a drop in slot counts can be told apart from an engine change.
+pub const ENGINE_VERSION: &str = "9";
+
+/// A half-open time window `[start, end)` within a single day.
+pub type TimeWindow = (NaiveTime, NaiveTime);
+
+/// Time taken by a pending or confirmed booking, and the wider window its
+/// buffer keeps free of other meetings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct BookedWindow {
+    pub window: TimeWindow,
+    pub padded: TimeWindow,
+}
+
+/// Stored times are validated on the way in; an unreadable one falls back to
+/// the start of the day so it cannot shrink a range unnoticed.
+pub fn parse_start_time(value: &str) -> NaiveTime {
+    time_of_day::parse(value).unwrap_or_else(|| NaiveTime::from_hms_opt(0, 0, 0).unwrap())
+}
+
+pub fn parse_end_time(value: &str) -> NaiveTime {
+    time_of_day::parse(value).unwrap_or_else(|| NaiveTime::from_hms_opt(23, 59, 59).unwrap())
+}
+
+pub fn to_naive_date(date: &DateTime) -> NaiveDate {
+    to_utc(date).date_naive()
+}
+
+fn to_utc(date: &DateTime) -> chrono::DateTime<Utc> {
+    chrono::DateTime::from_timestamp_millis(date.timestamp_millis()).unwrap_or_default()
+}
+
+pub fn day_of_week(date: NaiveDate) -> String {
+    date.format("%A").to_string().to_lowercase()
+}
+
+/// The host's timezone. Dates, times and weekdays in availability rules,
+/// working hours and bookings are all wall-clock values in it.
+pub fn host_timezone(settings: &CalendarSettings) -> Tz {
+    timezone::resolve_timezone(None, None, Some(&settings.timezone))
+        .map(|(tz, _)| tz)
+        .unwrap_or(Tz::UTC)
+}
+
+/// When a host-local slot starts and ends, or `None` if either end falls in
+/// the hour skipped when the clocks go forward.
+pub fn slot_instants(tz: Tz, date: NaiveDate, start: NaiveTime, end: NaiveTime) -> Option<(chrono::DateTime<Tz>, chrono::DateTime<Tz>)> {
+    Some((timezone::local_instant(tz, date.and_time(start))?, timezone::local_instant(tz, date.and_time(end))?))
+}
+
+/// The instants from the start of `start_day` to the end of `end_day` in the
+/// requester's `viewer_tz`, and the host-local days that range touches.
+pub fn viewer_window(
+    start_day: NaiveDate,
+    end_day: NaiveDate,
+    viewer_tz: Tz,
+    host_tz: Tz,
+) -> ((chrono::DateTime<Utc>, chrono::DateTime<Utc>), (NaiveDate, NaiveDate)) {
+    let from = timezone::start_of_day(viewer_tz, start_day);
+    let until = end_day.succ_opt()
+        .map(|next| timezone::start_of_day(viewer_tz, next))
+        .unwrap_or(chrono::DateTime::<Utc>::MAX_UTC);
+    let host_days = (
+        from.with_timezone(&host_tz).date_naive(),
+        (until - Duration::seconds(1)).with_timezone(&host_tz).date_naive(),
+    );
+    ((from, until), host_days)
+}
+
+/// Keeps the slots starting inside `[from, until)` and stamps each with its
+/// start and end in `viewer_tz`. The host-local date and times stay as they
+/// are, since bookings are made with them.
+pub fn localize_slots(
+    slots: &mut Vec<AvailableTimeSlot>,
+    host_tz: Tz,
+    viewer_tz: Tz,
+    (from, until): (chrono::DateTime<Utc>, chrono::DateTime<Utc>),
+) {
+    slots.retain_mut(|slot| {
+        let Ok(date) = NaiveDate::parse_from_str(&slot.date, "%Y-%m-%d") else {
+            return false;
+        };
+        let start = parse_start_time(&slot.start_time);
+        let end = parse_end_time(&slot.end_time);
+        let Some((starts_at, ends_at)) = slot_instants(host_tz, date, start, end) else {
+            return false;
+        };
+        if starts_at < from || starts_at >= until {
+            return false;
+        }
+        slot.starts_at = Some(starts_at.with_timezone(&viewer_tz).to_rfc3339());
+        slot.ends_at = Some(ends_at.with_timezone(&viewer_tz).to_rfc3339());
+        true
+    });
+}
+
+/// Whether `date` falls inside the rule's start/end date range and, for
+/// recurring rules, on one of its occurrences.
+pub fn rule_covers_date(rule: &AvailabilityRule, date: NaiveDate) -> bool {
+    let rule_start = to_naive_date(&rule.start_date);
+    let rule_end = rule.end_date.as_ref().map(to_naive_date).unwrap_or(NaiveDate::MAX);
+    if date < rule_start || date > rule_end {
+        return false;
+    }
+    match rule.recurrence() {
+        // Rule dates are calendar dates stored as UTC midnights
+        Some(spec) => recurrence::occurrences(&spec, to_utc(&rule.start_date), Tz::UTC, (date, date)).next().is_some(),
+        None => true,
+    }
+}
+
+/// Resolves the windows in which the host is available on `date`.
+///
+/// Rules covering the date are applied from the highest priority down. A slot
+/// (available or not) claims its time range for its priority level, so lower
+/// priority rules can no longer add availability there; this is how an
+/// `is_available: false` slot masks lower-priority availability. Rules sharing
+/// a priority are unioned, so availability wins over unavailability on ties.
+pub fn resolve_day_windows<'a, I>(rules: I, date: NaiveDate) -> Vec<TimeWindow>
+where
+    I: IntoIterator<Item = &'a AvailabilityRule>,
+{
+    let day = day_of_week(date);
+
+    let mut levels: BTreeMap<Reverse<i32>, Vec<&AvailabilityRule>> = BTreeMap::new();
+    for rule in rules {
+        if rule_covers_date(rule, date) {
+            levels.entry(Reverse(rule.priority)).or_default().push(rule);
+        }
+    }
+
+    let mut available: Vec<TimeWindow> = Vec::new();
+    let mut claimed: Vec<TimeWindow> = Vec::new();
+
+    for level in levels.values() {
+        let mut level_claimed = Vec::new();
+        let slots = level
+            .iter()
+            .flat_map(|rule| rule.slots.iter().map(move |slot| (rule, slot)));
+        for (rule, slot) in slots {
+            if rule.slots_follow_weekdays() && slot.day_of_week != day {
+                continue;
+            }
+
+            let window = (parse_start_time(&slot.start_time), parse_end_time(&slot.end_time));
+            if window.0 >= window.1 {
+                continue;
+            }
+
+            if slot.is_available {
+                available.extend(subtract_windows(&[window], &claimed));
+            }
+            level_claimed.push(window);
+        }
+        claimed.extend(level_claimed);
+        claimed = merge_windows(claimed);
+    }
+
+    merge_windows(available)
+}
+
+/// The schedule's override for `date`, if it has one.
+pub fn date_override(overrides: &[DateOverride], date: NaiveDate) -> Option<&DateOverride> {
+    let date = date.format("%Y-%m-%d").to_string();
+    overrides.iter().find(|date_override| date_override.date == date)
+}
+
+/// The windows open on `date`: the date override's when there is one,
+/// otherwise whatever the weekly rules resolve to.
+pub fn day_windows(rules: &[AvailabilityRule], overrides: &[DateOverride], date: NaiveDate) -> Vec<TimeWindow> {
+    match date_override(overrides, date) {
+        Some(date_override) => merge_windows(
+            date_override.slots
+                .iter()
+                .map(|slot| (parse_start_time(&slot.start), parse_end_time(&slot.end)))
+                .filter(|(start, end)| start < end)
+                .collect(),
+        ),
+        None => resolve_day_windows(rules, date),
+    }
+}
+
+/// The highest-priority rule with an unavailable slot overlapping `[start, end)`
+/// on `date`, i.e. the rule that blocks the range if it is blocked at all.
+pub fn masking_rule<'a, I>(rules: I, date: NaiveDate, start: NaiveTime, end: NaiveTime) -> Option<&'a AvailabilityRule>
+where
+    I: IntoIterator<Item = &'a AvailabilityRule>,
+{
+    let day = day_of_week(date);
+    rules
+        .into_iter()
+        .filter(|rule| rule_covers_date(rule, date))
+        .filter(|rule| {
+            rule.slots.iter().any(|slot| {
+                !slot.is_available
+                    && (!rule.slots_follow_weekdays() || slot.day_of_week == day)
+                    && parse_start_time(&slot.start_time) < end
+                    && parse_end_time(&slot.end_time) > start
+            })
+        })
+        .max_by_key(|rule| rule.priority)
+}
+
+/// Sorts windows and coalesces any that overlap or touch.
+pub fn merge_windows(mut windows: Vec<TimeWindow>) -> Vec<TimeWindow> {
+    windows.sort();
+    let mut merged: Vec<TimeWindow> = Vec::with_capacity(windows.len());
+    for (start, end) in windows {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+/// Removes every part of `windows` covered by `mask`.
+pub fn subtract_windows(windows: &[TimeWindow], mask: &[TimeWindow]) -> Vec<TimeWindow> {
+    let mut remaining = windows.to_vec();
+    for &(mask_start, mask_end) in mask {
+        remaining = remaining
+            .into_iter()
+            .flat_map(|(start, end)| {
+                if mask_end <= start || mask_start >= end {
+                    return vec![(start, end)];
+                }
+                let mut pieces = Vec::new();
+                if start < mask_start {
+                    pieces.push((start, mask_start));
+                }
+                if mask_end < end {
+                    pieces.push((mask_end, end));
+                }
+                pieces
+            })
+            .collect();
+    }
+    remaining
+}
+
+/// Duration and buffer that apply to an event type on a given weekday.
+#[derive(Debug, Clone)]
+pub struct DayConfig {
+    pub duration: i32,
+    pub buffer_time: Option<BufferTime>,  // None falls back to the calendar settings
+}
+
+/// Applies the event type's override for `weekday` ("monday", ...) on top of its defaults.
+pub fn resolve_day_config(event_type: &EventType, weekday: &str) -> DayConfig {
+    let day_override = event_type.day_overrides.as_ref().and_then(|overrides| overrides.get(weekday));
+    DayConfig {
+        duration: day_override
+            .and_then(|o| o.duration)
+            .unwrap_or(event_type.duration),
+        buffer_time: day_override
+            .and_then(|o| o.buffer_time.clone())
+            .or_else(|| event_type.buffer_time.clone()),
+    }
+}
+
+/// The buffer kept free around a booking of `event_type` on `date` (YYYY-MM-DD):
+/// the event type's, including its day override, when set, otherwise the calendar settings'.
+pub fn booking_buffer(event_type: Option<&EventType>, date: &str, default_buffer: &BufferTime) -> BufferTime {
+    let weekday = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map(day_of_week)
+        .unwrap_or_default();
+    event_type
+        .and_then(|event_type| resolve_day_config(event_type, &weekday).buffer_time)
+        .unwrap_or_else(|| default_buffer.clone())
+}
+
+/// The time a booking or hold of `event_type` on `date` from `start_time` to
+/// `end_time` takes, padded by its buffer.
+pub fn booked_window(
+    event_type: Option<&EventType>,
+    date: &str,
+    start_time: &str,
+    end_time: &str,
+    default_buffer: &BufferTime,
+) -> BookedWindow {
+    let window = (parse_start_time(start_time), parse_end_time(end_time));
+    let buffer_time = booking_buffer(event_type, date, default_buffer);
+    BookedWindow { window, padded: pad_window(window, &buffer_time) }
+}
+
+/// Carves bookable slots of `duration` minutes out of the given windows,
+/// reserving the buffer before and after each slot. Slots start every
+/// `interval` minutes when given, and may then overlap; otherwise back to back.
+pub fn generate_slots(
+    windows: &[TimeWindow],
+    date: NaiveDate,
+    duration: i32,
+    buffer_time: &BufferTime,
+    interval: Option<i32>,
+) -> Vec<AvailableTimeSlot> {
+    let mut slots = Vec::new();
+    let total_duration = Duration::minutes((duration + buffer_time.before + buffer_time.after) as i64);
+
+    for &(window_start, window_end) in windows {
+        let mut current_time = window_start;
+
+        loop {
+            // Stop instead of wrapping past midnight
+            let (block_end, wrapped) = current_time.overflowing_add_signed(total_duration);
+            if wrapped != 0 || block_end > window_end {
+                break;
+            }
+
+            let actual_start = current_time + Duration::minutes(buffer_time.before as i64);
+            let actual_end = actual_start + Duration::minutes(duration as i64);
+
+            slots.push(AvailableTimeSlot {
+                date: date.format("%Y-%m-%d").to_string(),
+                start_time: actual_start.format("%H:%M").to_string(),
+                end_time: actual_end.format("%H:%M").to_string(),
+                spots_remaining: None,
+                starts_at: None,
+                ends_at: None,
+            });
+
+            // Move to next slot including buffer after, or by the interval
+            current_time = match interval {
+                Some(interval) => {
+                    let (next, wrapped) = current_time.overflowing_add_signed(Duration::minutes(interval.max(1) as i64));
+                    if wrapped != 0 {
+                        break;
+                    }
+                    next
+                }
+                None => block_end,
+            };
+        }
+    }
+
+    slots
+}
+
+/// Sorts slots by date and start time and drops duplicates. Unless
+/// `allow_overlap` (slots on a start interval), also drops any slot
+/// overlapping an earlier one on the same day, so the same moment is never
+/// offered twice.
+pub fn normalize_slots(mut slots: Vec<AvailableTimeSlot>, allow_overlap: bool) -> Vec<AvailableTimeSlot> {
+    slots.sort();
+    let mut normalized: Vec<AvailableTimeSlot> = Vec::with_capacity(slots.len());
+    for slot in slots {
+        let overlaps_previous = normalized.last().is_some_and(|last| {
+            last.date == slot.date
+                && (last.start_time == slot.start_time || (!allow_overlap && slot.start_time < last.end_time))
+        });
+        if !overlaps_previous {
+            normalized.push(slot);
+        }
+    }
+    normalized
+}
+
+/// Drops group event slots that are full and tells invitees how many places
+/// are left in the rest. `seats_taken` is keyed by (YYYY-MM-DD, HH:mm).
+pub fn apply_capacity(
+    slots: &mut Vec<AvailableTimeSlot>,
+    capacity: i32,
+    seats_taken: &HashMap<(String, String), u32>,
+) {
+    let capacity = capacity.max(1) as u32;
+    slots.retain_mut(|slot| {
+        let taken = seats_taken
+            .get(&(slot.date.clone(), slot.start_time.clone()))
+            .copied()
+            .unwrap_or(0);
+        slot.spots_remaining = Some(capacity.saturating_sub(taken));
+        taken < capacity
+    });
+}
+
+/// Widens `window` by the buffer on each side, clamped to the same day.
+pub fn pad_window((start, end): TimeWindow, buffer_time: &BufferTime) -> TimeWindow {
+    let (padded_start, wrapped) = start.overflowing_sub_signed(Duration::minutes(buffer_time.before as i64));
+    let padded_start = if wrapped != 0 { NaiveTime::MIN } else { padded_start };
+    let (padded_end, wrapped) = end.overflowing_add_signed(Duration::minutes(buffer_time.after as i64));
+    let padded_end = if wrapped != 0 { NaiveTime::from_hms_opt(23, 59, 59).unwrap() } else { padded_end };
+    (padded_start, padded_end)
+}
+
+/// Drops slots that overlap a booking once either side's buffer is added, so
+/// a partial overlap removes the slot as well as an exact match. Buffers may
+/// overlap each other but never a meeting.
+pub fn exclude_booked(
+    slots: Vec<AvailableTimeSlot>,
+    booked: &[BookedWindow],
+    buffer_time: &BufferTime,
+) -> Vec<AvailableTimeSlot> {
+    if booked.is_empty() {
+        return slots;
+    }
+
+    slots
+        .into_iter()
+        .filter(|slot| {
+            let window = (parse_start_time(&slot.start_time), parse_end_time(&slot.end_time));
+            !booked.iter().any(|booked| booked_conflicts(booked, window, buffer_time))
+        })
+        .collect()
+}
+
+/// Whether a meeting in `window` with `buffer_time` around it clashes with
+/// `booked`: its buffer reaches into the booking, or the booking's buffer reaches into it.
+pub fn booked_conflicts(booked: &BookedWindow, window: TimeWindow, buffer_time: &BufferTime) -> bool {
+    let overlaps = |(a_start, a_end): TimeWindow, (b_start, b_end): TimeWindow| a_start < b_end && b_start < a_end;
+    overlaps(pad_window(window, buffer_time), booked.window) || overlaps(window, booked.padded)
+}
+
+/// The parts of the day covered by both `a` and `b`. Both must be merged
+/// (sorted, non-overlapping), as `resolve_day_windows` returns them.
+pub fn intersect_windows(a: &[TimeWindow], b: &[TimeWindow]) -> Vec<TimeWindow> {
+    let mut intersection = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        let start = a[i].0.max(b[j].0);
+        let end = a[i].1.min(b[j].1);
+        if start < end {
+            intersection.push((start, end));
+        }
+        // Whichever window ends first cannot overlap anything further
+        if a[i].1 <= b[j].1 {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    intersection
+}
+
+/// Bookable slots from `start_day` to `end_day` inclusive in the time every
+/// one of `rule_sets` makes available, one set per host, minus time taken by
+/// `booked` (keyed by YYYY-MM-DD). Slot length, buffer and start interval come
+/// from `event_type` (per weekday) when given, otherwise from
+/// `default_duration` and the calendar settings.
+pub fn collect_slots(
+    schedules: &[(&[AvailabilityRule], &[DateOverride])],
+    start_day: NaiveDate,
+    end_day: NaiveDate,
+    event_type: Option<&EventType>,
+    default_duration: i32,
+    settings: &CalendarSettings,
+    booked: &HashMap<String, Vec<BookedWindow>>,
+) -> Vec<AvailableTimeSlot> {
+    let default_buffer = &settings.buffer_time;
+    let host_tz = host_timezone(settings);
+    let interval = event_type
+        .and_then(|event_type| event_type.slot_interval)
+        .or(settings.slot_interval);
+    let mut available_slots = Vec::new();
+    let mut current_date = start_day;
+    while current_date <= end_day {
+        let (duration, buffer_time) = match event_type {
+            Some(event_type) => {
+                let day_config = resolve_day_config(event_type, &day_of_week(current_date));
+                (
+                    day_config.duration,
+                    day_config.buffer_time.unwrap_or_else(|| default_buffer.clone()),
+                )
+            }
+            None => (default_duration, default_buffer.clone()),
+        };
+
+        let windows = schedules
+            .iter()
+            .map(|(rules, overrides)| day_windows(rules, overrides, current_date))
+            .reduce(|common, windows| intersect_windows(&common, &windows))
+            .unwrap_or_default();
+        let mut day_slots = generate_slots(&windows, current_date, duration, &buffer_time, interval);
+        // Wall-clock times skipped by a daylight saving change never happen
+        day_slots.retain(|slot| {
+            let start = parse_start_time(&slot.start_time);
+            let end = parse_end_time(&slot.end_time);
+            slot_instants(host_tz, current_date, start, end).is_some()
+        });
+        let day_booked = booked
+            .get(&current_date.format("%Y-%m-%d").to_string())
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        available_slots.extend(exclude_booked(day_slots, day_booked, &buffer_time));
+
+        match current_date.succ_opt() {
+            Some(next) => current_date = next,
+            None => break,
+        }
+    }
+
+    // Sort, dedupe and drop overlapping slots
+    normalize_slots(available_slots, interval.is_some())
+}
+
+/// Whether `[start, end)` fits entirely inside one of the resolved windows.
+pub fn window_contains(windows: &[TimeWindow], start: NaiveTime, end: NaiveTime) -> bool {
+    windows.iter().any(|&(window_start, window_end)| start >= window_start && end <= window_end)
+}
+
+/// How far ahead invitees may book, set by the operator.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct BookingHorizon {
+    pub default_days: i64,   // Applies to event types without a max_booking_notice
+    pub hard_max_days: i64,  // No event type may allow booking further out
+}
+
+impl BookingHorizon {
+    pub fn hard_max_minutes(&self) -> i64 {
+        self.hard_max_days * 24 * 60
+    }
+
+    /// The maximum notice in minutes for `event_type`: its own setting capped
+    /// at the hard maximum, or the default when it has none. Older event types
+    /// may store values above today's cap, which are clamped here.
+    pub fn max_notice_minutes(&self, event_type: &EventType) -> i64 {
+        match event_type.max_booking_notice {
+            Some(max_notice) => (max_notice.max(0) as i64).min(self.hard_max_minutes()),
+            None => self.default_days * 24 * 60,
+        }
+    }
+}
+
+/// Why a slot starting at `starts_at` falls outside the event type's booking
+/// notice, if it does. `now` and `starts_at` are both in the host's timezone.
+pub fn notice_conflict(
+    event_type: &EventType,
+    horizon: &BookingHorizon,
+    starts_at: NaiveDateTime,
+    now: NaiveDateTime,
+) -> Option<SlotConflict> {
+    if let Some(min_notice) = event_type.min_booking_notice
+        && starts_at < now + Duration::minutes(min_notice.max(0) as i64)
+    {
+        return Some(SlotConflict::new(
+            "below_min_notice",
+            &format!("Bookings need at least {} minutes notice", min_notice),
+        ));
+    }
+    let max_notice = horizon.max_notice_minutes(event_type);
+    if starts_at > now + Duration::minutes(max_notice) {
+        return Some(SlotConflict::new(
+            "beyond_max_notice",
+            &format!("Bookings can be made at most {} minutes in advance", max_notice),
+        ));
+    }
+    None
+}
+
+/// First and last date the event type's scheduling window allows, given
+/// today's date in the host's timezone. None when it has no window.
+pub fn scheduling_window_bounds(event_type: &EventType, today: NaiveDate) -> Option<(NaiveDate, NaiveDate)> {
+    match event_type.scheduling_window.as_ref()? {
+        SchedulingWindow::Rolling { days } => {
+            let last = today + Duration::days((*days).max(1) as i64 - 1);
+            Some((today, last))
+        }
+        SchedulingWindow::Range { start, end } => {
+            let start = NaiveDate::parse_from_str(start, "%Y-%m-%d").ok()?;
+            let end = NaiveDate::parse_from_str(end, "%Y-%m-%d").ok()?;
+            Some((start, end))
+        }
+    }
+}
+
+/// Narrows `[start_day, end_day]` to the event type's scheduling window. The
+/// result is empty (start after end) when they do not overlap.
+pub fn clamp_to_scheduling_window(
+    event_type: &EventType,
+    start_day: NaiveDate,
+    end_day: NaiveDate,
+    today: NaiveDate,
+) -> (NaiveDate, NaiveDate) {
+    match scheduling_window_bounds(event_type, today) {
+        Some((first, last)) => (start_day.max(first), end_day.min(last)),
+        None => (start_day, end_day),
+    }
+}
+
+/// Why `date` cannot be booked under the event type's scheduling window, if it cannot.
+pub fn scheduling_window_conflict(event_type: &EventType, date: NaiveDate, today: NaiveDate) -> Option<SlotConflict> {
+    let (first, last) = scheduling_window_bounds(event_type, today)?;
+    if date < first || date > last {
+        return Some(SlotConflict::new(
+            "outside_scheduling_window",
+            &format!("Bookings are only open from {} to {}", first.format("%Y-%m-%d"), last.format("%Y-%m-%d")),
+        ));
+    }
+    None
+}
+
+/// Drops slots outside the event type's booking notice; see `notice_conflict`.
+pub fn retain_within_notice(
+    slots: &mut Vec<AvailableTimeSlot>,
+    event_type: &EventType,
+    horizon: &BookingHorizon,
+    now: NaiveDateTime,
+) {
+    slots.retain(|slot| {
+        NaiveDate::parse_from_str(&slot.date, "%Y-%m-%d")
+            .map(|date| date.and_time(parse_start_time(&slot.start_time)))
+            .is_ok_and(|starts_at| notice_conflict(event_type, horizon, starts_at, now).is_none())
+    });
+}
+
+/// Checks `[start_time, end_time)` on `date` against the host's working hours
+/// and availability rules, recording why it is unavailable in `conflicts`.
+/// A date override replaces both for its date.
+pub fn is_slot_available(
+    date: &str,
+    start_time: &str,
+    end_time: &str,
+    settings: &CalendarSettings,
+    rules: &[AvailabilityRule],
+    overrides: &[DateOverride],
+    conflicts: &mut Vec<SlotConflict>,
+) -> bool {
+    if let Ok(slot_date) = NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+        let host_tz = host_timezone(settings);
+        if slot_instants(host_tz, slot_date, parse_start_time(start_time), parse_end_time(end_time)).is_none() {
+            conflicts.push(SlotConflict::new(
+                "skipped_by_clock_change",
+                &format!("Time slot falls in the hour skipped by the daylight saving change in {}", host_tz.name()),
+            ));
+            return false;
+        }
+    }
+
+    if let Ok(slot_date) = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        && let Some(date_override) = date_override(overrides, slot_date)
+    {
+        let windows = day_windows(rules, overrides, slot_date);
+        if window_contains(&windows, parse_start_time(start_time), parse_end_time(end_time)) {
+            return true;
+        }
+        let conflict = if date_override.slots.is_empty() {
+            SlotConflict::new("blocked_by_date_override", "This date is blocked in your schedule")
+        } else {
+            SlotConflict::new("outside_date_override", "Time slot is outside the hours set for this date")
+        };
+        conflicts.push(conflict);
+        return false;
+    }
+
+    // Check if date is within working hours
+    let day_of_week = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .ok()
+        .map(|d| d.format("%A").to_string().to_lowercase())
+        .unwrap_or_default();
+
+    if let Some(working_hours) = settings.working_hours.get(&day_of_week) {
+        if working_hours.is_empty() {
+            conflicts.push(SlotConflict::new("no_working_hours", "No working hours set for this day"));
+            return false;
+        }
+
+        // Check if time slot is within working hours
+        let slot_start = parse_start_time(start_time);
+        let slot_end = parse_end_time(end_time);
+
+        let is_within_working_hours = working_hours.iter().any(|wh| {
+            slot_start >= parse_start_time(&wh.start) && slot_end <= parse_end_time(&wh.end)
+        });
+
+        if !is_within_working_hours {
+            conflicts.push(SlotConflict::new("outside_working_hours", "Time slot is outside working hours"));
+            return false;
+        }
+    } else {
+        conflicts.push(SlotConflict::new("no_working_hours", "No working hours set for this day"));
+        return false;
+    }
+
+    // Check if time slot is within availability rules, honoring rule priority
+    let Ok(slot_date) = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d") else {
+        conflicts.push(SlotConflict::new("outside_availability", "Time slot is not available in your schedule"));
+        return false;
+    };
+    let slot_start = parse_start_time(start_time);
+    let slot_end = parse_end_time(end_time);
+
+    let windows = resolve_day_windows(rules, slot_date);
+    if !window_contains(&windows, slot_start, slot_end) {
+        let masking_rule = masking_rule(rules, slot_date, slot_start, slot_end);
+        let conflict = match masking_rule {
+            Some(rule) => SlotConflict {
+                rule_id: Some(rule.rule_id.clone()).filter(|id| !id.is_empty()),
+                ..SlotConflict::new("blocked_by_rule", "Time slot is blocked by an availability rule")
+            },
+            None => SlotConflict::new("outside_availability", "Time slot is not available in your schedule"),
+        };
+        conflicts.push(conflict);
+        return false;
+    }
+
+    true
+}
+
+// Weights for `recommend_slots`; a slot's score is the sum of its bonuses.
+const RECOMMEND_ADJACENT_WEIGHT: f64 = 3.0;     // Slot touches an existing booking
+const RECOMMEND_TIME_OF_DAY_WEIGHT: f64 = 2.0;  // Slot starts near a preferred time
+const RECOMMEND_EARLINESS_WEIGHT: f64 = 1.5;    // Slot is on an earlier date
+const RECOMMEND_ADJACENT_TOLERANCE_MINUTES: i64 = 15;
+const RECOMMEND_PREFERRED_TIMES: [(u32, u32); 2] = [(10, 30), (14, 30)];  // Mid-morning, mid-afternoon
+const RECOMMEND_TIME_OF_DAY_RANGE_MINUTES: f64 = 180.0;  // Bonus fades to zero this far from a preferred time
+
+/// Picks the `count` best slots from `slots`: ones next to existing bookings
+/// (less fragmentation), near mid-morning or mid-afternoon, and on earlier
+/// dates. Deterministic; ties go to the earlier slot.
+pub fn recommend_slots(
+    slots: &[AvailableTimeSlot],
+    booked: &HashMap<String, Vec<BookedWindow>>,
+    count: usize,
+) -> Vec<AvailableTimeSlot> {
+    let first_date = slots.iter().map(|slot| slot.date.as_str()).min();
+    let last_date = slots.iter().map(|slot| slot.date.as_str()).max();
+    let parse = |date: &str| NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap_or_default();
+    let (Some(first_date), Some(last_date)) = (first_date.map(parse), last_date.map(parse)) else {
+        return Vec::new();
+    };
+    let span_days = ((last_date - first_date).num_days().max(1)) as f64;
+
+    let mut scored: Vec<(f64, &AvailableTimeSlot)> = slots
+        .iter()
+        .map(|slot| {
+            let start = parse_start_time(&slot.start_time);
+            let end = parse_end_time(&slot.end_time);
+            let tolerance = Duration::minutes(RECOMMEND_ADJACENT_TOLERANCE_MINUTES);
+
+            let adjacent = booked.get(&slot.date).is_some_and(|windows| {
+                windows.iter().any(|&BookedWindow { window: (booked_start, booked_end), .. }| {
+                    (start >= booked_end && start - booked_end <= tolerance)
+                        || (end <= booked_start && booked_start - end <= tolerance)
+                })
+            });
+
+            let distance = RECOMMEND_PREFERRED_TIMES
+                .iter()
+                .filter_map(|&(hour, minute)| NaiveTime::from_hms_opt(hour, minute, 0))
+                .map(|preferred| (start - preferred).num_minutes().abs() as f64)
+                .fold(f64::MAX, f64::min);
+            let time_of_day = (1.0 - distance / RECOMMEND_TIME_OF_DAY_RANGE_MINUTES).max(0.0);
+
+            let days_out = (parse(&slot.date) - first_date).num_days() as f64;
+            let earliness = 1.0 - days_out / span_days;
+
+            let score = if adjacent { RECOMMEND_ADJACENT_WEIGHT } else { 0.0 }
+                + RECOMMEND_TIME_OF_DAY_WEIGHT * time_of_day
+                + RECOMMEND_EARLINESS_WEIGHT * earliness;
+            (score, slot)
+        })
+        .collect();
+
+    // Stable sort keeps the chronological order of `slots` among equal scores
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+    scored.into_iter().take(count).map(|(_, slot)| slot.clone()).collect()
+}
+
+/// Fingerprint of everything a slot computation depends on, as hex SHA-256.
+/// JSON objects serialize with sorted keys and every list whose order does
+/// not change the slots is sorted, so equal inputs always hash the same, on
+/// any build and across restarts.
+pub fn inputs_hash(
+    rules: &[AvailabilityRule],
+    overrides: &[DateOverride],
+    settings: &CalendarSettings,
+    event_type: &EventType,
+    booked: &HashMap<String, Vec<BookedWindow>>,
+) -> String {
+    let working_hours: BTreeMap<&String, Vec<_>> = settings.working_hours
+        .iter()
+        .map(|(day, slots)| {
+            let mut slots: Vec<_> = slots.iter().map(|slot| (&slot.start, &slot.end)).collect();
+            slots.sort();
+            (day, slots)
+        })
+        .collect();
+    let booked: BTreeMap<&String, Vec<BookedWindow>> = booked
+        .iter()
+        .map(|(date, windows)| {
+            let mut windows = windows.clone();
+            windows.sort();
+            (date, windows)
+        })
+        .collect();
+    let inputs = serde_json::json!({
+        "rules": sorted_json(rules),
+        "date_overrides": sorted_json(overrides),
+        "timezone": settings.timezone,
+        "working_hours": working_hours,
+        "buffer_time": settings.buffer_time,
+        "slot_interval": settings.slot_interval,
+        "max_meeting_minutes_per_day": settings.max_meeting_minutes_per_day,
+        "event_type": {
+            "duration": event_type.duration,
+            "buffer_time": event_type.buffer_time,
+            "min_booking_notice": event_type.min_booking_notice,
+            "max_booking_notice": event_type.max_booking_notice,
+            "scheduling_window": event_type.scheduling_window,
+            "slot_interval": event_type.slot_interval,
+        },
+        "booked": booked,
+    });
+
+    Sha256::digest(inputs.to_string().as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// `items` as JSON, in the order of their serialized form. Rules sharing a
+/// priority are unioned and overrides are keyed by date, so their order
+/// never matters.
+fn sorted_json<T: Serialize>(items: &[T]) -> Vec<serde_json::Value> {
+    let mut values: Vec<serde_json::Value> = items.iter().map(|item| serde_json::json!(item)).collect();
+    values.sort_by_cached_key(|value| value.to_string());
+    values
+}
+
+/// Number of slots on each YYYY-MM-DD date.
+pub fn count_slots_per_day(slots: &[AvailableTimeSlot]) -> BTreeMap<String, u32> {
+    let mut counts = BTreeMap::new();
+    for slot in slots {
+        *counts.entry(slot.date.clone()).or_insert(0) += 1;
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mongodb::bson::oid::ObjectId;
+
+    use crate::modules::calendar::calendar_model::{Availability, AvailabilitySlot, DayOverride, TimeSlot};
+    use crate::test_support;
+
+    const NEW_YORK: Tz = chrono_tz::America::New_York;
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    fn utc(value: &str) -> chrono::DateTime<Utc> {
+        chrono::DateTime::parse_from_rfc3339(value).unwrap().with_timezone(&Utc)
+    }
+
+    fn settings(timezone: &str) -> CalendarSettings {
+        CalendarSettings {
+            id: None,
+            user_id: ObjectId::new(),
+            timezone: timezone.to_string(),
+            working_hours: HashMap::new(),
+            buffer_time: BufferTime { before: 0, after: 0 },
+            default_meeting_duration: 30,
+            slot_interval: None,
+            max_meeting_minutes_per_day: None,
+            calendar_name: "Test calendar".to_string(),
+            date_format: "YYYY-MM-DD".to_string(),
+            time_format: "24h".to_string(),
+            public_page_enabled: true,
+            public_page_message: None,
+            diagnostics_until: None,
+            created_at: DateTime::now(),
+            updated_at: DateTime::now(),
+        }
+    }
+
+    /// A weekly rule open on Sundays from `start` to `end`.
+    fn sunday_rule(start: &str, end: &str) -> AvailabilityRule {
+        let slot = AvailabilitySlot {
+            day_of_week: "sunday".to_string(),
+            start_time: start.to_string(),
+            end_time: end.to_string(),
+            is_available: true,
+        };
+        AvailabilityRule::new("2026-01-01T00:00:00Z", None, true, Some("weekly".to_string()), vec![slot], 0).unwrap()
+    }
+
+    fn slot(date: &str, start_time: &str, end_time: &str) -> AvailableTimeSlot {
+        AvailableTimeSlot {
+            date: date.to_string(),
+            start_time: start_time.to_string(),
+            end_time: end_time.to_string(),
+            spots_remaining: None,
+            starts_at: None,
+            ends_at: None,
+        }
+    }
+
+    fn start_times(slots: &[AvailableTimeSlot]) -> Vec<&str> {
+        slots.iter().map(|slot| slot.start_time.as_str()).collect()
+    }
+
+    #[test]
+    fn viewer_window_spans_the_short_spring_forward_day() {
+        let ((from, until), host_days) = viewer_window(date("2026-03-08"), date("2026-03-08"), NEW_YORK, Tz::UTC);
+
+        assert_eq!(from, utc("2026-03-08T05:00:00Z"));
+        assert_eq!(until, utc("2026-03-09T04:00:00Z"));
+        assert_eq!(until - from, Duration::hours(23));
+        assert_eq!(host_days, (date("2026-03-08"), date("2026-03-09")));
+    }
+
+    #[test]
+    fn viewer_window_spans_the_long_fall_back_day() {
+        let ((from, until), host_days) = viewer_window(date("2026-11-01"), date("2026-11-01"), NEW_YORK, Tz::UTC);
+
+        assert_eq!(from, utc("2026-11-01T04:00:00Z"));
+        assert_eq!(until, utc("2026-11-02T05:00:00Z"));
+        assert_eq!(until - from, Duration::hours(25));
+        assert_eq!(host_days, (date("2026-11-01"), date("2026-11-02")));
+    }
+
+    #[test]
+    fn viewer_window_maps_to_the_hosts_days() {
+        // A Tokyo day runs from Sunday morning to Monday morning in New York
+        let tokyo: Tz = chrono_tz::Asia::Tokyo;
+        let ((from, until), host_days) = viewer_window(date("2026-03-09"), date("2026-03-09"), tokyo, NEW_YORK);
+
+        assert_eq!(from, utc("2026-03-08T15:00:00Z"));
+        assert_eq!(until, utc("2026-03-09T15:00:00Z"));
+        assert_eq!(host_days, (date("2026-03-08"), date("2026-03-09")));
+    }
+
+    #[test]
+    fn collect_slots_skips_the_missing_spring_forward_hour() {
+        let rules = [sunday_rule("00:00", "04:00")];
+        let slots = collect_slots(
+            &[(&rules, &[])],
+            date("2026-03-08"),
+            date("2026-03-08"),
+            None,
+            30,
+            &settings("America/New_York"),
+            &HashMap::new(),
+        );
+
+        // 01:30 would end at 02:00, which does not exist either
+        assert_eq!(start_times(&slots), ["00:00", "00:30", "01:00", "03:00", "03:30"]);
+    }
+
+    #[test]
+    fn collect_slots_offer_the_repeated_fall_back_hour_once() {
+        let rules = [sunday_rule("00:00", "04:00")];
+        let slots = collect_slots(
+            &[(&rules, &[])],
+            date("2026-11-01"),
+            date("2026-11-01"),
+            None,
+            30,
+            &settings("America/New_York"),
+            &HashMap::new(),
+        );
+
+        assert_eq!(start_times(&slots), ["00:00", "00:30", "01:00", "01:30", "02:00", "02:30", "03:00", "03:30"]);
+    }
+
+    #[test]
+    fn localize_slots_across_spring_forward() {
+        let london: Tz = chrono_tz::Europe::London;
+        let (window, _) = viewer_window(date("2026-03-08"), date("2026-03-08"), london, NEW_YORK);
+        let mut slots = vec![
+            slot("2026-03-08", "01:00", "01:30"),
+            slot("2026-03-08", "02:30", "03:00"),
+            slot("2026-03-08", "03:00", "03:30"),
+            slot("2026-03-08", "19:30", "20:00"),
+            slot("2026-03-08", "20:00", "20:30"),
+        ];
+
+        localize_slots(&mut slots, NEW_YORK, london, window);
+
+        // 02:30 never happens in New York; 20:00 there is already Monday in London
+        assert_eq!(start_times(&slots), ["01:00", "03:00", "19:30"]);
+        let starts_at: Vec<_> = slots.iter().map(|slot| slot.starts_at.as_deref().unwrap()).collect();
+        assert_eq!(starts_at, ["2026-03-08T06:00:00+00:00", "2026-03-08T07:00:00+00:00", "2026-03-08T23:30:00+00:00"]);
+        assert_eq!(slots[1].ends_at.as_deref(), Some("2026-03-08T07:30:00+00:00"));
+    }
+
+    #[test]
+    fn localize_slots_across_fall_back() {
+        let (window, _) = viewer_window(date("2026-11-01"), date("2026-11-01"), Tz::UTC, NEW_YORK);
+        let mut slots = vec![
+            slot("2026-11-01", "00:30", "01:00"),
+            slot("2026-11-01", "01:30", "02:00"),
+            slot("2026-11-01", "02:00", "02:30"),
+        ];
+
+        localize_slots(&mut slots, NEW_YORK, Tz::UTC, window);
+
+        // The repeated 01:30 is the first one, still on daylight saving time
+        let localized: Vec<_> = slots
+            .iter()
+            .map(|slot| (slot.starts_at.as_deref().unwrap(), slot.ends_at.as_deref().unwrap()))
+            .collect();
+        assert_eq!(localized, [
+            ("2026-11-01T04:30:00+00:00", "2026-11-01T05:00:00+00:00"),
+            ("2026-11-01T05:30:00+00:00", "2026-11-01T07:00:00+00:00"),
+            ("2026-11-01T07:00:00+00:00", "2026-11-01T07:30:00+00:00"),
+        ]);
+    }
+
+    /// A rule open 09:00 to 10:00, on `day_of_week` where the pattern follows weekdays.
+    fn rule(start: &str, end: Option<&str>, pattern: Option<&str>, day_of_week: &str) -> AvailabilityRule {
+        let slot = AvailabilitySlot {
+            day_of_week: day_of_week.to_string(),
+            start_time: "09:00".to_string(),
+            end_time: "10:00".to_string(),
+            is_available: true,
+        };
+        let start = format!("{}T00:00:00Z", start);
+        let end = end.map(|end| format!("{}T00:00:00Z", end));
+        AvailabilityRule::new(&start, end.as_deref(), pattern.is_some(), pattern.map(str::to_string), vec![slot], 0).unwrap()
+    }
+
+    fn open_dates<'a>(rule: &AvailabilityRule, dates: &[&'a str]) -> Vec<&'a str> {
+        dates
+            .iter()
+            .copied()
+            .filter(|day| !resolve_day_windows([rule], date(day)).is_empty())
+            .collect()
+    }
+
+    #[test]
+    fn one_off_rules_apply_between_their_dates() {
+        let rule = rule("2026-03-02", Some("2026-03-15"), None, "monday");
+        let dates = ["2026-02-23", "2026-03-02", "2026-03-03", "2026-03-09", "2026-03-16"];
+        assert_eq!(open_dates(&rule, &dates), ["2026-03-02", "2026-03-09"]);
+    }
+
+    #[test]
+    fn weekly_rules_apply_on_their_weekdays_every_week() {
+        let rule = rule("2026-03-02", None, Some("weekly"), "monday");
+        let dates = ["2026-02-23", "2026-03-02", "2026-03-09", "2026-03-10", "2027-03-01"];
+        assert_eq!(open_dates(&rule, &dates), ["2026-03-02", "2026-03-09", "2027-03-01"]);
+    }
+
+    #[test]
+    fn biweekly_rules_apply_every_other_week() {
+        let rule = rule("2026-03-02", None, Some("biweekly"), "monday");
+        let dates = ["2026-03-02", "2026-03-09", "2026-03-16", "2026-03-23", "2026-03-30"];
+        assert_eq!(open_dates(&rule, &dates), ["2026-03-02", "2026-03-16", "2026-03-30"]);
+    }
+
+    #[test]
+    fn daily_rules_apply_every_day_whatever_the_weekday() {
+        let rule = rule("2026-03-02", None, Some("daily"), "monday");
+        let dates = ["2026-03-01", "2026-03-02", "2026-03-03", "2026-03-07", "2026-12-25"];
+        assert_eq!(open_dates(&rule, &dates), ["2026-03-02", "2026-03-03", "2026-03-07", "2026-12-25"]);
+    }
+
+    #[test]
+    fn monthly_rules_apply_on_the_start_day_of_the_month() {
+        let rule = rule("2026-03-15", None, Some("monthly"), "monday");
+        let dates = ["2026-02-15", "2026-03-15", "2026-04-14", "2026-04-15", "2026-04-16", "2026-05-15"];
+        assert_eq!(open_dates(&rule, &dates), ["2026-03-15", "2026-04-15", "2026-05-15"]);
+    }
+
+    #[test]
+    fn monthly_rules_from_the_31st_fall_on_the_last_day_of_short_months() {
+        let rule = rule("2026-01-31", None, Some("monthly"), "monday");
+        let dates = [
+            "2026-01-30", "2026-01-31", "2026-02-27", "2026-02-28", "2026-03-30", "2026-03-31",
+            "2026-04-30", "2026-05-31", "2028-02-28", "2028-02-29",
+        ];
+        assert_eq!(open_dates(&rule, &dates), [
+            "2026-01-31", "2026-02-28", "2026-03-31", "2026-04-30", "2026-05-31", "2028-02-29",
+        ]);
+    }
+
+    #[test]
+    fn unknown_patterns_are_rejected() {
+        let error = AvailabilityRule::new("2026-03-02T00:00:00Z", None, true, Some("yearly".to_string()), Vec::new(), 0)
+            .unwrap_err();
+        assert!(error.contains("Unknown recurrence pattern 'yearly'"));
+    }
+
+    /// A weekly Sunday rule at `priority` with `(start, end, is_available)` slots.
+    fn priority_rule(priority: i32, slots: &[(&str, &str, bool)]) -> AvailabilityRule {
+        let slots = slots
+            .iter()
+            .map(|&(start, end, is_available)| AvailabilitySlot {
+                day_of_week: "sunday".to_string(),
+                start_time: start.to_string(),
+                end_time: end.to_string(),
+                is_available,
+            })
+            .collect();
+        AvailabilityRule::new("2026-01-01T00:00:00Z", None, true, Some("weekly".to_string()), slots, priority).unwrap()
+    }
+
+    fn windows(values: &[(&str, &str)]) -> Vec<TimeWindow> {
+        values.iter().map(|&(start, end)| (parse_start_time(start), parse_end_time(end))).collect()
+    }
+
+    #[test]
+    fn higher_priority_unavailability_masks_lower_priority_availability() {
+        let rules = [
+            priority_rule(0, &[("09:00", "17:00", true)]),
+            priority_rule(10, &[("12:00", "13:00", false)]),
+        ];
+
+        let open = resolve_day_windows(&rules, date("2026-03-01"));
+
+        assert_eq!(open, windows(&[("09:00", "12:00"), ("13:00", "17:00")]));
+    }
+
+    #[test]
+    fn rules_of_equal_priority_are_unioned() {
+        let rules = [
+            priority_rule(5, &[("09:00", "12:00", true)]),
+            priority_rule(5, &[("11:00", "14:00", true), ("10:00", "11:00", false)]),
+        ];
+
+        let open = resolve_day_windows(&rules, date("2026-03-01"));
+
+        assert_eq!(open, windows(&[("09:00", "14:00")]));
+    }
+
+    #[test]
+    fn lower_priority_unavailability_does_not_mask() {
+        let rules = [
+            priority_rule(10, &[("09:00", "17:00", true)]),
+            priority_rule(0, &[("12:00", "13:00", false)]),
+        ];
+
+        let open = resolve_day_windows(&rules, date("2026-03-01"));
+
+        assert_eq!(open, windows(&[("09:00", "17:00")]));
+    }
+
+    fn event_type_for(settings: &CalendarSettings) -> EventType {
+        let schedule = Availability {
+            id: Some(ObjectId::new()),
+            user_id: settings.user_id,
+            calendar_settings_id: ObjectId::new(),
+            name: "Working hours".to_string(),
+            is_default: true,
+            rules: Vec::new(),
+            date_overrides: Vec::new(),
+            version: 0,
+            deleted_at: None,
+            created_at: DateTime::now(),
+            updated_at: DateTime::now(),
+        };
+        test_support::event_type(&settings.user_id, &schedule)
+    }
+
+    fn booked_at(start_time: &str, end_time: &str) -> BookedWindow {
+        booked_window(None, "2026-03-08", start_time, end_time, &BufferTime { before: 0, after: 0 })
+    }
+
+    #[test]
+    fn inputs_hash_ignores_the_order_of_unordered_inputs() {
+        let mut settings = settings("America/New_York");
+        let event_type = event_type_for(&settings);
+        let rules = [sunday_rule("09:00", "12:00"), sunday_rule("13:00", "17:00")];
+        let overrides = [
+            DateOverride { date: "2026-03-09".to_string(), slots: Vec::new() },
+            DateOverride { date: "2026-03-10".to_string(), slots: Vec::new() },
+        ];
+        let morning = || TimeSlot { start: "09:00".to_string(), end: "12:00".to_string() };
+        let afternoon = || TimeSlot { start: "13:00".to_string(), end: "17:00".to_string() };
+        let booked = HashMap::from([("2026-03-08".to_string(), vec![booked_at("09:00", "09:30"), booked_at("14:00", "14:30")])]);
+
+        settings.working_hours = HashMap::from([("monday".to_string(), vec![morning(), afternoon()])]);
+        let hash = inputs_hash(&rules, &overrides, &settings, &event_type, &booked);
+
+        let reversed_rules = [rules[1].clone(), rules[0].clone()];
+        let reversed_overrides = [overrides[1].clone(), overrides[0].clone()];
+        let reversed_booked = HashMap::from([("2026-03-08".to_string(), vec![booked_at("14:00", "14:30"), booked_at("09:00", "09:30")])]);
+        settings.working_hours = HashMap::from([("monday".to_string(), vec![afternoon(), morning()])]);
+        let reordered = inputs_hash(&reversed_rules, &reversed_overrides, &settings, &event_type, &reversed_booked);
+
+        assert_eq!(hash.len(), 64);
+        assert_eq!(hash, reordered);
+    }
+
+    #[test]
+    fn inputs_hash_changes_with_the_bookings() {
+        let settings = settings("America/New_York");
+        let event_type = event_type_for(&settings);
+        let rules = [sunday_rule("09:00", "17:00")];
+        let mut booked = HashMap::from([("2026-03-08".to_string(), vec![booked_at("09:00", "09:30")])]);
+        let hash = inputs_hash(&rules, &[], &settings, &event_type, &booked);
+
+        booked.get_mut("2026-03-08").unwrap().push(booked_at("10:00", "10:30"));
+
+        assert_ne!(hash, inputs_hash(&rules, &[], &settings, &event_type, &booked));
+    }
+
+    /// Random slots over two days, with clashing start times and lengths.
+    fn random_slots(rng: &mut impl rand::Rng) -> Vec<AvailableTimeSlot> {
+        (0..rng.gen_range(0..24))
+            .map(|_| {
+                let date = ["2026-03-01", "2026-03-02"][rng.gen_range(0..2)];
+                let start = NaiveTime::from_hms_opt(8, 0, 0).unwrap() + Duration::minutes(15 * rng.gen_range(0..16));
+                let end = start + Duration::minutes(15 * rng.gen_range(1..5));
+                slot(date, &start.format("%H:%M").to_string(), &end.format("%H:%M").to_string())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn normalized_slots_are_sorted_unique_and_stable() {
+        use rand::SeedableRng;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(974);
+        for _ in 0..500 {
+            let slots = random_slots(&mut rng);
+            for allow_overlap in [false, true] {
+                let normalized = normalize_slots(slots.clone(), allow_overlap);
+
+                assert!(normalized.iter().all(|slot| slots.contains(slot)));
+                assert_eq!(normalize_slots(normalized.clone(), allow_overlap), normalized);
+                for pair in normalized.windows(2) {
+                    assert!(pair[0] < pair[1], "Not sorted: {:?}", pair);
+                    if pair[0].date == pair[1].date {
+                        assert_ne!(pair[0].start_time, pair[1].start_time);
+                        if !allow_overlap {
+                            assert!(pair[1].start_time >= pair[0].end_time, "Overlapping: {:?}", pair);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn buffer(before: i32, after: i32) -> BufferTime {
+        BufferTime { before, after }
+    }
+
+    #[test]
+    fn day_overrides_replace_duration_and_buffer_on_their_weekday() {
+        let event_type = EventType {
+            duration: 30,
+            buffer_time: Some(buffer(5, 5)),
+            day_overrides: Some(HashMap::from([
+                ("monday".to_string(), DayOverride { duration: Some(60), buffer_time: Some(buffer(15, 0)) }),
+                ("friday".to_string(), DayOverride { duration: Some(15), buffer_time: None }),
+            ])),
+            ..event_type_for(&settings("Europe/Berlin"))
+        };
+
+        let monday = resolve_day_config(&event_type, "monday");
+        assert_eq!((monday.duration, monday.buffer_time.map(|b| (b.before, b.after))), (60, Some((15, 0))));
+        // An override without a buffer keeps the event type's
+        let friday = resolve_day_config(&event_type, "friday");
+        assert_eq!((friday.duration, friday.buffer_time.map(|b| (b.before, b.after))), (15, Some((5, 5))));
+        let tuesday = resolve_day_config(&event_type, "tuesday");
+        assert_eq!((tuesday.duration, tuesday.buffer_time.map(|b| (b.before, b.after))), (30, Some((5, 5))));
+    }
+
+    #[test]
+    fn booking_buffer_falls_back_to_the_settings() {
+        let default_buffer = buffer(10, 10);
+        let event_type = EventType {
+            buffer_time: None,
+            day_overrides: Some(HashMap::from([
+                ("monday".to_string(), DayOverride { duration: None, buffer_time: Some(buffer(0, 20)) }),
+            ])),
+            ..event_type_for(&settings("Europe/Berlin"))
+        };
+        let before_after = |b: BufferTime| (b.before, b.after);
+
+        // 2026-03-09 is a Monday, 2026-03-10 a Tuesday
+        assert_eq!(before_after(booking_buffer(Some(&event_type), "2026-03-09", &default_buffer)), (0, 20));
+        assert_eq!(before_after(booking_buffer(Some(&event_type), "2026-03-10", &default_buffer)), (10, 10));
+        assert_eq!(before_after(booking_buffer(None, "2026-03-09", &default_buffer)), (10, 10));
+    }
+
+    #[test]
+    fn booked_time_removes_overlapping_slots_only() {
+        let slots = vec![
+            slot("2026-03-08", "09:30", "10:00"),
+            slot("2026-03-08", "10:00", "10:30"),
+            slot("2026-03-08", "10:15", "10:45"),
+            slot("2026-03-08", "10:30", "11:00"),
+        ];
+
+        let open = exclude_booked(slots, &[booked_at("10:00", "10:30")], &buffer(0, 0));
+        assert_eq!(start_times(&open), ["09:30", "10:30"]);
+    }
+
+    #[test]
+    fn padded_windows_stay_within_the_day() {
+        let time = |value| NaiveTime::parse_from_str(value, "%H:%M").unwrap();
+
+        assert_eq!(pad_window((time("09:00"), time("10:00")), &buffer(15, 30)), (time("08:45"), time("10:30")));
+        assert_eq!(
+            pad_window((time("00:10"), time("23:50")), &buffer(30, 30)),
+            (NaiveTime::MIN, NaiveTime::from_hms_opt(23, 59, 59).unwrap()),
+        );
+    }
+
+    #[test]
+    fn recommendations_prefer_slots_next_to_bookings() {
+        let slots = vec![
+            slot("2026-03-08", "08:00", "08:30"),
+            slot("2026-03-08", "10:30", "11:00"),
+            slot("2026-03-08", "12:30", "13:00"),
+        ];
+        let booked = HashMap::from([("2026-03-08".to_string(), vec![booked_at("12:00", "12:30")])]);
+
+        assert_eq!(start_times(&recommend_slots(&slots, &booked, 2)), ["12:30", "10:30"]);
+        // Without the booking, mid-morning wins
+        assert_eq!(start_times(&recommend_slots(&slots, &HashMap::new(), 1)), ["10:30"]);
+    }
+
+    #[test]
+    fn recommendations_prefer_earlier_dates_and_break_ties_chronologically() {
+        let slots = vec![
+            slot("2026-03-08", "10:00", "10:30"),
+            slot("2026-03-08", "11:00", "11:30"),
+            slot("2026-03-09", "10:30", "11:00"),
+        ];
+
+        let recommended = recommend_slots(&slots, &HashMap::new(), 10);
+        assert_eq!(recommended.len(), 3);
+        // 10:00 and 11:00 are as far from 10:30, so the earlier one goes first
+        assert_eq!(
+            recommended.iter().map(|slot| (slot.date.as_str(), slot.start_time.as_str())).collect::<Vec<_>>(),
+            [("2026-03-08", "10:00"), ("2026-03-08", "11:00"), ("2026-03-09", "10:30")],
+        );
+        assert!(recommend_slots(&[], &HashMap::new(), 3).is_empty());
+    }
+
+    const HORIZON: BookingHorizon = BookingHorizon { default_days: 60, hard_max_days: 365 };
+
+    #[test]
+    fn max_notice_defaults_and_is_capped_at_the_hard_maximum() {
+        let event_type = |max_booking_notice| EventType {
+            max_booking_notice,
+            ..event_type_for(&settings("Europe/Berlin"))
+        };
+        let day = 24 * 60;
+
+        assert_eq!(HORIZON.max_notice_minutes(&event_type(None)), 60 * day);
+        assert_eq!(HORIZON.max_notice_minutes(&event_type(Some(7 * day as i32))), 7 * day);
+        // Stored before the cap existed
+        assert_eq!(HORIZON.max_notice_minutes(&event_type(Some(400 * day as i32))), 365 * day);
+        assert_eq!(HORIZON.max_notice_minutes(&event_type(Some(-5))), 0);
+    }
+
+    #[test]
+    fn slots_past_the_horizon_are_dropped() {
+        let event_type = event_type_for(&settings("Europe/Berlin"));
+        let now = date("2026-03-01").and_hms_opt(12, 0, 0).unwrap();
+        let mut slots = vec![
+            slot("2026-04-30", "11:00", "11:30"),
+            slot("2026-04-30", "12:00", "12:30"),
+            slot("2026-04-30", "12:30", "13:00"),
+        ];
+
+        // The default 60 days end at noon on April 30
+        retain_within_notice(&mut slots, &event_type, &HORIZON, now);
+        assert_eq!(start_times(&slots), ["11:00", "12:00"]);
+
+        let beyond = date("2026-04-30").and_hms_opt(12, 30, 0).unwrap();
+        let conflict = notice_conflict(&event_type, &HORIZON, beyond, now).unwrap();
+        assert_eq!(conflict.code, "beyond_max_notice");
+    }
+
+    #[test]
+    fn buffers_keep_meetings_apart_but_may_overlap_each_other() {
+        let time = |value| NaiveTime::parse_from_str(value, "%H:%M").unwrap();
+        let window = |start, end| (time(start), time(end));
+        // A 10:00-10:30 booking whose event type keeps 15 minutes free after it
+        let booked = booked_window(None, "2026-03-08", "10:00", "10:30", &buffer(0, 15));
+
+        // The booking's buffer reaches into a slot right after it
+        assert!(booked_conflicts(&booked, window("10:30", "11:00"), &buffer(0, 0)));
+        assert!(!booked_conflicts(&booked, window("10:45", "11:15"), &buffer(0, 0)));
+        // The slot's own buffer reaches into the booking
+        assert!(booked_conflicts(&booked, window("09:30", "10:00"), &buffer(0, 10)));
+        assert!(!booked_conflicts(&booked, window("09:30", "10:00"), &buffer(0, 0)));
+        // Both buffers fill 10:30-10:45 together without touching either meeting
+        assert!(!booked_conflicts(&booked, window("10:45", "11:15"), &buffer(15, 0)));
+    }
+
+    #[test]
+    fn booked_window_uses_the_event_type_buffer_for_its_weekday() {
+        let event_type = EventType {
+            buffer_time: Some(buffer(5, 5)),
+            day_overrides: Some(HashMap::from([
+                ("sunday".to_string(), DayOverride { duration: None, buffer_time: Some(buffer(0, 30)) }),
+            ])),
+            ..event_type_for(&settings("Europe/Berlin"))
+        };
+        let time = |value| NaiveTime::parse_from_str(value, "%H:%M").unwrap();
+
+        // 2026-03-08 is a Sunday
+        let sunday = booked_window(Some(&event_type), "2026-03-08", "10:00", "10:30", &buffer(10, 10));
+        assert_eq!(sunday.padded, (time("10:00"), time("11:00")));
+        let monday = booked_window(Some(&event_type), "2026-03-09", "10:00", "10:30", &buffer(10, 10));
+        assert_eq!(monday.padded, (time("09:55"), time("10:35")));
+        let settings_buffer = booked_window(None, "2026-03-09", "10:00", "10:30", &buffer(10, 10));
+        assert_eq!(settings_buffer.padded, (time("09:50"), time("10:40")));
+    }
+}
@@ -1,6 +1,26 @@
 use mongodb::bson::{DateTime, oid::ObjectId};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+use crate::utils::recurrence::{ALL_WEEKDAYS, Frequency, RecurrenceSpec};
+use crate::utils::time_of_day;
+
+/// Days a deleted availability schedule can be restored before it is purged.
+pub const AVAILABILITY_RESTORE_DAYS: u64 = 30;
+/// Minutes before the start that attendees are reminded when an event type sets no reminders.
+pub const DEFAULT_REMINDER_MINUTES: i32 = 60;
+/// Longest reminder offset an event type can set.
+pub const MAX_REMINDER_OFFSET_MINUTES: i32 = 10_080;
+/// Emails an event type can give its own wording.
+pub const EMAIL_TEMPLATE_KINDS: &[&str] = &["confirmation", "reminder", "cancellation"];
+/// Name given to schedules created without one, and to schedules stored before names existed.
+pub const DEFAULT_SCHEDULE_NAME: &str = "Working hours";
+
+/// How long an admin-enabled availability diagnostic mode lasts.
+pub const DIAGNOSTICS_DAYS: i64 = 7;
+/// Earlier slugs of an event type that public links still resolve.
+pub const SLUG_HISTORY_MAX: usize = 5;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TimeSlot {
@@ -14,7 +34,102 @@ pub struct BufferTime {
     pub after: i32,   // minutes
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// A reminder sent `offset_minutes` before a booking starts, on each of its channels.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Reminder {
+    pub offset_minutes: i32,
+    #[serde(default = "default_reminder_channels")]
+    pub channels: Vec<ReminderChannel>,  // Reminders stored before channels existed are emails
+}
+
+/// How a reminder reaches the invitee.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ReminderChannel {
+    Email,  // To the invitee and their guests
+    Sms,    // To the invitee's phone number, which booking then requires
+}
+
+impl ReminderChannel {
+    pub const ALL: [ReminderChannel; 2] = [ReminderChannel::Email, ReminderChannel::Sms];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReminderChannel::Email => "email",
+            ReminderChannel::Sms => "sms",
+        }
+    }
+}
+
+fn default_reminder_channels() -> Vec<ReminderChannel> {
+    vec![ReminderChannel::Email]
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CancellationPolicy {
+    #[serde(default = "default_policy_allowed")]
+    pub allowed: bool,  // False leaves cancelling to the host
+    pub min_notice_minutes: i32,  // Invitees cannot cancel closer than this to the start
+    pub policy_text: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReschedulePolicy {
+    #[serde(default = "default_policy_allowed")]
+    pub allowed: bool,  // False leaves rescheduling to the host
+    pub min_notice_minutes: i32,  // Invitees cannot reschedule closer than this to the start
+    pub policy_text: Option<String>,
+}
+
+fn default_policy_allowed() -> bool {
+    true
+}
+
+/// What cancellation and reschedule policies have in common, so an invitee's
+/// change is checked the same way against either.
+pub trait NoticePolicy {
+    fn allowed(&self) -> bool;
+    fn min_notice_minutes(&self) -> i32;
+    fn policy_text(&self) -> Option<&str>;
+}
+
+impl NoticePolicy for CancellationPolicy {
+    fn allowed(&self) -> bool {
+        self.allowed
+    }
+
+    fn min_notice_minutes(&self) -> i32 {
+        self.min_notice_minutes
+    }
+
+    fn policy_text(&self) -> Option<&str> {
+        self.policy_text.as_deref()
+    }
+}
+
+impl NoticePolicy for ReschedulePolicy {
+    fn allowed(&self) -> bool {
+        self.allowed
+    }
+
+    fn min_notice_minutes(&self) -> i32 {
+        self.min_notice_minutes
+    }
+
+    fn policy_text(&self) -> Option<&str> {
+        self.policy_text.as_deref()
+    }
+}
+
+/// The dates invitees may book, on top of the booking notice.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SchedulingWindow {
+    Rolling { days: i32 },  // Today and the following days, `days` in all, in the host's timezone
+    Range { start: String, end: String },  // YYYY-MM-DD, both inclusive
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct CalendarSettings {
     #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
     pub id: Option<ObjectId>,
@@ -23,13 +138,27 @@ pub struct CalendarSettings {
     pub working_hours: HashMap<String, Vec<TimeSlot>>,
     pub buffer_time: BufferTime,
     pub default_meeting_duration: i32,
+    #[serde(default)]
+    pub slot_interval: Option<i32>,  // Minutes between slot starts for event types without their own; None is back to back
+    #[serde(default)]
+    pub max_meeting_minutes_per_day: Option<i32>,  // Caps booked meeting time per day across event types; None is no cap
     pub calendar_name: String,
     pub date_format: String,
     pub time_format: String,
+    #[serde(default = "default_public_page_enabled")]
+    pub public_page_enabled: bool,
+    #[serde(default)]
+    pub public_page_message: Option<String>,  // Sanitized HTML shown while the page is hidden
+    #[serde(default)]
+    pub diagnostics_until: Option<DateTime>,  // Admin-enabled availability snapshots are kept until then
     pub created_at: DateTime,
     pub updated_at: DateTime,
 }
 
+fn default_public_page_enabled() -> bool {
+    true
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AvailabilitySlot {
     pub day_of_week: String,  // "monday", "tuesday", etc.
@@ -40,15 +169,19 @@ pub struct AvailabilitySlot {
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AvailabilityRule {
+    #[serde(default)]
+    pub rule_id: String,  // Stable id; empty on rules stored before ids existed
     pub start_date: DateTime,
     pub end_date: Option<DateTime>,
     pub is_recurring: bool,
-    pub recurrence_pattern: Option<String>,  // "daily", "weekly", "monthly"
+    pub recurrence_pattern: Option<String>,  // "daily", "weekly", "biweekly", "monthly"
     pub slots: Vec<AvailabilitySlot>,
+    #[serde(default)]
+    pub priority: i32,  // Higher priority rules win when rules conflict
 }
 
 impl AvailabilityRule {
-    pub fn new(start_date_str: &str, end_date_str: Option<&str>, is_recurring: bool, recurrence_pattern: Option<String>, slots: Vec<AvailabilitySlot>) -> Result<Self, String> {
+    pub fn new(start_date_str: &str, end_date_str: Option<&str>, is_recurring: bool, recurrence_pattern: Option<String>, mut slots: Vec<AvailabilitySlot>, priority: i32) -> Result<Self, String> {
         let start_date = DateTime::parse_rfc3339_str(start_date_str)
             .map_err(|e| format!("Invalid start date: {}", e))?;
         
@@ -59,14 +192,69 @@ impl AvailabilityRule {
             None
         };
 
+        if is_recurring
+            && let Some(pattern) = recurrence_pattern.as_deref()
+            && RecurrenceSpec::from_pattern(pattern).is_none()
+        {
+            return Err(format!(
+                "Unknown recurrence pattern '{}', expected daily, weekly, biweekly or monthly",
+                pattern
+            ));
+        }
+
+        // Slots accept 12-hour input but are always stored as 24-hour HH:mm
+        for slot in &mut slots {
+            for (field, value) in [("start_time", &mut slot.start_time), ("end_time", &mut slot.end_time)] {
+                let time = time_of_day::parse(value).ok_or_else(|| time_of_day::invalid_message(field, value))?;
+                *value = time.format("%H:%M").to_string();
+            }
+        }
+
         Ok(Self {
+            rule_id: new_rule_id(),
             start_date,
             end_date,
             is_recurring,
             recurrence_pattern,
             slots,
+            priority,
         })
     }
+
+    /// Which dates in the rule's range it applies to; `None` means all of them.
+    /// Weekly patterns cover whole weeks because the slots pick the weekdays.
+    /// Unknown stored patterns fall back to weekly, the behaviour before patterns were read.
+    pub fn recurrence(&self) -> Option<RecurrenceSpec> {
+        if !self.is_recurring {
+            return None;
+        }
+        let mut spec = self
+            .recurrence_pattern
+            .as_deref()
+            .and_then(RecurrenceSpec::from_pattern)
+            .unwrap_or_else(|| RecurrenceSpec::new(Frequency::Weekly));
+        if spec.frequency == Frequency::Weekly {
+            spec.weekdays = ALL_WEEKDAYS.to_vec();
+        }
+        Some(spec)
+    }
+
+    /// Whether a slot only applies on its `day_of_week`. Daily and monthly
+    /// rules already pick their dates, so their slots apply on each of them.
+    pub fn slots_follow_weekdays(&self) -> bool {
+        !self.recurrence().is_some_and(|spec| matches!(spec.frequency, Frequency::Daily | Frequency::Monthly))
+    }
+
+    /// Assigns an id to a rule stored before rule ids existed.
+    pub fn ensure_rule_id(&mut self) {
+        if self.rule_id.is_empty() {
+            self.rule_id = new_rule_id();
+        }
+    }
+}
+
+fn new_rule_id() -> String {
+    Uuid::new_v4().simple().to_string()[..12].to_string()
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -75,29 +263,468 @@ pub struct Availability {
     pub id: Option<ObjectId>,
     pub user_id: ObjectId,
     pub calendar_settings_id: ObjectId,
+    #[serde(default = "default_schedule_name")]
+    pub name: String,
+    #[serde(default)]
+    pub is_default: bool,  // Used when no event type picks a schedule; at most one per user
     pub rules: Vec<AvailabilityRule>,
+    #[serde(default)]
+    pub date_overrides: Vec<DateOverride>,  // At most one per date
+    #[serde(default)]
+    pub version: i64,  // Incremented on every update
+    #[serde(default)]
+    pub deleted_at: Option<DateTime>,  // Set when soft-deleted; restorable until purged
     pub created_at: DateTime,
     pub updated_at: DateTime,
 }
 
+impl Availability {
+    /// Which version of this schedule a decision was based on.
+    pub fn schedule_version(&self) -> Option<ScheduleVersion> {
+        self.id.map(|availability_id| ScheduleVersion { availability_id, version: self.version })
+    }
+}
+
+/// A schedule as it was when a slot was checked against it.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct ScheduleVersion {
+    pub availability_id: ObjectId,
+    pub version: i64,
+}
+
+/// Replaces a schedule's weekly rules and the host's working hours on one date.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DateOverride {
+    pub date: String,  // YYYY-MM-DD
+    #[serde(default)]
+    pub slots: Vec<TimeSlot>,  // The only open time that day; empty blocks the whole day
+}
+
+fn default_schedule_name() -> String {
+    DEFAULT_SCHEDULE_NAME.to_string()
+}
+
+/// Replaces an event type's duration and/or buffer on one weekday.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DayOverride {
+    pub duration: Option<i32>,  // minutes
+    pub buffer_time: Option<BufferTime>,
+}
+
+/// Display options for the booking widget when embedded in an iframe.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EmbedSettings {
+    pub hide_event_details: bool,
+    pub background_color: String,  // Hex color code
+    pub text_color: String,        // Hex color code
+    pub hide_gdpr_banner: bool,
+}
+
+/// What invitees see once they have booked.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ConfirmationSettings {
+    pub redirect_url: Option<String>,  // https only; invitees go there instead of the default confirmation page
+    pub custom_message: Option<String>,
+    #[serde(default)]
+    pub pass_booking_details_as_query: bool,  // Adds booking_id, date, start_time and end_time to redirect_url
+}
+
+/// Who dials for a phone location.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WhoCalls {
+    Host,     // At the number the invitee gives in a phone question
+    Invitee,  // At the location's number
+}
+
+/// Where a meeting happens. Event types offer one or more and invitees pick
+/// one per booking. Locations stored before they were tagged by kind hold
+/// `location_type`, `meeting_link` and `address`, and still read.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case", from = "StoredLocation")]
+pub enum Location {
+    InPerson { address: String },
+    Phone { number: Option<String>, who_calls: WhoCalls },  // number is set when the invitee calls
+    Video { provider: Option<String>, link: Option<String> },  // link may hold {{question}} placeholders
+    Custom { note: String },
+}
+
+impl Location {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Location::InPerson { .. } => "in_person",
+            Location::Phone { .. } => "phone",
+            Location::Video { .. } => "video",
+            Location::Custom { .. } => "custom",
+        }
+    }
+
+    /// A location from the fields event types and locations had before kinds:
+    /// "in_person", "phone" or "video", with an address (or phone number) and a link.
+    pub fn from_legacy(location_type: &str, meeting_link: Option<String>, address: Option<String>) -> Self {
+        match location_type {
+            "in_person" => Location::InPerson { address: address.unwrap_or_default() },
+            "phone" => Location::Phone {
+                who_calls: if address.is_some() { WhoCalls::Invitee } else { WhoCalls::Host },
+                number: address,
+            },
+            "video" => Location::Video { provider: None, link: meeting_link },
+            other => Location::Custom { note: address.unwrap_or_else(|| other.to_string()) },
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredLocation {
+    Tagged(TaggedLocation),
+    Legacy {
+        location_type: String,
+        #[serde(default)]
+        meeting_link: Option<String>,
+        #[serde(default)]
+        address: Option<String>,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum TaggedLocation {
+    InPerson { address: String },
+    Phone {
+        #[serde(default)]
+        number: Option<String>,
+        who_calls: WhoCalls,
+    },
+    Video {
+        #[serde(default)]
+        provider: Option<String>,
+        #[serde(default)]
+        link: Option<String>,
+    },
+    Custom { note: String },
+}
+
+impl From<StoredLocation> for Location {
+    fn from(stored: StoredLocation) -> Self {
+        match stored {
+            StoredLocation::Tagged(TaggedLocation::InPerson { address }) => Location::InPerson { address },
+            StoredLocation::Tagged(TaggedLocation::Phone { number, who_calls }) => Location::Phone { number, who_calls },
+            StoredLocation::Tagged(TaggedLocation::Video { provider, link }) => Location::Video { provider, link },
+            StoredLocation::Tagged(TaggedLocation::Custom { note }) => Location::Custom { note },
+            StoredLocation::Legacy { location_type, meeting_link, address } => {
+                Location::from_legacy(&location_type, meeting_link, address)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum QuestionKind {
+    Text,
+    Textarea,
+    Radio,
+    Checkbox,
+    Phone,
+}
+
+impl QuestionKind {
+    /// Radio and checkbox questions are answered from a fixed list of options.
+    pub fn has_options(self) -> bool {
+        matches!(self, QuestionKind::Radio | QuestionKind::Checkbox)
+    }
+}
+
+/// A question invitees answer when booking. Event types stored before
+/// question kinds existed hold plain labels, which read as required text questions.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(from = "StoredQuestion")]
+pub struct Question {
+    pub label: String,
+    pub kind: QuestionKind,
+    pub required: bool,
+    pub options: Vec<String>,  // Choices for radio and checkbox questions
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredQuestion {
+    Label(String),
+    Structured {
+        label: String,
+        kind: QuestionKind,
+        #[serde(default = "default_question_required")]
+        required: bool,
+        #[serde(default)]
+        options: Vec<String>,
+    },
+}
+
+fn default_question_required() -> bool {
+    true
+}
+
+impl From<StoredQuestion> for Question {
+    fn from(stored: StoredQuestion) -> Self {
+        match stored {
+            StoredQuestion::Label(label) => Question {
+                label,
+                kind: QuestionKind::Text,
+                required: true,
+                options: Vec::new(),
+            },
+            StoredQuestion::Structured { label, kind, required, options } => Question { label, kind, required, options },
+        }
+    }
+}
+
+/// Event type text in another language, shown to invitees of that locale.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EventTypeTranslation {
+    pub name: String,
+    pub description: Option<String>,
+    pub confirmation_email_extra: Option<String>,  // Appended to the invitee's confirmation email
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct EventType {
     #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
     pub id: Option<ObjectId>,
     pub user_id: ObjectId,
     pub name: String,
+    #[serde(default)]
+    pub slug: String,  // Unique per user; names the event type in public URLs
+    #[serde(default)]
+    pub slug_history: Vec<String>,  // Earlier slugs, oldest first, so links with them keep working
     pub description: Option<String>,
     pub duration: i32,
     pub color: String,
-    pub location_type: String,
-    pub meeting_link: Option<String>,
-    pub questions: Vec<String>,
+    #[serde(default)]
+    pub locations: Vec<Location>,  // Invitees pick one per booking; empty leaves the place open
+    pub questions: Vec<Question>,
     pub availability_schedule_id: ObjectId,
     pub buffer_time: Option<BufferTime>,
-    pub min_booking_notice: Option<i32>,
-    pub max_booking_notice: Option<i32>,
+    pub min_booking_notice: Option<i32>,  // minutes; slots starting sooner are not offered
+    pub max_booking_notice: Option<i32>,  // minutes; slots starting later are not offered
+    #[serde(default)]
+    pub slot_interval: Option<i32>,  // minutes between slot starts; None uses the calendar settings
+    #[serde(default)]
+    pub cancellation_policy: Option<CancellationPolicy>,
+    #[serde(default)]
+    pub reschedule_policy: Option<ReschedulePolicy>,
+    #[serde(default)]
+    pub scheduling_window: Option<SchedulingWindow>,
+    #[serde(default)]
+    pub embed_settings: Option<EmbedSettings>,
+    #[serde(default)]
+    pub confirmation_settings: Option<ConfirmationSettings>,
+    #[serde(default)]
+    pub day_overrides: Option<HashMap<String, DayOverride>>,  // Keyed by "monday", "tuesday", etc.
+    #[serde(default)]
+    pub translations: Option<HashMap<String, EventTypeTranslation>>,  // Keyed by locale code, e.g. "de"
+    #[serde(default)]
+    pub email_templates: Option<HashMap<String, String>>,  // Keyed by one of EMAIL_TEMPLATE_KINDS
+    #[serde(default)]
+    pub requires_confirmation: bool,  // Bookings stay pending until the host approves them
+    #[serde(default)]
+    pub max_attendees: Option<i32>,  // Group events: invitees per slot; None is one-on-one
+    #[serde(default)]
+    pub reminders: Option<Vec<Reminder>>,  // Longest offset first; None uses the default, empty sends none
+    #[serde(default)]
+    pub hosts: Vec<ObjectId>,  // Users who host besides the owner, as host_assignment says
+    #[serde(default)]
+    pub host_assignment: HostAssignment,
+    #[serde(default)]
+    pub round_robin_strategy: RoundRobinStrategy,
+    #[serde(default)]
+    pub is_secret: bool,  // Bookable through its link but left off the public page
+    #[serde(default)]
+    pub position: i32,  // Lists sort by this, then by creation time
     pub is_active: bool,
+    #[serde(default)]
+    pub archived_at: Option<DateTime>,  // Set when deleted; kept so past bookings still resolve
     pub created_at: DateTime,
     pub updated_at: DateTime,
 } 
- 
\ No newline at end of file
+
+impl EventType {
+    /// Renames the event type in public URLs. The old slug goes to the
+    /// history, dropping the oldest beyond `SLUG_HISTORY_MAX`; going back to
+    /// an old slug takes it out of the history again.
+    pub fn change_slug(&mut self, slug: String) {
+        if slug == self.slug {
+            return;
+        }
+        self.slug_history.retain(|old| *old != slug);
+        let old = std::mem::replace(&mut self.slug, slug);
+        if !old.is_empty() {
+            self.slug_history.push(old);
+        }
+        let excess = self.slug_history.len().saturating_sub(SLUG_HISTORY_MAX);
+        self.slug_history.drain(..excess);
+    }
+
+    /// Whether invitees can book it: switched on and not archived.
+    pub fn accepts_bookings(&self) -> bool {
+        self.is_active && self.archived_at.is_none()
+    }
+
+    /// How many invitees can book the same slot.
+    pub fn capacity(&self) -> i32 {
+        self.max_attendees.unwrap_or(1).max(1)
+    }
+
+    /// Minutes before the start that attendees are reminded.
+    pub fn reminder_offsets(&self) -> Vec<i32> {
+        match &self.reminders {
+            Some(reminders) => reminders.iter().map(|reminder| reminder.offset_minutes).collect(),
+            None => vec![DEFAULT_REMINDER_MINUTES],
+        }
+    }
+
+    /// The channels of the reminder at `offset_minutes`; the default reminder is an email.
+    pub fn reminder_channels(&self, offset_minutes: i32) -> Vec<ReminderChannel> {
+        match &self.reminders {
+            Some(reminders) => reminders
+                .iter()
+                .find(|reminder| reminder.offset_minutes == offset_minutes)
+                .map(|reminder| reminder.channels.clone())
+                .unwrap_or_default(),
+            None => default_reminder_channels(),
+        }
+    }
+
+    /// Whether any reminder goes out by SMS, so bookings need a phone number.
+    pub fn sends_sms_reminders(&self) -> bool {
+        self.reminders
+            .iter()
+            .flatten()
+            .any(|reminder| reminder.channels.contains(&ReminderChannel::Sms))
+    }
+
+    /// The host's own wording for one of `EMAIL_TEMPLATE_KINDS`, if they set it.
+    pub fn email_template(&self, kind: &str) -> Option<&str> {
+        self.email_templates.as_ref()?.get(kind).map(String::as_str)
+    }
+
+    /// Whether each booking goes to one of the owner and hosts in turn.
+    pub fn is_round_robin(&self) -> bool {
+        self.host_assignment == HostAssignment::RoundRobin && !self.hosts.is_empty()
+    }
+
+    /// The hosts who attend every booking along with the owner.
+    pub fn collective_hosts(&self) -> &[ObjectId] {
+        match self.host_assignment {
+            HostAssignment::Collective => &self.hosts,
+            HostAssignment::RoundRobin => &[],
+        }
+    }
+}
+
+/// How an event type with hosts shares its bookings among them.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum HostAssignment {
+    #[default]
+    Collective,  // The owner and every host attend each booking
+    RoundRobin,  // Each booking goes to one of them, as round_robin_strategy picks
+}
+
+/// Which free host a round-robin event type assigns a booking to. Ties go to
+/// whoever was assigned one of its bookings longest ago, then to the owner and
+/// hosts in their listed order.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RoundRobinStrategy {
+    #[default]
+    LeastBooked,  // Fewest pending and confirmed bookings in the booking's week, Monday to Sunday
+    Rotation,     // The owner and hosts in listed order, after whoever was assigned last
+}
+
+/// Where a host invitation stands. Only accepted invitations let the owner
+/// list the invited user as a host.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HostInviteStatus {
+    Pending,
+    Accepted,
+    Declined,
+}
+
+/// An owner's request to list another user as a host of their event types.
+/// Bookings of those event types block the host's time and email them, so
+/// the host has to agree first.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HostInvite {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub owner_user_id: ObjectId,
+    pub host_user_id: ObjectId,
+    pub status: HostInviteStatus,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+/// What the slot engine produced for one public availability request,
+/// without invitee data. Kept while diagnostic mode is on for the host.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AvailabilitySnapshot {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub user_id: ObjectId,
+    pub event_type_id: ObjectId,
+    pub inputs_hash: String,  // Changes whenever rules, settings, event type or bookings change
+    pub window_start: String,  // YYYY-MM-DD format
+    pub window_end: String,    // YYYY-MM-DD format
+    pub slots_per_day: BTreeMap<String, u32>,
+    pub engine_version: String,
+    pub date: String,  // YYYY-MM-DD of computed_at, for day-over-day lookups
+    pub computed_at: DateTime,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    fn event_type_with_slug(slug: &str) -> EventType {
+        let schedule = Availability {
+            id: Some(ObjectId::new()),
+            user_id: ObjectId::new(),
+            calendar_settings_id: ObjectId::new(),
+            name: DEFAULT_SCHEDULE_NAME.to_string(),
+            is_default: true,
+            rules: Vec::new(),
+            date_overrides: Vec::new(),
+            version: 0,
+            deleted_at: None,
+            created_at: DateTime::now(),
+            updated_at: DateTime::now(),
+        };
+        EventType { slug: slug.to_string(), ..test_support::event_type(&ObjectId::new(), &schedule) }
+    }
+
+    #[test]
+    fn slug_history_keeps_the_latest_old_slugs() {
+        let mut event_type = event_type_with_slug("intro");
+        for slug in ["intro-2", "intro-3", "intro-4", "intro-5", "intro-6", "intro-7"] {
+            event_type.change_slug(slug.to_string());
+        }
+        assert_eq!(event_type.slug, "intro-7");
+        assert_eq!(event_type.slug_history, ["intro-2", "intro-3", "intro-4", "intro-5", "intro-6"]);
+
+        event_type.change_slug("intro-7".to_string());
+        assert_eq!(event_type.slug_history.len(), SLUG_HISTORY_MAX);
+    }
+
+    #[test]
+    fn going_back_to_an_old_slug_takes_it_out_of_the_history() {
+        let mut event_type = event_type_with_slug("intro");
+        event_type.change_slug("welcome".to_string());
+        event_type.change_slug("intro".to_string());
+
+        assert_eq!(event_type.slug, "intro");
+        assert_eq!(event_type.slug_history, ["welcome"]);
+    }
+}
@@ -1,17 +1,25 @@
 use actix_web::{web, Scope};
 use crate::modules::calendar::calendar_controller::CalendarController;
+use crate::modules::calendar::calendar_model::HostInviteStatus;
 use crate::modules::calendar::calendar_schema::{
     CreateCalendarSettingsRequest,
     CreateAvailabilityRequest,
     UpdateAvailabilityRequest,
+    SetDateOverrideRequest,
+    DateOverridePath,
     CheckAvailabilityRequest,
     CheckTimeSlotRequest,
+    DeleteEventTypeQuery, ListAvailabilityQuery, ListEventTypesQuery,
     CreateEventTypeRequest,
+    CreateHostInviteRequest,
+    ReorderEventTypesRequest,
     UpdateEventTypeRequest
 };
 use crate::modules::user::user_schema::Claims;
 use crate::errors::error::AppError;
+use crate::errors::error_handler::method_not_allowed;
 use crate::middleware::auth::AuthMiddleware;
+use crate::utils::object_id::PathObjectId;
 use crate::app::AppState;
 
 pub fn calendar_routes() -> Result<Scope, AppError> {
@@ -23,6 +31,7 @@ pub fn calendar_routes() -> Result<Scope, AppError> {
         .app_data(controller.clone())
         .service(
             web::resource("/settings")
+                .default_service(method_not_allowed("GET, POST, PUT, DELETE"))
                 .wrap(AuthMiddleware)
                 .route(web::get().to(|claims: web::ReqData<Claims>, controller: web::Data<CalendarController>| {
                     async move { controller.get_settings(claims).await }
@@ -39,6 +48,7 @@ pub fn calendar_routes() -> Result<Scope, AppError> {
         )
         .service(
             web::resource("/availability/check")
+                .default_service(method_not_allowed("POST"))
                 .wrap(AuthMiddleware)
                 .route(web::post().to(|claims: web::ReqData<Claims>, data: web::Json<CheckTimeSlotRequest>, controller: web::Data<CalendarController>| {
                     async move { controller.check_time_slot(claims, data).await }
@@ -46,23 +56,59 @@ pub fn calendar_routes() -> Result<Scope, AppError> {
         )
         .service(
             web::resource("/availability")
+                .default_service(method_not_allowed("GET, POST"))
                 .wrap(AuthMiddleware)
+                .route(web::get().to(|claims: web::ReqData<Claims>, query: web::Query<ListAvailabilityQuery>, controller: web::Data<CalendarController>| {
+                    async move { controller.list_availability(claims, query).await }
+                }))
                 .route(web::post().to(|claims: web::ReqData<Claims>, data: web::Json<CreateAvailabilityRequest>, controller: web::Data<CalendarController>| {
                     async move { controller.create_availability(claims, data).await }
                 }))
         )
         .service(
             web::resource("/availability/{id}")
+                .default_service(method_not_allowed("GET, PUT, DELETE"))
                 .wrap(AuthMiddleware)
-                .route(web::put().to(|claims: web::ReqData<Claims>, id: web::Path<String>, data: web::Json<UpdateAvailabilityRequest>, controller: web::Data<CalendarController>| {
+                .route(web::get().to(|claims: web::ReqData<Claims>, id: PathObjectId, controller: web::Data<CalendarController>| {
+                    async move { controller.get_availability(claims, id).await }
+                }))
+                .route(web::put().to(|claims: web::ReqData<Claims>, id: PathObjectId, data: web::Json<UpdateAvailabilityRequest>, controller: web::Data<CalendarController>| {
                     async move { controller.update_availability(claims, id, data).await }
                 }))
-                .route(web::delete().to(|claims: web::ReqData<Claims>, id: web::Path<String>, controller: web::Data<CalendarController>| {
+                .route(web::delete().to(|claims: web::ReqData<Claims>, id: PathObjectId, controller: web::Data<CalendarController>| {
                     async move { controller.delete_availability(claims, id).await }
                 }))
         )
+        .service(
+            web::resource("/availability/{id}/default")
+                .default_service(method_not_allowed("POST"))
+                .wrap(AuthMiddleware)
+                .route(web::post().to(|claims: web::ReqData<Claims>, id: PathObjectId, controller: web::Data<CalendarController>| {
+                    async move { controller.set_default_availability(claims, id).await }
+                }))
+        )
+        .service(
+            web::resource("/availability/{id}/overrides/{date}")
+                .default_service(method_not_allowed("PUT, DELETE"))
+                .wrap(AuthMiddleware)
+                .route(web::put().to(|claims: web::ReqData<Claims>, id: PathObjectId, path: web::Path<DateOverridePath>, data: web::Json<SetDateOverrideRequest>, controller: web::Data<CalendarController>| {
+                    async move { controller.set_date_override(claims, id, path, data).await }
+                }))
+                .route(web::delete().to(|claims: web::ReqData<Claims>, id: PathObjectId, path: web::Path<DateOverridePath>, controller: web::Data<CalendarController>| {
+                    async move { controller.delete_date_override(claims, id, path).await }
+                }))
+        )
+        .service(
+            web::resource("/availability/{id}/restore")
+                .default_service(method_not_allowed("POST"))
+                .wrap(AuthMiddleware)
+                .route(web::post().to(|claims: web::ReqData<Claims>, id: PathObjectId, controller: web::Data<CalendarController>| {
+                    async move { controller.restore_availability(claims, id).await }
+                }))
+        )
         .service(
             web::resource("/check-availability")
+                .default_service(method_not_allowed("POST"))
                 .wrap(AuthMiddleware)
                 .route(web::post().to(|claims: web::ReqData<Claims>, data: web::Json<CheckAvailabilityRequest>, controller: web::Data<CalendarController>| {
                     async move { controller.check_availability(claims, data).await }
@@ -70,22 +116,86 @@ pub fn calendar_routes() -> Result<Scope, AppError> {
         )
         .service(
             web::resource("/event-types")
+                .default_service(method_not_allowed("GET, POST"))
                 .wrap(AuthMiddleware)
-                .route(web::get().to(|claims: web::ReqData<Claims>, controller: web::Data<CalendarController>| {
-                    async move { controller.list_event_types(claims).await }
+                .route(web::get().to(|claims: web::ReqData<Claims>, query: web::Query<ListEventTypesQuery>, controller: web::Data<CalendarController>| {
+                    async move { controller.list_event_types(claims, query).await }
                 }))
                 .route(web::post().to(|claims: web::ReqData<Claims>, data: web::Json<CreateEventTypeRequest>, controller: web::Data<CalendarController>| {
                     async move { controller.create_event_type(claims, data).await }
                 }))
         )
+        .service(
+            web::resource("/event-types/reorder")
+                .default_service(method_not_allowed("PUT"))
+                .wrap(AuthMiddleware)
+                .route(web::put().to(|claims: web::ReqData<Claims>, data: web::Json<ReorderEventTypesRequest>, controller: web::Data<CalendarController>| {
+                    async move { controller.reorder_event_types(claims, data).await }
+                }))
+        )
+        .service(
+            web::resource("/event-types/slug/{slug}")
+                .default_service(method_not_allowed("GET"))
+                .wrap(AuthMiddleware)
+                .route(web::get().to(|claims: web::ReqData<Claims>, slug: web::Path<String>, controller: web::Data<CalendarController>| {
+                    async move { controller.get_event_type_by_slug(claims, slug).await }
+                }))
+        )
         .service(
             web::resource("/event-types/{id}")
+                .default_service(method_not_allowed("GET, PUT, DELETE"))
                 .wrap(AuthMiddleware)
-                .route(web::put().to(|claims: web::ReqData<Claims>, id: web::Path<String>, data: web::Json<UpdateEventTypeRequest>, controller: web::Data<CalendarController>| {
+                .route(web::get().to(|claims: web::ReqData<Claims>, id: PathObjectId, controller: web::Data<CalendarController>| {
+                    async move { controller.get_event_type(claims, id).await }
+                }))
+                .route(web::put().to(|claims: web::ReqData<Claims>, id: PathObjectId, data: web::Json<UpdateEventTypeRequest>, controller: web::Data<CalendarController>| {
                     async move { controller.update_event_type(claims, id, data).await }
                 }))
-                .route(web::delete().to(|claims: web::ReqData<Claims>, id: web::Path<String>, controller: web::Data<CalendarController>| {
-                    async move { controller.delete_event_type(claims, id).await }
+                .route(web::delete().to(|claims: web::ReqData<Claims>, id: PathObjectId, query: web::Query<DeleteEventTypeQuery>, controller: web::Data<CalendarController>| {
+                    async move { controller.delete_event_type(claims, id, query).await }
+                }))
+        )
+        .service(
+            web::resource("/host-invites")
+                .default_service(method_not_allowed("GET, POST"))
+                .wrap(AuthMiddleware)
+                .route(web::get().to(|claims: web::ReqData<Claims>, controller: web::Data<CalendarController>| {
+                    async move { controller.list_host_invites(claims).await }
+                }))
+                .route(web::post().to(|claims: web::ReqData<Claims>, data: web::Json<CreateHostInviteRequest>, controller: web::Data<CalendarController>| {
+                    async move { controller.create_host_invite(claims, data).await }
+                }))
+        )
+        .service(
+            web::resource("/host-invites/{id}")
+                .default_service(method_not_allowed("DELETE"))
+                .wrap(AuthMiddleware)
+                .route(web::delete().to(|claims: web::ReqData<Claims>, id: PathObjectId, controller: web::Data<CalendarController>| {
+                    async move { controller.delete_host_invite(claims, id).await }
+                }))
+        )
+        .service(
+            web::resource("/host-invites/{id}/accept")
+                .default_service(method_not_allowed("POST"))
+                .wrap(AuthMiddleware)
+                .route(web::post().to(|claims: web::ReqData<Claims>, id: PathObjectId, controller: web::Data<CalendarController>| {
+                    async move { controller.respond_to_host_invite(claims, id, HostInviteStatus::Accepted).await }
+                }))
+        )
+        .service(
+            web::resource("/host-invites/{id}/decline")
+                .default_service(method_not_allowed("POST"))
+                .wrap(AuthMiddleware)
+                .route(web::post().to(|claims: web::ReqData<Claims>, id: PathObjectId, controller: web::Data<CalendarController>| {
+                    async move { controller.respond_to_host_invite(claims, id, HostInviteStatus::Declined).await }
+                }))
+        )
+        .service(
+            web::resource("/event-types/{id}/restore")
+                .default_service(method_not_allowed("POST"))
+                .wrap(AuthMiddleware)
+                .route(web::post().to(|claims: web::ReqData<Claims>, id: PathObjectId, controller: web::Data<CalendarController>| {
+                    async move { controller.restore_event_type(claims, id).await }
                 }))
         )
     )
@@ -1,23 +1,33 @@
 use std::collections::HashMap;use serde::{Deserialize, Serialize};
 use validator::Validate;
 use crate::modules::calendar::calendar_model::{
-    AvailabilityRule, BufferTime, TimeSlot, AvailabilitySlot
+    Availability, AvailabilityRule, CalendarSettings, BufferTime, ConfirmationSettings, DateOverride, TimeSlot, AvailabilitySlot, CancellationPolicy, ReschedulePolicy, SchedulingWindow, DayOverride, EmbedSettings, EventType, EventTypeTranslation, HostAssignment, HostInvite, HostInviteStatus, Location, Question, Reminder, RoundRobinStrategy
 };
+use crate::utils::markdown;
+use crate::utils::timezone::TimezoneResolution;
 
 #[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct CreateCalendarSettingsRequest {
-    #[validate(length(min = 1, message = "Timezone is required"))]
+    #[validate(length(min = 1, max = 64, message = "Timezone must be between 1 and 64 characters"))]
     pub timezone: String,
     pub working_hours: HashMap<String, Vec<TimeSlot>>,
     pub buffer_time: BufferTime,
     #[validate(range(min = 15, max = 120, message = "Meeting duration must be between 15 and 120 minutes"))]
     pub default_meeting_duration: i32,
-    #[validate(length(min = 1, message = "Calendar name is required"))]
+    #[validate(range(min = 5, max = 120, message = "Slot interval must be between 5 and 120 minutes"))]
+    pub slot_interval: Option<i32>,  // Omit to start slots back to back
+    #[validate(range(min = 15, max = 1440, message = "Daily meeting minutes must be between 15 and 1440"))]
+    pub max_meeting_minutes_per_day: Option<i32>,  // Omit for no cap
+    #[validate(length(min = 1, max = 100, message = "Calendar name must be between 1 and 100 characters"))]
     pub calendar_name: String,
-    #[validate(length(min = 1, message = "Date format is required"))]
+    #[validate(length(min = 1, max = 32, message = "Date format must be between 1 and 32 characters"))]
     pub date_format: String,
-    #[validate(length(min = 1, message = "Time format is required"))]
+    #[validate(length(min = 1, max = 32, message = "Time format must be between 1 and 32 characters"))]
     pub time_format: String,
+    pub public_page_enabled: Option<bool>,  // Defaults to true; kept as-is on update when omitted
+    #[validate(length(max = 500, message = "Public page message must be at most 500 characters"))]
+    pub public_page_message: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -28,27 +38,64 @@ pub struct CalendarSettingsResponse {
     pub working_hours: HashMap<String, Vec<TimeSlot>>,
     pub buffer_time: BufferTime,
     pub default_meeting_duration: i32,
+    pub slot_interval: Option<i32>,
+    pub max_meeting_minutes_per_day: Option<i32>,
     pub calendar_name: String,
     pub date_format: String,
     pub time_format: String,
+    pub public_page_enabled: bool,
+    pub public_page_message: Option<String>,
     pub created_at: String,
     pub updated_at: String,
 }
 
+impl From<CalendarSettings> for CalendarSettingsResponse {
+    fn from(settings: CalendarSettings) -> Self {
+        Self {
+            id: settings.id.unwrap().to_hex(),
+            user_id: settings.user_id.to_hex(),
+            timezone: settings.timezone,
+            working_hours: settings.working_hours,
+            buffer_time: settings.buffer_time,
+            default_meeting_duration: settings.default_meeting_duration,
+            slot_interval: settings.slot_interval,
+            max_meeting_minutes_per_day: settings.max_meeting_minutes_per_day,
+            calendar_name: settings.calendar_name,
+            date_format: settings.date_format,
+            time_format: settings.time_format,
+            public_page_enabled: settings.public_page_enabled,
+            public_page_message: settings.public_page_message,
+            created_at: settings.created_at.to_string(),
+            updated_at: settings.updated_at.to_string(),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct CreateAvailabilityRuleRequest {
+    #[validate(length(max = 64, message = "Rule ID must be at most 64 characters"))]
+    pub rule_id: Option<String>,  // On update, keeps the id of an existing rule
+    #[validate(length(max = 64, message = "Start date must be at most 64 characters"))]
     pub start_date: String,  // ISO 8601 format
+    #[validate(length(max = 64, message = "End date must be at most 64 characters"))]
     pub end_date: Option<String>,  // ISO 8601 format
     pub is_recurring: bool,
+    #[validate(length(max = 100, message = "Recurrence pattern must be at most 100 characters"))]
     pub recurrence_pattern: Option<String>,
     pub slots: Vec<AvailabilitySlot>,
+    pub priority: Option<i32>,  // Defaults to 0
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct CreateAvailabilityRequest {
     pub calendar_settings_id: String,
-    #[validate(length(min = 1, message = "At least one availability rule is required"))]
+    #[validate(length(min = 1, max = 100, message = "Schedule name must be between 1 and 100 characters"))]
+    pub name: Option<String>,  // Defaults to "Working hours"; a user's first schedule becomes their default
+    #[validate(length(min = 1, message = "At least one availability rule is required"), nested)]
     pub rules: Vec<CreateAvailabilityRuleRequest>,
+    pub date_overrides: Option<Vec<DateOverride>>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -56,16 +103,109 @@ pub struct AvailabilityResponse {
     pub id: String,
     pub user_id: String,
     pub calendar_settings_id: String,
+    pub name: String,
+    pub is_default: bool,
     pub rules: Vec<AvailabilityRule>,
+    pub date_overrides: Vec<DateOverride>,
+    pub version: i64,
+    pub deleted_at: Option<String>,
     pub created_at: String,
     pub updated_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_type_ids: Option<Vec<String>>,  // Event types booking against this schedule, on reads only
+}
+
+impl AvailabilityResponse {
+    pub fn with_event_types(mut self, event_type_ids: Vec<String>) -> Self {
+        self.event_type_ids = Some(event_type_ids);
+        self
+    }
+}
+
+impl From<Availability> for AvailabilityResponse {
+    fn from(availability: Availability) -> Self {
+        let mut rules = availability.rules;
+        rules.sort_by(|a, b| {
+            a.start_date.cmp(&b.start_date).then_with(|| a.rule_id.cmp(&b.rule_id))
+        });
+
+        Self {
+            id: availability.id.unwrap().to_hex(),
+            user_id: availability.user_id.to_hex(),
+            calendar_settings_id: availability.calendar_settings_id.to_hex(),
+            name: availability.name,
+            is_default: availability.is_default,
+            rules,
+            date_overrides: availability.date_overrides,
+            version: availability.version,
+            deleted_at: availability.deleted_at.map(|at| at.to_string()),
+            created_at: availability.created_at.to_string(),
+            updated_at: availability.updated_at.to_string(),
+            event_type_ids: None,
+        }
+    }
+}
+
+/// An upcoming booking that no longer fits the host's availability after a change.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AffectedBooking {
+    pub booking_id: String,
+    pub date: String,
+    pub start_time: String,
+    pub invitee_name: String,
+}
+
+/// An update response plus the bookings the change left outside availability.
+/// The change itself is never blocked by them.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WithAffectedBookings<T> {
+    #[serde(flatten)]
+    pub inner: T,
+    pub affected_bookings: Vec<AffectedBooking>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ListAvailabilityQuery {
+    #[serde(default)]
+    pub include_deleted: bool,  // Also list schedules that can still be restored
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct ListEventTypesQuery {
+    #[serde(default)]
+    pub include_archived: bool,
+    #[validate(length(min = 1, max = 100, message = "Search must be between 1 and 100 characters"))]
+    pub q: Option<String>,  // Case-insensitive match on name or description
+    pub is_active: Option<bool>,
+    pub location_type: Option<String>,  // "in_person", "phone", "video" or "custom"
+    #[validate(range(min = 1, message = "duration_min must be at least 1 minute"))]
+    pub duration_min: Option<i32>,
+    #[validate(range(min = 1, message = "duration_max must be at least 1 minute"))]
+    pub duration_max: Option<i32>,
+    pub sort: Option<String>,  // "name", "created_at" or "position"; the user's order by default
+    pub direction: Option<String>,  // "asc", the default, or "desc"
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DeleteEventTypeQuery {
+    #[serde(default)]
+    pub permanent: bool,  // Remove the document instead of archiving it
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct CheckAvailabilityRequest {
     pub start_date: String,  // ISO 8601 format
     pub end_date: String,    // ISO 8601 format
-    pub duration: i32,       // minutes
+    #[validate(range(min = 1, message = "Duration must be at least 1 minute"))]
+    pub duration: Option<i32>,        // minutes; required unless event_type_id is given
+    pub event_type_id: Option<String>,  // Takes precedence over duration; applies per-day overrides
+    pub timezone: Option<String>,  // IANA name, overrides the profile/settings timezone
+    #[validate(range(min = 1, max = 10, message = "Recommend must be between 1 and 10"))]
+    pub recommend: Option<usize>,  // How many recommended slots to pick, if any
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Ord, PartialOrd, Eq, PartialEq)]
@@ -73,50 +213,143 @@ pub struct AvailableTimeSlot {
     pub date: String,        // YYYY-MM-DD format
     pub start_time: String,  // HH:mm format
     pub end_time: String,    // HH:mm format
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spots_remaining: Option<u32>,  // Group events only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub starts_at: Option<String>,  // RFC 3339 in the requested timezone; date and times above are the host's
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ends_at: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CheckAvailabilityResponse {
     pub available_slots: Vec<AvailableTimeSlot>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recommended: Option<Vec<AvailableTimeSlot>>,  // Subset of available_slots, best first
+    pub timezone_resolution: TimezoneResolution,
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct UpdateAvailabilityRequest {
-    #[validate(length(min = 1, message = "At least one availability rule is required"))]
+    #[validate(length(min = 1, max = 100, message = "Schedule name must be between 1 and 100 characters"))]
+    pub name: Option<String>,  // Keeps the current name when omitted
+    #[validate(length(min = 1, message = "At least one availability rule is required"), nested)]
     pub rules: Vec<CreateAvailabilityRuleRequest>,
+    pub date_overrides: Option<Vec<DateOverride>>,  // Replaces all date overrides; kept when omitted
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SetDateOverrideRequest {
+    pub slots: Vec<TimeSlot>,  // Empty blocks the whole day
+}
+
+/// The `{date}` segment of date override routes, YYYY-MM-DD.
+#[derive(Debug, Deserialize)]
+pub struct DateOverridePath {
+    pub date: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct CheckTimeSlotRequest {
     pub date: String,         // YYYY-MM-DD format
     pub start_time: String,   // HH:mm format
     pub end_time: String,     // HH:mm format
+    pub event_type_id: Option<String>,  // Checks against its schedule instead of the default one
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ConflictRange {
+    pub start: String,
+    pub end: String,
+}
+
+/// Why a slot is not available. Optional fields are only present when the
+/// conflict comes from that kind of source.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SlotConflict {
+    pub code: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub booking_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub range: Option<ConflictRange>,
+}
+
+impl SlotConflict {
+    pub fn new(code: &str, message: &str) -> Self {
+        Self {
+            code: code.to_string(),
+            message: message.to_string(),
+            booking_id: None,
+            rule_id: None,
+            range: None,
+        }
+    }
+
+    /// The conflict without which booking, rule or time range caused it, for
+    /// anyone but the host.
+    pub fn without_details(self) -> Self {
+        Self { booking_id: None, rule_id: None, range: None, ..self }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CheckTimeSlotResponse {
     pub is_available: bool,
-    pub conflicts: Option<Vec<String>>,  // Reasons why the slot is not available, if any
+    pub conflicts: Vec<SlotConflict>,
+    pub messages: Vec<String>,  // Conflict messages only, for older clients
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct CreateEventTypeRequest {
-    #[validate(length(min = 1, message = "Name is required"))]
+    #[validate(length(min = 1, max = 100, message = "Name must be between 1 and 100 characters"))]
     pub name: String,
+    #[validate(length(max = 5000, message = "Description must be at most 5000 characters"))]
     pub description: Option<String>,
     #[validate(range(min = 15, max = 480, message = "Duration must be between 15 and 480 minutes"))]
     pub duration: i32,
-    #[validate(length(min = 1, message = "Color is required"))]
+    #[validate(length(min = 1, max = 7, message = "Color must be between 1 and 7 characters"))]
     pub color: String,
-    #[validate(length(min = 1, message = "Location type is required"))]
-    pub location_type: String,
-    pub meeting_link: Option<String>,
-    pub questions: Vec<String>,
+    #[serde(default)]
+    #[validate(length(max = 10, message = "At most 10 locations are allowed"))]
+    pub locations: Vec<Location>,
+    pub questions: Vec<Question>,
     #[validate(length(min = 1, message = "Availability schedule ID is required"))]
     pub availability_schedule_id: String,
     pub buffer_time: Option<BufferTime>,
     pub min_booking_notice: Option<i32>,
     pub max_booking_notice: Option<i32>,
+    #[validate(range(min = 5, max = 120, message = "Slot interval must be between 5 and 120 minutes"))]
+    pub slot_interval: Option<i32>,  // Omit to use the calendar settings
+    pub cancellation_policy: Option<CancellationPolicy>,
+    pub reschedule_policy: Option<ReschedulePolicy>,
+    pub scheduling_window: Option<SchedulingWindow>,
+    pub embed_settings: Option<EmbedSettings>,
+    pub confirmation_settings: Option<ConfirmationSettings>,
+    pub day_overrides: Option<HashMap<String, DayOverride>>,
+    pub translations: Option<HashMap<String, EventTypeTranslation>>,
+    pub email_templates: Option<HashMap<String, String>>,  // Keyed by "confirmation", "reminder" or "cancellation"
+    #[serde(default)]
+    pub requires_confirmation: bool,
+    #[validate(range(min = 1, max = 1000, message = "Max attendees must be between 1 and 1000"))]
+    pub max_attendees: Option<i32>,
+    #[validate(length(max = 5, message = "At most 5 reminders are allowed"))]
+    pub reminders: Option<Vec<Reminder>>,  // Omit for the default reminder; an empty list sends none
+    #[validate(length(max = 10, message = "At most 10 hosts are allowed"))]
+    #[serde(default)]
+    pub hosts: Vec<String>,  // Hosts besides the owner, who always hosts
+    #[serde(default)]
+    pub host_assignment: HostAssignment,  // Collective: all of them attend; round robin: one per booking
+    #[serde(default)]
+    pub round_robin_strategy: RoundRobinStrategy,
+    #[serde(default)]
+    pub is_secret: bool,
     pub is_active: bool,
 }
 
@@ -125,38 +358,344 @@ pub struct EventTypeResponse {
     pub id: String,
     pub user_id: String,
     pub name: String,
-    pub description: Option<String>,
+    pub slug: String,
+    pub slug_history: Vec<String>,  // Earlier slugs that public links still resolve, oldest first
+    pub description: Option<String>,  // Markdown source
+    pub description_html: Option<String>,  // Sanitized HTML rendering of description
     pub duration: i32,
     pub color: String,
-    pub location_type: String,
-    pub meeting_link: Option<String>,
-    pub questions: Vec<String>,
+    pub locations: Vec<Location>,
+    pub questions: Vec<Question>,
     pub availability_schedule_id: String,
     pub buffer_time: Option<BufferTime>,
     pub min_booking_notice: Option<i32>,
     pub max_booking_notice: Option<i32>,
+    pub slot_interval: Option<i32>,
+    pub cancellation_policy: Option<CancellationPolicy>,
+    pub reschedule_policy: Option<ReschedulePolicy>,
+    pub scheduling_window: Option<SchedulingWindow>,
+    pub embed_settings: Option<EmbedSettings>,
+    pub confirmation_settings: Option<ConfirmationSettings>,
+    pub day_overrides: Option<HashMap<String, DayOverride>>,
+    pub translations: Option<HashMap<String, EventTypeTranslation>>,
+    pub email_templates: Option<HashMap<String, String>>,
+    pub requires_confirmation: bool,
+    pub max_attendees: Option<i32>,
+    pub reminders: Option<Vec<Reminder>>,
+    pub hosts: Vec<String>,
+    pub host_assignment: HostAssignment,
+    pub round_robin_strategy: RoundRobinStrategy,
+    pub is_secret: bool,
+    pub position: i32,
     pub is_active: bool,
+    pub archived_at: Option<String>,
     pub created_at: String,
     pub updated_at: String,
 }
 
+impl From<EventType> for EventTypeResponse {
+    fn from(event_type: EventType) -> Self {
+        Self {
+            id: event_type.id.unwrap().to_hex(),
+            user_id: event_type.user_id.to_hex(),
+            name: event_type.name,
+            slug: event_type.slug,
+            slug_history: event_type.slug_history,
+            description_html: event_type.description.as_deref().map(markdown::render_markdown),
+            description: event_type.description,
+            duration: event_type.duration,
+            color: event_type.color,
+            locations: event_type.locations,
+            questions: event_type.questions,
+            availability_schedule_id: event_type.availability_schedule_id.to_hex(),
+            buffer_time: event_type.buffer_time,
+            min_booking_notice: event_type.min_booking_notice,
+            max_booking_notice: event_type.max_booking_notice,
+            slot_interval: event_type.slot_interval,
+            cancellation_policy: event_type.cancellation_policy,
+            reschedule_policy: event_type.reschedule_policy,
+            scheduling_window: event_type.scheduling_window,
+            embed_settings: event_type.embed_settings,
+            confirmation_settings: event_type.confirmation_settings,
+            day_overrides: event_type.day_overrides,
+            translations: event_type.translations,
+            email_templates: event_type.email_templates,
+            requires_confirmation: event_type.requires_confirmation,
+            max_attendees: event_type.max_attendees,
+            reminders: event_type.reminders,
+            hosts: event_type.hosts.iter().map(|host| host.to_hex()).collect(),
+            host_assignment: event_type.host_assignment,
+            round_robin_strategy: event_type.round_robin_strategy,
+            is_secret: event_type.is_secret,
+            position: event_type.position,
+            is_active: event_type.is_active,
+            archived_at: event_type.archived_at.map(|at| at.to_string()),
+            created_at: event_type.created_at.to_string(),
+            updated_at: event_type.updated_at.to_string(),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct UpdateEventTypeRequest {
-    #[validate(length(min = 1, message = "Name is required"))]
+    #[validate(length(min = 1, max = 100, message = "Name must be between 1 and 100 characters"))]
     pub name: Option<String>,
+    pub slug: Option<String>,  // Links with the old slug keep working until another event type takes it
+    #[validate(length(max = 5000, message = "Description must be at most 5000 characters"))]
     pub description: Option<String>,
     #[validate(range(min = 15, max = 480, message = "Duration must be between 15 and 480 minutes"))]
     pub duration: Option<i32>,
-    #[validate(length(min = 1, message = "Color is required"))]
+    #[validate(length(min = 1, max = 7, message = "Color must be between 1 and 7 characters"))]
     pub color: Option<String>,
-    #[validate(length(min = 1, message = "Location type is required"))]
-    pub location_type: Option<String>,
-    pub meeting_link: Option<String>,
-    pub questions: Option<Vec<String>>,
+    #[validate(length(max = 10, message = "At most 10 locations are allowed"))]
+    pub locations: Option<Vec<Location>>,  // Replaces all locations
+    pub questions: Option<Vec<Question>>,
     pub buffer_time: Option<BufferTime>,
     pub min_booking_notice: Option<i32>,
     pub max_booking_notice: Option<i32>,
+    #[validate(range(min = 5, max = 120, message = "Slot interval must be between 5 and 120 minutes"))]
+    pub slot_interval: Option<i32>,
+    #[serde(default, with = "crate::utils::nullable")]
+    pub cancellation_policy: Option<Option<CancellationPolicy>>,  // Null removes the policy
+    #[serde(default, with = "crate::utils::nullable")]
+    pub reschedule_policy: Option<Option<ReschedulePolicy>>,
+    pub scheduling_window: Option<SchedulingWindow>,
+    pub embed_settings: Option<EmbedSettings>,
+    pub confirmation_settings: Option<ConfirmationSettings>,
+    pub day_overrides: Option<HashMap<String, DayOverride>>,
+    pub translations: Option<HashMap<String, EventTypeTranslation>>,  // Replaces all translations
+    pub email_templates: Option<HashMap<String, String>>,  // Replaces all email templates
+    pub requires_confirmation: Option<bool>,
+    #[validate(range(min = 1, max = 1000, message = "Max attendees must be between 1 and 1000"))]
+    pub max_attendees: Option<i32>,  // 1 turns a group event back into a one-on-one event
+    #[validate(length(max = 5, message = "At most 5 reminders are allowed"))]
+    pub reminders: Option<Vec<Reminder>>,  // Replaces all reminders
+    #[validate(length(max = 10, message = "At most 10 hosts are allowed"))]
+    pub hosts: Option<Vec<String>>,  // Replaces all hosts; empty makes the event the owner's alone again
+    pub host_assignment: Option<HostAssignment>,
+    pub round_robin_strategy: Option<RoundRobinStrategy>,
+    pub is_secret: Option<bool>,
     pub is_active: Option<bool>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct ReorderEventTypesRequest {
+    #[validate(length(min = 1, max = 500, message = "Between 1 and 500 event type IDs are required"))]
+    pub event_type_ids: Vec<String>,  // New order; event types left out keep their order after these
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreateHostInviteRequest {
+    #[validate(email(message = "Invalid email"), length(max = 254, message = "Email must be at most 254 characters"))]
+    pub email: String,
+}
+
+/// A host invitation as either side sees it. The other user's name is only
+/// shown to the host, or to the owner once the host has accepted.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HostInviteResponse {
+    pub id: String,
+    pub owner_user_id: String,
+    pub host_user_id: String,
+    pub status: HostInviteStatus,
+    pub received: bool,  // Whether the caller is the invited host
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub other_name: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl HostInviteResponse {
+    pub fn new(invite: HostInvite, received: bool, other_name: Option<String>) -> Self {
+        Self {
+            id: invite.id.map(|id| id.to_hex()).unwrap_or_default(),
+            owner_user_id: invite.owner_user_id.to_hex(),
+            host_user_id: invite.host_user_id.to_hex(),
+            status: invite.status,
+            received,
+            other_name,
+            created_at: invite.created_at.to_string(),
+            updated_at: invite.updated_at.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::de::DeserializeOwned;
+    use serde_json::json;
+    use validator::{ValidationErrors, ValidationErrorsKind};
+
+    fn validation_errors<T: DeserializeOwned + Validate>(body: serde_json::Value) -> ValidationErrors {
+        let request: T = serde_json::from_value(body).unwrap();
+        request.validate().unwrap_err()
+    }
+
+    fn field_names(errors: &ValidationErrors) -> Vec<String> {
+        let mut fields: Vec<String> = errors.field_errors().keys().map(|field| field.to_string()).collect();
+        fields.sort();
+        fields
+    }
 
+    /// The invalid fields of the first rule of an availability request.
+    fn first_rule_field_names(errors: &ValidationErrors) -> Vec<String> {
+        match errors.errors().get("rules") {
+            Some(ValidationErrorsKind::List(rules)) => field_names(&rules[&0]),
+            other => panic!("Expected rule errors, got {:?}", other),
+        }
+    }
+
+    fn oversized_rule() -> serde_json::Value {
+        json!({
+            "rule_id": "r".repeat(65),
+            "start_date": "2024-01-01T00:00:00Z".repeat(4),
+            "end_date": "2024-12-31T00:00:00Z".repeat(4),
+            "is_recurring": true,
+            "recurrence_pattern": "weekly".repeat(20),
+            "slots": [],
+            "priority": null,
+        })
+    }
+
+    #[test]
+    fn oversized_settings_fields_are_named_in_the_errors() {
+        let errors = validation_errors::<CreateCalendarSettingsRequest>(json!({
+            "timezone": "T".repeat(65),
+            "working_hours": {},
+            "buffer_time": { "before": 0, "after": 0 },
+            "default_meeting_duration": 30,
+            "slot_interval": null,
+            "calendar_name": "c".repeat(101),
+            "date_format": "d".repeat(33),
+            "time_format": "t".repeat(33),
+            "public_page_enabled": null,
+            "public_page_message": "m".repeat(501),
+        }));
+        assert_eq!(field_names(&errors), ["calendar_name", "date_format", "public_page_message", "time_format", "timezone"]);
+    }
+
+    #[test]
+    fn oversized_availability_fields_are_named_in_the_errors() {
+        let rule_fields = ["end_date", "recurrence_pattern", "rule_id", "start_date"];
+
+        let errors = validation_errors::<CreateAvailabilityRequest>(json!({
+            "calendar_settings_id": "65f0c0ffee0000000000beef",
+            "name": "n".repeat(101),
+            "rules": [oversized_rule()],
+        }));
+        assert_eq!(field_names(&errors), ["name"]);
+        assert_eq!(first_rule_field_names(&errors), rule_fields);
+
+        let errors = validation_errors::<UpdateAvailabilityRequest>(json!({
+            "name": "n".repeat(101),
+            "rules": [oversized_rule()],
+        }));
+        assert_eq!(field_names(&errors), ["name"]);
+        assert_eq!(first_rule_field_names(&errors), rule_fields);
+    }
+
+    #[test]
+    fn oversized_event_type_fields_are_named_in_the_errors() {
+        let errors = validation_errors::<CreateEventTypeRequest>(json!({
+            "name": "n".repeat(101),
+            "description": "d".repeat(5001),
+            "duration": 30,
+            "color": "#0000000",
+            "questions": [],
+            "availability_schedule_id": "65f0c0ffee0000000000beef",
+            "is_active": true,
+        }));
+        assert_eq!(field_names(&errors), ["color", "description", "name"]);
+
+        let errors = validation_errors::<UpdateEventTypeRequest>(json!({
+            "name": "n".repeat(101),
+            "description": "d".repeat(5001),
+            "color": "#0000000",
+        }));
+        assert_eq!(field_names(&errors), ["color", "description", "name"]);
+    }
+
+    #[test]
+    fn oversized_search_and_invite_fields_are_named_in_the_errors() {
+        let errors = validation_errors::<ListEventTypesQuery>(json!({ "q": "q".repeat(101) }));
+        assert_eq!(field_names(&errors), ["q"]);
+
+        let errors = validation_errors::<CreateHostInviteRequest>(json!({ "email": format!("{}@example.com", "e".repeat(250)) }));
+        assert_eq!(field_names(&errors), ["email"]);
+    }
+
+    fn booking_conflict() -> SlotConflict {
+        SlotConflict {
+            booking_id: Some("65f0c0ffee0000000000beef".to_string()),
+            range: Some(ConflictRange { start: "10:00".to_string(), end: "10:30".to_string() }),
+            ..SlotConflict::new("booking_overlap", "Time slot overlaps an existing booking")
+        }
+    }
+
+    #[test]
+    fn check_time_slot_response_shape() {
+        let response = CheckTimeSlotResponse {
+            is_available: false,
+            messages: vec!["Time slot overlaps an existing booking".to_string(), "Outside your availability".to_string()],
+            conflicts: vec![
+                booking_conflict(),
+                SlotConflict { rule_id: Some("rule-1".to_string()), ..SlotConflict::new("outside_rule", "Outside your availability") },
+            ],
+        };
+
+        assert_eq!(serde_json::to_value(&response).unwrap(), json!({
+            "is_available": false,
+            "conflicts": [
+                {
+                    "code": "booking_overlap",
+                    "message": "Time slot overlaps an existing booking",
+                    "booking_id": "65f0c0ffee0000000000beef",
+                    "range": { "start": "10:00", "end": "10:30" },
+                },
+                {
+                    "code": "outside_rule",
+                    "message": "Outside your availability",
+                    "rule_id": "rule-1",
+                },
+            ],
+            "messages": ["Time slot overlaps an existing booking", "Outside your availability"],
+        }));
+    }
+
+    #[test]
+    fn available_slot_has_empty_conflicts() {
+        let response = CheckTimeSlotResponse { is_available: true, conflicts: Vec::new(), messages: Vec::new() };
+
+        assert_eq!(
+            serde_json::to_value(&response).unwrap(),
+            json!({ "is_available": true, "conflicts": [], "messages": [] })
+        );
+    }
+
+    #[test]
+    fn conflicts_without_details_keep_only_code_and_message() {
+        assert_eq!(serde_json::to_value(booking_conflict().without_details()).unwrap(), json!({
+            "code": "booking_overlap",
+            "message": "Time slot overlaps an existing booking",
+        }));
+    }
+
+    #[test]
+    fn event_type_policies_are_cleared_by_null_and_kept_when_left_out() {
+        let update: UpdateEventTypeRequest = serde_json::from_value(json!({
+            "cancellation_policy": null,
+            "reschedule_policy": { "allowed": false, "min_notice_minutes": 60, "policy_text": null },
+        }))
+        .unwrap();
+        assert!(matches!(update.cancellation_policy, Some(None)));
+        assert!(matches!(update.reschedule_policy, Some(Some(ReschedulePolicy { allowed: false, min_notice_minutes: 60, .. }))));
+
+        let update: UpdateEventTypeRequest = serde_json::from_value(json!({ "name": "Intro call" })).unwrap();
+        assert!(update.cancellation_policy.is_none());
+        assert!(update.reschedule_policy.is_none());
+    }
+}
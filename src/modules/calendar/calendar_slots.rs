@@ -0,0 +1,526 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use actix_web::http::StatusCode;
+use chrono::{NaiveDate, NaiveTime};
+use mongodb::bson::oid::ObjectId;
+use mongodb::Database;
+
+use crate::errors::error::AppError;
+use crate::modules::booking::booking_crud::{BookingRepository, SlotHoldRepository};
+use crate::modules::booking::booking_model::Booking;
+use crate::modules::calendar::calendar_crud::{AvailabilityRepository, CalendarSettingsRepository, EventTypeRepository, HostInviteRepository};
+use crate::modules::calendar::calendar_engine::{self, BookedWindow};
+use crate::modules::calendar::calendar_model::{Availability, AvailabilityRule, BufferTime, CalendarSettings, DateOverride, EventType, ScheduleVersion};
+use crate::modules::calendar::calendar_schema::{AvailableTimeSlot, ConflictRange, SlotConflict};
+use crate::modules::user::user_crud::UserRepository;
+
+/// Loads what slot generation needs to know about a host, so availability
+/// and booking see every host the same way.
+pub struct SlotSources {
+    settings_repository: CalendarSettingsRepository,
+    availability_repository: AvailabilityRepository,
+    event_type_repository: EventTypeRepository,
+    booking_repository: BookingRepository,
+    slot_hold_repository: SlotHoldRepository,
+    host_invite_repository: HostInviteRepository,
+    user_repository: UserRepository,
+}
+
+/// A host of an event type and what decides when they are free.
+#[derive(Clone)]
+pub struct HostSchedule {
+    pub user_id: ObjectId,
+    pub settings: CalendarSettings,
+    pub rules: Vec<AvailabilityRule>,  // Co-hosts are free by their default schedule
+    pub date_overrides: Vec<DateOverride>,
+    pub schedule_version: Option<ScheduleVersion>,
+}
+
+impl HostSchedule {
+    pub fn new(user_id: ObjectId, settings: CalendarSettings, schedule: Availability) -> Self {
+        let schedule_version = schedule.schedule_version();
+        Self { user_id, settings, rules: schedule.rules, date_overrides: schedule.date_overrides, schedule_version }
+    }
+
+    /// Whether `[start_time, end_time)` on `date` is inside this host's availability.
+    pub fn is_available(&self, date: &str, start_time: &str, end_time: &str, conflicts: &mut Vec<SlotConflict>) -> bool {
+        calendar_engine::is_slot_available(date, start_time, end_time, &self.settings, &self.rules, &self.date_overrides, conflicts)
+    }
+}
+
+/// Minutes of meetings in `bookings`. A group event slot counts once,
+/// however many attendees it has.
+pub fn meeting_minutes<'a>(bookings: impl IntoIterator<Item = &'a Booking>) -> i64 {
+    let mut slots = HashSet::new();
+    bookings
+        .into_iter()
+        .filter(|booking| slots.insert((booking.event_type_id, booking.start_time.as_str())))
+        .map(|booking| {
+            let minutes = calendar_engine::parse_end_time(&booking.end_time) - calendar_engine::parse_start_time(&booking.start_time);
+            minutes.num_minutes().max(0)
+        })
+        .sum()
+}
+
+/// Why a meeting of `event_type_id` over `slot` would take a host whose day
+/// already holds `bookings` past `cap` minutes of meetings, if it would.
+/// Joining a group event slot that is already booked adds no time.
+pub fn daily_cap_conflict<'a>(
+    cap: Option<i32>,
+    bookings: impl IntoIterator<Item = &'a Booking> + Clone,
+    event_type_id: Option<&ObjectId>,
+    (start_time, end_time): (NaiveTime, NaiveTime),
+) -> Option<SlotConflict> {
+    let cap = i64::from(cap?);
+    let joins_booked_slot = bookings.clone().into_iter().any(|booking| {
+        Some(&booking.event_type_id) == event_type_id && calendar_engine::parse_start_time(&booking.start_time) == start_time
+    });
+    let minutes = if joins_booked_slot { 0 } else { (end_time - start_time).num_minutes() };
+    if minutes > 0 && meeting_minutes(bookings) + minutes > cap {
+        return Some(SlotConflict::new("daily_minutes_cap", "Daily meeting minutes cap reached"));
+    }
+    None
+}
+
+/// Time in a date range that a host cannot be booked.
+#[derive(Debug, Default)]
+pub struct BusyTime {
+    pub booked: HashMap<String, Vec<BookedWindow>>,  // Keyed by YYYY-MM-DD
+    pub seats_held: HashMap<(String, String), u32>,  // Held seats of the group event type per slot
+}
+
+impl BusyTime {
+    /// Adds another host's busy time, for slots that need every host free.
+    pub fn merge(&mut self, other: BusyTime) {
+        for (date, windows) in other.booked {
+            self.booked.entry(date).or_default().extend(windows);
+        }
+        for (slot, held) in other.seats_held {
+            *self.seats_held.entry(slot).or_default() += held;
+        }
+    }
+}
+
+impl SlotSources {
+    pub fn new(db: Database) -> Self {
+        Self {
+            settings_repository: CalendarSettingsRepository::new(db.clone()),
+            availability_repository: AvailabilityRepository::new(db.clone()),
+            event_type_repository: EventTypeRepository::new(db.clone()),
+            booking_repository: BookingRepository::new(db.clone()),
+            slot_hold_repository: SlotHoldRepository::new(db.clone()),
+            host_invite_repository: HostInviteRepository::new(db),
+            user_repository: UserRepository::new(),
+        }
+    }
+
+    /// The host's pending and confirmed bookings and unexpired slot holds from
+    /// `start_day` to `end_day` inclusive, each padded by its event type's
+    /// buffer. Bookings and holds of `group_event_type` count as seats instead.
+    pub async fn busy_time(
+        &self,
+        host_user_id: &ObjectId,
+        settings: &CalendarSettings,
+        start_day: NaiveDate,
+        end_day: NaiveDate,
+        group_event_type: Option<&ObjectId>,
+    ) -> Result<BusyTime, AppError> {
+        let host_event_types = self.event_type_repository.find_map_by_user_id(host_user_id).await?;
+        let mut booked = self.booking_repository
+            .find_booked_windows(host_user_id, start_day, end_day, group_event_type, &host_event_types, &settings.buffer_time)
+            .await?;
+        // Slots invitees are holding while they fill in the booking form are busy too
+        let seats_held = self.slot_hold_repository
+            .add_held_windows(&mut booked, host_user_id, start_day, end_day, group_event_type, &host_event_types, &settings.buffer_time)
+            .await?;
+
+        Ok(BusyTime { booked, seats_held })
+    }
+
+    /// Open slots from `start_day` to `end_day` inclusive for `owner`, or for
+    /// `event_type` when given, and the time that is busy for its hosts.
+    /// Collective event types need every host free; round-robin ones any one.
+    pub async fn team_slots(
+        &self,
+        event_type: Option<&EventType>,
+        owner: &HostSchedule,
+        start_day: NaiveDate,
+        end_day: NaiveDate,
+        default_duration: i32,
+    ) -> Result<(Vec<AvailableTimeSlot>, BusyTime), AppError> {
+        if let Some(event_type) = event_type.filter(|event_type| event_type.is_round_robin()) {
+            let mut members = vec![owner.clone()];
+            members.extend(self.host_schedules(&event_type.hosts, &owner.settings).await?);
+
+            let mut slots = BTreeSet::new();
+            let mut busy = BusyTime::default();
+            for member in &members {
+                let member_busy = self.busy_time(&member.user_id, &member.settings, start_day, end_day, None).await?;
+                let member_slots = calendar_engine::collect_slots(
+                    &[(member.rules.as_slice(), member.date_overrides.as_slice())],
+                    start_day,
+                    end_day,
+                    Some(event_type),
+                    event_type.duration,
+                    &member.settings,
+                    &member_busy.booked,
+                );
+                let mut member_slots: Vec<_> = member_slots.into_iter().filter(|slot| {
+                    member.is_available(&slot.date, &slot.start_time, &slot.end_time, &mut Vec::new())
+                }).collect();
+                self.retain_under_daily_cap(&mut member_slots, member, Some(event_type), start_day, end_day).await?;
+                slots.extend(member_slots);
+                busy.merge(member_busy);
+            }
+            return Ok((slots.into_iter().collect(), busy));
+        }
+
+        // Group event slots stay open until every seat is taken
+        let group_event_type_id = event_type
+            .filter(|event_type| event_type.capacity() > 1)
+            .and_then(|event_type| event_type.id);
+        let mut busy = self
+            .busy_time(&owner.user_id, &owner.settings, start_day, end_day, group_event_type_id.as_ref())
+            .await?;
+
+        // Collective event types only offer slots every co-host is free for too
+        let co_hosts = match event_type {
+            Some(event_type) => self.co_host_schedules(event_type, &owner.settings).await?,
+            None => Vec::new(),
+        };
+        for host in &co_hosts {
+            busy.merge(self.busy_time(&host.user_id, &host.settings, start_day, end_day, None).await?);
+        }
+        let schedules: Vec<(&[AvailabilityRule], &[DateOverride])> = std::iter::once(owner)
+            .chain(&co_hosts)
+            .map(|host| (host.rules.as_slice(), host.date_overrides.as_slice()))
+            .collect();
+
+        let mut slots = calendar_engine::collect_slots(
+            &schedules,
+            start_day,
+            end_day,
+            event_type,
+            default_duration,
+            &owner.settings,
+            &busy.booked,
+        );
+        slots.retain(|slot| co_hosts.iter().all(|host| {
+            host.is_available(&slot.date, &slot.start_time, &slot.end_time, &mut Vec::new())
+        }));
+        for host in std::iter::once(owner).chain(&co_hosts) {
+            self.retain_under_daily_cap(&mut slots, host, event_type, start_day, end_day).await?;
+        }
+
+        Ok((slots, busy))
+    }
+
+    /// Drops slots on days where a meeting would take `host` past their
+    /// daily meeting minutes cap.
+    async fn retain_under_daily_cap(
+        &self,
+        slots: &mut Vec<AvailableTimeSlot>,
+        host: &HostSchedule,
+        event_type: Option<&EventType>,
+        start_day: NaiveDate,
+        end_day: NaiveDate,
+    ) -> Result<(), AppError> {
+        let cap = host.settings.max_meeting_minutes_per_day;
+        if cap.is_none() || slots.is_empty() {
+            return Ok(());
+        }
+        let bookings = self.booking_repository
+            .find_holding_by_host_in_range(
+                &host.user_id,
+                &start_day.format("%Y-%m-%d").to_string(),
+                &end_day.format("%Y-%m-%d").to_string(),
+            )
+            .await?;
+        let mut by_date: HashMap<&str, Vec<&Booking>> = HashMap::new();
+        for booking in &bookings {
+            by_date.entry(booking.date.as_str()).or_default().push(booking);
+        }
+
+        let event_type_id = event_type.and_then(|event_type| event_type.id);
+        slots.retain(|slot| {
+            let day = by_date.get(slot.date.as_str()).map(Vec::as_slice).unwrap_or_default();
+            let window = (calendar_engine::parse_start_time(&slot.start_time), calendar_engine::parse_end_time(&slot.end_time));
+            daily_cap_conflict(cap, day.iter().copied(), event_type_id.as_ref(), window).is_none()
+        });
+        Ok(())
+    }
+
+    /// Whether `booking`, now stored, took one of its hosts past their daily
+    /// meeting minutes cap, e.g. because another booking landed at the same time.
+    pub async fn exceeds_daily_cap(&self, booking: &Booking) -> Result<bool, AppError> {
+        for host_id in booking.host_ids() {
+            let Some(cap) = self.settings_repository.find_by_user_id(host_id).await?
+                .and_then(|settings| settings.max_meeting_minutes_per_day) else {
+                continue;
+            };
+            let day = self.booking_repository.find_holding_by_host_and_date(host_id, &booking.date).await?;
+            let with = meeting_minutes(&day);
+            let without = meeting_minutes(day.iter().filter(|other| other.id != booking.id));
+            if with > i64::from(cap) && with > without {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Adds why the host's other pending or confirmed bookings and slot holds
+    /// on `date_str` keep `slot` from being booked, and returns how many seats
+    /// of `event_type`'s slot they take. Neither the buffer around the booking
+    /// nor the buffer around any other meeting may reach into the other,
+    /// except between fellow attendees of the same group event slot. Meetings
+    /// shared with the `checked` hosts were already checked as theirs.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn host_conflicts(
+        &self,
+        host: &HostSchedule,
+        event_type: Option<&EventType>,
+        date_str: &str,
+        (start_time, end_time): (NaiveTime, NaiveTime),
+        buffer_time: &BufferTime,
+        (exclude, exclude_hold): (Option<&ObjectId>, Option<&ObjectId>),
+        checked: &[ObjectId],
+        conflicts: &mut Vec<SlotConflict>,
+    ) -> Result<i32, AppError> {
+        let host_user_id = &host.user_id;
+        let host_settings = &host.settings;
+        let already_checked = |host_user_id: &ObjectId, co_host_user_ids: &[ObjectId]| {
+            checked.contains(host_user_id) || co_host_user_ids.iter().any(|co_host_id| checked.contains(co_host_id))
+        };
+        let capacity = event_type.map_or(1, EventType::capacity);
+        let event_type_id = event_type.and_then(|event_type| event_type.id);
+        let mut seats_taken = 0;
+
+        let existing = self.booking_repository
+            .find_holding_by_host_and_date(host_user_id, date_str)
+            .await?;
+        let holds = self.slot_hold_repository
+            .find_active_by_host_in_range(host_user_id, date_str, date_str)
+            .await?;
+        let counted = existing.iter().filter(|booking| exclude.is_none() || booking.id.as_ref() != exclude);
+        let cap = host_settings.max_meeting_minutes_per_day;
+        conflicts.extend(daily_cap_conflict(cap, counted, event_type_id.as_ref(), (start_time, end_time)));
+        let host_event_types = if existing.is_empty() && holds.is_empty() {
+            HashMap::new()
+        } else {
+            self.event_type_repository.find_map_by_user_id(host_user_id).await?
+        };
+        for booking in existing {
+            if exclude.is_some() && booking.id.as_ref() == exclude {
+                continue;
+            }
+            if already_checked(&booking.host_user_id, &booking.co_host_user_ids) {
+                continue;
+            }
+            if capacity > 1
+                && Some(booking.event_type_id) == event_type_id
+                && calendar_engine::parse_start_time(&booking.start_time) == start_time
+            {
+                seats_taken += 1;
+                continue;
+            }
+            let booked = calendar_engine::booked_window(
+                host_event_types.get(&booking.event_type_id),
+                &booking.date,
+                &booking.start_time,
+                &booking.end_time,
+                &host_settings.buffer_time,
+            );
+            if calendar_engine::booked_conflicts(&booked, (start_time, end_time), buffer_time) {
+                conflicts.push(SlotConflict {
+                    booking_id: booking.id.map(|id| id.to_hex()),
+                    range: Some(ConflictRange { start: booking.start_time, end: booking.end_time }),
+                    ..SlotConflict::new("booking_overlap", "Time slot overlaps an existing booking")
+                });
+            }
+        }
+        for hold in holds {
+            if exclude_hold.is_some() && hold.id.as_ref() == exclude_hold {
+                continue;
+            }
+            if already_checked(&hold.host_user_id, &hold.co_host_user_ids) {
+                continue;
+            }
+            if capacity > 1
+                && Some(hold.event_type_id) == event_type_id
+                && calendar_engine::parse_start_time(&hold.start_time) == start_time
+            {
+                seats_taken += 1;
+                continue;
+            }
+            let held = calendar_engine::booked_window(
+                host_event_types.get(&hold.event_type_id),
+                &hold.date,
+                &hold.start_time,
+                &hold.end_time,
+                &host_settings.buffer_time,
+            );
+            if calendar_engine::booked_conflicts(&held, (start_time, end_time), buffer_time) {
+                conflicts.push(SlotConflict {
+                    range: Some(ConflictRange { start: hold.start_time, end: hold.end_time }),
+                    ..SlotConflict::new("slot_held", "Time slot is being held for another invitee")
+                });
+            }
+        }
+
+        Ok(seats_taken)
+    }
+
+    /// The schedules of a collective event type's co-hosts.
+    pub async fn co_host_schedules(&self, event_type: &EventType, owner_settings: &CalendarSettings) -> Result<Vec<HostSchedule>, AppError> {
+        self.host_schedules(event_type.collective_hosts(), owner_settings).await
+    }
+
+    /// The schedules of hosts of the owner's event types. Invitees can reach
+    /// this, so a host who cannot take bookings fails without saying who or why.
+    pub async fn host_schedules(&self, host_ids: &[ObjectId], owner_settings: &CalendarSettings) -> Result<Vec<HostSchedule>, AppError> {
+        let accepted = self.host_invite_repository.accepted_hosts(&owner_settings.user_id, host_ids).await?;
+        let mut schedules = Vec::with_capacity(host_ids.len());
+        for user_id in host_ids {
+            if !accepted.contains(user_id) {
+                return Err(host_not_ready());
+            }
+            match self.load_host(user_id, owner_settings).await? {
+                Ok(schedule) => schedules.push(schedule),
+                Err(_) => return Err(host_not_ready()),
+            }
+        }
+        Ok(schedules)
+    }
+
+    /// Checks hosts the owner is adding to an event type. Only users who
+    /// accepted the owner's host invitation may be listed, and unknown ids get
+    /// the same answer. The owner learns which accepted host is not set up yet.
+    pub async fn check_new_hosts(&self, host_ids: &[ObjectId], owner_settings: &CalendarSettings) -> Result<(), AppError> {
+        let accepted = self.host_invite_repository.accepted_hosts(&owner_settings.user_id, host_ids).await?;
+        if host_ids.iter().any(|user_id| !accepted.contains(user_id)) {
+            return Err(AppError::coded(
+                StatusCode::BAD_REQUEST,
+                "host_not_authorized",
+                "Every host must have accepted your host invitation",
+            ));
+        }
+        for user_id in host_ids {
+            if let Err(problem) = self.load_host(user_id, owner_settings).await? {
+                let host = match self.user_repository.find_by_id(&user_id.to_hex()).await {
+                    Ok(Some(user)) => user.name,
+                    _ => user_id.to_hex(),
+                };
+                return Err(AppError::coded(StatusCode::BAD_REQUEST, "host_not_ready", &format!("Host {} {}", host, problem)));
+            }
+        }
+        Ok(())
+    }
+
+    /// A host's default schedule, or what keeps them from hosting with the
+    /// owner. Booking times are local to the owner's timezone, so every host must share it.
+    async fn load_host(&self, user_id: &ObjectId, owner_settings: &CalendarSettings) -> Result<Result<HostSchedule, &'static str>, AppError> {
+        let Some(settings) = self.settings_repository.find_by_user_id(user_id).await? else {
+            return Ok(Err("has not set up their calendar settings"));
+        };
+        let Some(schedule) = self.availability_repository
+            .find_default_by_user_id(user_id)
+            .await?
+            .filter(|schedule| !schedule.rules.is_empty())
+        else {
+            return Ok(Err("has no availability"));
+        };
+        if settings.timezone != owner_settings.timezone {
+            return Ok(Err("uses a different timezone from yours"));
+        }
+        Ok(Ok(HostSchedule::new(*user_id, settings, schedule)))
+    }
+}
+
+/// Said to whoever asks for slots or bookings when a host cannot take them.
+fn host_not_ready() -> AppError {
+    AppError::coded(StatusCode::CONFLICT, "host_not_ready", "A host of this event type is not available for bookings right now")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    fn booked(event_type_id: &ObjectId, start_time: &str, end_time: &str) -> Booking {
+        Booking {
+            end_time: end_time.to_string(),
+            ..test_support::booking(event_type_id, &ObjectId::new(), "2026-03-02", start_time)
+        }
+    }
+
+    fn window(start_time: &str, end_time: &str) -> (NaiveTime, NaiveTime) {
+        (calendar_engine::parse_start_time(start_time), calendar_engine::parse_end_time(end_time))
+    }
+
+    #[test]
+    fn a_meeting_may_fill_the_day_up_to_the_cap_but_not_a_minute_past_it() {
+        let other = ObjectId::new();
+        let day_at_210 = [booked(&other, "09:00", "12:00"), booked(&other, "13:00", "13:30")];
+        let day_at_211 = [booked(&other, "09:00", "12:00"), booked(&other, "13:00", "13:31")];
+
+        assert!(daily_cap_conflict(Some(240), &day_at_210, None, window("14:00", "14:30")).is_none());
+        let conflict = daily_cap_conflict(Some(240), &day_at_211, None, window("14:00", "14:30")).unwrap();
+        assert_eq!(conflict.code, "daily_minutes_cap");
+        assert_eq!(conflict.message, "Daily meeting minutes cap reached");
+        assert!(daily_cap_conflict(None, &day_at_211, None, window("14:00", "14:30")).is_none());
+    }
+
+    #[test]
+    fn a_day_exactly_at_the_cap_only_takes_more_attendees_of_a_booked_group_slot() {
+        let group = ObjectId::new();
+        let full_day = [booked(&group, "09:00", "12:00"), booked(&group, "09:00", "12:00"), booked(&group, "13:00", "14:00")];
+
+        assert_eq!(meeting_minutes(&full_day), 240);
+        assert!(daily_cap_conflict(Some(240), &full_day, Some(&group), window("09:00", "12:00")).is_none());
+        assert!(daily_cap_conflict(Some(240), &full_day, Some(&group), window("15:00", "15:15")).is_some());
+        assert!(daily_cap_conflict(Some(240), &full_day, None, window("15:00", "15:15")).is_some());
+    }
+
+    #[test]
+    fn capped_days_offer_no_slots_and_late_bookings_past_the_cap_are_caught() {
+        test_support::with_database(|db| async move {
+            let sources = SlotSources::new(db.clone());
+            let booking_repository = BookingRepository::new(db.clone());
+            let settings_repository = CalendarSettingsRepository::new(db.clone());
+            let (settings, schedule) = test_support::create_host(&db, "UTC").await;
+            let event_type = test_support::event_type(&settings.user_id, &schedule);
+            let date = test_support::date_in("UTC", 3);
+            let day = NaiveDate::parse_from_str(&date, "%Y-%m-%d").unwrap();
+            let first = test_support::booking(&ObjectId::new(), &settings.user_id, &date, "09:00");
+            booking_repository.create_in_free_seat(first, 1).await.unwrap();
+
+            let host = HostSchedule::new(settings.user_id, settings, schedule);
+            let set_cap = |cap| {
+                let (settings_repository, host) = (&settings_repository, &host);
+                async move {
+                    let mut host = host.clone();
+                    host.settings.max_meeting_minutes_per_day = Some(cap);
+                    settings_repository.update(&host.settings.id.unwrap(), host.settings.clone()).await.unwrap();
+                    host
+                }
+            };
+            let slot_count = |host| {
+                let (sources, event_type) = (&sources, &event_type);
+                async move {
+                    let (slots, _) = sources.team_slots(Some(event_type), &host, day, day, event_type.duration).await.unwrap();
+                    slots.len()
+                }
+            };
+            // 30 minutes booked, so one more 30 minute meeting fits a 60 minute cap exactly
+            assert!(slot_count(set_cap(60).await).await > 0);
+            assert_eq!(slot_count(set_cap(59).await).await, 0);
+
+            // Booked behind the checks' back, as a concurrent request would
+            let second = test_support::booking(&ObjectId::new(), &host.user_id, &date, "11:00");
+            let second = booking_repository.create_in_free_seat(second, 1).await.unwrap();
+            assert!(sources.exceeds_daily_cap(&second).await.unwrap());
+            set_cap(60).await;
+            assert!(!sources.exceeds_daily_cap(&second).await.unwrap());
+        });
+    }
+}
@@ -1,5 +1,7 @@
 pub mod calendar_model;
 pub mod calendar_schema;
 pub mod calendar_crud;
+pub mod calendar_engine;
+pub mod calendar_slots;
 pub mod calendar_controller;
 pub mod calendar_router;
\ No newline at end of file
@@ -0,0 +1,92 @@
+use actix_web::{http::StatusCode, web, HttpResponse};
+use mongodb::bson::oid::ObjectId;
+use mongodb::Database;
+use validator::Validate;
+
+use crate::app::AppState;
+use crate::errors::error::AppError;
+use crate::modules::conferencing::conferencing_crud::ConferencingConnectionRepository;
+use crate::modules::conferencing::conferencing_schema::{
+    AuthorizeResponse, ConferencingConnectionResponse, OAuthCallbackQuery,
+};
+use crate::modules::user::user_schema::Claims;
+use crate::services::conferencing::{self, ZoomProvider, ZOOM};
+
+pub struct ConferencingController {
+    db: Database,
+    connection_repository: ConferencingConnectionRepository,
+}
+
+impl ConferencingController {
+    pub fn new(db: Database) -> Self {
+        Self {
+            connection_repository: ConferencingConnectionRepository::new(db.clone()),
+            db,
+        }
+    }
+
+    pub async fn list_connections(&self, claims: web::ReqData<Claims>) -> Result<HttpResponse, AppError> {
+        let user_id = ObjectId::parse_str(&claims.sub)
+            .map_err(|_| AppError::BadRequest("Invalid user ID".to_string()))?;
+
+        let connections = self.connection_repository.find_by_user_id(&user_id).await?;
+        let response: Vec<ConferencingConnectionResponse> = connections.into_iter()
+            .map(ConferencingConnectionResponse::from)
+            .collect();
+
+        Ok(HttpResponse::Ok().json(response))
+    }
+
+    pub async fn zoom_authorize(&self, claims: web::ReqData<Claims>) -> Result<HttpResponse, AppError> {
+        let user_id = ObjectId::parse_str(&claims.sub)
+            .map_err(|_| AppError::BadRequest("Invalid user ID".to_string()))?;
+
+        let zoom = self.zoom()?;
+        let state = conferencing::sign_state(AppState::get().action_signing_secret.as_bytes(), &user_id);
+
+        Ok(HttpResponse::Ok().json(AuthorizeResponse {
+            authorize_url: zoom.authorize_url(&state),
+        }))
+    }
+
+    /// Zoom redirects the host here, so the signed state stands in for a login.
+    pub async fn zoom_callback(&self, query: web::Query<OAuthCallbackQuery>) -> Result<HttpResponse, AppError> {
+        // Validate request data
+        query.validate()
+            .map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+        let zoom = self.zoom()?;
+        let user_id = conferencing::verify_state(AppState::get().action_signing_secret.as_bytes(), &query.state)
+            .ok_or_else(|| AppError::coded(
+                StatusCode::BAD_REQUEST,
+                "invalid_oauth_state",
+                "Connection link is invalid or has expired, please start again",
+            ))?;
+
+        let connection = zoom.connect(&user_id, &query.code).await?;
+
+        Ok(HttpResponse::Ok().json(ConferencingConnectionResponse::from(connection)))
+    }
+
+    pub async fn zoom_disconnect(&self, claims: web::ReqData<Claims>) -> Result<HttpResponse, AppError> {
+        let user_id = ObjectId::parse_str(&claims.sub)
+            .map_err(|_| AppError::BadRequest("Invalid user ID".to_string()))?;
+
+        if !self.connection_repository.delete(&user_id, ZOOM).await? {
+            return Err(AppError::NotFound("Zoom is not connected".to_string()));
+        }
+
+        Ok(HttpResponse::NoContent().finish())
+    }
+
+    fn zoom(&self) -> Result<ZoomProvider, AppError> {
+        let config = AppState::get().zoom.clone()
+            .ok_or_else(|| AppError::coded(
+                StatusCode::BAD_REQUEST,
+                "provider_not_configured",
+                "Zoom is not available on this server",
+            ))?;
+
+        Ok(ZoomProvider::new(config, self.db.clone()))
+    }
+}
@@ -0,0 +1,99 @@
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime},
+    options::{FindOneAndUpdateOptions, IndexOptions, ReturnDocument},
+    Collection, Database, IndexModel,
+};
+use futures::TryStreamExt;
+use crate::errors::error::AppError;
+use crate::modules::conferencing::conferencing_model::ConferencingConnection;
+
+pub struct ConferencingConnectionRepository {
+    collection: Collection<ConferencingConnection>,
+}
+
+impl ConferencingConnectionRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection("conferencing_connections");
+        Self { collection }
+    }
+
+    /// One connection per user and provider. Safe to call on every start.
+    pub async fn ensure_indexes(&self) -> Result<(), AppError> {
+        let index = IndexModel::builder()
+            .keys(doc! { "user_id": 1, "provider": 1 })
+            .options(IndexOptions::builder().name("user_provider".to_string()).unique(true).build())
+            .build();
+
+        self.collection
+            .create_index(index, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    pub async fn find(&self, user_id: &ObjectId, provider: &str) -> Result<Option<ConferencingConnection>, AppError> {
+        self.collection
+            .find_one(doc! { "user_id": user_id, "provider": provider }, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    pub async fn find_by_user_id(&self, user_id: &ObjectId) -> Result<Vec<ConferencingConnection>, AppError> {
+        let mut connections = Vec::new();
+        let mut cursor = self.collection
+            .find(doc! { "user_id": user_id }, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        while let Some(connection) = cursor.try_next().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))? {
+            connections.push(connection);
+        }
+
+        Ok(connections)
+    }
+
+    /// Stores fresh tokens, connecting the user if they were not yet.
+    pub async fn save_tokens(
+        &self,
+        user_id: &ObjectId,
+        provider: &str,
+        access_token: &str,
+        refresh_token: &str,
+        expires_at: DateTime,
+    ) -> Result<ConferencingConnection, AppError> {
+        let options = FindOneAndUpdateOptions::builder()
+            .upsert(true)
+            .return_document(ReturnDocument::After)
+            .build();
+
+        self.collection
+            .find_one_and_update(
+                doc! { "user_id": user_id, "provider": provider },
+                doc! {
+                    "$set": {
+                        "access_token": access_token,
+                        "refresh_token": refresh_token,
+                        "expires_at": expires_at,
+                        "updated_at": DateTime::now(),
+                    },
+                    "$setOnInsert": { "created_at": DateTime::now() },
+                },
+                options,
+            )
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?
+            .ok_or_else(|| AppError::InternalServerError("Failed to save conferencing connection".to_string()))
+    }
+
+    /// Whether there was a connection to remove.
+    pub async fn delete(&self, user_id: &ObjectId, provider: &str) -> Result<bool, AppError> {
+        let result = self.collection
+            .delete_one(doc! { "user_id": user_id, "provider": provider }, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(result.deleted_count > 0)
+    }
+}
@@ -0,0 +1,17 @@
+use mongodb::bson::{DateTime, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+
+/// A host's authorization to create meetings with a video conferencing
+/// provider on their behalf. One per user and provider.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ConferencingConnection {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub user_id: ObjectId,
+    pub provider: String,  // e.g. "zoom"
+    pub access_token: String,
+    pub refresh_token: String,
+    pub expires_at: DateTime,  // When access_token stops working
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
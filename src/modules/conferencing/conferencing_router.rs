@@ -0,0 +1,52 @@
+use std::time::Duration;
+use actix_web::{web, Scope};
+use crate::modules::conferencing::conferencing_controller::ConferencingController;
+use crate::modules::conferencing::conferencing_schema::OAuthCallbackQuery;
+use crate::modules::user::user_schema::Claims;
+use crate::errors::error::AppError;
+use crate::errors::error_handler::method_not_allowed;
+use crate::middleware::auth::AuthMiddleware;
+use crate::middleware::rate_limit::RateLimit;
+use crate::app::AppState;
+
+/// Per client IP, on the unauthenticated OAuth callback.
+const OAUTH_CALLBACK_REQUESTS_PER_MINUTE: u32 = 10;
+
+pub fn conferencing_routes() -> Result<Scope, AppError> {
+    let controller = web::Data::new(ConferencingController::new(AppState::get().db.clone()));
+
+    Ok(web::scope("/conferencing")
+        .app_data(controller.clone())
+        .service(
+            web::resource("")
+                .default_service(method_not_allowed("GET"))
+                .wrap(AuthMiddleware)
+                .route(web::get().to(|claims: web::ReqData<Claims>, controller: web::Data<ConferencingController>| {
+                    async move { controller.list_connections(claims).await }
+                }))
+        )
+        .service(
+            web::resource("/zoom/authorize")
+                .default_service(method_not_allowed("GET"))
+                .wrap(AuthMiddleware)
+                .route(web::get().to(|claims: web::ReqData<Claims>, controller: web::Data<ConferencingController>| {
+                    async move { controller.zoom_authorize(claims).await }
+                }))
+        )
+        .service(
+            web::resource("/zoom/callback")
+                .default_service(method_not_allowed("GET"))
+                .wrap(RateLimit::new("conferencing_callback", OAUTH_CALLBACK_REQUESTS_PER_MINUTE, Duration::from_secs(60)))
+                .route(web::get().to(|query: web::Query<OAuthCallbackQuery>, controller: web::Data<ConferencingController>| {
+                    async move { controller.zoom_callback(query).await }
+                }))
+        )
+        .service(
+            web::resource("/zoom")
+                .default_service(method_not_allowed("DELETE"))
+                .wrap(AuthMiddleware)
+                .route(web::delete().to(|claims: web::ReqData<Claims>, controller: web::Data<ConferencingController>| {
+                    async move { controller.zoom_disconnect(claims).await }
+                }))
+        ))
+}
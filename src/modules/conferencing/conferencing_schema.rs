@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+use crate::modules::conferencing::conferencing_model::ConferencingConnection;
+
+#[derive(Debug, Serialize)]
+pub struct ConferencingConnectionResponse {
+    pub provider: String,
+    pub connected_at: String,
+}
+
+impl From<ConferencingConnection> for ConferencingConnectionResponse {
+    fn from(connection: ConferencingConnection) -> Self {
+        Self {
+            provider: connection.provider,
+            connected_at: connection.created_at.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuthorizeResponse {
+    pub authorize_url: String,  // Send the host here to grant access
+}
+
+/// Where the provider sends the host back after they grant access.
+#[derive(Debug, Deserialize, Validate)]
+pub struct OAuthCallbackQuery {
+    #[validate(length(min = 1, max = 2048, message = "Authorization code is required"))]
+    pub code: String,
+    #[validate(length(min = 1, max = 512, message = "State is required"))]
+    pub state: String,
+}
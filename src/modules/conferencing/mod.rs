@@ -0,0 +1,5 @@
+pub mod conferencing_model;
+pub mod conferencing_schema;
+pub mod conferencing_crud;
+pub mod conferencing_controller;
+pub mod conferencing_router;
@@ -1,2 +1,9 @@
 pub mod user;
-pub mod calendar;
\ No newline at end of file
+pub mod calendar;
+pub mod admin;
+pub mod system;
+pub mod analytics;
+pub mod booking;
+pub mod bootstrap;
+pub mod notification;
+pub mod conferencing;
@@ -0,0 +1,5 @@
+pub mod notification_model;
+pub mod notification_schema;
+pub mod notification_crud;
+pub mod notification_controller;
+pub mod notification_router;
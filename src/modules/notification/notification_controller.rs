@@ -0,0 +1,84 @@
+use actix_web::{web, HttpResponse};
+use mongodb::bson::oid::ObjectId;
+use mongodb::Database;
+use serde_json::json;
+use validator::Validate;
+
+use crate::errors::error::AppError;
+use crate::modules::notification::notification_crud::NotificationRepository;
+use crate::modules::notification::notification_schema::{
+    ListNotificationsQuery, NotificationListResponse, NotificationResponse,
+};
+use crate::modules::user::user_schema::Claims;
+use crate::utils::object_id::PathObjectId;
+
+const PAGE_SIZE: u64 = 20;
+
+pub struct NotificationController {
+    notification_repository: NotificationRepository,
+}
+
+impl NotificationController {
+    pub fn new(db: Database) -> Self {
+        Self {
+            notification_repository: NotificationRepository::new(db),
+        }
+    }
+
+    pub async fn list_notifications(
+        &self,
+        claims: web::ReqData<Claims>,
+        query: web::Query<ListNotificationsQuery>,
+    ) -> Result<HttpResponse, AppError> {
+        // Validate request data
+        query.validate()
+            .map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+        let user_id = ObjectId::parse_str(&claims.sub)
+            .map_err(|_| AppError::BadRequest("Invalid user ID".to_string()))?;
+        let page = query.page.unwrap_or(1);
+
+        // One extra row tells whether another page follows
+        let (notifications, unread_count) = futures::join!(
+            self.notification_repository.find_by_user_id(&user_id, query.unread_only, page, PAGE_SIZE + 1),
+            self.notification_repository.count_unread(&user_id),
+        );
+        let mut notifications = notifications?;
+        let has_more = notifications.len() as u64 > PAGE_SIZE;
+        notifications.truncate(PAGE_SIZE as usize);
+
+        Ok(HttpResponse::Ok().json(NotificationListResponse {
+            notifications: notifications.into_iter().map(NotificationResponse::from).collect(),
+            page,
+            page_size: PAGE_SIZE,
+            has_more,
+            unread_count: unread_count?,
+        }))
+    }
+
+    pub async fn mark_read(
+        &self,
+        claims: web::ReqData<Claims>,
+        PathObjectId(notification_id): PathObjectId,
+    ) -> Result<HttpResponse, AppError> {
+        let user_id = ObjectId::parse_str(&claims.sub)
+            .map_err(|_| AppError::BadRequest("Invalid user ID".to_string()))?;
+
+        let notification = self.notification_repository.mark_read(&notification_id, &user_id).await?
+            .ok_or_else(|| AppError::NotFound("Notification not found".to_string()))?;
+
+        Ok(HttpResponse::Ok().json(NotificationResponse::from(notification)))
+    }
+
+    pub async fn mark_all_read(
+        &self,
+        claims: web::ReqData<Claims>,
+    ) -> Result<HttpResponse, AppError> {
+        let user_id = ObjectId::parse_str(&claims.sub)
+            .map_err(|_| AppError::BadRequest("Invalid user ID".to_string()))?;
+
+        let marked = self.notification_repository.mark_all_read(&user_id).await?;
+
+        Ok(HttpResponse::Ok().json(json!({ "marked_read": marked })))
+    }
+}
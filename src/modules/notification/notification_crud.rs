@@ -0,0 +1,179 @@
+use std::time::Duration;
+
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime},
+    options::{FindOneAndUpdateOptions, FindOptions, IndexOptions, ReturnDocument},
+    Collection, Database, IndexModel,
+};
+use futures::TryStreamExt;
+use crate::errors::error::AppError;
+use crate::modules::notification::notification_model::{MessageLogEntry, Notification, NotificationKind};
+
+const RETENTION: Duration = Duration::from_secs(90 * 24 * 60 * 60);
+
+pub struct NotificationRepository {
+    collection: Collection<Notification>,
+}
+
+impl NotificationRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection("notifications");
+        Self { collection }
+    }
+
+    /// Creates the listing index and the 90-day TTL index. Safe to call on every start.
+    pub async fn ensure_indexes(&self) -> Result<(), AppError> {
+        let indexes = vec![
+            IndexModel::builder()
+                .keys(doc! { "user_id": 1, "created_at": -1 })
+                .build(),
+            IndexModel::builder()
+                .keys(doc! { "created_at": 1 })
+                .options(IndexOptions::builder().expire_after(RETENTION).build())
+                .build(),
+        ];
+
+        self.collection
+            .create_indexes(indexes, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Best-effort: a failed write is logged and never fails the triggering request.
+    pub async fn notify(
+        &self,
+        user_id: &ObjectId,
+        kind: NotificationKind,
+        title: &str,
+        body: &str,
+        related_id: Option<&ObjectId>,
+    ) {
+        let notification = Notification {
+            id: None,
+            user_id: *user_id,
+            kind,
+            title: title.to_string(),
+            body: body.to_string(),
+            related_id: related_id.copied(),
+            read_at: None,
+            created_at: DateTime::now(),
+        };
+
+        if let Err(e) = self.collection.insert_one(&notification, None).await {
+            println!("Failed to store notification for user {}: {}", user_id.to_hex(), e);
+        }
+    }
+
+    /// Newest first, `page_size` at a time from the 1-based `page`.
+    pub async fn find_by_user_id(
+        &self,
+        user_id: &ObjectId,
+        unread_only: bool,
+        page: u64,
+        page_size: u64,
+    ) -> Result<Vec<Notification>, AppError> {
+        let filter = if unread_only {
+            doc! { "user_id": user_id, "read_at": null }
+        } else {
+            doc! { "user_id": user_id }
+        };
+        let options = FindOptions::builder()
+            .sort(doc! { "created_at": -1, "_id": -1 })
+            .skip((page - 1) * page_size)
+            .limit(page_size as i64)
+            .build();
+
+        let mut notifications = Vec::new();
+        let mut cursor = self.collection
+            .find(filter, options)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        while let Some(notification) = cursor.try_next().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))? {
+            notifications.push(notification);
+        }
+
+        Ok(notifications)
+    }
+
+    pub async fn count_unread(&self, user_id: &ObjectId) -> Result<u64, AppError> {
+        self.collection
+            .count_documents(doc! { "user_id": user_id, "read_at": null }, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// Marks one of the user's notifications read, keeping the first read time.
+    pub async fn mark_read(&self, id: &ObjectId, user_id: &ObjectId) -> Result<Option<Notification>, AppError> {
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+
+        self.collection
+            .find_one_and_update(
+                doc! { "_id": id, "user_id": user_id },
+                vec![doc! { "$set": { "read_at": { "$ifNull": ["$read_at", DateTime::now()] } } }],
+                options
+            )
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// Marks every unread notification of the user read, returning how many changed.
+    pub async fn mark_all_read(&self, user_id: &ObjectId) -> Result<u64, AppError> {
+        let result = self.collection
+            .update_many(
+                doc! { "user_id": user_id, "read_at": null },
+                doc! { "$set": { "read_at": DateTime::now() } },
+                None
+            )
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(result.modified_count)
+    }
+}
+
+pub struct MessageLogRepository {
+    collection: Collection<MessageLogEntry>,
+}
+
+impl MessageLogRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection("message_log");
+        Self { collection }
+    }
+
+    /// Creates the per-booking index and the 90-day TTL index. Safe to call on every start.
+    pub async fn ensure_indexes(&self) -> Result<(), AppError> {
+        let indexes = vec![
+            IndexModel::builder()
+                .keys(doc! { "booking_id": 1, "created_at": -1 })
+                .build(),
+            IndexModel::builder()
+                .keys(doc! { "created_at": 1 })
+                .options(IndexOptions::builder().expire_after(RETENTION).build())
+                .build(),
+        ];
+
+        self.collection
+            .create_indexes(indexes, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Best-effort: a failed write is logged and never fails the delivery it describes.
+    pub async fn record(&self, entries: &[MessageLogEntry]) {
+        if entries.is_empty() {
+            return;
+        }
+        if let Err(e) = self.collection.insert_many(entries, None).await {
+            println!("Failed to store {} message log entries: {}", entries.len(), e);
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use mongodb::bson::{DateTime, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+
+use crate::modules::calendar::calendar_model::ReminderChannel;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    BookingCreated,
+    BookingRequested,
+    BookingCancelled,
+    BookingRescheduled,
+    AffectedBookings,
+    HostInvite,
+}
+
+/// An in-app notice for the dashboard, shown next to the host emails.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Notification {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub user_id: ObjectId,
+    pub kind: NotificationKind,
+    pub title: String,
+    pub body: String,
+    pub related_id: Option<ObjectId>,  // Booking, schedule or settings the notice is about
+    pub read_at: Option<DateTime>,
+    pub created_at: DateTime,  // Drives the TTL
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageStatus {
+    Sent,    // Handed to the channel; emails are then retried by the queue
+    Failed,
+}
+
+/// One message to an attendee on one channel, for support to see what went out.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MessageLogEntry {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub channel: ReminderChannel,
+    pub kind: String,  // e.g. "booking_reminder"
+    pub recipient: String,  // Email address or phone number
+    pub booking_id: Option<ObjectId>,
+    pub status: MessageStatus,
+    pub error: Option<String>,
+    pub created_at: DateTime,  // Drives the TTL
+}
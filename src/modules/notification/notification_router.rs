@@ -0,0 +1,40 @@
+use actix_web::{web, Scope};
+use crate::modules::notification::notification_controller::NotificationController;
+use crate::modules::notification::notification_schema::ListNotificationsQuery;
+use crate::modules::user::user_schema::Claims;
+use crate::errors::error::AppError;
+use crate::errors::error_handler::method_not_allowed;
+use crate::middleware::auth::AuthMiddleware;
+use crate::utils::object_id::PathObjectId;
+use crate::app::AppState;
+
+pub fn notification_routes() -> Result<Scope, AppError> {
+    let controller = web::Data::new(NotificationController::new(AppState::get().db.clone()));
+
+    Ok(web::scope("/notifications")
+        .app_data(controller.clone())
+        .service(
+            web::resource("")
+                .default_service(method_not_allowed("GET"))
+                .wrap(AuthMiddleware)
+                .route(web::get().to(|claims: web::ReqData<Claims>, query: web::Query<ListNotificationsQuery>, controller: web::Data<NotificationController>| {
+                    async move { controller.list_notifications(claims, query).await }
+                }))
+        )
+        .service(
+            web::resource("/read-all")
+                .default_service(method_not_allowed("POST"))
+                .wrap(AuthMiddleware)
+                .route(web::post().to(|claims: web::ReqData<Claims>, controller: web::Data<NotificationController>| {
+                    async move { controller.mark_all_read(claims).await }
+                }))
+        )
+        .service(
+            web::resource("/{id}/read")
+                .default_service(method_not_allowed("POST"))
+                .wrap(AuthMiddleware)
+                .route(web::post().to(|claims: web::ReqData<Claims>, id: PathObjectId, controller: web::Data<NotificationController>| {
+                    async move { controller.mark_read(claims, id).await }
+                }))
+        ))
+}
@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+use crate::modules::notification::notification_model::{Notification, NotificationKind};
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct ListNotificationsQuery {
+    #[serde(default)]
+    pub unread_only: bool,
+    #[validate(range(min = 1, message = "Page must be at least 1"))]
+    pub page: Option<u64>,  // 1-based, defaults to the first page
+}
+
+#[derive(Debug, Serialize)]
+pub struct NotificationResponse {
+    pub id: String,
+    pub kind: NotificationKind,
+    pub title: String,
+    pub body: String,
+    pub related_id: Option<String>,
+    pub read_at: Option<String>,
+    pub created_at: String,
+}
+
+impl From<Notification> for NotificationResponse {
+    fn from(notification: Notification) -> Self {
+        Self {
+            id: notification.id.unwrap().to_hex(),
+            kind: notification.kind,
+            title: notification.title,
+            body: notification.body,
+            related_id: notification.related_id.map(|id| id.to_hex()),
+            read_at: notification.read_at.map(|read_at| read_at.to_string()),
+            created_at: notification.created_at.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct NotificationListResponse {
+    pub notifications: Vec<NotificationResponse>,
+    pub page: u64,
+    pub page_size: u64,
+    pub has_more: bool,
+    pub unread_count: u64,
+}
@@ -0,0 +1,2 @@
+pub mod system_controller;
+pub mod system_router;
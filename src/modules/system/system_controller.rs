@@ -0,0 +1,22 @@
+use actix_web::HttpResponse;
+use serde_json::json;
+
+use crate::app::AppState;
+use crate::errors::error::AppError;
+
+pub struct SystemController;
+
+impl SystemController {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub async fn version(&self) -> Result<HttpResponse, AppError> {
+        Ok(HttpResponse::Ok().json(json!({
+            "name": env!("CARGO_PKG_NAME"),
+            "version": env!("CARGO_PKG_VERSION"),
+            "features": AppState::get().features.enabled_names(),
+            "booking_horizon": AppState::get().booking_horizon
+        })))
+    }
+}
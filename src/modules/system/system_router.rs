@@ -0,0 +1,18 @@
+use actix_web::{web, Scope};
+use crate::modules::system::system_controller::SystemController;
+use crate::errors::error::AppError;
+use crate::errors::error_handler::method_not_allowed;
+
+pub fn system_routes() -> Result<Scope, AppError> {
+    let controller = web::Data::new(SystemController::new());
+
+    Ok(web::scope("/version")
+        .app_data(controller.clone())
+        .service(
+            web::resource("")
+                .default_service(method_not_allowed("GET"))
+                .route(web::get().to(|controller: web::Data<SystemController>| {
+                    async move { controller.version().await }
+                }))
+        ))
+}
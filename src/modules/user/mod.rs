@@ -1,5 +1,6 @@
 pub mod user_controller;
 pub mod user_crud;
 pub mod user_model;
+pub mod user_permission;
 pub mod user_router;
 pub mod user_schema; 
\ No newline at end of file
@@ -1,4 +1,4 @@
-use actix_web::{web, HttpResponse, HttpRequest, HttpMessage};
+use actix_web::{http::StatusCode, web, HttpResponse, HttpRequest, HttpMessage};
 use chrono::{Duration, Utc};
 use jsonwebtoken::{encode, EncodingKey, Header};
 use rand::{thread_rng, Rng};
@@ -13,40 +13,44 @@ use crate::modules::user::{
 };
 use bcrypt::{hash, verify, DEFAULT_COST};
 use crate::config::environment::Environment;
-use crate::services::email::EmailService;
+use crate::services::email_queue::{EmailJob, EmailQueue};
 use crate::errors::error::AppError;
+use crate::utils::signed_actions;
 use mongodb::bson::DateTime as BsonDateTime;
+use validator::Validate;
 
 #[derive(Clone)]
 pub struct UserController {
     repository: UserRepository,
     env: Environment,
-    email_service: EmailService,
+    email_queue: EmailQueue,
 }
 
 impl UserController {
     pub fn new() -> Result<Self, AppError> {
         let env = Environment::load();
-        let email_service = EmailService::new(&env)?;
+        let email_queue = crate::app::AppState::get().email_queue.clone();
         
         Ok(Self {
             repository: UserRepository::new(),
             env,
-            email_service,
+            email_queue,
         })
     }
 
     fn generate_jwt(&self, user: &User) -> Result<String, AppError> {
-        let expiration = Utc::now()
-            .checked_add_signed(Duration::days(7))
-            .expect("valid timestamp")
-            .timestamp();
+        self.generate_jwt_at(user, Utc::now().timestamp())
+    }
 
+    /// The access token for `user` issued at `issued_at`, in seconds. Signing
+    /// is deterministic, so the same user and time give the same token.
+    fn generate_jwt_at(&self, user: &User, issued_at: i64) -> Result<String, AppError> {
         let claims = Claims {
             sub: user.id.as_ref().unwrap().to_hex(),
-            exp: expiration,
-            iat: Utc::now().timestamp(),
+            exp: issued_at + Duration::days(7).num_seconds(),
+            iat: issued_at,
             email: user.email.clone(),
+            role: user.role.clone(),
         };
 
         encode(
@@ -76,8 +80,12 @@ impl UserController {
         &self,
         user_data: web::Json<CreateUserRequest>,
     ) -> Result<HttpResponse, AppError> {
+        // Validate request data
+        user_data.validate()
+            .map_err(|e| AppError::ValidationError(e.to_string()))?;
+
         // Check if user already exists
-        if let Some(_) = self.repository.find_by_email(&user_data.email).await? {
+        if self.repository.find_by_email(&user_data.email).await?.is_some() {
             return Err(AppError::BadRequest("Email already registered".to_string()));
         }
 
@@ -99,7 +107,10 @@ impl UserController {
         let created_user = self.repository.create(user).await?;
 
         // Send verification email
-        self.email_service.send_verification_email(&created_user.email, &verification_code).await?;
+        self.email_queue.enqueue(EmailJob::Verification {
+            to: created_user.email.clone(),
+            code: verification_code,
+        }).await?;
 
         Ok(HttpResponse::Created().json(serde_json::json!({
             "message": "Registration successful! Please check your email for a verification code.",
@@ -116,6 +127,10 @@ impl UserController {
         &self,
         credentials: web::Json<LoginRequest>,
     ) -> Result<HttpResponse, AppError> {
+        // Validate request data
+        credentials.validate()
+            .map_err(|e| AppError::ValidationError(e.to_string()))?;
+
         let mut user = self.repository
             .find_by_email(&credentials.email)
             .await?
@@ -139,12 +154,7 @@ impl UserController {
         Ok(HttpResponse::Ok().json(AuthResponse {
             access_token,
             refresh_token,
-            user: UserResponse {
-                id: user.id.unwrap().to_hex(),
-                email: user.email,
-                name: user.name,
-                is_verified: user.is_verified,
-            },
+            user: UserResponse::from(user),
         }))
     }
 
@@ -152,6 +162,10 @@ impl UserController {
         &self,
         verification_data: web::Json<VerifyEmailRequest>,
     ) -> Result<HttpResponse, AppError> {
+        // Validate request data
+        verification_data.validate()
+            .map_err(|e| AppError::ValidationError(e.to_string()))?;
+
         let mut user = self.repository
             .find_by_verification_token(&verification_data.token)
             .await?
@@ -170,27 +184,74 @@ impl UserController {
         &self,
         token_data: web::Json<RefreshTokenRequest>,
     ) -> Result<HttpResponse, AppError> {
-        let mut user = self.repository
-            .find_by_refresh_token(&token_data.refresh_token)
-            .await?
-            .ok_or_else(|| AppError::Unauthorized("Invalid refresh token".to_string()))?;
+        // Validate request data
+        token_data.validate()
+            .map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+        let presented = &token_data.refresh_token;
+
+        if let Some(mut user) = self.repository.find_by_refresh_token(presented).await? {
+            if user.refresh_token_expires.is_some_and(|expires| expires < BsonDateTime::now()) {
+                return Err(AppError::coded(
+                    StatusCode::UNAUTHORIZED,
+                    "refresh_token_expired",
+                    "Refresh token has expired, please log in again",
+                ));
+            }
 
-        let access_token = self.generate_jwt(&user)?;
-        let refresh_token = Self::generate_refresh_token();
-        user.set_refresh_token(refresh_token.clone());
-        
-        self.repository.update(&user.id.unwrap().to_hex(), &user).await?;
+            let issued_at = Utc::now().timestamp();
+            let access_token = self.generate_jwt_at(&user, issued_at)?;
+            let refresh_token = Self::generate_refresh_token();
+            user.rotate_refresh_token(refresh_token.clone(), &access_token, issued_at);
 
-        Ok(HttpResponse::Ok().json(TokenResponse {
-            access_token,
-            refresh_token,
-        }))
+            if self.repository.rotate_refresh_token(presented, &user).await? {
+                return Ok(HttpResponse::Ok().json(TokenResponse {
+                    access_token,
+                    refresh_token,
+                }));
+            }
+            // Another request rotated this token first; answer like a replay below
+        }
+
+        if let Some(user) = self.repository.find_by_previous_refresh_token(presented).await? {
+            let in_grace = user.previous_refresh_token_expires
+                .is_some_and(|expires| expires >= BsonDateTime::now());
+
+            // The rebuilt token only matches the issued one if the user's claims are unchanged
+            if in_grace
+                && let (Some(user_id), Some(hash), Some(issued_at), Some(refresh_token)) =
+                    (user.id, &user.last_access_token_hash, user.last_access_token_issued_at, &user.refresh_token)
+                && let access_token = self.generate_jwt_at(&user, issued_at)?
+                && signed_actions::token_hash(&access_token) == *hash
+                && self.repository.claim_refresh_replay(&user_id, presented).await? {
+                return Ok(HttpResponse::Ok().json(TokenResponse {
+                    access_token,
+                    refresh_token: refresh_token.clone(),
+                }));
+            }
+
+            return Err(AppError::coded(
+                StatusCode::UNAUTHORIZED,
+                "refresh_token_revoked",
+                "Refresh token has already been used",
+            ));
+        }
+
+        Err(AppError::coded(
+            StatusCode::UNAUTHORIZED,
+            "refresh_token_unknown",
+            "Invalid refresh token",
+        ))
     }
 
     pub async fn forgot_password(
         &self,
         request: web::Json<ForgotPasswordRequest>,
     ) -> Result<HttpResponse, AppError> {
+        // Validate request data
+        request.validate()
+            .map_err(|e| AppError::ValidationError(e.to_string()))?;
+
         let mut user = self.repository
             .find_by_email(&request.email)
             .await?
@@ -201,7 +262,10 @@ impl UserController {
         
         self.repository.update(&user.id.unwrap().to_hex(), &user).await?;
 
-        self.email_service.send_password_reset_email(&request.email, &reset_token).await?;
+        self.email_queue.enqueue(EmailJob::PasswordReset {
+            to: request.email.clone(),
+            code: reset_token,
+        }).await?;
 
         Ok(HttpResponse::Ok().json(VerificationResponse {
             message: "Password reset email sent".to_string(),
@@ -212,16 +276,19 @@ impl UserController {
         &self,
         request: web::Json<ResetPasswordRequest>,
     ) -> Result<HttpResponse, AppError> {
+        // Validate request data
+        request.validate()
+            .map_err(|e| AppError::ValidationError(e.to_string()))?;
+
         let mut user = self.repository
             .find_by_password_reset_token(&request.token)
             .await?
             .ok_or_else(|| AppError::BadRequest("Invalid reset token".to_string()))?;
 
         // Check if token is expired
-        if let Some(expires) = user.password_reset_expires {
-            if expires < BsonDateTime::now() {
-                return Ok(HttpResponse::BadRequest().json("Reset token has expired"));
-            }
+        if let Some(expires) = user.password_reset_expires
+            && expires < BsonDateTime::now() {
+            return Ok(HttpResponse::BadRequest().json("Reset token has expired"));
         }
 
         // Hash new password
@@ -240,9 +307,9 @@ impl UserController {
 
     pub async fn get_current_user(&self, req: HttpRequest) -> Result<HttpResponse, AppError> {
         // Get claims from request extensions (set by AuthMiddleware)
-        let extensions = req.extensions();
-        let claims = extensions
+        let claims = req.extensions()
             .get::<Claims>()
+            .cloned()
             .ok_or_else(|| AppError::Unauthorized("Not authenticated".to_string()))?;
 
         // Find user by ID
@@ -251,11 +318,6 @@ impl UserController {
             .await?
             .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
 
-        Ok(HttpResponse::Ok().json(UserResponse {
-            id: user.id.unwrap().to_hex(),
-            email: user.email,
-            name: user.name,
-            is_verified: user.is_verified,
-        }))
+        Ok(HttpResponse::Ok().json(UserResponse::from(user)))
     }
 }
@@ -1,5 +1,6 @@
+use futures::TryStreamExt;
 use mongodb::{
-    bson::{doc, oid::ObjectId},
+    bson::{doc, oid::ObjectId, Bson, DateTime},
     Collection,
 };
 use crate::modules::user::user_model::User;
@@ -53,6 +54,54 @@ impl UserRepository {
             .await
     }
 
+    pub async fn find_by_previous_refresh_token(&self, token: &str) -> Result<Option<User>, mongodb::error::Error> {
+        self.collection
+            .find_one(doc! { "previous_refresh_token": token }, None)
+            .await
+    }
+
+    /// Stores a rotated refresh token only if `current_token` is still the
+    /// active one, so concurrent refreshes cannot both rotate.
+    pub async fn rotate_refresh_token(&self, current_token: &str, user: &User) -> Result<bool, mongodb::error::Error> {
+        let result = self.collection
+            .update_one(
+                doc! { "_id": user.id, "refresh_token": current_token },
+                doc! { "$set": {
+                    "refresh_token": &user.refresh_token,
+                    "refresh_token_expires": user.refresh_token_expires,
+                    "previous_refresh_token": &user.previous_refresh_token,
+                    "previous_refresh_token_expires": user.previous_refresh_token_expires,
+                    "last_access_token_hash": &user.last_access_token_hash,
+                    "last_access_token_issued_at": user.last_access_token_issued_at,
+                    "updated_at": user.updated_at,
+                },
+                // Stored in plaintext before only its hash was kept
+                "$unset": { "last_access_token": "" } },
+                None,
+            )
+            .await?;
+
+        Ok(result.modified_count == 1)
+    }
+
+    /// Uses up the one replay the previous refresh token allows: returns true
+    /// if `previous_token` was still within its grace window, and ends it.
+    pub async fn claim_refresh_replay(&self, user_id: &ObjectId, previous_token: &str) -> Result<bool, mongodb::error::Error> {
+        let result = self.collection
+            .update_one(
+                doc! {
+                    "_id": user_id,
+                    "previous_refresh_token": previous_token,
+                    "previous_refresh_token_expires": { "$gte": DateTime::now() },
+                },
+                doc! { "$set": { "previous_refresh_token_expires": DateTime::from_millis(0) } },
+                None,
+            )
+            .await?;
+
+        Ok(result.modified_count == 1)
+    }
+
     pub async fn find_by_password_reset_token(&self, token: &str) -> Result<Option<User>, mongodb::error::Error> {
         self.collection
             .find_one(doc! { "password_reset_token": token }, None)
@@ -70,6 +119,30 @@ impl UserRepository {
             .await
     }
 
+    /// Number of users and how many of them have verified their email.
+    pub async fn count_by_verification(&self) -> Result<(u64, u64), mongodb::error::Error> {
+        let pipeline = vec![
+            doc! { "$group": {
+                "_id": null,
+                "total": { "$sum": 1 },
+                "verified": { "$sum": { "$cond": ["$is_verified", 1, 0] } },
+            } },
+        ];
+
+        let mut cursor = self.collection.aggregate(pipeline, None).await?;
+        let Some(row) = cursor.try_next().await? else {
+            return Ok((0, 0));
+        };
+
+        let count = |field: &str| match row.get(field) {
+            Some(Bson::Int32(n)) => *n as u64,
+            Some(Bson::Int64(n)) => *n as u64,
+            _ => 0,
+        };
+        Ok((count("total"), count("verified")))
+    }
+
+    #[allow(dead_code)]
     pub async fn delete(&self, id: &str) -> Result<(), mongodb::error::Error> {
         let object_id = match ObjectId::parse_str(id) {
             Ok(id) => id,
@@ -83,3 +156,33 @@ impl UserRepository {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+    use crate::test_support::with_database;
+
+    #[test]
+    fn a_rotated_refresh_token_can_be_replayed_once() {
+        with_database(|_| async move {
+            let repository = UserRepository::new();
+            let email = format!("{}@example.com", ObjectId::new().to_hex());
+            let mut user = User::new(email, "hash".to_string(), "Host".to_string());
+            user.set_refresh_token("first".to_string());
+            let mut user = repository.create(user).await.unwrap();
+            let user_id = user.id.unwrap();
+
+            user.rotate_refresh_token("second".to_string(), "access-token", Utc::now().timestamp());
+            assert!(repository.rotate_refresh_token("first", &user).await.unwrap());
+
+            assert!(repository.claim_refresh_replay(&user_id, "first").await.unwrap());
+            assert!(!repository.claim_refresh_replay(&user_id, "first").await.unwrap());
+            assert!(!repository.claim_refresh_replay(&user_id, "second").await.unwrap());
+
+            let stored = repository.find_by_previous_refresh_token("first").await.unwrap().unwrap();
+            assert!(stored.previous_refresh_token_expires.unwrap() < DateTime::now());
+        });
+    }
+}
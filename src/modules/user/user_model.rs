@@ -2,6 +2,11 @@ use mongodb::bson::{oid::ObjectId, DateTime};
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 
+use crate::utils::signed_actions;
+
+pub const REFRESH_TOKEN_TTL_DAYS: i64 = 30;
+pub const REFRESH_GRACE_SECONDS: i64 = 30;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct User {
     #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
@@ -10,14 +15,29 @@ pub struct User {
     pub password: String,
     pub name: String,
     pub is_verified: bool,
+    #[serde(default = "default_role")]
+    pub role: String,  // "member" or "admin"
     pub verification_token: Option<String>,
     pub refresh_token: Option<String>,
+    pub refresh_token_expires: Option<DateTime>,
+    // The token rotated out by the last refresh, honored briefly so racing
+    // clients get the same new pair instead of being logged out
+    pub previous_refresh_token: Option<String>,
+    pub previous_refresh_token_expires: Option<DateTime>,  // Moved into the past once the replay is used
+    // The access token issued with the current refresh token is rebuilt from
+    // its issue time for a replay, so only its hash is stored
+    pub last_access_token_hash: Option<String>,
+    pub last_access_token_issued_at: Option<i64>,
     pub password_reset_token: Option<String>,
     pub password_reset_expires: Option<DateTime>,
     pub created_at: DateTime,
     pub updated_at: DateTime,
 }
 
+fn default_role() -> String {
+    "member".to_string()
+}
+
 impl User {
     pub fn new(email: String, password: String, name: String) -> Self {
         Self {
@@ -26,8 +46,14 @@ impl User {
             password,
             name,
             is_verified: false,
+            role: default_role(),
             verification_token: None,
             refresh_token: None,
+            refresh_token_expires: None,
+            previous_refresh_token: None,
+            previous_refresh_token_expires: None,
+            last_access_token_hash: None,
+            last_access_token_issued_at: None,
             password_reset_token: None,
             password_reset_expires: None,
             created_at: DateTime::now(),
@@ -47,10 +73,28 @@ impl User {
     }
 
     pub fn set_refresh_token(&mut self, token: String) {
+        let expires = Utc::now() + chrono::Duration::days(REFRESH_TOKEN_TTL_DAYS);
         self.refresh_token = Some(token);
+        self.refresh_token_expires = Some(DateTime::from_millis(expires.timestamp_millis()));
+        self.previous_refresh_token = None;
+        self.previous_refresh_token_expires = None;
+        self.last_access_token_hash = None;
+        self.last_access_token_issued_at = None;
         self.updated_at = DateTime::now();
     }
 
+    /// Replaces the refresh token while keeping the old one usable once more
+    /// within the grace window. `access_token` was issued at `issued_at`.
+    pub fn rotate_refresh_token(&mut self, token: String, access_token: &str, issued_at: i64) {
+        let grace_expires = Utc::now() + chrono::Duration::seconds(REFRESH_GRACE_SECONDS);
+        let previous = self.refresh_token.take();
+        self.set_refresh_token(token);
+        self.previous_refresh_token = previous;
+        self.previous_refresh_token_expires = Some(DateTime::from_millis(grace_expires.timestamp_millis()));
+        self.last_access_token_hash = Some(signed_actions::token_hash(access_token));
+        self.last_access_token_issued_at = Some(issued_at);
+    }
+
     pub fn set_password_reset_token(&mut self, token: String) {
         self.password_reset_token = Some(token);
         let now = Utc::now();
@@ -65,3 +109,36 @@ impl User {
         self.updated_at = DateTime::now();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotation_keeps_only_a_hash_of_the_access_token() {
+        let mut user = User::new("host@example.com".to_string(), "hash".to_string(), "Host".to_string());
+        user.set_refresh_token("first".to_string());
+
+        user.rotate_refresh_token("second".to_string(), "access-token", 1_700_000_000);
+
+        assert_eq!(user.refresh_token.as_deref(), Some("second"));
+        assert_eq!(user.previous_refresh_token.as_deref(), Some("first"));
+        assert_eq!(user.last_access_token_hash, Some(signed_actions::token_hash("access-token")));
+        assert_eq!(user.last_access_token_issued_at, Some(1_700_000_000));
+        let stored = serde_json::to_string(&user).unwrap();
+        assert!(!stored.contains("access-token"));
+    }
+
+    #[test]
+    fn a_new_session_forgets_the_previous_rotation() {
+        let mut user = User::new("host@example.com".to_string(), "hash".to_string(), "Host".to_string());
+        user.set_refresh_token("first".to_string());
+        user.rotate_refresh_token("second".to_string(), "access-token", 1_700_000_000);
+
+        user.set_refresh_token("login".to_string());
+
+        assert!(user.previous_refresh_token.is_none());
+        assert!(user.last_access_token_hash.is_none());
+        assert!(user.last_access_token_issued_at.is_none());
+    }
+}
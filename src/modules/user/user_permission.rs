@@ -0,0 +1,77 @@
+use std::fmt;
+
+/// Actions a route can require. Roles map to a fixed set of these so route
+/// checks never compare role names directly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    ManageOwnCalendar,
+    ManageOrg,
+    AdminUsers,
+    ReadReports,
+    ManageSystem,
+}
+
+impl Permission {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Permission::ManageOwnCalendar => "manage_own_calendar",
+            Permission::ManageOrg => "manage_org",
+            Permission::AdminUsers => "admin_users",
+            Permission::ReadReports => "read_reports",
+            Permission::ManageSystem => "manage_system",
+        }
+    }
+}
+
+impl fmt::Display for Permission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Permissions granted to `role`. Unknown roles get nothing.
+pub fn permissions_for_role(role: &str) -> &'static [Permission] {
+    match role {
+        "admin" => &[
+            Permission::ManageOwnCalendar,
+            Permission::ManageOrg,
+            Permission::AdminUsers,
+            Permission::ReadReports,
+            Permission::ManageSystem,
+        ],
+        "org_admin" => &[
+            Permission::ManageOwnCalendar,
+            Permission::ManageOrg,
+            Permission::ReadReports,
+        ],
+        "member" => &[Permission::ManageOwnCalendar],
+        _ => &[],
+    }
+}
+
+pub fn role_has_permission(role: &str, permission: Permission) -> bool {
+    permissions_for_role(role).contains(&permission)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_role_gets_its_own_permissions() {
+        assert!(role_has_permission("admin", Permission::ManageSystem));
+        assert!(role_has_permission("admin", Permission::AdminUsers));
+        assert!(role_has_permission("org_admin", Permission::ManageOrg));
+        assert!(role_has_permission("org_admin", Permission::ReadReports));
+        assert!(!role_has_permission("org_admin", Permission::ManageSystem));
+        assert!(!role_has_permission("org_admin", Permission::AdminUsers));
+        assert_eq!(permissions_for_role("member"), [Permission::ManageOwnCalendar]);
+    }
+
+    #[test]
+    fn unknown_roles_get_nothing() {
+        assert!(permissions_for_role("superuser").is_empty());
+        assert!(permissions_for_role("").is_empty());
+        assert!(!role_has_permission("Admin", Permission::ManageOwnCalendar));
+    }
+}
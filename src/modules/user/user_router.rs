@@ -1,6 +1,7 @@
 use actix_web::{web, Scope, HttpRequest};
 use crate::modules::user::user_controller::UserController;
 use crate::errors::error::AppError;
+use crate::errors::error_handler::method_not_allowed;
 use crate::middleware::auth::AuthMiddleware;
 
 pub fn user_routes() -> Result<Scope, AppError> {
@@ -11,42 +12,49 @@ pub fn user_routes() -> Result<Scope, AppError> {
         .app_data(controller.clone())
         .service(
             web::resource("/register")
+                .default_service(method_not_allowed("POST"))
                 .route(web::post().to(|data, controller: web::Data<UserController>| {
                     async move { controller.register(data).await }
                 }))
         )
         .service(
             web::resource("/login")
+                .default_service(method_not_allowed("POST"))
                 .route(web::post().to(|data, controller: web::Data<UserController>| {
                     async move { controller.login(data).await }
                 }))
         )
         .service(
             web::resource("/verify-email")
+                .default_service(method_not_allowed("POST"))
                 .route(web::post().to(|data, controller: web::Data<UserController>| {
                     async move { controller.verify_email(data).await }
                 }))
         )
         .service(
             web::resource("/refresh-token")
+                .default_service(method_not_allowed("POST"))
                 .route(web::post().to(|data, controller: web::Data<UserController>| {
                     async move { controller.refresh_token(data).await }
                 }))
         )
         .service(
             web::resource("/forgot-password")
+                .default_service(method_not_allowed("POST"))
                 .route(web::post().to(|data, controller: web::Data<UserController>| {
                     async move { controller.forgot_password(data).await }
                 }))
         )
         .service(
             web::resource("/reset-password")
+                .default_service(method_not_allowed("POST"))
                 .route(web::post().to(|data, controller: web::Data<UserController>| {
                     async move { controller.reset_password(data).await }
                 }))
         )
         .service(
             web::resource("/me")
+                .default_service(method_not_allowed("GET"))
                 .wrap(AuthMiddleware)
                 .route(web::get().to(|req: HttpRequest, controller: web::Data<UserController>| {
                     async move { controller.get_current_user(req).await }
@@ -1,15 +1,24 @@
 use serde::{Deserialize, Serialize};
+use validator::Validate;
+use crate::modules::user::user_model::User;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct CreateUserRequest {
+    #[validate(length(max = 254, message = "Email must be at most 254 characters"))]
     pub email: String,
+    #[validate(length(max = 128, message = "Password must be at most 128 characters"))]
     pub password: String,
+    #[validate(length(max = 100, message = "Name must be at most 100 characters"))]
     pub name: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct LoginRequest {
+    #[validate(length(max = 254, message = "Email must be at most 254 characters"))]
     pub email: String,
+    #[validate(length(max = 128, message = "Password must be at most 128 characters"))]
     pub password: String,
 }
 
@@ -21,6 +30,17 @@ pub struct UserResponse {
     pub is_verified: bool,
 }
 
+impl From<User> for UserResponse {
+    fn from(user: User) -> Self {
+        Self {
+            id: user.id.unwrap().to_hex(),
+            email: user.email,
+            name: user.name,
+            is_verified: user.is_verified,
+        }
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct AuthResponse {
     pub access_token: String,
@@ -34,6 +54,13 @@ pub struct Claims {
     pub exp: i64,     // expiration time
     pub iat: i64,     // issued at
     pub email: String,
+    // Tokens issued before roles were embedded decode as members
+    #[serde(default = "default_claims_role")]
+    pub role: String,
+}
+
+fn default_claims_role() -> String {
+    "member".to_string()
 }
 
 #[derive(Debug, Serialize)]
@@ -41,24 +68,33 @@ pub struct VerificationResponse {
     pub message: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct VerifyEmailRequest {
+    #[validate(length(max = 64, message = "Token must be at most 64 characters"))]
     pub token: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct RefreshTokenRequest {
+    #[validate(length(max = 128, message = "Refresh token must be at most 128 characters"))]
     pub refresh_token: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct ForgotPasswordRequest {
+    #[validate(length(max = 254, message = "Email must be at most 254 characters"))]
     pub email: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct ResetPasswordRequest {
+    #[validate(length(max = 64, message = "Token must be at most 64 characters"))]
     pub token: String,
+    #[validate(length(max = 128, message = "Password must be at most 128 characters"))]
     pub new_password: String,
 }
 
@@ -67,3 +103,62 @@ pub struct TokenResponse {
     pub access_token: String,
     pub refresh_token: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use serde::de::DeserializeOwned;
+    use serde_json::json;
+
+    use super::*;
+
+    fn invalid_fields<T: DeserializeOwned + Validate>(body: serde_json::Value) -> Vec<String> {
+        let request: T = serde_json::from_value(body).unwrap();
+        let errors = request.validate().unwrap_err();
+        let mut fields: Vec<String> = errors.field_errors().keys().map(|field| field.to_string()).collect();
+        fields.sort();
+        fields
+    }
+
+    #[test]
+    fn oversized_fields_are_named_in_the_errors() {
+        let email = format!("{}@example.com", "e".repeat(250));
+        let password = "p".repeat(129);
+        let token = "t".repeat(65);
+
+        assert_eq!(
+            invalid_fields::<CreateUserRequest>(json!({ "email": email, "password": password, "name": "n".repeat(101) })),
+            ["email", "name", "password"],
+        );
+        assert_eq!(invalid_fields::<LoginRequest>(json!({ "email": email, "password": password })), ["email", "password"]);
+        assert_eq!(invalid_fields::<VerifyEmailRequest>(json!({ "token": token })), ["token"]);
+        assert_eq!(invalid_fields::<RefreshTokenRequest>(json!({ "refresh_token": "r".repeat(129) })), ["refresh_token"]);
+        assert_eq!(invalid_fields::<ForgotPasswordRequest>(json!({ "email": email })), ["email"]);
+        assert_eq!(
+            invalid_fields::<ResetPasswordRequest>(json!({ "token": token, "new_password": password })),
+            ["new_password", "token"],
+        );
+    }
+
+    #[test]
+    fn fields_at_their_limit_are_accepted() {
+        let request: CreateUserRequest = serde_json::from_value(json!({
+            "email": format!("{}@example.com", "e".repeat(242)),
+            "password": "p".repeat(128),
+            "name": "n".repeat(100),
+        }))
+        .unwrap();
+        assert!(request.validate().is_ok());
+    }
+
+    #[test]
+    fn claims_without_a_role_decode_as_member() {
+        let claims: Claims = serde_json::from_value(json!({
+            "sub": "user",
+            "exp": 0,
+            "iat": 0,
+            "email": "user@example.com",
+        }))
+        .unwrap();
+        assert_eq!(claims.role, "member");
+    }
+}
@@ -0,0 +1,250 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde_json::json;
+
+use crate::errors::error::AppError;
+use crate::modules::booking::booking_model::Booking;
+use crate::modules::calendar::calendar_model::EventType;
+
+/// How long a Slack post may take before the booking request goes on without it.
+const SLACK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// In-process extension points of the booking lifecycle, for behaviour that
+/// belongs to one deployment rather than to the crate. Every method does
+/// nothing unless overridden.
+#[async_trait]
+pub trait BookingHooks: Send + Sync {
+    /// Names the hook in log lines.
+    fn name(&self) -> &'static str;
+
+    /// Runs before a booking is stored. An error refuses the booking and is
+    /// what the invitee gets back.
+    async fn before_create(&self, _booking: &Booking, _event_type: &EventType) -> Result<(), AppError> {
+        Ok(())
+    }
+
+    /// Runs once a booking is stored and its confirmations are sent.
+    async fn after_create(&self, _booking: &Booking, _event_type: &EventType) -> Result<(), AppError> {
+        Ok(())
+    }
+
+    /// Runs once a booking is cancelled. `event_type` is None when it was
+    /// deleted for good.
+    async fn after_cancel(&self, _booking: &Booking, _event_type: Option<&EventType>) -> Result<(), AppError> {
+        Ok(())
+    }
+}
+
+/// The hooks registered at startup, run in the order they were registered.
+#[derive(Clone, Default)]
+pub struct HookRegistry {
+    hooks: Vec<Arc<dyn BookingHooks>>,
+}
+
+impl fmt::Debug for HookRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.hooks.iter().map(|hook| hook.name())).finish()
+    }
+}
+
+impl HookRegistry {
+    pub fn register(&mut self, hook: impl BookingHooks + 'static) {
+        self.hooks.push(Arc::new(hook));
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.hooks.iter().map(|hook| hook.name()).collect()
+    }
+
+    /// Stops at the first hook that refuses the booking.
+    pub async fn before_create(&self, booking: &Booking, event_type: &EventType) -> Result<(), AppError> {
+        for hook in &self.hooks {
+            hook.before_create(booking, event_type).await?;
+        }
+        Ok(())
+    }
+
+    /// The booking already exists, so a failing hook is logged and the rest still run.
+    pub async fn after_create(&self, booking: &Booking, event_type: &EventType) {
+        for hook in &self.hooks {
+            if let Err(e) = hook.after_create(booking, event_type).await {
+                println!("Booking hook {} failed after creating booking {}: {}", hook.name(), Self::id_of(booking), e);
+            }
+        }
+    }
+
+    pub async fn after_cancel(&self, booking: &Booking, event_type: Option<&EventType>) {
+        for hook in &self.hooks {
+            if let Err(e) = hook.after_cancel(booking, event_type).await {
+                println!("Booking hook {} failed after cancelling booking {}: {}", hook.name(), Self::id_of(booking), e);
+            }
+        }
+    }
+
+    fn id_of(booking: &Booking) -> String {
+        booking.id.map(|id| id.to_hex()).unwrap_or_default()
+    }
+}
+
+/// Posts new and cancelled bookings to a Slack channel through an incoming webhook.
+pub struct SlackHook {
+    webhook_url: String,
+    http: reqwest::Client,
+}
+
+impl SlackHook {
+    pub fn new(webhook_url: String) -> Self {
+        Self { webhook_url, http: reqwest::Client::new() }
+    }
+
+    async fn post(&self, text: String) -> Result<(), AppError> {
+        let response = self.http
+            .post(&self.webhook_url)
+            .timeout(SLACK_TIMEOUT)
+            .json(&json!({ "text": text }))
+            .send()
+            .await
+            .map_err(|e| AppError::InternalServerError(format!("Slack request failed: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(AppError::InternalServerError(format!("Slack answered {}", response.status())));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl BookingHooks for SlackHook {
+    fn name(&self) -> &'static str {
+        "slack"
+    }
+
+    async fn after_create(&self, booking: &Booking, event_type: &EventType) -> Result<(), AppError> {
+        self.post(format!(
+            "New booking: {} with {} on {} at {}",
+            event_type.name, booking.invitee_name, booking.date, booking.start_time,
+        ))
+        .await
+    }
+
+    async fn after_cancel(&self, booking: &Booking, event_type: Option<&EventType>) -> Result<(), AppError> {
+        let event_name = event_type.map(|event_type| event_type.name.as_str()).unwrap_or("A meeting");
+        self.post(format!(
+            "Cancelled: {} with {} on {} at {}",
+            event_name, booking.invitee_name, booking.date, booking.start_time,
+        ))
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use mongodb::bson::{oid::ObjectId, DateTime};
+
+    use super::*;
+    use crate::modules::calendar::calendar_model::{Availability, DEFAULT_SCHEDULE_NAME};
+    use crate::test_support;
+
+    type Calls = Arc<Mutex<Vec<String>>>;
+
+    /// Records each call, refusing or failing it when told to.
+    struct RecordingHook {
+        name: &'static str,
+        refuses: bool,
+        fails: bool,
+        calls: Calls,
+    }
+
+    impl RecordingHook {
+        fn new(name: &'static str, calls: &Calls) -> Self {
+            Self { name, refuses: false, fails: false, calls: calls.clone() }
+        }
+
+        fn record(&self, method: &str) -> Result<(), AppError> {
+            self.calls.lock().unwrap().push(format!("{}.{}", self.name, method));
+            if self.fails {
+                return Err(AppError::InternalServerError("CRM is down".to_string()));
+            }
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl BookingHooks for RecordingHook {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        async fn before_create(&self, _booking: &Booking, _event_type: &EventType) -> Result<(), AppError> {
+            self.record("before_create")?;
+            if self.refuses {
+                return Err(AppError::Forbidden("Bookings from this domain are not accepted".to_string()));
+            }
+            Ok(())
+        }
+
+        async fn after_create(&self, _booking: &Booking, _event_type: &EventType) -> Result<(), AppError> {
+            self.record("after_create")
+        }
+
+        async fn after_cancel(&self, _booking: &Booking, _event_type: Option<&EventType>) -> Result<(), AppError> {
+            self.record("after_cancel")
+        }
+    }
+
+    fn booking_and_event_type() -> (Booking, EventType) {
+        let owner = ObjectId::new();
+        let schedule = Availability {
+            id: Some(ObjectId::new()),
+            user_id: owner,
+            calendar_settings_id: ObjectId::new(),
+            name: DEFAULT_SCHEDULE_NAME.to_string(),
+            is_default: true,
+            rules: Vec::new(),
+            date_overrides: Vec::new(),
+            version: 0,
+            deleted_at: None,
+            created_at: DateTime::now(),
+            updated_at: DateTime::now(),
+        };
+        let event_type = test_support::event_type(&owner, &schedule);
+        let booking = test_support::booking(&ObjectId::new(), &owner, "2026-03-02", "10:00");
+        (booking, event_type)
+    }
+
+    #[tokio::test]
+    async fn a_refusing_hook_stops_the_booking_and_the_hooks_after_it() {
+        let calls = Calls::default();
+        let mut registry = HookRegistry::default();
+        registry.register(RecordingHook { refuses: true, ..RecordingHook::new("vetting", &calls) });
+        registry.register(RecordingHook::new("crm", &calls));
+        let (booking, event_type) = booking_and_event_type();
+
+        let refused = registry.before_create(&booking, &event_type).await;
+
+        assert!(matches!(refused, Err(AppError::Forbidden(_))));
+        assert_eq!(*calls.lock().unwrap(), ["vetting.before_create"]);
+    }
+
+    #[tokio::test]
+    async fn failing_hooks_after_the_fact_do_not_stop_the_others() {
+        let calls = Calls::default();
+        let mut registry = HookRegistry::default();
+        registry.register(RecordingHook { fails: true, ..RecordingHook::new("crm", &calls) });
+        registry.register(RecordingHook::new("audit", &calls));
+        let (booking, event_type) = booking_and_event_type();
+
+        registry.after_create(&booking, &event_type).await;
+        registry.after_cancel(&booking, None).await;
+
+        assert_eq!(
+            *calls.lock().unwrap(),
+            ["crm.after_create", "audit.after_create", "crm.after_cancel", "audit.after_cancel"],
+        );
+        assert_eq!(registry.names(), ["crm", "audit"]);
+    }
+}
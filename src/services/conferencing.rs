@@ -0,0 +1,271 @@
+use async_trait::async_trait;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{Duration, Utc};
+use hmac::{Hmac, Mac};
+use mongodb::bson::{oid::ObjectId, DateTime};
+use mongodb::Database;
+use serde::Deserialize;
+use serde_json::json;
+use sha2::Sha256;
+
+use crate::app::AppState;
+use crate::errors::error::AppError;
+use crate::modules::booking::booking_model::Booking;
+use crate::modules::calendar::calendar_crud::CalendarSettingsRepository;
+use crate::modules::calendar::calendar_model::EventType;
+use crate::modules::conferencing::conferencing_crud::ConferencingConnectionRepository;
+use crate::modules::conferencing::conferencing_model::ConferencingConnection;
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub const ZOOM: &str = "zoom";
+
+/// Keeps OAuth states from being valid for anything else signed with the same secret.
+const STATE_DOMAIN: &[u8] = b"conferencing-state:v1:";
+/// How long a host has to grant access after starting to connect.
+const STATE_MINUTES: i64 = 15;
+/// Access tokens this close to expiry are refreshed before use.
+const REFRESH_MARGIN_SECONDS: i64 = 60;
+
+const ZOOM_AUTHORIZE_URL: &str = "https://zoom.us/oauth/authorize";
+const ZOOM_TOKEN_URL: &str = "https://zoom.us/oauth/token";
+const ZOOM_MEETINGS_URL: &str = "https://api.zoom.us/v2/users/me/meetings";
+
+/// Creates a unique meeting for each booking of a video location.
+#[async_trait]
+pub trait ConferencingProvider: Send + Sync {
+    /// Creates a meeting for `booking` with the host's account and returns the
+    /// link attendees join with.
+    async fn create_meeting(&self, event_type: &EventType, booking: &Booking) -> Result<String, AppError>;
+}
+
+/// The provider a video location names, when the operator has configured it.
+pub fn provider(name: &str, db: Database) -> Option<Box<dyn ConferencingProvider>> {
+    match name {
+        ZOOM => AppState::get().zoom.clone().map(|config| Box::new(ZoomProvider::new(config, db)) as Box<dyn ConferencingProvider>),
+        _ => None,
+    }
+}
+
+/// Whether `name` is a provider that can create meetings, configured or not.
+pub fn is_known_provider(name: &str) -> bool {
+    name == ZOOM
+}
+
+/// Signs `user_id` into the OAuth state parameter, so the callback knows whom
+/// to connect and that the flow started here.
+pub fn sign_state(secret: &[u8], user_id: &ObjectId) -> String {
+    let expires_at = (Utc::now() + Duration::minutes(STATE_MINUTES)).timestamp();
+    let payload = URL_SAFE_NO_PAD.encode(format!("{}:{}", user_id.to_hex(), expires_at));
+    let signature = URL_SAFE_NO_PAD.encode(state_mac(secret, &payload).finalize().into_bytes());
+    format!("{}.{}", payload, signature)
+}
+
+/// The user a state was signed for, unless it is forged, mangled or expired.
+pub fn verify_state(secret: &[u8], state: &str) -> Option<ObjectId> {
+    let (payload, signature) = state.split_once('.')?;
+    let signature = URL_SAFE_NO_PAD.decode(signature).ok()?;
+    state_mac(secret, payload).verify_slice(&signature).ok()?;
+
+    let payload = String::from_utf8(URL_SAFE_NO_PAD.decode(payload).ok()?).ok()?;
+    let (user_id, expires_at) = payload.split_once(':')?;
+    if expires_at.parse::<i64>().ok()? < Utc::now().timestamp() {
+        return None;
+    }
+    ObjectId::parse_str(user_id).ok()
+}
+
+fn state_mac(secret: &[u8], payload: &str) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(STATE_DOMAIN);
+    mac.update(payload.as_bytes());
+    mac
+}
+
+/// OAuth app credentials for Zoom, set by the operator.
+#[derive(Debug, Clone)]
+pub struct ZoomConfig {
+    pub client_id: String,
+    pub client_secret: String,
+    pub redirect_uri: String,  // Must match the redirect URL registered with Zoom
+}
+
+#[derive(Deserialize)]
+struct ZoomTokens {
+    access_token: String,
+    refresh_token: String,
+    expires_in: i64,  // seconds
+}
+
+#[derive(Deserialize)]
+struct ZoomMeeting {
+    join_url: String,
+}
+
+/// Creates Zoom meetings with each host's own Zoom account.
+pub struct ZoomProvider {
+    config: ZoomConfig,
+    http: reqwest::Client,
+    connection_repository: ConferencingConnectionRepository,
+    settings_repository: CalendarSettingsRepository,
+}
+
+impl ZoomProvider {
+    pub fn new(config: ZoomConfig, db: Database) -> Self {
+        Self {
+            config,
+            http: reqwest::Client::new(),
+            connection_repository: ConferencingConnectionRepository::new(db.clone()),
+            settings_repository: CalendarSettingsRepository::new(db),
+        }
+    }
+
+    /// Where to send a host to grant access to their Zoom account.
+    pub fn authorize_url(&self, state: &str) -> String {
+        format!(
+            "{}?response_type=code&client_id={}&redirect_uri={}&state={}",
+            ZOOM_AUTHORIZE_URL,
+            urlencoding::encode(&self.config.client_id),
+            urlencoding::encode(&self.config.redirect_uri),
+            urlencoding::encode(state),
+        )
+    }
+
+    /// Trades the code from the OAuth callback for tokens and stores them.
+    pub async fn connect(&self, user_id: &ObjectId, code: &str) -> Result<ConferencingConnection, AppError> {
+        let tokens = self.request_tokens(&[
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", &self.config.redirect_uri),
+        ]).await?;
+        self.save(user_id, tokens).await
+    }
+
+    /// A working access token for the host, refreshed first if it is about to expire.
+    async fn access_token(&self, user_id: &ObjectId) -> Result<String, AppError> {
+        let connection = self.connection_repository.find(user_id, ZOOM).await?
+            .ok_or_else(|| AppError::BadRequest("Host has not connected Zoom".to_string()))?;
+
+        let fresh_until = DateTime::now().timestamp_millis() + REFRESH_MARGIN_SECONDS * 1000;
+        if connection.expires_at.timestamp_millis() > fresh_until {
+            return Ok(connection.access_token);
+        }
+
+        let tokens = self.request_tokens(&[
+            ("grant_type", "refresh_token"),
+            ("refresh_token", &connection.refresh_token),
+        ]).await?;
+        Ok(self.save(user_id, tokens).await?.access_token)
+    }
+
+    async fn request_tokens(&self, form: &[(&str, &str)]) -> Result<ZoomTokens, AppError> {
+        let response = self.http
+            .post(ZOOM_TOKEN_URL)
+            .basic_auth(&self.config.client_id, Some(&self.config.client_secret))
+            .form(form)
+            .send()
+            .await
+            .map_err(|e| AppError::InternalServerError(format!("Zoom token request failed: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(AppError::BadRequest(format!("Zoom rejected the token request ({})", response.status())));
+        }
+        response
+            .json()
+            .await
+            .map_err(|e| AppError::InternalServerError(format!("Unexpected Zoom token response: {}", e)))
+    }
+
+    async fn save(&self, user_id: &ObjectId, tokens: ZoomTokens) -> Result<ConferencingConnection, AppError> {
+        let expires_at = DateTime::from_millis(DateTime::now().timestamp_millis() + tokens.expires_in * 1000);
+        self.connection_repository
+            .save_tokens(user_id, ZOOM, &tokens.access_token, &tokens.refresh_token, expires_at)
+            .await
+    }
+}
+
+#[async_trait]
+impl ConferencingProvider for ZoomProvider {
+    async fn create_meeting(&self, event_type: &EventType, booking: &Booking) -> Result<String, AppError> {
+        let access_token = self.access_token(&booking.host_user_id).await?;
+        // Booking dates and times are in the host's timezone, which Zoom is told
+        let timezone = self.settings_repository.find_by_user_id(&booking.host_user_id).await?
+            .map(|settings| settings.timezone)
+            .unwrap_or_else(|| "UTC".to_string());
+
+        let body = json!({
+            "topic": format!("{} with {}", event_type.name, booking.invitee_name),
+            "type": 2,  // Scheduled meeting
+            "start_time": format!("{}T{}:00", booking.date, booking.start_time),
+            "timezone": timezone,
+            "duration": event_type.duration,
+        });
+        let response = self.http
+            .post(ZOOM_MEETINGS_URL)
+            .bearer_auth(access_token)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| AppError::InternalServerError(format!("Zoom meeting request failed: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(AppError::InternalServerError(format!("Zoom rejected the meeting ({})", response.status())));
+        }
+        let meeting: ZoomMeeting = response
+            .json()
+            .await
+            .map_err(|e| AppError::InternalServerError(format!("Unexpected Zoom meeting response: {}", e)))?;
+
+        Ok(meeting.join_url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &[u8] = b"test-conferencing-secret";
+
+    /// A state signed like `sign_state` would, but with the given expiry.
+    fn state_expiring_at(user_id: &ObjectId, expires_at: i64) -> String {
+        let payload = URL_SAFE_NO_PAD.encode(format!("{}:{}", user_id.to_hex(), expires_at));
+        let signature = URL_SAFE_NO_PAD.encode(state_mac(SECRET, &payload).finalize().into_bytes());
+        format!("{}.{}", payload, signature)
+    }
+
+    #[test]
+    fn signed_state_names_the_user() {
+        let user_id = ObjectId::new();
+        assert_eq!(verify_state(SECRET, &sign_state(SECRET, &user_id)), Some(user_id));
+    }
+
+    #[test]
+    fn forged_or_mangled_states_are_refused() {
+        let user_id = ObjectId::new();
+        let state = sign_state(SECRET, &user_id);
+        assert_eq!(verify_state(b"another-secret", &state), None);
+
+        // Swapping in another user's payload breaks the signature
+        let (_, signature) = state.split_once('.').unwrap();
+        let other = URL_SAFE_NO_PAD.encode(format!("{}:{}", ObjectId::new().to_hex(), Utc::now().timestamp() + 600));
+        assert_eq!(verify_state(SECRET, &format!("{}.{}", other, signature)), None);
+
+        assert_eq!(verify_state(SECRET, "no-signature"), None);
+        assert_eq!(verify_state(SECRET, "not base64!.at all!"), None);
+        assert_eq!(verify_state(SECRET, ""), None);
+    }
+
+    #[test]
+    fn expired_state_is_refused() {
+        let user_id = ObjectId::new();
+        let past = (Utc::now() - Duration::minutes(1)).timestamp();
+        let future = (Utc::now() + Duration::minutes(1)).timestamp();
+        assert_eq!(verify_state(SECRET, &state_expiring_at(&user_id, past)), None);
+        assert_eq!(verify_state(SECRET, &state_expiring_at(&user_id, future)), Some(user_id));
+    }
+
+    #[test]
+    fn only_zoom_is_a_known_provider() {
+        assert!(is_known_provider(ZOOM));
+        assert!(!is_known_provider("teams"));
+        assert!(!is_known_provider("Zoom"));
+    }
+}
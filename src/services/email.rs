@@ -1,14 +1,149 @@
 use lettre::{
+    message::header::{ContentType, Header, HeaderName, HeaderValue},
+    message::{Attachment, MultiPart, SinglePart},
     transport::smtp::authentication::Credentials,
     Message, SmtpTransport, Transport,
 };
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use crate::config::environment::Environment;
 use crate::errors::error::AppError;
+use crate::utils::ics::IcsMethod;
+use crate::utils::template;
+use crate::utils::text::truncate_for_display;
+
+/// Longest names and free text shown in emails; longer values are cut.
+const DISPLAY_NAME_CHARS: usize = 100;
+const DISPLAY_TEXT_CHARS: usize = 500;
+
+/// Per-message headers beyond from/to/subject. Threading ids should be stable
+/// for everything sent about the same booking so mail clients group them.
+#[derive(Debug, Clone, Default)]
+pub struct MessageOptions {
+    pub reply_to: Option<String>,
+    pub message_id: Option<String>,
+    pub in_reply_to: Option<String>,
+    pub references: Vec<String>,
+    pub list_unsubscribe: Option<String>,  // URL; only for non-transactional mail
+    pub attachments: Vec<EmailAttachment>,
+}
+
+/// A host's own wording for a booking email, from the event type's email
+/// templates. Replaces the built-in body for invitees and guests.
+#[derive(Debug, Clone)]
+pub struct CustomCopy {
+    pub template: String,
+    pub invitee_name: String,
+}
+
+/// A file sent alongside the body, which turns the message into multipart/mixed.
+#[derive(Debug, Clone)]
+pub struct EmailAttachment {
+    pub filename: String,
+    pub content_type: String,  // Full MIME type, parameters included
+    pub content: String,
+}
+
+impl EmailAttachment {
+    /// A calendar invitation; `method` must match the METHOD inside `ics`.
+    pub fn calendar(ics: &str, method: IcsMethod) -> Self {
+        Self {
+            filename: if method == IcsMethod::Cancel { "cancel.ics" } else { "invite.ics" }.to_string(),
+            content_type: format!("text/calendar; charset=utf-8; method={}", method.as_str()),
+            content: ics.to_string(),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct ListUnsubscribe(String);
+
+impl Header for ListUnsubscribe {
+    fn name() -> HeaderName {
+        HeaderName::new_from_ascii_str("List-Unsubscribe")
+    }
+
+    fn parse(s: &str) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Self(s.trim().trim_start_matches('<').trim_end_matches('>').to_string()))
+    }
+
+    fn display(&self) -> HeaderValue {
+        HeaderValue::new(Self::name(), format!("<{}>", self.0))
+    }
+}
+
+/// Kinds of email we send, for duplicate suppression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EmailTemplate {
+    Verification,
+    PasswordReset,
+    BookingConfirmed,
+    BookingRequested,
+    BookingDeclined,
+    BookingCancelled,
+    BookingRescheduled,
+    BookingReminder,
+}
+
+impl EmailTemplate {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EmailTemplate::Verification => "verification",
+            EmailTemplate::PasswordReset => "password_reset",
+            EmailTemplate::BookingConfirmed => "booking_confirmed",
+            EmailTemplate::BookingRequested => "booking_requested",
+            EmailTemplate::BookingDeclined => "booking_declined",
+            EmailTemplate::BookingCancelled => "booking_cancelled",
+            EmailTemplate::BookingRescheduled => "booking_rescheduled",
+            EmailTemplate::BookingReminder => "booking_reminder",
+        }
+    }
+
+    /// Templates where every request must produce an email, even a repeat.
+    pub fn skips_dedup(&self) -> bool {
+        matches!(self, EmailTemplate::PasswordReset)
+    }
+}
+
+type DedupKey = (String, EmailTemplate, String);
+
+/// Recipients are compared case-insensitively.
+fn dedup_key(to_email: &str, template: EmailTemplate, resource_id: &str) -> DedupKey {
+    (to_email.to_lowercase(), template, resource_id.to_string())
+}
+
+/// Remembers recently sent emails so the same one is not sent twice within the window.
+struct SentEmails {
+    window: Duration,
+    sent: Mutex<HashMap<DedupKey, Instant>>,
+}
+
+impl SentEmails {
+    /// Records the send and returns true, or returns false if it is a duplicate.
+    fn claim(&self, key: DedupKey) -> bool {
+        let now = Instant::now();
+        let mut sent = self.sent.lock().unwrap();
+        sent.retain(|_, at| now.duration_since(*at) < self.window);
+        if sent.contains_key(&key) {
+            return false;
+        }
+        sent.insert(key, now);
+        true
+    }
+
+    /// Forgets a send that failed so a retry is not suppressed.
+    fn release(&self, key: &DedupKey) {
+        self.sent.lock().unwrap().remove(key);
+    }
+}
 
 #[derive(Clone)]
 pub struct EmailService {
     mailer: SmtpTransport,
     from_email: String,
+    app_url: String,  // APP_URL with BASE_PATH; every link in an email starts with it
+    sent_emails: Arc<SentEmails>,
 }
 
 impl EmailService {
@@ -26,36 +161,72 @@ impl EmailService {
         Ok(Self {
             mailer,
             from_email: env.email_user.clone(),
+            app_url: env.app_base_url(),
+            sent_emails: Arc::new(SentEmails {
+                window: Duration::from_secs(env.email_dedup_window_seconds),
+                sent: Mutex::new(HashMap::new()),
+            }),
         })
     }
 
+    /// Plain SMTP to `host`, for tests that need a server which misbehaves.
+    #[cfg(test)]
+    pub(crate) fn unencrypted(host: &str, port: u16, timeout: Duration) -> Self {
+        Self {
+            mailer: SmtpTransport::builder_dangerous(host).port(port).timeout(Some(timeout)).build(),
+            from_email: "test@example.com".to_string(),
+            app_url: "http://localhost:3000".to_string(),
+            sent_emails: Arc::new(SentEmails { window: Duration::ZERO, sent: Mutex::new(HashMap::new()) }),
+        }
+    }
+
+    /// Sends unless an identical email (same recipient, template and related
+    /// resource) already went out within the suppression window.
+    fn send_deduplicated(
+        &self,
+        template: EmailTemplate,
+        resource_id: &str,
+        to_email: &str,
+        subject: &str,
+        body: String,
+        options: MessageOptions,
+    ) -> Result<(), AppError> {
+        if template.skips_dedup() {
+            return self.send_message(to_email, subject, body, &options);
+        }
+
+        let key = dedup_key(to_email, template, resource_id);
+        if !self.sent_emails.claim(key.clone()) {
+            println!("Suppressed duplicate {} email to {}", template.as_str(), to_email);
+            return Ok(());
+        }
+
+        let result = self.send_message(to_email, subject, body, &options);
+        if result.is_err() {
+            self.sent_emails.release(&key);
+        }
+        result
+    }
+
     pub async fn send_verification_email(
         &self,
         to_email: &str,
         code: &str,
     ) -> Result<(), AppError> {
-        let email = Message::builder()
-            .from(self.from_email.parse().unwrap())
-            .to(to_email.parse().unwrap())
-            .subject("Your Calendly Verification Code")
-            .body(format!(
-                r#"
+        let body = format!(
+            r#"
                 <h1>Welcome to Calendly!</h1>
                 <p>Your verification code is:</p>
                 <h2 style="font-size: 24px; padding: 10px; background-color: #f5f5f5; text-align: center;">{}</h2>
                 <p>Please enter this code to verify your email address.</p>
                 <p>This code will expire in 30 minutes.</p>
                 <p>If you didn't create a Calendly account, please ignore this email.</p>
-                "#,
-                code
-            ))
-            .map_err(|e| AppError::EmailError(e.to_string()))?;
-
-        self.mailer
-            .send(&email)
-            .map_err(|e| AppError::EmailError(e.to_string()))?;
+            "#,
+            code
+        );
 
-        Ok(())
+        // The code identifies the email: a new code is a new email, a repeat is a duplicate
+        self.send_deduplicated(EmailTemplate::Verification, code, to_email, "Your Calendly Verification Code", body, MessageOptions::default())
     }
 
     pub async fn send_password_reset_email(
@@ -63,22 +234,325 @@ impl EmailService {
         to_email: &str,
         code: &str,
     ) -> Result<(), AppError> {
-        let email = Message::builder()
-            .from(self.from_email.parse().unwrap())
-            .to(to_email.parse().unwrap())
-            .subject("Reset Your Calendly Password")
-            .body(format!(
-                r#"
+        let body = format!(
+            r#"
                 <h1>Password Reset Code</h1>
                 <p>Your password reset code is:</p>
                 <h2 style="font-size: 24px; padding: 10px; background-color: #f5f5f5; text-align: center;">{}</h2>
                 <p>Enter this code to reset your password.</p>
                 <p>This code will expire in 30 minutes.</p>
                 <p>If you didn't request a password reset, please ignore this email.</p>
+            "#,
+            code
+        );
+
+        self.send_deduplicated(EmailTemplate::PasswordReset, code, to_email, "Reset Your Calendly Password", body, MessageOptions::default())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn send_booking_confirmed_email(
+        &self,
+        to_email: &str,
+        booking_id: &str,
+        event_name: &str,
+        date: &str,
+        start_time: &str,
+        location: Option<&str>,
+        ics: &str,
+        management_token: Option<&str>,
+        custom_copy: Option<&CustomCopy>,
+    ) -> Result<(), AppError> {
+        let body = match custom_copy {
+            Some(copy) => self.custom_body(copy, event_name, date, start_time, location, management_token),
+            None => self.booking_confirmed_body(event_name, date, start_time, location, management_token),
+        };
+
+        let options = MessageOptions {
+            attachments: vec![EmailAttachment::calendar(ics, IcsMethod::Request)],
+            ..MessageOptions::default()
+        };
+        self.send_deduplicated(EmailTemplate::BookingConfirmed, booking_id, to_email, "Booking Confirmed", body, options)
+    }
+
+    fn booking_confirmed_body(
+        &self,
+        event_name: &str,
+        date: &str,
+        start_time: &str,
+        location: Option<&str>,
+        management_token: Option<&str>,
+    ) -> String {
+        let location = location
+            .map(|location| format!("<p>Location: {}</p>", ammonia::clean_text(&truncate_for_display(location, DISPLAY_TEXT_CHARS))))
+            .unwrap_or_default();
+        // Only the invitee gets the links; the token is all it takes to change the booking
+        let manage = management_token
+            .map(|token| {
+                let base = format!("{}/bookings/{}", self.app_url, token);
+                format!(
+                    r#"<p>Need to change something? <a href="{0}/reschedule">Reschedule</a> or <a href="{0}/cancel">cancel</a> this booking.</p>"#,
+                    base
+                )
+            })
+            .unwrap_or_default();
+        format!(
+            r#"
+                <h1>Booking Confirmed</h1>
+                <p>Your booking for <strong>{}</strong> on {} at {} is confirmed.</p>
+                {}
+                <p>The attached invitation adds it to your calendar.</p>
+                {}
+            "#,
+            ammonia::clean_text(&truncate_for_display(event_name, DISPLAY_NAME_CHARS)),
+            date,
+            start_time,
+            location,
+            manage
+        )
+    }
+
+    /// Renders the host's template with the booking's details. Only the
+    /// invitee has management links, so the URLs are empty for anyone else.
+    fn custom_body(
+        &self,
+        copy: &CustomCopy,
+        event_name: &str,
+        date: &str,
+        start_time: &str,
+        location: Option<&str>,
+        management_token: Option<&str>,
+    ) -> String {
+        let manage_url = |action: &str| {
+            management_token
+                .map(|token| format!("{}/bookings/{}/{}", self.app_url, token, action))
+                .unwrap_or_default()
+        };
+        let values = [
+            ("invitee_name", truncate_for_display(&copy.invitee_name, DISPLAY_NAME_CHARS).into_owned()),
+            ("event_name", truncate_for_display(event_name, DISPLAY_NAME_CHARS).into_owned()),
+            ("date", date.to_string()),
+            ("start_time", start_time.to_string()),
+            ("location", location.map(|location| truncate_for_display(location, DISPLAY_TEXT_CHARS).into_owned()).unwrap_or_default()),
+            ("cancel_url", manage_url("cancel")),
+            ("reschedule_url", manage_url("reschedule")),
+        ];
+        format!("<p>{}</p>", template::render_email(&copy.template, &values))
+    }
+
+    /// Asks the host to approve or decline a booking of an event type that requires confirmation.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn send_booking_requested_email(
+        &self,
+        to_email: &str,
+        booking_id: &str,
+        event_name: &str,
+        invitee_name: &str,
+        date: &str,
+        start_time: &str,
+        expires_in_hours: u64,
+        approve_token: &str,
+        decline_token: &str,
+    ) -> Result<(), AppError> {
+        let body = format!(
+            r#"
+                <h1>Booking Request</h1>
+                <p><strong>{}</strong> asked to book <strong>{}</strong> on {} at {}.</p>
+                <p>The slot is held until you approve or decline the request.</p>
+                <p><a href="{}/actions/{}">Approve</a> | <a href="{}/actions/{}">Decline</a></p>
+                <p>These links work once, for 24 hours. You can also answer from your bookings.</p>
+                <p>Requests that are not answered within {} hours are declined automatically.</p>
+            "#,
+            ammonia::clean_text(&truncate_for_display(invitee_name, DISPLAY_NAME_CHARS)),
+            ammonia::clean_text(&truncate_for_display(event_name, DISPLAY_NAME_CHARS)),
+            date,
+            start_time,
+            self.app_url,
+            approve_token,
+            self.app_url,
+            decline_token,
+            expires_in_hours
+        );
+
+        self.send_deduplicated(EmailTemplate::BookingRequested, booking_id, to_email, "New Booking Request", body, MessageOptions::default())
+    }
+
+    pub async fn send_booking_declined_email(
+        &self,
+        to_email: &str,
+        booking_id: &str,
+        event_name: &str,
+        date: &str,
+        start_time: &str,
+    ) -> Result<(), AppError> {
+        let body = format!(
+            r#"
+                <h1>Booking Declined</h1>
+                <p>Your request to book <strong>{}</strong> on {} at {} was not accepted.</p>
+                <p>You are welcome to pick another time.</p>
+            "#,
+            ammonia::clean_text(&truncate_for_display(event_name, DISPLAY_NAME_CHARS)),
+            date,
+            start_time
+        );
+
+        self.send_deduplicated(EmailTemplate::BookingDeclined, booking_id, to_email, "Booking Declined", body, MessageOptions::default())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn send_booking_cancelled_email(
+        &self,
+        to_email: &str,
+        booking_id: &str,
+        event_name: &str,
+        date: &str,
+        start_time: &str,
+        reason: Option<&str>,
+        ics: Option<&str>,
+        custom_copy: Option<&CustomCopy>,
+    ) -> Result<(), AppError> {
+        let reason = reason
+            .map(|reason| format!("<p>Reason: {}</p>", ammonia::clean_text(&truncate_for_display(reason, DISPLAY_TEXT_CHARS))))
+            .unwrap_or_default();
+        let body = match custom_copy {
+            Some(copy) => format!("{}{}", self.custom_body(copy, event_name, date, start_time, None, None), reason),
+            None => format!(
+                r#"
+                    <h1>Booking Cancelled</h1>
+                    <p>Your booking for <strong>{}</strong> on {} at {} has been cancelled.</p>
+                    {}
                 "#,
-                code
-            ))
-            .map_err(|e| AppError::EmailError(e.to_string()))?;
+                ammonia::clean_text(&truncate_for_display(event_name, DISPLAY_NAME_CHARS)),
+                date,
+                start_time,
+                reason
+            ),
+        };
+
+        // The CANCEL invitation removes the event from the recipient's calendar
+        let options = MessageOptions {
+            attachments: ics.map(|ics| EmailAttachment::calendar(ics, IcsMethod::Cancel)).into_iter().collect(),
+            ..MessageOptions::default()
+        };
+        self.send_deduplicated(EmailTemplate::BookingCancelled, booking_id, to_email, "Booking Cancelled", body, options)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn send_booking_rescheduled_email(
+        &self,
+        to_email: &str,
+        booking_id: &str,
+        event_name: &str,
+        previous_date: &str,
+        previous_start_time: &str,
+        date: &str,
+        start_time: &str,
+        ics: Option<&str>,
+    ) -> Result<(), AppError> {
+        let body = format!(
+            r#"
+                <h1>Booking Rescheduled</h1>
+                <p>Your booking for <strong>{}</strong> has moved from {} at {} to {} at {}.</p>
+            "#,
+            ammonia::clean_text(&truncate_for_display(event_name, DISPLAY_NAME_CHARS)),
+            previous_date,
+            previous_start_time,
+            date,
+            start_time
+        );
+
+        // Each new time is a new email, so keep the slot in the key
+        let resource_id = format!("{}:{}T{}", booking_id, date, start_time);
+        // Same uid with a higher sequence, so calendars move the existing event
+        let options = MessageOptions {
+            attachments: ics.map(|ics| EmailAttachment::calendar(ics, IcsMethod::Request)).into_iter().collect(),
+            ..MessageOptions::default()
+        };
+        self.send_deduplicated(EmailTemplate::BookingRescheduled, &resource_id, to_email, "Booking Rescheduled", body, options)
+    }
+
+    /// Reminds an attendee of a confirmed booking `offset_minutes` before it starts.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn send_booking_reminder_email(
+        &self,
+        to_email: &str,
+        booking_id: &str,
+        event_name: &str,
+        date: &str,
+        start_time: &str,
+        location: Option<&str>,
+        offset_minutes: i32,
+        management_token: Option<&str>,
+        custom_copy: Option<&CustomCopy>,
+    ) -> Result<(), AppError> {
+        let body = match custom_copy {
+            Some(copy) => self.custom_body(copy, event_name, date, start_time, location, management_token),
+            None => {
+                let location = location
+                    .map(|location| format!("<p>Location: {}</p>", ammonia::clean_text(&truncate_for_display(location, DISPLAY_TEXT_CHARS))))
+                    .unwrap_or_default();
+                format!(
+                    r#"
+                        <h1>Upcoming Booking</h1>
+                        <p>This is a reminder that <strong>{}</strong> starts on {} at {}.</p>
+                        {}
+                    "#,
+                    ammonia::clean_text(&truncate_for_display(event_name, DISPLAY_NAME_CHARS)),
+                    date,
+                    start_time,
+                    location
+                )
+            }
+        };
+
+        // Each reminder, and each new time after a reschedule, is a new email
+        let resource_id = format!("{}:{}T{}:{}", booking_id, date, start_time, offset_minutes);
+        self.send_deduplicated(EmailTemplate::BookingReminder, &resource_id, to_email, "Booking Reminder", body, MessageOptions::default())
+    }
+
+    pub fn send_message(
+        &self,
+        to_email: &str,
+        subject: &str,
+        body: String,
+        options: &MessageOptions,
+    ) -> Result<(), AppError> {
+        let parse_mailbox = |address: &str| {
+            address.parse().map_err(|e: lettre::address::AddressError| AppError::EmailError(e.to_string()))
+        };
+
+        let mut builder = Message::builder()
+            .from(parse_mailbox(&self.from_email)?)
+            .to(parse_mailbox(to_email)?)
+            .subject(subject)
+            .message_id(options.message_id.clone());
+
+        if let Some(reply_to) = &options.reply_to {
+            builder = builder.reply_to(parse_mailbox(reply_to)?);
+        }
+        if let Some(in_reply_to) = &options.in_reply_to {
+            builder = builder.in_reply_to(in_reply_to.clone());
+        }
+        if !options.references.is_empty() {
+            builder = builder.references(options.references.join(" "));
+        }
+        if let Some(url) = &options.list_unsubscribe {
+            builder = builder.header(ListUnsubscribe(url.clone()));
+        }
+
+        let email = if options.attachments.is_empty() {
+            builder.body(body)
+        } else {
+            let mut multipart = MultiPart::mixed().singlepart(SinglePart::html(body));
+            for attachment in &options.attachments {
+                let content_type = ContentType::parse(&attachment.content_type)
+                    .map_err(|e| AppError::EmailError(e.to_string()))?;
+                multipart = multipart.singlepart(
+                    Attachment::new(attachment.filename.clone()).body(attachment.content.clone(), content_type)
+                );
+            }
+            builder.multipart(multipart)
+        }
+        .map_err(|e| AppError::EmailError(e.to_string()))?;
 
         self.mailer
             .send(&email)
@@ -87,3 +561,47 @@ impl EmailService {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sent_emails(window: Duration) -> SentEmails {
+        SentEmails { window, sent: Mutex::new(HashMap::new()) }
+    }
+
+    fn confirmation(to_email: &str, booking_id: &str) -> DedupKey {
+        dedup_key(to_email, EmailTemplate::BookingConfirmed, booking_id)
+    }
+
+    #[test]
+    fn an_identical_second_send_is_suppressed() {
+        let sent = sent_emails(Duration::from_secs(60));
+
+        assert!(sent.claim(confirmation("ivy@example.com", "booking-1")));
+        assert!(!sent.claim(confirmation("ivy@example.com", "booking-1")));
+        assert!(!sent.claim(confirmation("Ivy@Example.com", "booking-1")));
+    }
+
+    #[test]
+    fn a_send_about_another_booking_is_not_suppressed() {
+        let sent = sent_emails(Duration::from_secs(60));
+
+        assert!(sent.claim(confirmation("ivy@example.com", "booking-1")));
+        assert!(sent.claim(confirmation("ivy@example.com", "booking-2")));
+        assert!(sent.claim(confirmation("guest@example.com", "booking-1")));
+        assert!(sent.claim(dedup_key("ivy@example.com", EmailTemplate::BookingCancelled, "booking-1")));
+    }
+
+    #[test]
+    fn a_released_or_expired_send_can_go_out_again() {
+        let sent = sent_emails(Duration::from_secs(60));
+        assert!(sent.claim(confirmation("ivy@example.com", "booking-1")));
+        sent.release(&confirmation("ivy@example.com", "booking-1"));
+        assert!(sent.claim(confirmation("ivy@example.com", "booking-1")));
+
+        let no_window = sent_emails(Duration::ZERO);
+        assert!(no_window.claim(confirmation("ivy@example.com", "booking-1")));
+        assert!(no_window.claim(confirmation("ivy@example.com", "booking-1")));
+    }
+}
@@ -0,0 +1,532 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tokio::sync::Notify;
+
+use crate::errors::error::AppError;
+use crate::services::email::{CustomCopy, EmailService};
+
+const MAX_ATTEMPTS: u32 = 3;
+const RETRY_DELAY: Duration = Duration::from_secs(60);
+const INLINE_SEND_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone)]
+pub enum EmailJob {
+    Verification { to: String, code: String },
+    PasswordReset { to: String, code: String },
+    BookingConfirmed {
+        to: String,
+        booking_id: String,
+        event_name: String,
+        date: String,
+        start_time: String,
+        location: Option<String>,
+        ics: String,
+        management_token: Option<String>,  // Set for the invitee only
+        custom_copy: Option<CustomCopy>,  // Set for the invitee and guests when the event type has its own
+    },
+    BookingRequested {
+        to: String,
+        booking_id: String,
+        event_name: String,
+        invitee_name: String,
+        date: String,
+        start_time: String,
+        expires_in_hours: u64,
+        approve_token: String,  // Signed, single-use links for the host
+        decline_token: String,
+    },
+    BookingDeclined {
+        to: String,
+        booking_id: String,
+        event_name: String,
+        date: String,
+        start_time: String,
+    },
+    BookingCancelled {
+        to: String,
+        booking_id: String,
+        event_name: String,
+        date: String,
+        start_time: String,
+        reason: Option<String>,
+        ics: Option<String>,
+        custom_copy: Option<CustomCopy>,
+    },
+    BookingRescheduled {
+        to: String,
+        booking_id: String,
+        event_name: String,
+        previous_date: String,
+        previous_start_time: String,
+        date: String,
+        start_time: String,
+        ics: Option<String>,
+    },
+    BookingReminder {
+        to: String,
+        booking_id: String,
+        event_name: String,
+        date: String,
+        start_time: String,
+        location: Option<String>,
+        offset_minutes: i32,  // Which of the event type's reminders this is
+        management_token: Option<String>,
+        custom_copy: Option<CustomCopy>,
+    },
+}
+
+impl EmailJob {
+    /// Critical mail falls back to a synchronous send when the queue is full;
+    /// anything else is dropped with a log line instead of blocking the request.
+    /// Account mail and the news of a booking being made, requested, cancelled
+    /// or moved are critical, since nothing sends them again.
+    pub fn is_critical(&self) -> bool {
+        match self {
+            EmailJob::Verification { .. }
+            | EmailJob::PasswordReset { .. }
+            | EmailJob::BookingConfirmed { .. }
+            | EmailJob::BookingRequested { .. }
+            | EmailJob::BookingCancelled { .. }
+            | EmailJob::BookingRescheduled { .. } => true,
+            EmailJob::BookingDeclined { .. } | EmailJob::BookingReminder { .. } => false,
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            EmailJob::Verification { .. } => "verification",
+            EmailJob::PasswordReset { .. } => "password_reset",
+            EmailJob::BookingConfirmed { .. } => "booking_confirmed",
+            EmailJob::BookingRequested { .. } => "booking_requested",
+            EmailJob::BookingDeclined { .. } => "booking_declined",
+            EmailJob::BookingCancelled { .. } => "booking_cancelled",
+            EmailJob::BookingRescheduled { .. } => "booking_rescheduled",
+            EmailJob::BookingReminder { .. } => "booking_reminder",
+        }
+    }
+
+    pub fn recipient(&self) -> &str {
+        match self {
+            EmailJob::Verification { to, .. }
+            | EmailJob::PasswordReset { to, .. }
+            | EmailJob::BookingConfirmed { to, .. }
+            | EmailJob::BookingRequested { to, .. }
+            | EmailJob::BookingDeclined { to, .. }
+            | EmailJob::BookingCancelled { to, .. }
+            | EmailJob::BookingRescheduled { to, .. }
+            | EmailJob::BookingReminder { to, .. } => to,
+        }
+    }
+
+    async fn send(&self, email_service: &EmailService) -> Result<(), AppError> {
+        match self {
+            EmailJob::Verification { to, code } => email_service.send_verification_email(to, code).await,
+            EmailJob::PasswordReset { to, code } => email_service.send_password_reset_email(to, code).await,
+            EmailJob::BookingConfirmed {
+                to, booking_id, event_name, date, start_time, location, ics, management_token, custom_copy,
+            } => {
+                email_service
+                    .send_booking_confirmed_email(
+                        to,
+                        booking_id,
+                        event_name,
+                        date,
+                        start_time,
+                        location.as_deref(),
+                        ics,
+                        management_token.as_deref(),
+                        custom_copy.as_ref(),
+                    )
+                    .await
+            }
+            EmailJob::BookingRequested {
+                to, booking_id, event_name, invitee_name, date, start_time, expires_in_hours, approve_token, decline_token,
+            } => {
+                email_service
+                    .send_booking_requested_email(
+                        to, booking_id, event_name, invitee_name, date, start_time, *expires_in_hours, approve_token, decline_token,
+                    )
+                    .await
+            }
+            EmailJob::BookingDeclined { to, booking_id, event_name, date, start_time } => {
+                email_service
+                    .send_booking_declined_email(to, booking_id, event_name, date, start_time)
+                    .await
+            }
+            EmailJob::BookingCancelled { to, booking_id, event_name, date, start_time, reason, ics, custom_copy } => {
+                email_service
+                    .send_booking_cancelled_email(
+                        to, booking_id, event_name, date, start_time, reason.as_deref(), ics.as_deref(), custom_copy.as_ref(),
+                    )
+                    .await
+            }
+            EmailJob::BookingRescheduled {
+                to, booking_id, event_name, previous_date, previous_start_time, date, start_time, ics,
+            } => {
+                email_service
+                    .send_booking_rescheduled_email(
+                        to, booking_id, event_name, previous_date, previous_start_time, date, start_time, ics.as_deref(),
+                    )
+                    .await
+            }
+            EmailJob::BookingReminder {
+                to, booking_id, event_name, date, start_time, location, offset_minutes, management_token, custom_copy,
+            } => {
+                email_service
+                    .send_booking_reminder_email(
+                        to,
+                        booking_id,
+                        event_name,
+                        date,
+                        start_time,
+                        location.as_deref(),
+                        *offset_minutes,
+                        management_token.as_deref(),
+                        custom_copy.as_ref(),
+                    )
+                    .await
+            }
+        }
+    }
+}
+
+struct QueuedEmail {
+    job: EmailJob,
+    queued_at: Instant,
+    attempts: u32,
+    not_before: Instant,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EmailQueueStats {
+    pub depth: usize,
+    pub capacity: usize,
+    pub oldest_item_age_seconds: Option<u64>,
+    pub retrying: usize,
+    pub sent: u64,
+    pub failed: u64,
+    pub sent_inline: u64,
+    pub dropped: u64,
+}
+
+struct QueueState {
+    pending: Mutex<VecDeque<QueuedEmail>>,
+    capacity: usize,
+    inline_send_timeout: Duration,
+    wake: Notify,
+    force: AtomicBool,
+    sent: AtomicU64,
+    failed: AtomicU64,
+    sent_inline: AtomicU64,
+    dropped: AtomicU64,
+}
+
+/// Bounded in-process queue for outgoing mail, drained by a background worker.
+#[derive(Clone)]
+pub struct EmailQueue {
+    state: Arc<QueueState>,
+    email_service: EmailService,
+}
+
+impl fmt::Debug for EmailQueue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EmailQueue")
+            .field("capacity", &self.state.capacity)
+            .finish_non_exhaustive()
+    }
+}
+
+impl EmailQueue {
+    pub fn new(email_service: EmailService, capacity: usize) -> Self {
+        Self {
+            state: Arc::new(QueueState {
+                pending: Mutex::new(VecDeque::new()),
+                capacity,
+                inline_send_timeout: INLINE_SEND_TIMEOUT,
+                wake: Notify::new(),
+                force: AtomicBool::new(false),
+                sent: AtomicU64::new(0),
+                failed: AtomicU64::new(0),
+                sent_inline: AtomicU64::new(0),
+                dropped: AtomicU64::new(0),
+            }),
+            email_service,
+        }
+    }
+
+    /// Queues `job` without waiting for delivery. When the queue is full the
+    /// job is sent inline if critical, otherwise dropped.
+    pub async fn enqueue(&self, job: EmailJob) -> Result<(), AppError> {
+        let job = {
+            let mut pending = self.state.pending.lock().unwrap();
+            if pending.len() < self.state.capacity {
+                let now = Instant::now();
+                pending.push_back(QueuedEmail { job, queued_at: now, attempts: 0, not_before: now });
+                None
+            } else {
+                Some(job)
+            }
+        };
+
+        let Some(job) = job else {
+            self.state.wake.notify_one();
+            return Ok(());
+        };
+
+        if job.is_critical() {
+            println!("Email queue full, sending {} email to {} inline", job.kind(), job.recipient());
+            self.send_inline(job).await?;
+            self.state.sent_inline.fetch_add(1, Ordering::Relaxed);
+        } else {
+            eprintln!("Email queue full, dropping {} email to {}", job.kind(), job.recipient());
+            self.state.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+
+        Ok(())
+    }
+
+    /// SMTP sends block, so the inline send runs on a blocking thread and the
+    /// request gives up on it after `inline_send_timeout`.
+    async fn send_inline(&self, job: EmailJob) -> Result<(), AppError> {
+        let kind = job.kind();
+        let email_service = self.email_service.clone();
+        let runtime = tokio::runtime::Handle::current();
+        let send = tokio::task::spawn_blocking(move || runtime.block_on(job.send(&email_service)));
+
+        match tokio::time::timeout(self.state.inline_send_timeout, send).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(e)) => Err(AppError::EmailError(e.to_string())),
+            Err(_) => {
+                self.state.failed.fetch_add(1, Ordering::Relaxed);
+                Err(AppError::EmailError(format!("Timed out sending {} email", kind)))
+            }
+        }
+    }
+
+    /// Processes everything in the queue now, including jobs waiting to be retried.
+    pub fn flush(&self) {
+        self.state.force.store(true, Ordering::Relaxed);
+        self.state.wake.notify_one();
+    }
+
+    pub fn stats(&self) -> EmailQueueStats {
+        let pending = self.state.pending.lock().unwrap();
+        let now = Instant::now();
+
+        EmailQueueStats {
+            depth: pending.len(),
+            capacity: self.state.capacity,
+            oldest_item_age_seconds: pending
+                .iter()
+                .map(|item| now.duration_since(item.queued_at).as_secs())
+                .max(),
+            retrying: pending.iter().filter(|item| item.attempts > 0).count(),
+            sent: self.state.sent.load(Ordering::Relaxed),
+            failed: self.state.failed.load(Ordering::Relaxed),
+            sent_inline: self.state.sent_inline.load(Ordering::Relaxed),
+            dropped: self.state.dropped.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Worker loop; runs for the lifetime of the server.
+    pub async fn run(self) {
+        loop {
+            tokio::select! {
+                _ = self.state.wake.notified() => {}
+                _ = tokio::time::sleep(RETRY_DELAY) => {}
+            }
+
+            let force = self.state.force.swap(false, Ordering::Relaxed);
+            let now = Instant::now();
+            let due: Vec<QueuedEmail> = {
+                let mut pending = self.state.pending.lock().unwrap();
+                let (due, waiting): (VecDeque<_>, VecDeque<_>) = pending
+                    .drain(..)
+                    .partition(|item| force || item.not_before <= now);
+                *pending = waiting;
+                due.into()
+            };
+
+            for mut item in due {
+                match item.job.send(&self.email_service).await {
+                    Ok(()) => {
+                        self.state.sent.fetch_add(1, Ordering::Relaxed);
+                    }
+                    Err(e) => {
+                        item.attempts += 1;
+                        if item.attempts >= MAX_ATTEMPTS {
+                            eprintln!(
+                                "Giving up on {} email to {} after {} attempts: {}",
+                                item.job.kind(), item.job.recipient(), item.attempts, e
+                            );
+                            self.state.failed.fetch_add(1, Ordering::Relaxed);
+                        } else {
+                            item.not_before = Instant::now() + RETRY_DELAY;
+                            self.state.pending.lock().unwrap().push_back(item);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::mpsc;
+    use std::thread;
+
+    use super::*;
+
+    fn verification(to: &str) -> EmailJob {
+        EmailJob::Verification { to: to.to_string(), code: "123456".to_string() }
+    }
+
+    fn declined(to: &str) -> EmailJob {
+        EmailJob::BookingDeclined {
+            to: to.to_string(),
+            booking_id: "booking-1".to_string(),
+            event_name: "Intro call".to_string(),
+            date: "2024-05-01".to_string(),
+            start_time: "09:00".to_string(),
+        }
+    }
+
+    fn confirmed(to: &str) -> EmailJob {
+        EmailJob::BookingConfirmed {
+            to: to.to_string(),
+            booking_id: "booking-1".to_string(),
+            event_name: "Intro call".to_string(),
+            date: "2024-05-01".to_string(),
+            start_time: "09:00".to_string(),
+            location: None,
+            ics: "BEGIN:VCALENDAR\r\nEND:VCALENDAR\r\n".to_string(),
+            management_token: None,
+            custom_copy: None,
+        }
+    }
+
+    /// Answers SMTP on `listener` like a working server and reports the
+    /// recipient of each message it accepts. The mailer's pool opens a spare
+    /// connection of its own, so every connection gets a session.
+    fn accept_mail(listener: TcpListener) -> mpsc::Receiver<String> {
+        let (accepted, received) = mpsc::channel();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let accepted = accepted.clone();
+                thread::spawn(move || answer_smtp(stream.unwrap(), accepted));
+            }
+        });
+        received
+    }
+
+    fn answer_smtp(stream: TcpStream, accepted: mpsc::Sender<String>) {
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut writer = stream;
+        writer.write_all(b"220 localhost ready\r\n").unwrap();
+        let (mut recipient, mut in_data) = (String::new(), false);
+        let mut line = String::new();
+        while reader.read_line(&mut line).unwrap_or(0) > 0 {
+            let command = line.trim_end().to_ascii_uppercase();
+            let reply: &[u8] = if in_data {
+                if command == "." {
+                    in_data = false;
+                    let _ = accepted.send(recipient.clone());
+                    b"250 queued\r\n"
+                } else {
+                    b""
+                }
+            } else if command.starts_with("RCPT TO:") {
+                recipient = line.trim_end()[8..].trim_matches(|c| c == '<' || c == '>').to_string();
+                b"250 ok\r\n"
+            } else if command == "DATA" {
+                in_data = true;
+                b"354 go ahead\r\n"
+            } else if command == "QUIT" {
+                let _ = writer.write_all(b"221 bye\r\n");
+                return;
+            } else {
+                b"250 ok\r\n"
+            };
+            if writer.write_all(reply).is_err() {
+                return;
+            }
+            line.clear();
+        }
+    }
+
+    /// A queue whose mail goes to `listener`, which accepts connections but
+    /// never answers, like a stalled SMTP server.
+    fn queue_to(listener: &TcpListener, capacity: usize, inline_send_timeout: Duration) -> EmailQueue {
+        let port = listener.local_addr().unwrap().port();
+        let email_service = EmailService::unencrypted("127.0.0.1", port, Duration::from_secs(1));
+        let mut queue = EmailQueue::new(email_service, capacity);
+        Arc::get_mut(&mut queue.state).unwrap().inline_send_timeout = inline_send_timeout;
+        queue
+    }
+
+    #[tokio::test]
+    async fn a_queue_with_room_takes_the_job_without_sending() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let queue = queue_to(&listener, 1, Duration::from_millis(100));
+
+        queue.enqueue(verification("ivy@example.com")).await.unwrap();
+
+        let stats = queue.stats();
+        assert_eq!(stats.depth, 1);
+        assert_eq!(stats.sent_inline, 0);
+        assert_eq!(stats.dropped, 0);
+    }
+
+    #[tokio::test]
+    async fn a_full_queue_drops_non_critical_mail() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let queue = queue_to(&listener, 1, Duration::from_millis(100));
+        queue.enqueue(declined("ivy@example.com")).await.unwrap();
+
+        queue.enqueue(declined("guest@example.com")).await.unwrap();
+
+        let stats = queue.stats();
+        assert_eq!(stats.depth, 1);
+        assert_eq!(stats.dropped, 1);
+        assert_eq!(stats.sent_inline, 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn a_full_queue_sends_a_booking_confirmation_inline() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let queue = queue_to(&listener, 0, Duration::from_secs(5));
+        let received = accept_mail(listener);
+
+        queue.enqueue(confirmed("ivy@example.com")).await.unwrap();
+
+        assert_eq!(received.recv_timeout(Duration::from_secs(5)).unwrap(), "ivy@example.com");
+        let stats = queue.stats();
+        assert_eq!(stats.depth, 0);
+        assert_eq!(stats.sent_inline, 1);
+        assert_eq!(stats.dropped, 0);
+    }
+
+    #[tokio::test]
+    async fn a_full_queue_gives_up_on_a_stalled_inline_send() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let queue = queue_to(&listener, 0, Duration::from_millis(100));
+
+        let started = Instant::now();
+        let result = queue.enqueue(verification("ivy@example.com")).await;
+
+        assert!(matches!(result, Err(AppError::EmailError(_))));
+        assert!(started.elapsed() < Duration::from_secs(1));
+        let stats = queue.stats();
+        assert_eq!(stats.depth, 0);
+        assert_eq!(stats.sent_inline, 0);
+        assert_eq!(stats.failed, 1);
+    }
+}
@@ -1,4 +1,8 @@
-pub mod email; 
+pub mod booking_hooks;
+pub mod email;
+pub mod email_queue; 
+pub mod conferencing;
+pub mod notification_channel;
  
  
  
@@ -0,0 +1,388 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use async_trait::async_trait;
+use mongodb::bson::{oid::ObjectId, DateTime};
+use serde::Serialize;
+
+use crate::app::AppState;
+use crate::errors::error::AppError;
+use crate::modules::calendar::calendar_model::ReminderChannel;
+use crate::modules::notification::notification_model::{MessageLogEntry, MessageStatus};
+use crate::services::email::CustomCopy;
+use crate::services::email_queue::{EmailJob, EmailQueue};
+
+const TWILIO_API_URL: &str = "https://api.twilio.com/2010-04-01";
+/// Longer texts are cut, so a reminder costs at most two SMS segments.
+const SMS_MAX_CHARS: usize = 300;
+
+/// What a booking reminder says, whichever channel carries it.
+#[derive(Debug, Clone)]
+pub struct ReminderMessage {
+    pub booking_id: String,
+    pub event_name: String,
+    pub date: String,
+    pub start_time: String,
+    pub location: Option<String>,
+    pub offset_minutes: i32,  // The shortest of the reminders this message covers
+    pub management_token: Option<String>,  // Only the invitee gets the links to manage the booking
+    pub custom_copy: Option<CustomCopy>,
+}
+
+/// Someone a reminder goes to on one channel: an email address or a phone
+/// number, as the channel expects.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recipient {
+    pub channel: ReminderChannel,
+    pub to: String,
+    pub is_invitee: bool,  // Guests get the reminder without the management links
+}
+
+/// A way to reach an attendee.
+#[async_trait]
+pub trait NotificationChannel: Send + Sync {
+    fn kind(&self) -> ReminderChannel;
+
+    async fn send(&self, to: &str, message: &ReminderMessage) -> Result<(), AppError>;
+}
+
+/// The channels reminders go out on: email through the queue and SMS through
+/// the configured provider.
+pub fn reminder_channels(email_queue: EmailQueue) -> Vec<Box<dyn NotificationChannel>> {
+    vec![
+        Box::new(EmailChannel::new(email_queue)),
+        Box::new(SmsChannel::new(sms_provider())),
+    ]
+}
+
+/// Sends `message` to each recipient on its channel. A failed send is logged
+/// and counted without stopping the others. Returns what happened, for the
+/// message log.
+pub async fn dispatch_reminder(
+    channels: &[Box<dyn NotificationChannel>],
+    recipients: &[Recipient],
+    message: &ReminderMessage,
+    metrics: &ChannelMetrics,
+) -> Vec<MessageLogEntry> {
+    let booking_id = ObjectId::parse_str(&message.booking_id).ok();
+    let mut log = Vec::new();
+    for recipient in recipients {
+        let Some(channel) = channels.iter().find(|channel| channel.kind() == recipient.channel) else {
+            continue;
+        };
+        let guest_message;
+        let message = if recipient.is_invitee {
+            message
+        } else {
+            guest_message = ReminderMessage { management_token: None, ..message.clone() };
+            &guest_message
+        };
+        let result = channel.send(&recipient.to, message).await;
+        if let Err(e) = &result {
+            eprintln!("Failed to send {} reminder for booking {}: {}", recipient.channel.as_str(), message.booking_id, e);
+        }
+        metrics.record(recipient.channel, result.is_ok());
+        log.push(MessageLogEntry {
+            id: None,
+            channel: recipient.channel,
+            kind: "booking_reminder".to_string(),
+            recipient: recipient.to.clone(),
+            booking_id,
+            status: if result.is_ok() { MessageStatus::Sent } else { MessageStatus::Failed },
+            error: result.err().map(|e| e.to_string()),
+            created_at: DateTime::now(),
+        });
+    }
+    log
+}
+
+/// Reminders by email, through the outgoing email queue, which retries failed sends.
+pub struct EmailChannel {
+    email_queue: EmailQueue,
+}
+
+impl EmailChannel {
+    pub fn new(email_queue: EmailQueue) -> Self {
+        Self { email_queue }
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for EmailChannel {
+    fn kind(&self) -> ReminderChannel {
+        ReminderChannel::Email
+    }
+
+    async fn send(&self, to: &str, message: &ReminderMessage) -> Result<(), AppError> {
+        self.email_queue.enqueue(EmailJob::BookingReminder {
+            to: to.to_string(),
+            booking_id: message.booking_id.clone(),
+            event_name: message.event_name.clone(),
+            date: message.date.clone(),
+            start_time: message.start_time.clone(),
+            location: message.location.clone(),
+            offset_minutes: message.offset_minutes,
+            management_token: message.management_token.clone(),
+            custom_copy: message.custom_copy.clone(),
+        }).await
+    }
+}
+
+/// Reminders by text message, through whichever SMS provider is configured.
+pub struct SmsChannel {
+    provider: Box<dyn SmsProvider>,
+}
+
+impl SmsChannel {
+    pub fn new(provider: Box<dyn SmsProvider>) -> Self {
+        Self { provider }
+    }
+
+    /// The reminder as one short line of text.
+    fn text(message: &ReminderMessage) -> String {
+        let mut text = format!("Reminder: {} on {} at {}", message.event_name, message.date, message.start_time);
+        if let Some(location) = &message.location {
+            text.push_str(&format!(" ({})", location));
+        }
+        text.chars().take(SMS_MAX_CHARS).collect()
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for SmsChannel {
+    fn kind(&self) -> ReminderChannel {
+        ReminderChannel::Sms
+    }
+
+    async fn send(&self, to: &str, message: &ReminderMessage) -> Result<(), AppError> {
+        self.provider.send_sms(to, &Self::text(message)).await
+    }
+}
+
+/// Delivers text messages for the SMS channel.
+#[async_trait]
+pub trait SmsProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    async fn send_sms(&self, to: &str, body: &str) -> Result<(), AppError>;
+}
+
+/// The SMS provider the operator configured: Twilio, or the log when there is none.
+pub fn sms_provider() -> Box<dyn SmsProvider> {
+    match AppState::get().twilio.clone() {
+        Some(config) => Box::new(TwilioSmsProvider::new(config)),
+        None => Box::new(LogSmsProvider),
+    }
+}
+
+/// Twilio account credentials and sender, set by the operator.
+#[derive(Debug, Clone)]
+pub struct TwilioConfig {
+    pub account_sid: String,
+    pub auth_token: String,
+    pub from_number: String,  // A Twilio number of the account, in E.164 format
+}
+
+/// Sends text messages with Twilio's Messages API.
+pub struct TwilioSmsProvider {
+    config: TwilioConfig,
+    http: reqwest::Client,
+}
+
+impl TwilioSmsProvider {
+    pub fn new(config: TwilioConfig) -> Self {
+        Self { config, http: reqwest::Client::new() }
+    }
+}
+
+#[async_trait]
+impl SmsProvider for TwilioSmsProvider {
+    fn name(&self) -> &'static str {
+        "twilio"
+    }
+
+    async fn send_sms(&self, to: &str, body: &str) -> Result<(), AppError> {
+        let response = self.http
+            .post(format!("{}/Accounts/{}/Messages.json", TWILIO_API_URL, self.config.account_sid))
+            .basic_auth(&self.config.account_sid, Some(&self.config.auth_token))
+            .form(&[("To", to), ("From", self.config.from_number.as_str()), ("Body", body)])
+            .send()
+            .await
+            .map_err(|e| AppError::InternalServerError(format!("Twilio request failed: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(AppError::InternalServerError(format!("Twilio answered {}", response.status())));
+        }
+        Ok(())
+    }
+}
+
+/// Writes text messages to the log instead of sending them, for development.
+pub struct LogSmsProvider;
+
+#[async_trait]
+impl SmsProvider for LogSmsProvider {
+    fn name(&self) -> &'static str {
+        "log"
+    }
+
+    async fn send_sms(&self, to: &str, body: &str) -> Result<(), AppError> {
+        println!("SMS to {}: {}", to, body);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+struct ChannelCounters {
+    sent: AtomicU64,
+    failed: AtomicU64,
+}
+
+/// Messages sent and failed per channel since the server started.
+#[derive(Debug, Default)]
+pub struct ChannelMetrics {
+    email: ChannelCounters,
+    sms: ChannelCounters,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChannelStats {
+    pub channel: ReminderChannel,
+    pub sent: u64,
+    pub failed: u64,
+}
+
+impl ChannelMetrics {
+    fn counters(&self, channel: ReminderChannel) -> &ChannelCounters {
+        match channel {
+            ReminderChannel::Email => &self.email,
+            ReminderChannel::Sms => &self.sms,
+        }
+    }
+
+    pub fn record(&self, channel: ReminderChannel, sent: bool) {
+        let counters = self.counters(channel);
+        let counter = if sent { &counters.sent } else { &counters.failed };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn stats(&self) -> Vec<ChannelStats> {
+        ReminderChannel::ALL
+            .iter()
+            .map(|&channel| ChannelStats {
+                channel,
+                sent: self.counters(channel).sent.load(Ordering::Relaxed),
+                failed: self.counters(channel).failed.load(Ordering::Relaxed),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    type Sent = Arc<Mutex<Vec<(String, Option<String>)>>>;  // Recipient and management token
+
+    /// Keeps what it was asked to send instead of sending it.
+    struct RecordingChannel {
+        kind: ReminderChannel,
+        fails: bool,
+        sent: Sent,
+    }
+
+    #[async_trait]
+    impl NotificationChannel for RecordingChannel {
+        fn kind(&self) -> ReminderChannel {
+            self.kind
+        }
+
+        async fn send(&self, to: &str, message: &ReminderMessage) -> Result<(), AppError> {
+            if self.fails {
+                return Err(AppError::InternalServerError("provider is down".to_string()));
+            }
+            self.sent.lock().unwrap().push((to.to_string(), message.management_token.clone()));
+            Ok(())
+        }
+    }
+
+    /// An email and an SMS channel, the first failing every send if `email_fails`,
+    /// and what each of them sent.
+    fn channels(email_fails: bool) -> (Vec<Box<dyn NotificationChannel>>, Sent, Sent) {
+        let (email_sent, sms_sent) = (Sent::default(), Sent::default());
+        let channels: Vec<Box<dyn NotificationChannel>> = vec![
+            Box::new(RecordingChannel { kind: ReminderChannel::Email, fails: email_fails, sent: email_sent.clone() }),
+            Box::new(RecordingChannel { kind: ReminderChannel::Sms, fails: false, sent: sms_sent.clone() }),
+        ];
+        (channels, email_sent, sms_sent)
+    }
+
+    fn recipients() -> Vec<Recipient> {
+        vec![
+            Recipient { channel: ReminderChannel::Email, to: "ivy@example.com".to_string(), is_invitee: true },
+            Recipient { channel: ReminderChannel::Email, to: "guest@example.com".to_string(), is_invitee: false },
+            Recipient { channel: ReminderChannel::Sms, to: "+49 30 1234567".to_string(), is_invitee: true },
+        ]
+    }
+
+    fn message() -> ReminderMessage {
+        ReminderMessage {
+            booking_id: ObjectId::new().to_hex(),
+            event_name: "Intro call".to_string(),
+            date: "2026-05-04".to_string(),
+            start_time: "10:00".to_string(),
+            location: None,
+            offset_minutes: 60,
+            management_token: Some("token".to_string()),
+            custom_copy: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn reminders_fan_out_across_email_and_sms() {
+        let (channels, email_sent, sms_sent) = channels(false);
+        let metrics = ChannelMetrics::default();
+        let message = message();
+
+        let log = dispatch_reminder(&channels, &recipients(), &message, &metrics).await;
+
+        let token = Some("token".to_string());
+        assert_eq!(*email_sent.lock().unwrap(), vec![
+            ("ivy@example.com".to_string(), token.clone()),
+            ("guest@example.com".to_string(), None),
+        ]);
+        assert_eq!(*sms_sent.lock().unwrap(), vec![("+49 30 1234567".to_string(), token)]);
+        assert_eq!(log.len(), 3);
+        let booking_id = ObjectId::parse_str(&message.booking_id).ok();
+        assert!(log.iter().all(|entry| entry.status == MessageStatus::Sent && entry.booking_id == booking_id));
+        let stats = metrics.stats();
+        assert_eq!((stats[0].channel, stats[0].sent, stats[0].failed), (ReminderChannel::Email, 2, 0));
+        assert_eq!((stats[1].channel, stats[1].sent, stats[1].failed), (ReminderChannel::Sms, 1, 0));
+    }
+
+    #[tokio::test]
+    async fn a_failing_channel_does_not_stop_the_others() {
+        let (channels, _, sms_sent) = channels(true);
+        let metrics = ChannelMetrics::default();
+
+        let log = dispatch_reminder(&channels, &recipients(), &message(), &metrics).await;
+
+        assert_eq!(sms_sent.lock().unwrap().len(), 1);
+        assert_eq!(log.len(), 3);
+        assert_eq!((log[0].channel, log[0].status), (ReminderChannel::Email, MessageStatus::Failed));
+        assert!(log[0].error.is_some());
+        assert_eq!((log[2].channel, log[2].status), (ReminderChannel::Sms, MessageStatus::Sent));
+        let stats = metrics.stats();
+        assert_eq!((stats[0].sent, stats[0].failed), (0, 2));
+        assert_eq!((stats[1].sent, stats[1].failed), (1, 0));
+    }
+
+    #[test]
+    fn sms_text_is_one_short_line() {
+        let message = ReminderMessage { location: Some("Room 4".to_string()), ..message() };
+        assert_eq!(SmsChannel::text(&message), "Reminder: Intro call on 2026-05-04 at 10:00 (Room 4)");
+
+        let long = ReminderMessage { event_name: "x".repeat(400), ..message };
+        assert_eq!(SmsChannel::text(&long).chars().count(), SMS_MAX_CHARS);
+    }
+}
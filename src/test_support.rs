@@ -0,0 +1,257 @@
+//! Setup shared by tests that need MongoDB. They only run when
+//! TEST_MONGODB_URI is set, against one fresh database per test run; each
+//! test creates its own hosts, so tests never see each other's data.
+
+use std::collections::HashMap;
+use std::env;
+use std::future::Future;
+use std::sync::OnceLock;
+
+use chrono::{Duration, NaiveDate, NaiveTime, Utc};
+use chrono_tz::Tz;
+use mongodb::bson::{oid::ObjectId, DateTime};
+use mongodb::{Client, Database};
+use tokio::runtime::Runtime;
+use tokio::sync::OnceCell;
+use uuid::Uuid;
+
+use crate::app::{self, AppState};
+use crate::config::environment::Environment;
+use crate::config::features::FeatureFlags;
+use crate::modules::booking::booking_model::{Booking, BookingStatus};
+use crate::modules::calendar::calendar_crud::{AvailabilityRepository, CalendarSettingsRepository, HostInviteRepository};
+use crate::modules::calendar::calendar_engine::BookingHorizon;
+use crate::modules::calendar::calendar_model::{
+    Availability, AvailabilityRule, AvailabilitySlot, BufferTime, CalendarSettings, EventType, HostInviteStatus, TimeSlot,
+};
+use crate::services::email::EmailService;
+use crate::services::email_queue::EmailQueue;
+
+const WEEKDAYS: [&str; 7] = ["monday", "tuesday", "wednesday", "thursday", "friday", "saturday", "sunday"];
+
+static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+static DATABASE: OnceCell<Database> = OnceCell::const_new();
+
+/// Runs `test` against the test database, or skips it when
+/// TEST_MONGODB_URI is not set. On CI (where `CI` is set) a missing URI fails
+/// the test instead, so a misconfigured pipeline cannot pass by skipping.
+/// All tests share one runtime, so the client and `AppState` outlive each of them.
+pub fn with_database<F, Fut>(test: F)
+where
+    F: FnOnce(Database) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    let Ok(uri) = env::var("TEST_MONGODB_URI") else {
+        assert!(env::var_os("CI").is_none(), "TEST_MONGODB_URI must be set when CI is");
+        println!("TEST_MONGODB_URI is not set, skipping");
+        return;
+    };
+    let runtime = RUNTIME.get_or_init(|| Runtime::new().expect("Failed to start the test runtime"));
+    runtime.block_on(async {
+        let db = DATABASE.get_or_init(|| connect(uri)).await.clone();
+        test(db).await;
+    });
+}
+
+async fn connect(uri: String) -> Database {
+    let client = Client::with_uri_str(&uri).await.expect("Failed to connect to TEST_MONGODB_URI");
+    let database_name = format!("calendly_test_{}", Uuid::new_v4().simple());
+    let db = client.database(&database_name);
+    app::ensure_indexes(&db).await;
+
+    let env = Environment {
+        mongodb_uri: uri,
+        database_name,
+        port: 0,
+        jwt_secret: "test-jwt-secret".to_string(),
+        email_user: "test@example.com".to_string(),
+        email_password: String::new(),
+        base_path: String::new(),
+        trusted_proxies: Vec::new(),
+        email_queue_capacity: 10_000,
+        email_dedup_window_seconds: 60,
+        pending_booking_ttl_hours: 24,
+        action_signing_secret: "test-signing-secret".to_string(),
+        default_max_booking_horizon_days: 60,
+        hard_max_booking_horizon_days: 365,
+        app_url: "http://localhost:3000".to_string(),
+        zoom: None,
+        twilio: None,
+        slack_webhook_url: None,
+        retention_months: None,
+    };
+    // The worker is not started, so queued emails are never sent
+    let email_service = EmailService::new(&env).expect("Failed to build the test email service");
+    AppState::init(AppState {
+        db: db.clone(),
+        email_queue: EmailQueue::new(email_service, env.email_queue_capacity),
+        features: FeatureFlags::default(),
+        pending_booking_ttl_hours: env.pending_booking_ttl_hours,
+        action_signing_secret: env.action_signing_secret,
+        booking_horizon: BookingHorizon {
+            default_days: env.default_max_booking_horizon_days,
+            hard_max_days: env.hard_max_booking_horizon_days,
+        },
+        zoom: None,
+        twilio: None,
+        channel_metrics: Default::default(),
+        booking_hooks: Default::default(),
+        retention_months: env.retention_months,
+    });
+    db
+}
+
+/// A new host with calendar settings in `timezone` and a default schedule
+/// open from 09:00 to 17:00 every day.
+pub async fn create_host(db: &Database, timezone: &str) -> (CalendarSettings, Availability) {
+    let user_id = ObjectId::new();
+    let day = || vec![TimeSlot { start: "09:00".to_string(), end: "17:00".to_string() }];
+    let settings = CalendarSettings {
+        id: None,
+        user_id,
+        timezone: timezone.to_string(),
+        working_hours: WEEKDAYS.iter().map(|weekday| (weekday.to_string(), day())).collect::<HashMap<_, _>>(),
+        buffer_time: BufferTime { before: 0, after: 0 },
+        default_meeting_duration: 30,
+        slot_interval: None,
+        max_meeting_minutes_per_day: None,
+        calendar_name: "Test calendar".to_string(),
+        date_format: "YYYY-MM-DD".to_string(),
+        time_format: "24h".to_string(),
+        public_page_enabled: true,
+        public_page_message: None,
+        diagnostics_until: None,
+        created_at: DateTime::now(),
+        updated_at: DateTime::now(),
+    };
+    let settings = CalendarSettingsRepository::new(db.clone())
+        .create(&user_id, settings)
+        .await
+        .expect("Failed to create calendar settings");
+
+    let slots = WEEKDAYS
+        .iter()
+        .map(|weekday| AvailabilitySlot {
+            day_of_week: weekday.to_string(),
+            start_time: "09:00".to_string(),
+            end_time: "17:00".to_string(),
+            is_available: true,
+        })
+        .collect();
+    let rule = AvailabilityRule::new("2000-01-01T00:00:00Z", None, true, Some("weekly".to_string()), slots, 0)
+        .expect("Failed to build the availability rule");
+    let availability = AvailabilityRepository::new(db.clone())
+        .create(Availability {
+            id: None,
+            user_id,
+            calendar_settings_id: settings.id.expect("Calendar settings have no id"),
+            name: "Working hours".to_string(),
+            is_default: true,
+            rules: vec![rule],
+            date_overrides: Vec::new(),
+            version: 0,
+            deleted_at: None,
+            created_at: DateTime::now(),
+            updated_at: DateTime::now(),
+        })
+        .await
+        .expect("Failed to create availability");
+
+    (settings, availability)
+}
+
+/// Lets `owner` list `host` as a host of their event types.
+pub async fn accept_host_invite(db: &Database, owner: &ObjectId, host: &ObjectId) {
+    let repository = HostInviteRepository::new(db.clone());
+    let invite = repository.invite(owner, host).await.expect("Failed to invite host").expect("Host was already invited");
+    repository
+        .respond(&invite.id.expect("Host invite has no id"), host, HostInviteStatus::Accepted)
+        .await
+        .expect("Failed to accept host invite");
+}
+
+/// A one-on-one, 30 minute event type of `owner` on `schedule`, not yet stored.
+pub fn event_type(owner: &ObjectId, schedule: &Availability) -> EventType {
+    EventType {
+        id: None,
+        user_id: *owner,
+        name: "Test meeting".to_string(),
+        slug: format!("test-{}", Uuid::new_v4().simple()),
+        slug_history: Vec::new(),
+        description: None,
+        duration: 30,
+        color: "#000000".to_string(),
+        locations: Vec::new(),
+        questions: Vec::new(),
+        availability_schedule_id: schedule.id.expect("Availability has no id"),
+        buffer_time: None,
+        min_booking_notice: None,
+        max_booking_notice: None,
+        slot_interval: None,
+        cancellation_policy: None,
+        reschedule_policy: None,
+        scheduling_window: None,
+        embed_settings: None,
+        confirmation_settings: None,
+        day_overrides: None,
+        translations: None,
+        email_templates: None,
+        requires_confirmation: false,
+        max_attendees: None,
+        reminders: None,
+        hosts: Vec::new(),
+        host_assignment: Default::default(),
+        round_robin_strategy: Default::default(),
+        is_secret: false,
+        position: 0,
+        is_active: true,
+        archived_at: None,
+        created_at: DateTime::now(),
+        updated_at: DateTime::now(),
+    }
+}
+
+/// The date `days` from today in `timezone`, as YYYY-MM-DD.
+pub fn date_in(timezone: &str, days: i64) -> String {
+    let tz: Tz = timezone.parse().expect("Unknown timezone");
+    let today: NaiveDate = Utc::now().with_timezone(&tz).date_naive();
+    (today + Duration::days(days)).format("%Y-%m-%d").to_string()
+}
+
+/// A confirmed, one-on-one booking of a 30 minute slot, not yet stored.
+pub fn booking(event_type_id: &ObjectId, host: &ObjectId, date: &str, start_time: &str) -> Booking {
+    let start = NaiveTime::parse_from_str(start_time, "%H:%M").expect("Invalid start time");
+    Booking {
+        id: None,
+        event_type_id: *event_type_id,
+        host_user_id: *host,
+        co_host_user_ids: Vec::new(),
+        invitee_name: "Ivy Invitee".to_string(),
+        invitee_email: "ivy@example.com".to_string(),
+        guest_emails: Vec::new(),
+        attendees: Vec::new(),
+        invitee_phone: None,
+        date: date.to_string(),
+        start_time: start_time.to_string(),
+        end_time: (start + Duration::minutes(30)).format("%H:%M").to_string(),
+        status: BookingStatus::Confirmed,
+        seat: 0,
+        answers: Vec::new(),
+        chosen_location: None,
+        meeting_link: None,
+        tracking: None,
+        cancellation_token: Uuid::new_v4().simple().to_string(),
+        management_token: Uuid::new_v4().simple().to_string(),
+        cancelled_at: None,
+        cancelled_by: None,
+        cancellation_reason: None,
+        rescheduled_from: None,
+        reschedule_history: Vec::new(),
+        reminders_sent: Vec::new(),
+        schedule_versions: Vec::new(),
+        status_history: Vec::new(),
+        anonymized_at: None,
+        created_at: DateTime::now(),
+        updated_at: DateTime::now(),
+    }
+}
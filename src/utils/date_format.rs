@@ -0,0 +1,47 @@
+use chrono::{NaiveDate, NaiveTime};
+
+/// Formats `date` with a user's date format such as "MM/DD/YYYY" or
+/// "D MMM YYYY". Unrecognised letters are kept as they are.
+pub fn format_date(date: NaiveDate, pattern: &str) -> String {
+    const TOKENS: [(&str, &str); 8] = [
+        ("YYYY", "%Y"),
+        ("YY", "%y"),
+        ("MMMM", "%B"),
+        ("MMM", "%b"),
+        ("MM", "%m"),
+        ("M", "%-m"),
+        ("DD", "%d"),
+        ("D", "%-d"),
+    ];
+
+    let mut chrono_pattern = String::new();
+    let mut rest = pattern;
+    while let Some(c) = rest.chars().next() {
+        match TOKENS.iter().find(|(token, _)| rest.starts_with(token)) {
+            Some((token, specifier)) => {
+                chrono_pattern.push_str(specifier);
+                rest = &rest[token.len()..];
+            }
+            None => {
+                if c == '%' {
+                    chrono_pattern.push('%');
+                }
+                chrono_pattern.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+
+    date.format(&chrono_pattern).to_string()
+}
+
+/// Formats `time` as 12-hour when the user's time format asks for it
+/// ("12h", "h:mm A", ...), otherwise as 24-hour "HH:mm".
+pub fn format_time(time: NaiveTime, pattern: &str) -> String {
+    let twelve_hour = pattern.contains("12") || pattern.contains(['a', 'A']);
+    if twelve_hour {
+        time.format("%-I:%M %p").to_string()
+    } else {
+        time.format("%H:%M").to_string()
+    }
+}
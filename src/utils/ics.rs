@@ -0,0 +1,226 @@
+use chrono::{DateTime, Utc};
+
+/// iTIP method of the calendar object: an invitation or its withdrawal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IcsMethod {
+    Request,
+    Cancel,
+}
+
+impl IcsMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IcsMethod::Request => "REQUEST",
+            IcsMethod::Cancel => "CANCEL",
+        }
+    }
+}
+
+/// A required attendee besides the invitee, such as one the host added.
+pub struct IcsAttendee<'a> {
+    pub name: Option<&'a str>,
+    pub email: &'a str,
+}
+
+/// One meeting as a VEVENT. Times are written in UTC, so the calendar
+/// needs no VTIMEZONE and every client shows them in its own timezone.
+pub struct IcsEvent<'a> {
+    pub uid: &'a str,             // Stable per booking so updates replace the same event
+    pub sequence: i64,            // Must grow with every update of the same uid
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub summary: &'a str,
+    pub location: Option<&'a str>,
+    pub organizer_email: &'a str,
+    pub attendee_name: &'a str,
+    pub attendee_email: &'a str,
+    pub guest_emails: &'a [String],  // Optional attendees the invitee added
+    pub attendees: &'a [IcsAttendee<'a>],
+}
+
+/// Renders a VCALENDAR holding `event`, with CRLF line endings and folded lines.
+pub fn render(event: &IcsEvent, method: IcsMethod) -> String {
+    let utc = |at: &DateTime<Utc>| at.format("%Y%m%dT%H%M%SZ").to_string();
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//Calendly//Bookings//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        format!("METHOD:{}", method.as_str()),
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}", escape(event.uid)),
+        format!("SEQUENCE:{}", event.sequence),
+        format!("DTSTAMP:{}", Utc::now().format("%Y%m%dT%H%M%SZ")),
+        format!("DTSTART:{}", utc(&event.starts_at)),
+        format!("DTEND:{}", utc(&event.ends_at)),
+        format!("SUMMARY:{}", escape(event.summary)),
+    ];
+    if let Some(location) = event.location {
+        lines.push(format!("LOCATION:{}", escape(location)));
+    }
+    lines.push(format!("ORGANIZER:mailto:{}", strip_controls(event.organizer_email)));
+    lines.push(format!(
+        "ATTENDEE;CN=\"{}\";ROLE=REQ-PARTICIPANT;RSVP=FALSE:mailto:{}",
+        escape_param(event.attendee_name),
+        strip_controls(event.attendee_email)
+    ));
+    for attendee in event.attendees {
+        let name = attendee.name.map(|name| format!(";CN=\"{}\"", escape_param(name))).unwrap_or_default();
+        lines.push(format!(
+            "ATTENDEE{};ROLE=REQ-PARTICIPANT;RSVP=FALSE:mailto:{}",
+            name,
+            strip_controls(attendee.email)
+        ));
+    }
+    for guest_email in event.guest_emails {
+        lines.push(format!("ATTENDEE;ROLE=OPT-PARTICIPANT;RSVP=FALSE:mailto:{}", strip_controls(guest_email)));
+    }
+    lines.push(format!(
+        "STATUS:{}",
+        if method == IcsMethod::Cancel { "CANCELLED" } else { "CONFIRMED" }
+    ));
+    lines.push("END:VEVENT".to_string());
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|line| fold(line)).collect::<Vec<_>>().join("\r\n") + "\r\n"
+}
+
+/// Escapes TEXT values (RFC 5545 section 3.3.11). Line breaks become `\n`
+/// and other control characters are dropped, so a value stays on its line.
+fn escape(value: &str) -> String {
+    strip_controls(
+        &value
+            .replace('\\', "\\\\")
+            .replace(';', "\\;")
+            .replace(',', "\\,")
+            .replace("\r\n", "\\n")
+            .replace('\n', "\\n"),
+    )
+}
+
+/// Escapes a quoted parameter value (RFC 6868): `^` becomes `^^`, a line
+/// break `^n` and a double quote `^'`. Other control characters are dropped.
+fn escape_param(value: &str) -> String {
+    let value = value.replace("\r\n", "\n");
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '^' => escaped.push_str("^^"),
+            '\n' => escaped.push_str("^n"),
+            '"' => escaped.push_str("^'"),
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Drops control characters, which could otherwise end the content line.
+fn strip_controls(value: &str) -> String {
+    value.chars().filter(|c| !c.is_control()).collect()
+}
+
+/// Splits lines longer than 75 octets, continuing with a leading space,
+/// without breaking inside a UTF-8 character.
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn event<'a>(attendee_name: &'a str, summary: &'a str) -> IcsEvent<'a> {
+        IcsEvent {
+            uid: "booking@calendly",
+            sequence: 1,
+            starts_at: Utc.with_ymd_and_hms(2026, 3, 8, 15, 0, 0).unwrap(),
+            ends_at: Utc.with_ymd_and_hms(2026, 3, 8, 15, 30, 0).unwrap(),
+            summary,
+            location: None,
+            organizer_email: "host@example.com",
+            attendee_name,
+            attendee_email: "invitee@example.com",
+            guest_emails: &[],
+            attendees: &[],
+        }
+    }
+
+    /// The content lines, with folded lines joined again.
+    fn lines(ics: &str) -> Vec<String> {
+        ics.replace("\r\n ", "").split("\r\n").map(str::to_string).collect()
+    }
+
+    #[test]
+    fn attendee_name_cannot_add_properties() {
+        let ics = render(&event("Eve\r\nATTENDEE:mailto:attacker@example.com\"", "Intro"), IcsMethod::Request);
+        let lines = lines(&ics);
+
+        assert_eq!(lines.iter().filter(|line| line.starts_with("ATTENDEE")).count(), 1);
+        assert!(lines.contains(&
+            "ATTENDEE;CN=\"Eve^nATTENDEE:mailto:attacker@example.com^'\";ROLE=REQ-PARTICIPANT;RSVP=FALSE:mailto:invitee@example.com"
+                .to_string()
+        ));
+    }
+
+    #[test]
+    fn added_attendees_are_required() {
+        let attendees = [
+            IcsAttendee { name: Some("Sam \"SE\""), email: "sam@example.com" },
+            IcsAttendee { name: None, email: "lee@example.com" },
+        ];
+        let ics = render(&IcsEvent { attendees: &attendees, ..event("Eve", "Intro") }, IcsMethod::Request);
+        let lines = lines(&ics);
+
+        assert!(lines.contains(&"ATTENDEE;CN=\"Sam ^'SE^'\";ROLE=REQ-PARTICIPANT;RSVP=FALSE:mailto:sam@example.com".to_string()));
+        assert!(lines.contains(&"ATTENDEE;ROLE=REQ-PARTICIPANT;RSVP=FALSE:mailto:lee@example.com".to_string()));
+    }
+
+    #[test]
+    fn parameter_values_are_escaped() {
+        assert_eq!(escape_param("a^b"), "a^^b");
+        assert_eq!(escape_param("say \"hi\""), "say ^'hi^'");
+        assert_eq!(escape_param("one\ntwo\r\nthree"), "one^ntwo^nthree");
+        assert_eq!(escape_param("tab\there\rbell\u{7}"), "tabherebell");
+    }
+
+    #[test]
+    fn text_values_stay_on_their_line() {
+        assert_eq!(escape("a;b,c\\d"), r"a\;b\,c\\d");
+        assert_eq!(escape("one\ntwo\r\nthree\rfour"), "one\\ntwo\\nthreefour");
+
+        let ics = render(&event("Eve", "Intro\rMETHOD:CANCEL"), IcsMethod::Request);
+        assert!(lines(&ics).iter().all(|line| line != "METHOD:CANCEL"));
+    }
+
+    #[test]
+    fn times_are_written_in_utc() {
+        let ics = render(&event("Eve", "Intro"), IcsMethod::Request);
+        let lines = lines(&ics);
+
+        assert!(lines.contains(&"DTSTART:20260308T150000Z".to_string()));
+        assert!(lines.contains(&"DTEND:20260308T153000Z".to_string()));
+        assert!(!ics.contains("TZID"));
+    }
+
+    #[test]
+    fn long_lines_are_folded_at_75_octets() {
+        let summary = "é".repeat(60);
+        let ics = render(&event("Eve", &summary), IcsMethod::Request);
+
+        assert!(ics.split("\r\n").all(|line| line.len() <= 75));
+        assert!(lines(&ics).contains(&format!("SUMMARY:{}", summary)));
+    }
+}
@@ -0,0 +1,64 @@
+use std::collections::HashSet;
+
+use ammonia::Builder;
+use pulldown_cmark::{html, Options, Parser};
+
+/// Upper bound on rendered HTML returned to clients
+const MAX_RENDERED_BYTES: usize = 20_000;
+
+/// Cleans user-supplied HTML down to links, lists, paragraphs, emphasis and
+/// strikethrough.
+/// Scripts, iframes, inline event handlers and non-http(s) links are removed.
+pub fn sanitize_html(input: &str) -> String {
+    let tags: HashSet<&str> = [
+        "a", "p", "br", "ul", "ol", "li", "strong", "em", "b", "i", "code", "blockquote", "del",
+    ]
+    .into_iter()
+    .collect();
+    let url_schemes: HashSet<&str> = ["http", "https", "mailto"].into_iter().collect();
+
+    Builder::default()
+        .tags(tags)
+        .url_schemes(url_schemes)
+        .link_rel(Some("noopener noreferrer nofollow"))
+        .clean(input)
+        .to_string()
+}
+
+/// Renders Markdown to sanitized HTML, capped at `MAX_RENDERED_BYTES`.
+pub fn render_markdown(markdown: &str) -> String {
+    let mut rendered = String::new();
+    html::push_html(&mut rendered, Parser::new_ext(markdown, Options::ENABLE_STRIKETHROUGH));
+
+    let sanitized = sanitize_html(&rendered);
+    if sanitized.len() <= MAX_RENDERED_BYTES {
+        return sanitized;
+    }
+
+    // Cut on a char boundary and sanitize again so any tag left open is closed
+    let cut = sanitized
+        .char_indices()
+        .map(|(index, _)| index)
+        .take_while(|&index| index <= MAX_RENDERED_BYTES)
+        .last()
+        .unwrap_or(0);
+    sanitize_html(&sanitized[..cut])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strikethrough_survives_sanitizing() {
+        assert_eq!(render_markdown("~~cancelled~~ moved"), "<p><del>cancelled</del> moved</p>\n");
+    }
+
+    #[test]
+    fn scripts_and_unsafe_links_are_removed() {
+        let rendered = render_markdown("[click](javascript:alert(1)) <script>alert(1)</script>");
+
+        assert!(!rendered.contains("script"));
+        assert!(!rendered.contains("javascript:"));
+    }
+}
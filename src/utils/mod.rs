@@ -1,41 +1,14 @@
+pub mod csv;
+pub mod date_format;
+pub mod ics;
+pub mod markdown;
+pub mod nullable;
+pub mod object_id;
+pub mod recurrence;
 pub mod response;
-pub mod validation; 
- 
- 
- 
- 
- 
- 
- 
- 
- 
- 
- 
- 
- 
- 
- 
- 
- 
-
- 
- 
- 
- 
- 
- 
- 
- 
- 
- 
- 
- 
- 
- 
- 
- 
- 
- 
- 
- 
- 
\ No newline at end of file
+pub mod signed_actions;
+pub mod template;
+pub mod text;
+pub mod time_of_day;
+pub mod timezone;
+pub mod validation;
@@ -0,0 +1,38 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Serde helpers for update fields that can be cleared. Use with
+/// `#[serde(default, with = "crate::utils::nullable")]` on an
+/// `Option<Option<T>>`: a missing field is `None` and leaves the value alone,
+/// `null` is `Some(None)` and clears it, and anything else sets it.
+pub fn serialize<T: Serialize, S: Serializer>(value: &Option<Option<T>>, serializer: S) -> Result<S::Ok, S::Error> {
+    match value {
+        Some(value) => value.serialize(serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+pub fn deserialize<'de, T: Deserialize<'de>, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Option<T>>, D::Error> {
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    struct Update {
+        #[serde(default, with = "super")]
+        limit: Option<Option<i32>>,
+    }
+
+    fn limit_of(body: &str) -> Option<Option<i32>> {
+        serde_json::from_str::<Update>(body).unwrap().limit
+    }
+
+    #[test]
+    fn missing_null_and_set_fields_are_told_apart() {
+        assert_eq!(limit_of("{}"), None);
+        assert_eq!(limit_of(r#"{ "limit": null }"#), Some(None));
+        assert_eq!(limit_of(r#"{ "limit": 5 }"#), Some(Some(5)));
+    }
+}
@@ -0,0 +1,75 @@
+use actix_web::{dev::Payload, http::StatusCode, FromRequest, HttpRequest};
+use futures::future::{ready, Ready};
+use mongodb::bson::oid::ObjectId;
+
+use crate::errors::error::AppError;
+
+const MAX_ECHOED_LEN: usize = 64;
+
+/// The `{id}` path segment parsed as an ObjectId. Anything that is not exactly
+/// 24 hex characters is rejected with the same `INVALID_OBJECT_ID` error.
+#[derive(Debug, Clone, Copy)]
+pub struct PathObjectId(pub ObjectId);
+
+impl FromRequest for PathObjectId {
+    type Error = AppError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let result = match req.match_info().get("id") {
+            Some(value) => ObjectId::parse_str(value)
+                .map(PathObjectId)
+                .map_err(|_| invalid_object_id(value)),
+            None => Err(AppError::InternalServerError("Route has no {id} segment".to_string())),
+        };
+        ready(result)
+    }
+}
+
+fn invalid_object_id(value: &str) -> AppError {
+    let echoed: String = value.chars().take(MAX_ECHOED_LEN).collect();
+    AppError::coded(
+        StatusCode::BAD_REQUEST,
+        "INVALID_OBJECT_ID",
+        &format!("Invalid id '{}': expected a 24-character hex ObjectId", echoed),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test as actix_test;
+
+    async fn extract(id: &str) -> Result<PathObjectId, AppError> {
+        let req = actix_test::TestRequest::default().param("id", id.to_string()).to_http_request();
+        PathObjectId::extract(&req).await
+    }
+
+    #[actix_web::test]
+    async fn valid_id_is_parsed() {
+        let id = ObjectId::new();
+        assert_eq!(extract(&id.to_hex()).await.unwrap().0, id);
+    }
+
+    #[actix_web::test]
+    async fn malformed_ids_get_the_same_error() {
+        for id in ["abc", "zzzzzzzzzzzzzzzzzzzzzzzz", &"a".repeat(25)] {
+            match extract(id).await {
+                Err(AppError::Coded(400, code, message)) => {
+                    assert_eq!(code, "INVALID_OBJECT_ID");
+                    assert!(message.starts_with(&format!("Invalid id '{}'", id)), "{}", message);
+                }
+                other => panic!("{}: unexpected {:?}", id, other),
+            }
+        }
+    }
+
+    #[actix_web::test]
+    async fn long_ids_are_echoed_truncated() {
+        let Err(AppError::Coded(_, _, message)) = extract(&"x".repeat(500)).await else {
+            panic!("Long id was accepted");
+        };
+        assert!(message.contains(&format!("'{}'", "x".repeat(MAX_ECHOED_LEN))));
+        assert!(!message.contains(&"x".repeat(MAX_ECHOED_LEN + 1)));
+    }
+}
@@ -0,0 +1,310 @@
+use chrono::{DateTime, Datelike, Days, Months, NaiveDate, Utc, Weekday};
+use chrono_tz::Tz;
+
+pub const ALL_WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+/// A repeating schedule, anchored at the start passed to [`occurrences`].
+#[derive(Debug, Clone)]
+pub struct RecurrenceSpec {
+    pub frequency: Frequency,
+    pub interval: u32,             // Every `interval` days, weeks or months; 0 counts as 1
+    pub count: Option<u32>,        // Stop after this many occurrences, counted from the start
+    pub until: Option<NaiveDate>,  // Last date an occurrence may fall on
+    pub weekdays: Vec<Weekday>,    // Weekly only; empty means the start's weekday
+    pub month_day: Option<u32>,    // Monthly only; defaults to the start's day, clamped to short months
+}
+
+impl RecurrenceSpec {
+    pub fn new(frequency: Frequency) -> Self {
+        Self {
+            frequency,
+            interval: 1,
+            count: None,
+            until: None,
+            weekdays: Vec::new(),
+            month_day: None,
+        }
+    }
+
+    /// Parses the pattern names stored on availability rules.
+    pub fn from_pattern(pattern: &str) -> Option<Self> {
+        match pattern.trim().to_lowercase().as_str() {
+            "daily" => Some(Self::new(Frequency::Daily)),
+            "weekly" => Some(Self::new(Frequency::Weekly)),
+            "biweekly" => Some(Self { interval: 2, ..Self::new(Frequency::Weekly) }),
+            "monthly" => Some(Self::new(Frequency::Monthly)),
+            _ => None,
+        }
+    }
+
+    fn interval(&self) -> u32 {
+        self.interval.max(1)
+    }
+
+    fn weekly_anchors(&self, start: NaiveDate) -> Vec<Weekday> {
+        let mut anchors = if self.weekdays.is_empty() { vec![start.weekday()] } else { self.weekdays.clone() };
+        anchors.sort_by_key(|day| day.num_days_from_monday());
+        anchors.dedup();
+        anchors
+    }
+
+    /// Number of the period (day, week or month block) containing `date`.
+    fn period_of(&self, start: NaiveDate, date: NaiveDate) -> u32 {
+        if date <= start {
+            return 0;
+        }
+        let elapsed = match self.frequency {
+            Frequency::Daily => (date - start).num_days(),
+            Frequency::Weekly => (week_start(date) - week_start(start)).num_days() / 7,
+            Frequency::Monthly => {
+                (date.year() - start.year()) as i64 * 12 + date.month() as i64 - start.month() as i64
+            }
+        };
+        u32::try_from(elapsed / self.interval() as i64).unwrap_or(u32::MAX)
+    }
+
+    /// Occurrences in period `k`, in order. Only the first period can have
+    /// dates before `start`, which are left out. `None` once past the calendar.
+    fn dates_in_period(&self, start: NaiveDate, k: u32) -> Option<Vec<NaiveDate>> {
+        let steps = k.checked_mul(self.interval())?;
+        let dates = match self.frequency {
+            Frequency::Daily => vec![start.checked_add_days(Days::new(steps as u64))?],
+            Frequency::Weekly => {
+                let monday = week_start(start).checked_add_days(Days::new(steps as u64 * 7))?;
+                self.weekly_anchors(start)
+                    .iter()
+                    .filter_map(|day| monday.checked_add_days(Days::new(day.num_days_from_monday() as u64)))
+                    .collect()
+            }
+            Frequency::Monthly => {
+                let first = start.with_day(1)?.checked_add_months(Months::new(steps))?;
+                let day = self.month_day.unwrap_or(start.day()).clamp(1, days_in_month(first));
+                vec![first.with_day(day)?]
+            }
+        };
+        Some(dates.into_iter().filter(|date| *date >= start).collect())
+    }
+
+    /// Occurrences falling in the periods before period `k`.
+    fn occurrences_before(&self, start: NaiveDate, k: u32) -> u64 {
+        if k == 0 {
+            return 0;
+        }
+        let per_period = match self.frequency {
+            Frequency::Weekly => self.weekly_anchors(start).len() as u64,
+            Frequency::Daily | Frequency::Monthly => 1,
+        };
+        let first = self.dates_in_period(start, 0).map_or(0, |dates| dates.len() as u64);
+        first + (k as u64 - 1) * per_period
+    }
+}
+
+/// Dates in `tz` on which `spec` occurs, starting on the date of `start`
+/// there, that fall inside the inclusive `window` of dates in `tz`. The series
+/// steps by calendar dates, so it keeps its weekday or day of the month across
+/// daylight saving changes. Periods before the window are skipped
+/// arithmetically, so this stays cheap for long-running schedules.
+pub fn occurrences(
+    spec: &RecurrenceSpec,
+    start: DateTime<Utc>,
+    tz: Tz,
+    window: (NaiveDate, NaiveDate),
+) -> impl Iterator<Item = NaiveDate> {
+    let start = start.with_timezone(&tz).date_naive();
+    let (window_start, window_end) = window;
+    let last = spec.until.map_or(window_end, |until| until.min(window_end));
+    let first_period = spec.period_of(start, window_start);
+    let skipped = spec.occurrences_before(start, first_period);
+    let limit = spec.count.map(u64::from);
+
+    (first_period..=u32::MAX)
+        .map_while(move |k| spec.dates_in_period(start, k))
+        .flatten()
+        .zip(skipped..)
+        .take_while(move |&(date, n)| date <= last && limit.is_none_or(|limit| n < limit))
+        .map(|(date, _)| date)
+        .filter(move |date| *date >= window_start)
+}
+
+fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Days::new(date.weekday().num_days_from_monday() as u64)
+}
+
+fn days_in_month(first: NaiveDate) -> u32 {
+    first
+        .checked_add_months(Months::new(1))
+        .and_then(|next| next.pred_opt())
+        .map_or(31, |last| last.day())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveTime, TimeZone};
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    /// Noon UTC on `value`, which is the same date in UTC.
+    fn noon(value: &str) -> DateTime<Utc> {
+        date(value).and_time(NaiveTime::from_hms_opt(12, 0, 0).unwrap()).and_utc()
+    }
+
+    fn dates(spec: &RecurrenceSpec, start: &str, window: (&str, &str)) -> Vec<String> {
+        occurrences(spec, noon(start), Tz::UTC, (date(window.0), date(window.1)))
+            .map(|date| date.format("%Y-%m-%d").to_string())
+            .collect()
+    }
+
+    #[test]
+    fn daily_series_includes_leap_days() {
+        let spec = RecurrenceSpec::new(Frequency::Daily);
+        assert_eq!(dates(&spec, "2024-02-27", ("2024-02-27", "2024-03-01")), [
+            "2024-02-27", "2024-02-28", "2024-02-29", "2024-03-01",
+        ]);
+        assert_eq!(dates(&spec, "2023-02-27", ("2023-02-27", "2023-03-01")), [
+            "2023-02-27", "2023-02-28", "2023-03-01",
+        ]);
+    }
+
+    #[test]
+    fn monthly_series_from_a_leap_day_returns_to_the_29th() {
+        let spec = RecurrenceSpec::new(Frequency::Monthly);
+        assert_eq!(dates(&spec, "2024-02-29", ("2024-01-01", "2025-03-31")).into_iter().step_by(12).collect::<Vec<_>>(), [
+            "2024-02-29", "2025-02-28",
+        ]);
+        assert_eq!(dates(&spec, "2024-02-29", ("2024-01-01", "2024-04-30")), [
+            "2024-02-29", "2024-03-29", "2024-04-29",
+        ]);
+    }
+
+    #[test]
+    fn monthly_series_clamp_to_short_months() {
+        let spec = RecurrenceSpec::new(Frequency::Monthly);
+        assert_eq!(dates(&spec, "2026-01-31", ("2026-01-01", "2026-05-31")), [
+            "2026-01-31", "2026-02-28", "2026-03-31", "2026-04-30", "2026-05-31",
+        ]);
+        assert_eq!(dates(&spec, "2026-01-30", ("2026-01-01", "2026-03-31")), [
+            "2026-01-30", "2026-02-28", "2026-03-30",
+        ]);
+        assert_eq!(dates(&spec, "2024-01-29", ("2024-01-01", "2024-03-31")), [
+            "2024-01-29", "2024-02-29", "2024-03-29",
+        ]);
+        assert_eq!(dates(&spec, "2023-01-29", ("2023-01-01", "2023-03-31")), [
+            "2023-01-29", "2023-02-28", "2023-03-29",
+        ]);
+
+        let on_the_31st = RecurrenceSpec { month_day: Some(31), ..RecurrenceSpec::new(Frequency::Monthly) };
+        assert_eq!(dates(&on_the_31st, "2026-02-01", ("2026-02-01", "2026-04-30")), [
+            "2026-02-28", "2026-03-31", "2026-04-30",
+        ]);
+    }
+
+    #[test]
+    fn weekly_series_keep_their_weekday_across_clock_changes() {
+        let new_york: Tz = "America/New_York".parse().unwrap();
+        // Monday 09:00 in New York, before clocks spring forward on 8 March
+        let start = new_york.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap().with_timezone(&Utc);
+        let spec = RecurrenceSpec::new(Frequency::Weekly);
+
+        let spring: Vec<NaiveDate> = occurrences(&spec, start, new_york, (date("2026-03-01"), date("2026-03-22"))).collect();
+        assert_eq!(spring, [date("2026-03-02"), date("2026-03-09"), date("2026-03-16")]);
+        assert!(spring.iter().all(|date| date.weekday() == Weekday::Mon));
+
+        // The meeting stays at 09:00 local, so its UTC time moves by an hour
+        let utc_hour = |date: NaiveDate| {
+            new_york
+                .from_local_datetime(&date.and_time(NaiveTime::from_hms_opt(9, 0, 0).unwrap()))
+                .unwrap()
+                .with_timezone(&Utc)
+                .format("%H:%M")
+                .to_string()
+        };
+        assert_eq!(spring.iter().map(|date| utc_hour(*date)).collect::<Vec<_>>(), ["14:00", "13:00", "13:00"]);
+
+        // Clocks fall back on 1 November
+        let autumn: Vec<NaiveDate> = occurrences(&spec, start, new_york, (date("2026-10-26"), date("2026-11-09"))).collect();
+        assert_eq!(autumn, [date("2026-10-26"), date("2026-11-02"), date("2026-11-09")]);
+        assert_eq!(autumn.iter().map(|date| utc_hour(*date)).collect::<Vec<_>>(), ["13:00", "14:00", "14:00"]);
+    }
+
+    #[test]
+    fn series_start_on_the_local_date() {
+        let new_york: Tz = "America/New_York".parse().unwrap();
+        // Already Tuesday in UTC, still Monday evening in New York
+        let start = Utc.with_ymd_and_hms(2026, 3, 3, 2, 0, 0).unwrap();
+        let spec = RecurrenceSpec::new(Frequency::Weekly);
+
+        let local: Vec<NaiveDate> = occurrences(&spec, start, new_york, (date("2026-03-01"), date("2026-03-10"))).collect();
+        assert_eq!(local, [date("2026-03-02"), date("2026-03-09")]);
+        let utc: Vec<NaiveDate> = occurrences(&spec, start, Tz::UTC, (date("2026-03-01"), date("2026-03-10"))).collect();
+        assert_eq!(utc, [date("2026-03-03"), date("2026-03-10")]);
+    }
+
+    #[test]
+    fn biweekly_pattern_skips_every_other_week() {
+        let spec = RecurrenceSpec::from_pattern("Biweekly").unwrap();
+        assert_eq!(dates(&spec, "2026-03-02", ("2026-03-01", "2026-04-05")), [
+            "2026-03-02", "2026-03-16", "2026-03-30",
+        ]);
+    }
+
+    #[test]
+    fn until_ends_the_series() {
+        let spec = RecurrenceSpec { until: Some(date("2026-03-04")), ..RecurrenceSpec::new(Frequency::Daily) };
+        assert_eq!(dates(&spec, "2026-03-01", ("2026-03-01", "2026-03-31")), [
+            "2026-03-01", "2026-03-02", "2026-03-03", "2026-03-04",
+        ]);
+    }
+
+    /// Every combination of frequency, interval, weekdays and count yields
+    /// exactly `count` occurrences, however the window is split.
+    #[test]
+    fn occurrence_counts_match_the_spec() {
+        let starts = ["2024-01-31", "2024-02-29", "2026-03-08", "2026-12-31"];
+        let weekday_sets = [vec![], vec![Weekday::Mon, Weekday::Thu], ALL_WEEKDAYS.to_vec()];
+        let far = date("2100-12-31");
+
+        for start in starts {
+            for frequency in [Frequency::Daily, Frequency::Weekly, Frequency::Monthly] {
+                for interval in 0..=3 {
+                    for weekdays in &weekday_sets {
+                        for count in [1, 2, 5, 13, 40] {
+                            let spec = RecurrenceSpec {
+                                interval,
+                                count: Some(count),
+                                weekdays: weekdays.clone(),
+                                ..RecurrenceSpec::new(frequency)
+                            };
+                            let all: Vec<NaiveDate> = occurrences(&spec, noon(start), Tz::UTC, (date(start), far)).collect();
+                            assert_eq!(all.len(), count as usize, "{:?} from {}", spec, start);
+                            assert!(all.windows(2).all(|pair| pair[0] < pair[1]), "{:?} from {}", spec, start);
+
+                            // Skipping to a later window must not change which dates count
+                            let split = all[all.len() / 2];
+                            let before = occurrences(&spec, noon(start), Tz::UTC, (date(start), split.pred_opt().unwrap()));
+                            let after = occurrences(&spec, noon(start), Tz::UTC, (split, far));
+                            assert_eq!(before.chain(after).collect::<Vec<_>>(), all, "{:?} from {}", spec, start);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
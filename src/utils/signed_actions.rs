@@ -0,0 +1,198 @@
+use std::fmt;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Keeps these signatures from being valid for anything else signed with the same secret.
+const DOMAIN: &[u8] = b"signed-action:v1:";
+
+/// Host actions that can be carried out through a signed link, without a session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignedAction {
+    Confirm,
+    Decline,
+    MarkNoShow,
+}
+
+impl SignedAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SignedAction::Confirm => "confirm",
+            SignedAction::Decline => "decline",
+            SignedAction::MarkNoShow => "mark_no_show",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "confirm" => Some(SignedAction::Confirm),
+            "decline" => Some(SignedAction::Decline),
+            "mark_no_show" => Some(SignedAction::MarkNoShow),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for SignedAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// What a valid token grants.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActionClaims {
+    pub booking_id: String,
+    pub action: SignedAction,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignedActionError {
+    Malformed,
+    BadSignature,
+    Expired,
+}
+
+/// Signs `claims` into a URL-safe token: the base64 payload, a dot, then the base64 HMAC-SHA256 of the payload.
+pub fn sign(secret: &[u8], claims: &ActionClaims) -> String {
+    let payload = format!("{}:{}:{}", claims.booking_id, claims.action.as_str(), claims.expires_at.timestamp());
+    let payload = URL_SAFE_NO_PAD.encode(payload);
+    let signature = URL_SAFE_NO_PAD.encode(mac(secret, &payload).finalize().into_bytes());
+    format!("{}.{}", payload, signature)
+}
+
+/// Checks the signature before looking at the payload, then the expiry.
+pub fn verify(secret: &[u8], token: &str, now: DateTime<Utc>) -> Result<ActionClaims, SignedActionError> {
+    let (payload, signature) = token.split_once('.').ok_or(SignedActionError::Malformed)?;
+    let signature = URL_SAFE_NO_PAD.decode(signature).map_err(|_| SignedActionError::Malformed)?;
+    mac(secret, payload)
+        .verify_slice(&signature)
+        .map_err(|_| SignedActionError::BadSignature)?;
+
+    let payload = URL_SAFE_NO_PAD.decode(payload).map_err(|_| SignedActionError::Malformed)?;
+    let payload = String::from_utf8(payload).map_err(|_| SignedActionError::Malformed)?;
+    let mut parts = payload.splitn(3, ':');
+    let (Some(booking_id), Some(action), Some(expires_at)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(SignedActionError::Malformed);
+    };
+    let action = SignedAction::parse(action).ok_or(SignedActionError::Malformed)?;
+    let expires_at = expires_at
+        .parse::<i64>()
+        .ok()
+        .and_then(|secs| DateTime::from_timestamp(secs, 0))
+        .ok_or(SignedActionError::Malformed)?;
+
+    if expires_at <= now {
+        return Err(SignedActionError::Expired);
+    }
+
+    Ok(ActionClaims { booking_id: booking_id.to_string(), action, expires_at })
+}
+
+/// Hex SHA-256 of a token, stored once it is used so the token itself never is.
+pub fn token_hash(token: &str) -> String {
+    Sha256::digest(token.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+fn mac(secret: &[u8], payload: &str) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(DOMAIN);
+    mac.update(payload.as_bytes());
+    mac
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+
+    const SECRET: &[u8] = b"test-signing-secret";
+
+    fn claims(now: DateTime<Utc>) -> ActionClaims {
+        ActionClaims {
+            booking_id: "65f000000000000000000001".to_string(),
+            action: SignedAction::MarkNoShow,
+            expires_at: DateTime::from_timestamp(now.timestamp(), 0).unwrap() + Duration::hours(1),
+        }
+    }
+
+    /// `token` with its payload replaced by `payload`, keeping the signature.
+    fn with_payload(token: &str, payload: &str) -> String {
+        let (_, signature) = token.split_once('.').unwrap();
+        format!("{}.{}", URL_SAFE_NO_PAD.encode(payload), signature)
+    }
+
+    #[test]
+    fn a_signed_token_verifies_to_its_claims() {
+        let now = Utc::now();
+        let token = sign(SECRET, &claims(now));
+
+        assert_eq!(verify(SECRET, &token, now), Ok(claims(now)));
+    }
+
+    #[test]
+    fn a_token_is_refused_from_its_expiry() {
+        let now = Utc::now();
+        let claims = claims(now);
+        let token = sign(SECRET, &claims);
+
+        assert!(verify(SECRET, &token, claims.expires_at - Duration::seconds(1)).is_ok());
+        assert_eq!(verify(SECRET, &token, claims.expires_at), Err(SignedActionError::Expired));
+        assert_eq!(verify(SECRET, &token, claims.expires_at + Duration::days(1)), Err(SignedActionError::Expired));
+    }
+
+    #[test]
+    fn a_tampered_token_is_refused() {
+        let now = Utc::now();
+        let claims = claims(now);
+        let token = sign(SECRET, &claims);
+        let later = claims.expires_at.timestamp() + 86_400;
+
+        let other_booking = with_payload(&token, &format!("65f000000000000000000002:mark_no_show:{}", claims.expires_at.timestamp()));
+        let other_action = with_payload(&token, &format!("{}:confirm:{}", claims.booking_id, claims.expires_at.timestamp()));
+        let extended = with_payload(&token, &format!("{}:mark_no_show:{}", claims.booking_id, later));
+        for tampered in [other_booking, other_action, extended] {
+            assert_eq!(verify(SECRET, &tampered, now), Err(SignedActionError::BadSignature));
+        }
+
+        let (payload, _) = token.split_once('.').unwrap();
+        let forged = format!("{}.{}", payload, URL_SAFE_NO_PAD.encode([0u8; 32]));
+        assert_eq!(verify(SECRET, &forged, now), Err(SignedActionError::BadSignature));
+        assert_eq!(verify(b"another-secret", &token, now), Err(SignedActionError::BadSignature));
+    }
+
+    #[test]
+    fn a_malformed_token_is_refused() {
+        let now = Utc::now();
+
+        assert_eq!(verify(SECRET, "no-dot", now), Err(SignedActionError::Malformed));
+        assert_eq!(verify(SECRET, "payload.not base64!", now), Err(SignedActionError::Malformed));
+
+        // Correctly signed, but not a payload this module produces
+        let payload = URL_SAFE_NO_PAD.encode("only-a-booking-id");
+        let signature = URL_SAFE_NO_PAD.encode(mac(SECRET, &payload).finalize().into_bytes());
+        assert_eq!(verify(SECRET, &format!("{}.{}", payload, signature), now), Err(SignedActionError::Malformed));
+    }
+
+    #[test]
+    fn each_token_has_its_own_stable_hash() {
+        let now = Utc::now();
+        let token = sign(SECRET, &claims(now));
+        let other = sign(SECRET, &ActionClaims { action: SignedAction::Confirm, ..claims(now) });
+
+        assert_eq!(token_hash(&token), token_hash(&token));
+        assert_eq!(token_hash(&token).len(), 64);
+        assert_ne!(token_hash(&token), token_hash(&other));
+    }
+}
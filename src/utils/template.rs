@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+
+/// Values available to `{{...}}` placeholders when a booking is made.
+pub struct TemplateContext<'a> {
+    pub invitee_name: &'a str,
+    pub invitee_email: &'a str,
+    /// Answers keyed by `question_key`
+    pub answers: &'a HashMap<String, String>,
+}
+
+/// Derives the placeholder key for a question, e.g. "Phone number?" -> "phone_number".
+pub fn question_key(question: &str) -> String {
+    question
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+/// Replaces `{{invitee_name}}`, `{{invitee_email}}` and `{{answer:<key>}}` in
+/// `template`. Values are percent-encoded when `url_encode` is set, and
+/// placeholders that cannot be resolved are removed.
+pub fn render(template: &str, context: &TemplateContext, url_encode: bool) -> String {
+    substitute(template, |name| {
+        let value = match name {
+            "invitee_name" => Some(context.invitee_name.to_string()),
+            "invitee_email" => Some(context.invitee_email.to_string()),
+            _ => name
+                .strip_prefix("answer:")
+                .and_then(|key| context.answers.get(key.trim()))
+                .cloned(),
+        }?;
+
+        Some(if url_encode { urlencoding::encode(&value).into_owned() } else { value })
+    })
+}
+
+/// Checks that every placeholder in `template` can be resolved from the given question labels.
+pub fn validate_placeholders<'a>(template: &str, labels: impl IntoIterator<Item = &'a str>) -> Result<(), String> {
+    let keys: Vec<String> = labels.into_iter().map(question_key).collect();
+    let mut unknown = Vec::new();
+
+    substitute(template, |name| {
+        let known = match name.strip_prefix("answer:") {
+            Some(key) => keys.iter().any(|k| k == key.trim()),
+            None => name == "invitee_name" || name == "invitee_email",
+        };
+        if !known {
+            unknown.push(format!("{{{{{}}}}}", name));
+        }
+        None
+    });
+
+    if unknown.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Unknown placeholders: {}", unknown.join(", ")))
+    }
+}
+
+/// Placeholders the email templates of an event type may use.
+pub const EMAIL_PLACEHOLDERS: &[&str] = &[
+    "invitee_name", "event_name", "date", "start_time", "location", "cancel_url", "reschedule_url",
+];
+
+/// Renders a host's plain-text email template as HTML: `{{name}}` placeholders
+/// are replaced from `values`, everything is escaped and lines are kept.
+pub fn render_email(template: &str, values: &[(&str, String)]) -> String {
+    let text = substitute(template, |name| {
+        values.iter().find(|(key, _)| *key == name).map(|(_, value)| value.clone())
+    });
+    text.lines().map(ammonia::clean_text).collect::<Vec<_>>().join("<br>")
+}
+
+/// Checks that `template` only uses the `known` placeholders.
+pub fn validate_known_placeholders(template: &str, known: &[&str]) -> Result<(), String> {
+    let mut unknown = Vec::new();
+
+    substitute(template, |name| {
+        if !known.contains(&name) {
+            unknown.push(format!("{{{{{}}}}}", name));
+        }
+        None
+    });
+
+    if unknown.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Unknown placeholders: {}", unknown.join(", ")))
+    }
+}
+
+/// Walks `template` and hands each placeholder name to `resolve`. When an
+/// opening `{{` contains another `{{` before it closes, the outer braces are
+/// kept as literal text and only the innermost placeholder is substituted.
+fn substitute<F>(template: &str, mut resolve: F) -> String
+where
+    F: FnMut(&str) -> Option<String>,
+{
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(open) = rest.find("{{") {
+        output.push_str(&rest[..open]);
+        let after_open = &rest[open + 2..];
+
+        let Some(close) = after_open.find("}}") else {
+            output.push_str(&rest[open..]);
+            return output;
+        };
+
+        let inner = &after_open[..close];
+        if let Some(nested) = inner.find("{{") {
+            output.push_str(&rest[open..open + 2 + nested]);
+            rest = &after_open[nested..];
+            continue;
+        }
+
+        if let Some(value) = resolve(inner.trim()) {
+            output.push_str(&value);
+        }
+        rest = &after_open[close + 2..];
+    }
+
+    output.push_str(rest);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render_for(template: &str, url_encode: bool) -> String {
+        let answers = HashMap::from([("company".to_string(), "Acme & Co".to_string())]);
+        let context = TemplateContext {
+            invitee_name: "Ivy Invitee",
+            invitee_email: "ivy@example.com",
+            answers: &answers,
+        };
+        render(template, &context, url_encode)
+    }
+
+    #[test]
+    fn unresolved_answers_are_removed() {
+        assert_eq!(render_for("Hi {{invitee_name}}{{answer:missing}}!", false), "Hi Ivy Invitee!");
+        assert_eq!(render_for("From {{ answer:company }}", false), "From Acme & Co");
+    }
+
+    #[test]
+    fn values_are_url_encoded_only_for_urls() {
+        let url = "https://meet.example.com/room?name={{invitee_name}}&org={{answer:company}}";
+        assert_eq!(
+            render_for(url, true),
+            "https://meet.example.com/room?name=Ivy%20Invitee&org=Acme%20%26%20Co"
+        );
+        assert_eq!(render_for("{{invitee_name}} from {{answer:company}}", false), "Ivy Invitee from Acme & Co");
+    }
+
+    #[test]
+    fn nested_braces_keep_the_outer_ones_as_text() {
+        assert_eq!(render_for("{{{{invitee_name}}}}", false), "{{Ivy Invitee}}");
+        assert_eq!(render_for("{{ {{invitee_email}}", false), "{{ ivy@example.com");
+    }
+
+    #[test]
+    fn unbalanced_braces_are_left_alone() {
+        assert_eq!(render_for("Hi {{invitee_name", false), "Hi {{invitee_name");
+        assert_eq!(render_for("Hi }} {{invitee_name}}", false), "Hi }} Ivy Invitee");
+        assert_eq!(render_for("{{invitee_name}} {{", false), "Ivy Invitee {{");
+    }
+
+    #[test]
+    fn email_templates_escape_values_and_keep_lines() {
+        let values = [
+            ("invitee_name", "<b>Ivy</b>".to_string()),
+            ("event_name", "Intro & chat".to_string()),
+        ];
+
+        let html = render_email("Hi {{invitee_name}},\nsee you at {{event_name}}{{location}}.", &values);
+
+        // clean_text escapes spaces too
+        assert_eq!(html, "Hi&#32;&lt;b&gt;Ivy&lt;&#47;b&gt;,<br>see&#32;you&#32;at&#32;Intro&#32;&amp;&#32;chat.");
+    }
+
+    #[test]
+    fn email_templates_escape_the_host_text_too() {
+        assert_eq!(render_email("<script>x</script>", &[]), "&lt;script&gt;x&lt;&#47;script&gt;");
+    }
+}
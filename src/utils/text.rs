@@ -0,0 +1,51 @@
+use std::borrow::Cow;
+
+/// Shortens `value` to at most `max_chars` characters, ending with "…" when
+/// cut, so oversized data stored before length limits existed still renders
+/// safely in emails and calendar invitations.
+pub fn truncate_for_display(value: &str, max_chars: usize) -> Cow<'_, str> {
+    match value.char_indices().nth(max_chars) {
+        None => Cow::Borrowed(value),
+        Some(_) => {
+            let end = value.char_indices().nth(max_chars.saturating_sub(1)).map_or(0, |(index, _)| index);
+            Cow::Owned(format!("{}…", &value[..end]))
+        }
+    }
+}
+
+/// Longest slug accepted in URLs.
+pub const SLUG_MAX_CHARS: usize = 64;
+
+/// Turns `value` into a URL-friendly slug: lowercase ASCII letters and digits
+/// separated by single hyphens, e.g. "30 Min Call!" becomes "30-min-call".
+/// Returns an empty string when nothing usable is left.
+pub fn slugify(value: &str) -> String {
+    let mut slug = String::new();
+    for c in value.chars().flat_map(char::to_lowercase) {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.truncate(SLUG_MAX_CHARS);
+    slug.trim_end_matches('-').to_string()
+}
+
+/// Whether `value` is already a slug as produced by `slugify`.
+pub fn is_slug(value: &str) -> bool {
+    !value.is_empty() && value.len() <= SLUG_MAX_CHARS && slugify(value) == value
+}
+
+/// Escapes regex metacharacters so `value` matches literally inside a
+/// MongoDB `$regex`.
+pub fn escape_regex(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if "\\^$.|?*+()[]{}".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
@@ -0,0 +1,96 @@
+use chrono::NaiveTime;
+
+use crate::errors::error::AppError;
+
+/// Parses a time of day written as 24-hour "HH:mm" or 12-hour "h:mm AM/PM".
+/// The suffix is case-insensitive and the space before it optional.
+pub fn parse(value: &str) -> Option<NaiveTime> {
+    let lower = value.trim().to_ascii_lowercase();
+    let (clock, pm) = if let Some(rest) = lower.strip_suffix("am") {
+        (rest.trim_end(), Some(false))
+    } else if let Some(rest) = lower.strip_suffix("pm") {
+        (rest.trim_end(), Some(true))
+    } else {
+        (lower.as_str(), None)
+    };
+
+    let (hour, minute) = clock.split_once(':')?;
+    let is_number = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+    if !is_number(hour) || hour.len() > 2 || !is_number(minute) || minute.len() != 2 {
+        return None;
+    }
+    let hour: u32 = hour.parse().ok()?;
+    let minute: u32 = minute.parse().ok()?;
+
+    let hour = match pm {
+        None => hour,
+        Some(_) if !(1..=12).contains(&hour) => return None,
+        Some(pm) => hour % 12 + if pm { 12 } else { 0 },
+    };
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+/// The canonical 24-hour "HH:mm" form of `value`, which is what gets stored and returned.
+pub fn normalize(field: &str, value: &str) -> Result<String, AppError> {
+    parse(value)
+        .map(|time| time.format("%H:%M").to_string())
+        .ok_or_else(|| AppError::ValidationError(invalid_message(field, value)))
+}
+
+pub fn invalid_message(field: &str, value: &str) -> String {
+    format!(
+        "Invalid {} '{}'. Use 24-hour HH:mm (e.g. 17:30) or 12-hour h:mm AM/PM (e.g. 5:30 PM)",
+        field, value
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_24_and_12_hour_times() {
+        let cases = [
+            ("09:00", Some("09:00")),
+            ("9:00", Some("09:00")),
+            ("17:30", Some("17:30")),
+            ("00:00", Some("00:00")),
+            ("23:59", Some("23:59")),
+            ("9:00 AM", Some("09:00")),
+            ("5:30 pm", Some("17:30")),
+            ("5:30pm", Some("17:30")),
+            (" 11:15 Am ", Some("11:15")),
+            ("12:00 AM", Some("00:00")),
+            ("12:30 PM", Some("12:30")),
+            ("12:59 am", Some("00:59")),
+            ("13:00 PM", None),
+            ("0:30 AM", None),
+            ("24:00", None),
+            ("9:60", None),
+            ("9:5", None),
+            ("900", None),
+            ("123:00", None),
+            ("-1:00", None),
+            ("9:00 XM", None),
+            ("AM", None),
+            ("", None),
+        ];
+
+        for (input, expected) in cases {
+            let parsed = parse(input).map(|time| time.format("%H:%M").to_string());
+            assert_eq!(parsed.as_deref(), expected, "parsing {:?}", input);
+        }
+    }
+
+    #[test]
+    fn invalid_times_name_both_formats() {
+        let Err(AppError::ValidationError(message)) = normalize("start_time", "13:00 PM") else {
+            panic!("13:00 PM was accepted");
+        };
+        assert!(message.contains("'13:00 PM'"));
+        assert!(message.contains("HH:mm"));
+        assert!(message.contains("h:mm AM/PM"));
+
+        assert_eq!(normalize("start_time", "9:00 AM").unwrap(), "09:00");
+    }
+}
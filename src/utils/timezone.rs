@@ -0,0 +1,97 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use chrono_tz::{Tz, TZ_VARIANTS};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::error::AppError;
+
+/// Where a resolved timezone came from, in order of precedence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimezoneSource {
+    Request,
+    Profile,
+    Settings,
+    DefaultUtc,
+}
+
+/// Reported on responses so clients can see which timezone was applied.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimezoneResolution {
+    pub source: TimezoneSource,
+    pub timezone: String,
+}
+
+impl TimezoneResolution {
+    pub fn new(tz: Tz, source: TimezoneSource) -> Self {
+        Self { source, timezone: tz.name().to_string() }
+    }
+}
+
+/// Picks the timezone to use: the request, then the user's profile, then the
+/// calendar settings, then UTC. An invalid request timezone is rejected with a
+/// suggestion; invalid stored values are skipped.
+pub fn resolve_timezone(
+    request_tz: Option<&str>,
+    profile_tz: Option<&str>,
+    settings_tz: Option<&str>,
+) -> Result<(Tz, TimezoneSource), AppError> {
+    if let Some(name) = request_tz.map(str::trim).filter(|name| !name.is_empty()) {
+        return name
+            .parse::<Tz>()
+            .map(|tz| (tz, TimezoneSource::Request))
+            .map_err(|_| AppError::BadRequest(unknown_timezone_message(name)));
+    }
+
+    let stored = [(profile_tz, TimezoneSource::Profile), (settings_tz, TimezoneSource::Settings)];
+    for (name, source) in stored {
+        if let Some(tz) = name.and_then(|name| name.trim().parse::<Tz>().ok()) {
+            return Ok((tz, source));
+        }
+    }
+
+    Ok((Tz::UTC, TimezoneSource::DefaultUtc))
+}
+
+/// The instant a wall-clock time in `tz` refers to. A time repeated when the
+/// clocks go back means its first occurrence; a time skipped when they go
+/// forward never happens, so it has none.
+pub fn local_instant(tz: Tz, local: NaiveDateTime) -> Option<DateTime<Tz>> {
+    tz.from_local_datetime(&local).earliest()
+}
+
+/// When `date` begins in `tz`. Where the clocks skip midnight, the day
+/// begins at the first wall-clock time that exists.
+pub fn start_of_day(tz: Tz, date: NaiveDate) -> DateTime<Utc> {
+    (0..24)
+        .filter_map(|hour| NaiveTime::from_hms_opt(hour, 0, 0))
+        .find_map(|time| local_instant(tz, date.and_time(time)))
+        .map(|instant| instant.with_timezone(&Utc))
+        .unwrap_or_else(|| date.and_time(NaiveTime::MIN).and_utc())
+}
+
+fn unknown_timezone_message(name: &str) -> String {
+    match suggest_timezone(name) {
+        Some(suggestion) => format!("Unknown timezone '{}'. Did you mean '{}'?", name, suggestion),
+        None => format!("Unknown timezone '{}'", name),
+    }
+}
+
+/// The IANA name sharing the longest case-insensitive prefix with `name`.
+fn suggest_timezone(name: &str) -> Option<&'static str> {
+    let name = name.to_lowercase();
+    TZ_VARIANTS
+        .iter()
+        .map(|tz| tz.name())
+        .map(|candidate| {
+            let common = candidate
+                .to_lowercase()
+                .chars()
+                .zip(name.chars())
+                .take_while(|(a, b)| a == b)
+                .count();
+            (common, candidate)
+        })
+        .filter(|(common, _)| *common > 0)
+        .max_by(|(a, a_name), (b, b_name)| a.cmp(b).then_with(|| b_name.cmp(a_name)))
+        .map(|(_, candidate)| candidate)
+}
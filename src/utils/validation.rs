@@ -0,0 +1,56 @@
+/// Whether `value` is a `#RRGGBB` hex color.
+pub fn is_hex_color(value: &str) -> bool {
+    value.len() == 7
+        && value.starts_with('#')
+        && value[1..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Whether `value` is a locale code like "de" or "pt-BR".
+pub fn is_locale_code(value: &str) -> bool {
+    let mut parts = value.split('-');
+    let language = parts.next().unwrap_or_default();
+    let region = parts.next();
+
+    (2..=3).contains(&language.len())
+        && language.chars().all(|c| c.is_ascii_lowercase())
+        && region.is_none_or(|r| r.len() == 2 && r.chars().all(|c| c.is_ascii_uppercase()))
+        && parts.next().is_none()
+}
+
+/// Whether `value` looks like a phone number: an optional leading "+", then
+/// 7 to 15 digits, optionally grouped with spaces, dashes, dots or parentheses.
+pub fn is_phone_number(value: &str) -> bool {
+    let rest = value.strip_prefix('+').unwrap_or(value);
+    let digits = rest.chars().filter(|c| c.is_ascii_digit()).count();
+
+    (7..=15).contains(&digits)
+        && rest.chars().all(|c| c.is_ascii_digit() || matches!(c, ' ' | '-' | '.' | '(' | ')'))
+}
+
+/// Whether `value` is an absolute http(s) URL with a host and no whitespace.
+/// `{{question}}` placeholders are allowed after the host.
+pub fn is_http_url(value: &str) -> bool {
+    let Some(rest) = value.strip_prefix("https://").or_else(|| value.strip_prefix("http://")) else {
+        return false;
+    };
+    let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+
+    !host.is_empty()
+        && host.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | ':' | '[' | ']'))
+        && !value.chars().any(char::is_whitespace)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locale_codes_are_a_language_and_an_optional_region() {
+        for valid in ["de", "fil", "pt-BR", "es-MX"] {
+            assert!(is_locale_code(valid), "{}", valid);
+        }
+        for invalid in ["", "d", "deut", "DE", "pt-br", "pt_BR", "pt-BRA", "zh-Hans-CN", "de-"] {
+            assert!(!is_locale_code(invalid), "{}", invalid);
+        }
+    }
+}